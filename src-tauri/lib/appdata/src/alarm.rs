@@ -0,0 +1,181 @@
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Debug)]
+#[serde(rename_all = "lowercase")]
+pub enum AlarmLevel {
+    Info,
+    Warning,
+    Error,
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, Debug)]
+#[serde(tag = "kind", rename_all = "camelCase")]
+pub enum AlarmCondition {
+    /// Active above the threshold, cleared once the value falls below `threshold - hysteresis`.
+    Above {
+        threshold: f64,
+        #[serde(default)]
+        hysteresis: f64,
+    },
+    /// Active below the threshold, cleared once the value rises above `threshold + hysteresis`.
+    Below {
+        threshold: f64,
+        #[serde(default)]
+        hysteresis: f64,
+    },
+    /// Active while the value changes faster than `max_rate` per second.
+    #[serde(rename_all = "camelCase")]
+    RateOfChange { max_rate: f64 },
+    /// Active if no value was received for `timeout_ms`.
+    #[serde(rename_all = "camelCase")]
+    Stale { timeout_ms: u64 },
+    /// Active while an enum object entry has the variant.
+    #[serde(rename_all = "camelCase")]
+    EnumEquals { variant: String },
+}
+
+/// Alarm on a single object entry, e.g. in the canzero.toml
+/// ```toml
+/// [[alarms]]
+/// name = "battery overtemperature"
+/// node = "input_board"
+/// objectEntry = "battery_temperature"
+/// level = "error"
+/// condition = { kind = "above", threshold = 55.0, hysteresis = 2.0 }
+/// ```
+#[derive(Serialize, Deserialize, Clone, PartialEq, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct AlarmRule {
+    pub name: String,
+    pub node: String,
+    pub object_entry: String,
+    pub level: AlarmLevel,
+    pub condition: AlarmCondition,
+    #[serde(default)]
+    pub description: Option<String>,
+}
+
+/// Enum object entry, which reports an error level of a node, e.g.
+/// ```toml
+/// [[errorEntries]]
+/// node = "mother_board"
+/// objectEntry = "error_level_over_temperature"
+/// levels = { WARNING = "warning", CRITICAL = "error" }
+/// ```
+/// Variants missing in `levels` are OK. Without `levels` the variants `OK`, `INFO`,
+/// `WARNING` and `ERROR` map to their level, like the error types of the codegen.
+#[derive(Serialize, Deserialize, Clone, PartialEq, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct ErrorEntry {
+    pub node: String,
+    pub object_entry: String,
+    #[serde(default)]
+    pub levels: BTreeMap<String, AlarmLevel>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Deserialize)]
+    #[serde(rename_all = "camelCase")]
+    struct Config {
+        alarms: Vec<AlarmRule>,
+        error_entries: Vec<ErrorEntry>,
+    }
+
+    #[test]
+    fn rules_of_the_canzero_toml() {
+        let config: Config = toml::from_str(
+            r#"
+            [[alarms]]
+            name = "battery overtemperature"
+            node = "input_board"
+            objectEntry = "battery_temperature"
+            level = "error"
+            condition = { kind = "above", threshold = 55.0, hysteresis = 2.0 }
+
+            [[alarms]]
+            name = "pressure drop"
+            node = "input_board"
+            objectEntry = "pressure"
+            level = "warning"
+            condition = { kind = "below", threshold = 1.5 }
+            description = "tank pressure"
+
+            [[alarms]]
+            name = "fast pressure drop"
+            node = "input_board"
+            objectEntry = "pressure"
+            level = "info"
+            condition = { kind = "rateOfChange", maxRate = 0.5 }
+
+            [[alarms]]
+            name = "no pressure"
+            node = "input_board"
+            objectEntry = "pressure"
+            level = "warning"
+            condition = { kind = "stale", timeoutMs = 500 }
+
+            [[alarms]]
+            name = "emergency"
+            node = "mother_board"
+            objectEntry = "state"
+            level = "error"
+            condition = { kind = "enumEquals", variant = "EMERGENCY" }
+
+            [[errorEntries]]
+            node = "mother_board"
+            objectEntry = "error_level_over_temperature"
+            levels = { WARNING = "warning", CRITICAL = "error" }
+            "#,
+        )
+        .unwrap();
+
+        let conditions: Vec<&AlarmCondition> =
+            config.alarms.iter().map(|alarm| &alarm.condition).collect();
+        assert_eq!(
+            conditions,
+            vec![
+                &AlarmCondition::Above {
+                    threshold: 55.0,
+                    hysteresis: 2.0
+                },
+                &AlarmCondition::Below {
+                    threshold: 1.5,
+                    hysteresis: 0.0
+                },
+                &AlarmCondition::RateOfChange { max_rate: 0.5 },
+                &AlarmCondition::Stale { timeout_ms: 500 },
+                &AlarmCondition::EnumEquals {
+                    variant: "EMERGENCY".to_owned()
+                },
+            ]
+        );
+        assert_eq!(config.alarms[0].level, AlarmLevel::Error);
+        assert_eq!(config.alarms[0].object_entry, "battery_temperature");
+        assert_eq!(config.alarms[0].description, None);
+        assert_eq!(config.alarms[1].description.as_deref(), Some("tank pressure"));
+        assert_eq!(
+            config.error_entries[0].levels.get("CRITICAL"),
+            Some(&AlarmLevel::Error)
+        );
+    }
+
+    #[test]
+    fn rules_round_trip() {
+        let rule = AlarmRule {
+            name: "stale".to_owned(),
+            node: "node".to_owned(),
+            object_entry: "entry".to_owned(),
+            level: AlarmLevel::Warning,
+            condition: AlarmCondition::Stale { timeout_ms: 100 },
+            description: None,
+        };
+        let toml = toml::to_string(&rule).unwrap();
+        assert!(toml.contains("objectEntry = \"entry\""));
+        assert_eq!(toml::from_str::<AlarmRule>(&toml).unwrap(), rule);
+    }
+}
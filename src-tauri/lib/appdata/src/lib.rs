@@ -3,6 +3,12 @@ use std::path::{Path, PathBuf};
 use canzero_config::config::NetworkRef;
 use serde::{Deserialize, Serialize};
 
+pub use alarm::{AlarmCondition, AlarmLevel, AlarmRule, ErrorEntry};
+pub use requests::{RequestSettings, VerifiedEntry};
+
+mod alarm;
+mod requests;
+
 pub type Result<T> = std::result::Result<T, AppDataError>;
 
 #[derive(Debug)]
@@ -44,6 +50,17 @@ pub struct AppDataConfig {
     config_path: Option<PathBuf>,
     frontend_wdg_lvl: WdgLevel,
     deadlock_wdg_lvl: WdgLevel,
+    /// Number of values per object entry, which are kept in memory.
+    #[serde(default)]
+    history_window: Option<usize>,
+    #[serde(default)]
+    alarms: Vec<AlarmRule>,
+    /// Object entries reporting errors, if None all entries with an enum type
+    /// named `error_flag` or `error_level` are used.
+    #[serde(default)]
+    error_entries: Option<Vec<ErrorEntry>>,
+    #[serde(default)]
+    requests: RequestSettings,
 }
 
 
@@ -106,6 +123,34 @@ impl AppData {
         }
     }
 
+    pub fn set_history_window(&mut self, window: Option<usize>) {
+        if self.config.history_window != window {
+            self.config.history_window = window;
+            self.config_change_flag = true;
+        }
+    }
+
+    pub fn set_alarms(&mut self, alarms: Vec<AlarmRule>) {
+        if self.config.alarms != alarms {
+            self.config.alarms = alarms;
+            self.config_change_flag = true;
+        }
+    }
+
+    pub fn set_error_entries(&mut self, error_entries: Option<Vec<ErrorEntry>>) {
+        if self.config.error_entries != error_entries {
+            self.config.error_entries = error_entries;
+            self.config_change_flag = true;
+        }
+    }
+
+    pub fn set_requests(&mut self, requests: RequestSettings) {
+        if self.config.requests != requests {
+            self.config.requests = requests;
+            self.config_change_flag = true;
+        }
+    }
+
     pub fn get_config_path(&self) -> Option<&PathBuf> {
         self.config.config_path.as_ref()
     }
@@ -118,6 +163,22 @@ impl AppData {
         self.config.deadlock_wdg_lvl
     }
 
+    pub fn get_history_window(&self) -> Option<usize> {
+        self.config.history_window
+    }
+
+    pub fn get_alarms(&self) -> &Vec<AlarmRule> {
+        &self.config.alarms
+    }
+
+    pub fn get_error_entries(&self) -> Option<&Vec<ErrorEntry>> {
+        self.config.error_entries.as_ref()
+    }
+
+    pub fn get_requests(&self) -> &RequestSettings {
+        &self.config.requests
+    }
+
     fn appdata_path() -> PathBuf {
         let mut appdata_path = dirs::home_dir().expect("No home directory avaiable on the OS");
         appdata_path.push(".canzero");
@@ -189,6 +250,10 @@ impl Default for AppData {
                 config_path: None,
                 deadlock_wdg_lvl: WdgLevel::Active,
                 frontend_wdg_lvl: WdgLevel::Active,
+                history_window: None,
+                alarms: vec![],
+                error_entries: None,
+                requests: RequestSettings::default(),
             },
         }
     }
@@ -0,0 +1,68 @@
+use serde::{Deserialize, Serialize};
+
+/// Scheduling of get and set requests, e.g. in the canzero.toml
+/// ```toml
+/// [requests]
+/// maxInflight = 4
+/// retries = 2
+///
+/// [[requests.verify]]
+/// node = "motor_driver"
+/// objectEntry = "current_limit"
+/// ```
+#[derive(Serialize, Deserialize, Clone, PartialEq, Debug)]
+#[serde(rename_all = "camelCase", default)]
+pub struct RequestSettings {
+    /// Requests per node, which are sent before the older ones were answered.
+    /// Requests of the same object entry are never in flight at the same time.
+    pub max_inflight: usize,
+    /// Time to wait for a response, before the request is sent again.
+    pub timeout_ms: u64,
+    /// Number of times a request is repeated after a timeout.
+    pub retries: u32,
+    /// Gap between the fragments of a set request on an idle bus.
+    pub fragment_gap_ms: u64,
+    /// Upper bound of the fragment gap, which grows with the load of the bus.
+    pub max_fragment_gap_ms: u64,
+    /// Object entries, whose set requests are read back after the node acknowledged them.
+    pub verify: Vec<VerifiedEntry>,
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct VerifiedEntry {
+    pub node: String,
+    pub object_entry: String,
+}
+
+impl RequestSettings {
+    pub fn verifies(&self, node: &str, object_entry: &str) -> bool {
+        self.verify
+            .iter()
+            .any(|entry| entry.node == node && entry.object_entry == object_entry)
+    }
+
+    pub fn set_verify(&mut self, node: &str, object_entry: &str, enabled: bool) {
+        self.verify
+            .retain(|entry| entry.node != node || entry.object_entry != object_entry);
+        if enabled {
+            self.verify.push(VerifiedEntry {
+                node: node.to_owned(),
+                object_entry: object_entry.to_owned(),
+            });
+        }
+    }
+}
+
+impl Default for RequestSettings {
+    fn default() -> Self {
+        Self {
+            max_inflight: 4,
+            timeout_ms: 1000,
+            retries: 2,
+            fragment_gap_ms: 20,
+            max_fragment_gap_ms: 200,
+            verify: vec![],
+        }
+    }
+}
@@ -0,0 +1,144 @@
+use std::{io::Write, time::Duration};
+
+use canzero_common::TNetworkFrame;
+use chrono::{DateTime, Local};
+
+use crate::{data_bytes, network_frame, parse_error, ChannelMap, Result};
+
+const DATE_FORMAT: &str = "%a %b %d %I:%M:%S%.3f %P %Y";
+
+pub fn write_header<W: Write>(
+    writer: &mut W,
+    channels: &ChannelMap,
+    start: &DateTime<Local>,
+) -> std::io::Result<()> {
+    let date = start.format(DATE_FORMAT);
+    writeln!(writer, "date {date}")?;
+    writeln!(writer, "base hex  timestamps absolute")?;
+    writeln!(writer, "internal events logged")?;
+    for (bus_id, name) in channels.iter() {
+        writeln!(writer, "// channel {} : {name}", bus_id + 1)?;
+    }
+    writeln!(writer, "Begin Triggerblock {date}")?;
+    writeln!(writer, "   0.000000 Start of measurement")
+}
+
+pub fn write_footer<W: Write>(writer: &mut W) -> std::io::Result<()> {
+    writeln!(writer, "End TriggerBlock")
+}
+
+/// `   0.012345 1  123             Rx   d 2 01 02`, extended ids have a `x` suffix.
+pub fn write_frame<W: Write>(writer: &mut W, frame: &TNetworkFrame) -> std::io::Result<()> {
+    let can_frame = &frame.can_frame;
+    let timestamp = format!(
+        "{}.{:06}",
+        frame.timestamp.as_secs(),
+        frame.timestamp.subsec_micros()
+    );
+    let id = if can_frame.get_ide_flag() {
+        format!("{:X}x", can_frame.get_id())
+    } else {
+        format!("{:X}", can_frame.get_id())
+    };
+    let channel = frame.bus_id + 1;
+    if can_frame.get_rtr_flag() {
+        writeln!(
+            writer,
+            "{timestamp:>11} {channel:<2} {id:<15} Rx   r {:X}",
+            can_frame.get_dlc()
+        )
+    } else {
+        let data: Vec<String> = data_bytes(can_frame)
+            .iter()
+            .map(|byte| format!("{byte:02X}"))
+            .collect();
+        writeln!(
+            writer,
+            "{timestamp:>11} {channel:<2} {id:<15} Rx   d {:X} {}",
+            can_frame.get_dlc(),
+            data.join(" ")
+        )
+    }
+}
+
+/// Parses classic CAN data and remote frames, all other events (error frames, statistics,
+/// CAN FD, ...) are skipped. The channel number `n` is mapped to the bus id `n - 1`.
+pub fn parse(src: &str) -> Result<Vec<TNetworkFrame>> {
+    let mut frames = vec![];
+    let mut radix = 16;
+    for (index, line) in src.lines().enumerate() {
+        let line_number = index + 1;
+        let line = line.trim();
+        if line.is_empty() || line.starts_with("//") {
+            continue;
+        }
+        let tokens: Vec<&str> = line.split_whitespace().collect();
+        if tokens[0] == "base" {
+            radix = match tokens.get(1) {
+                Some(&"dec") => 10,
+                _ => 16,
+            };
+            continue;
+        }
+        let Some(timestamp) = parse_timestamp(tokens[0]) else {
+            // date, Begin Triggerblock, ...
+            continue;
+        };
+        if tokens.len() < 5 {
+            continue;
+        }
+        let Ok(channel) = tokens[1].parse::<u32>() else {
+            continue;
+        };
+        let (id, ide) = match tokens[2].strip_suffix(['x', 'X']) {
+            Some(id) => (id, true),
+            None => (tokens[2], false),
+        };
+        let Ok(id) = u32::from_str_radix(id, radix) else {
+            // ErrorFrame, Statistic, ...
+            continue;
+        };
+        let rtr = match tokens[4] {
+            "d" => false,
+            "r" => true,
+            _ => continue,
+        };
+        let dlc = match tokens.get(5) {
+            Some(dlc) => u8::from_str_radix(dlc, radix)
+                .map_err(|_| parse_error(line_number, format!("invalid dlc {dlc:?}")))?,
+            None if rtr => 0,
+            None => return Err(parse_error(line_number, "missing dlc")),
+        };
+        let mut data = vec![];
+        if !rtr {
+            if tokens.len() < 6 + dlc.min(8) as usize {
+                return Err(parse_error(line_number, "missing data bytes"));
+            }
+            for token in &tokens[6..6 + dlc.min(8) as usize] {
+                data.push(u8::from_str_radix(token, radix).map_err(|_| {
+                    parse_error(line_number, format!("invalid data byte {token:?}"))
+                })?);
+            }
+        }
+        frames.push(network_frame(
+            timestamp,
+            channel.saturating_sub(1),
+            id,
+            ide,
+            rtr,
+            &data,
+            dlc,
+        ));
+    }
+    Ok(frames)
+}
+
+fn parse_timestamp(token: &str) -> Option<Duration> {
+    let (secs, fraction) = token.split_once('.')?;
+    let secs = secs.parse::<u64>().ok()?;
+    if fraction.is_empty() || fraction.len() > 9 {
+        return None;
+    }
+    let nanos = fraction.parse::<u32>().ok()? * 10u32.pow(9 - fraction.len() as u32);
+    Some(Duration::new(secs, nanos))
+}
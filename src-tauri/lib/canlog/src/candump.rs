@@ -0,0 +1,108 @@
+use std::{io::Write, time::Duration};
+
+use canzero_common::TNetworkFrame;
+
+use crate::{data_bytes, network_frame, parse_error, parse_hex_byte, ChannelMap, Error, Result};
+
+/// `(1436509052.249713) can0 123#DEADBEEF`, extended ids are written with 8 digits.
+pub fn write_frame<W: Write>(
+    writer: &mut W,
+    channels: &ChannelMap,
+    frame: &TNetworkFrame,
+) -> std::io::Result<()> {
+    let can_frame = &frame.can_frame;
+    let id = if can_frame.get_ide_flag() {
+        format!("{:08X}", can_frame.get_id())
+    } else {
+        format!("{:03X}", can_frame.get_id())
+    };
+    let data = if can_frame.get_rtr_flag() {
+        "R".to_owned()
+    } else {
+        data_bytes(can_frame)
+            .iter()
+            .map(|byte| format!("{byte:02X}"))
+            .collect()
+    };
+    writeln!(
+        writer,
+        "({}.{:06}) {} {id}#{data}",
+        frame.timestamp.as_secs(),
+        frame.timestamp.subsec_micros(),
+        channels.name(frame.bus_id),
+    )
+}
+
+pub fn parse(src: &str, channels: &ChannelMap) -> Result<Vec<TNetworkFrame>> {
+    let mut frames = vec![];
+    for (index, line) in src.lines().enumerate() {
+        let line_number = index + 1;
+        let line = line.trim();
+        if line.is_empty() {
+            continue;
+        }
+        let mut tokens = line.split_whitespace();
+        let (Some(timestamp), Some(channel), Some(frame)) =
+            (tokens.next(), tokens.next(), tokens.next())
+        else {
+            return Err(parse_error(line_number, "expected (timestamp) channel id#data"));
+        };
+        let timestamp = parse_timestamp(timestamp, line_number)?;
+        let bus_id = channels
+            .bus_id(channel)
+            .ok_or_else(|| Error::UnknownChannel(channel.to_owned()))?;
+
+        let Some((id, data)) = frame.split_once('#') else {
+            return Err(parse_error(line_number, format!("invalid frame {frame:?}")));
+        };
+        if data.starts_with('#') {
+            return Err(parse_error(line_number, "CAN FD frames are not supported"));
+        }
+        let ide = id.len() > 3;
+        let id = u32::from_str_radix(id, 16)
+            .map_err(|_| parse_error(line_number, format!("invalid id {id:?}")))?;
+        if let Some(dlc) = data.strip_prefix('R') {
+            let dlc = if dlc.is_empty() {
+                0
+            } else {
+                dlc.parse::<u8>()
+                    .map_err(|_| parse_error(line_number, format!("invalid dlc {dlc:?}")))?
+            };
+            frames.push(network_frame(timestamp, bus_id, id, ide, true, &[], dlc));
+            continue;
+        }
+        if data.len() % 2 != 0 || data.len() > 16 {
+            return Err(parse_error(line_number, format!("invalid data {data:?}")));
+        }
+        let bytes = (0..data.len())
+            .step_by(2)
+            .map(|i| parse_hex_byte(&data[i..i + 2], line_number))
+            .collect::<Result<Vec<u8>>>()?;
+        frames.push(network_frame(
+            timestamp,
+            bus_id,
+            id,
+            ide,
+            false,
+            &bytes,
+            bytes.len() as u8,
+        ));
+    }
+    Ok(frames)
+}
+
+fn parse_timestamp(token: &str, line: usize) -> Result<Duration> {
+    let invalid = || parse_error(line, format!("invalid timestamp {token:?}"));
+    let token = token
+        .strip_prefix('(')
+        .and_then(|token| token.strip_suffix(')'))
+        .ok_or_else(invalid)?;
+    let (secs, fraction) = token.split_once('.').unwrap_or((token, "0"));
+    let secs = secs.parse::<u64>().map_err(|_| invalid())?;
+    // the fraction is usually given in microseconds, but any precision up to ns is accepted.
+    if fraction.is_empty() || fraction.len() > 9 {
+        return Err(invalid());
+    }
+    let nanos = fraction.parse::<u32>().map_err(|_| invalid())? * 10u32.pow(9 - fraction.len() as u32);
+    Ok(Duration::new(secs, nanos))
+}
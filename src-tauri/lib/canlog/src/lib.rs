@@ -0,0 +1,252 @@
+use std::{
+    collections::BTreeMap,
+    fmt::Display,
+    fs::File,
+    io::{BufWriter, Write},
+    path::Path,
+    str::FromStr,
+    time::Duration,
+};
+
+use canzero_common::{CanFrame, NetworkFrame, TNetworkFrame, Timestamped};
+use canzero_config::config::bus::BusRef;
+
+mod asc;
+mod candump;
+mod trc;
+
+pub type Result<T> = std::result::Result<T, Error>;
+
+#[derive(Debug)]
+pub enum Error {
+    Io(std::io::Error),
+    Parse { line: usize, reason: String },
+    UnknownChannel(String),
+    UnknownFormat(String),
+}
+
+impl From<std::io::Error> for Error {
+    fn from(value: std::io::Error) -> Self {
+        Error::Io(value)
+    }
+}
+
+impl Display for Error {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match &self {
+            Error::Io(err) => write!(f, "{err}"),
+            Error::Parse { line, reason } => write!(f, "line {line} : {reason}"),
+            Error::UnknownChannel(channel) => {
+                write!(f, "channel {channel} is not a bus of the network")
+            }
+            Error::UnknownFormat(format) => {
+                write!(f, "unknown log format {format} (expected candump, asc or trc)")
+            }
+        }
+    }
+}
+
+impl std::error::Error for Error {}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LogFormat {
+    /// Linux can-utils `candump -l`.
+    Candump,
+    /// Vector ASCII log.
+    Asc,
+    /// PEAK trace file (version 2.1).
+    Trc,
+}
+
+impl LogFormat {
+    /// Guesses the format from the file extension.
+    pub fn from_path(path: &Path) -> Option<Self> {
+        match path.extension()?.to_str()?.to_lowercase().as_str() {
+            "log" | "candump" => Some(LogFormat::Candump),
+            "asc" => Some(LogFormat::Asc),
+            "trc" => Some(LogFormat::Trc),
+            _ => None,
+        }
+    }
+}
+
+impl FromStr for LogFormat {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.to_lowercase().as_str() {
+            "candump" | "log" => Ok(LogFormat::Candump),
+            "asc" | "vector" => Ok(LogFormat::Asc),
+            "trc" | "peak" => Ok(LogFormat::Trc),
+            _ => Err(Error::UnknownFormat(s.to_owned())),
+        }
+    }
+}
+
+/// Maps the bus ids of `NetworkFrame`s to the channel names written to logs.
+///
+/// candump refers to channels by name, ASC and TRC use the channel number `bus_id + 1`.
+#[derive(Debug, Clone, Default)]
+pub struct ChannelMap {
+    names: BTreeMap<u32, String>,
+}
+
+impl ChannelMap {
+    pub fn new(channels: impl IntoIterator<Item = (u32, String)>) -> Self {
+        Self {
+            names: channels.into_iter().collect(),
+        }
+    }
+
+    pub fn from_buses(buses: &[BusRef]) -> Self {
+        Self::new(buses.iter().map(|bus| (bus.id(), bus.name().to_owned())))
+    }
+
+    pub fn name(&self, bus_id: u32) -> String {
+        match self.names.get(&bus_id) {
+            Some(name) => name.clone(),
+            None => format!("can{bus_id}"),
+        }
+    }
+
+    pub fn bus_id(&self, name: &str) -> Option<u32> {
+        self.names
+            .iter()
+            .find(|(_, bus_name)| bus_name.as_str() == name)
+            .map(|(id, _)| *id)
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (u32, &str)> {
+        self.names.iter().map(|(id, name)| (*id, name.as_str()))
+    }
+}
+
+/// Writes frames to a log file, the header is written on creation and
+/// the footer by `finish`.
+pub struct LogWriter<W: Write> {
+    writer: W,
+    format: LogFormat,
+    channels: ChannelMap,
+    count: usize,
+}
+
+impl LogWriter<BufWriter<File>> {
+    pub fn create(path: &Path, format: LogFormat, channels: ChannelMap) -> Result<Self> {
+        Self::new(BufWriter::new(File::create(path)?), format, channels)
+    }
+}
+
+impl<W: Write> LogWriter<W> {
+    pub fn new(mut writer: W, format: LogFormat, channels: ChannelMap) -> Result<Self> {
+        let start = chrono::Local::now();
+        match format {
+            LogFormat::Candump => (),
+            LogFormat::Asc => asc::write_header(&mut writer, &channels, &start)?,
+            LogFormat::Trc => trc::write_header(&mut writer, &channels, &start)?,
+        }
+        Ok(Self {
+            writer,
+            format,
+            channels,
+            count: 0,
+        })
+    }
+
+    pub fn format(&self) -> LogFormat {
+        self.format
+    }
+
+    /// Number of frames written so far.
+    pub fn count(&self) -> usize {
+        self.count
+    }
+
+    pub fn write(&mut self, frame: &TNetworkFrame) -> Result<()> {
+        self.count += 1;
+        match self.format {
+            LogFormat::Candump => candump::write_frame(&mut self.writer, &self.channels, frame)?,
+            LogFormat::Asc => asc::write_frame(&mut self.writer, frame)?,
+            LogFormat::Trc => trc::write_frame(&mut self.writer, self.count, frame)?,
+        }
+        Ok(())
+    }
+
+    pub fn finish(mut self) -> Result<W> {
+        match self.format {
+            LogFormat::Candump | LogFormat::Trc => (),
+            LogFormat::Asc => asc::write_footer(&mut self.writer)?,
+        }
+        self.writer.flush()?;
+        Ok(self.writer)
+    }
+}
+
+/// Reads all frames of a log file, the format is guessed from the extension if not given.
+pub fn read_log(
+    path: &Path,
+    format: Option<LogFormat>,
+    channels: &ChannelMap,
+) -> Result<Vec<TNetworkFrame>> {
+    let format = match format.or_else(|| LogFormat::from_path(path)) {
+        Some(format) => format,
+        None => {
+            return Err(Error::UnknownFormat(
+                path.to_str().unwrap_or_default().to_owned(),
+            ))
+        }
+    };
+    parse_log(&std::fs::read_to_string(path)?, format, channels)
+}
+
+/// Parses the frames of a log, timestamps are taken as they are written in the log.
+pub fn parse_log(src: &str, format: LogFormat, channels: &ChannelMap) -> Result<Vec<TNetworkFrame>> {
+    match format {
+        LogFormat::Candump => candump::parse(src, channels),
+        LogFormat::Asc => asc::parse(src),
+        LogFormat::Trc => trc::parse(src),
+    }
+}
+
+fn network_frame(
+    timestamp: Duration,
+    bus_id: u32,
+    id: u32,
+    ide: bool,
+    rtr: bool,
+    data: &[u8],
+    dlc: u8,
+) -> TNetworkFrame {
+    let mut value = 0u64;
+    for (i, byte) in data.iter().take(8).enumerate() {
+        value |= (*byte as u64) << (i * 8);
+    }
+    Timestamped::new(
+        timestamp,
+        NetworkFrame {
+            bus_id,
+            can_frame: CanFrame::new(id, ide, rtr, dlc, value),
+        },
+    )
+}
+
+/// The data bytes of the frame in order of transmission.
+fn data_bytes(can_frame: &CanFrame) -> Vec<u8> {
+    let data = can_frame.get_data_u64();
+    (0..can_frame.get_dlc().min(8) as usize)
+        .map(|i| (data >> (i * 8)) as u8)
+        .collect()
+}
+
+fn parse_hex_byte(token: &str, line: usize) -> Result<u8> {
+    u8::from_str_radix(token, 16).map_err(|_| Error::Parse {
+        line,
+        reason: format!("invalid data byte {token:?}"),
+    })
+}
+
+fn parse_error(line: usize, reason: impl Into<String>) -> Error {
+    Error::Parse {
+        line,
+        reason: reason.into(),
+    }
+}
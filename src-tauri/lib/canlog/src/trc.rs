@@ -0,0 +1,165 @@
+use std::{io::Write, time::Duration};
+
+use canzero_common::TNetworkFrame;
+use chrono::{DateTime, Local};
+
+use crate::{data_bytes, network_frame, parse_error, parse_hex_byte, ChannelMap, Result};
+
+const COLUMNS: [&str; 9] = ["N", "O", "T", "B", "I", "d", "R", "L", "D"];
+// column layout of version 1.x files, which don't declare their columns.
+const COLUMNS_V1: [&str; 6] = ["N", "O", "d", "I", "L", "D"];
+
+pub fn write_header<W: Write>(
+    writer: &mut W,
+    channels: &ChannelMap,
+    start: &DateTime<Local>,
+) -> std::io::Result<()> {
+    // days since 1899-12-30 (OLE automation date).
+    let start_time = start.timestamp_millis() as f64 / 86_400_000.0 + 25569.0;
+    writeln!(writer, ";$FILEVERSION=2.1")?;
+    writeln!(writer, ";$STARTTIME={start_time:.10}")?;
+    writeln!(writer, ";$COLUMNS={}", COLUMNS.join(","))?;
+    writeln!(writer, ";")?;
+    writeln!(
+        writer,
+        ";   Start time: {}",
+        start.format("%d.%m.%Y %H:%M:%S%.3f.0")
+    )?;
+    writeln!(writer, ";-------------------------------------------------------------------------------")?;
+    writeln!(writer, ";   Bus  Name")?;
+    for (bus_id, name) in channels.iter() {
+        writeln!(writer, ";   {:<4} {name}", bus_id + 1)?;
+    }
+    writeln!(writer, ";-------------------------------------------------------------------------------")?;
+    writeln!(writer, ";   Message   Time    Type    ID     Rx/Tx")?;
+    writeln!(writer, ";   Number    Offset  |  Bus  [hex]  |  Reserved")?;
+    writeln!(writer, ";   |         [ms]    |  |    |      |  |  Data Length Code")?;
+    writeln!(writer, ";   |         |       |  |    |      |  |  |    Data [hex] ...")?;
+    writeln!(writer, ";   |         |       |  |    |      |  |  |    |")?;
+    writeln!(writer, ";---+-- ------+------ +- +- --+----- +- +- +--- +- -- -- -- -- -- -- --")
+}
+
+/// `      1      1059.900 DT 1      0300 Rx -  8    00 00 00 00 04 00 00 00`,
+/// extended ids are written with 8 digits.
+pub fn write_frame<W: Write>(
+    writer: &mut W,
+    number: usize,
+    frame: &TNetworkFrame,
+) -> std::io::Result<()> {
+    let can_frame = &frame.can_frame;
+    let offset = format!(
+        "{}.{:03}",
+        frame.timestamp.as_millis(),
+        frame.timestamp.subsec_micros() % 1000
+    );
+    let id = if can_frame.get_ide_flag() {
+        format!("{:08X}", can_frame.get_id())
+    } else {
+        format!("{:04X}", can_frame.get_id())
+    };
+    let ty = if can_frame.get_rtr_flag() { "RR" } else { "DT" };
+    let data: Vec<String> = if can_frame.get_rtr_flag() {
+        vec![]
+    } else {
+        data_bytes(can_frame)
+            .iter()
+            .map(|byte| format!("{byte:02X}"))
+            .collect()
+    };
+    writeln!(
+        writer,
+        "{number:>7} {offset:>13} {ty} {:<2} {id:>8} Rx - {:>2}    {}",
+        frame.bus_id + 1,
+        can_frame.get_dlc(),
+        data.join(" ")
+    )
+}
+
+/// Parses data and remote frames, other record types (errors, status, ...) are skipped.
+/// The bus number `n` is mapped to the bus id `n - 1`.
+pub fn parse(src: &str) -> Result<Vec<TNetworkFrame>> {
+    let mut frames = vec![];
+    let mut columns: Vec<String> = COLUMNS_V1.iter().map(|c| c.to_string()).collect();
+    for (index, line) in src.lines().enumerate() {
+        let line_number = index + 1;
+        let line = line.trim();
+        if line.is_empty() {
+            continue;
+        }
+        if let Some(comment) = line.strip_prefix(';') {
+            if let Some(declared) = comment.trim().strip_prefix("$COLUMNS=") {
+                columns = declared.split(',').map(|c| c.trim().to_owned()).collect();
+            }
+            continue;
+        }
+        let tokens: Vec<&str> = line.split_whitespace().collect();
+        let mut timestamp = Duration::ZERO;
+        let mut bus_id = 0;
+        let mut id = None;
+        let mut rtr = false;
+        let mut dlc = 0u8;
+        let mut data = vec![];
+        let mut skip = false;
+        for (i, column) in columns.iter().enumerate() {
+            if column == "D" {
+                for token in tokens.iter().skip(i).take(dlc.min(8) as usize) {
+                    data.push(parse_hex_byte(token, line_number)?);
+                }
+                break;
+            }
+            let Some(token) = tokens.get(i) else {
+                return Err(parse_error(line_number, format!("missing column {column}")));
+            };
+            match column.as_str() {
+                "O" => {
+                    let millis = token.parse::<f64>().map_err(|_| {
+                        parse_error(line_number, format!("invalid time offset {token:?}"))
+                    })?;
+                    timestamp = Duration::from_nanos((millis * 1e6).round() as u64);
+                }
+                "T" => match *token {
+                    "DT" => rtr = false,
+                    "RR" => rtr = true,
+                    // error, status, CAN FD, ... records.
+                    _ => {
+                        skip = true;
+                        break;
+                    }
+                },
+                "B" => {
+                    bus_id = token.parse::<u32>().map_err(|_| {
+                        parse_error(line_number, format!("invalid bus {token:?}"))
+                    })?;
+                    bus_id = bus_id.saturating_sub(1);
+                }
+                "I" => {
+                    id = Some((
+                        u32::from_str_radix(token, 16).map_err(|_| {
+                            parse_error(line_number, format!("invalid id {token:?}"))
+                        })?,
+                        token.len() > 4,
+                    ))
+                }
+                "L" | "l" => {
+                    dlc = token.parse::<u8>().map_err(|_| {
+                        parse_error(line_number, format!("invalid dlc {token:?}"))
+                    })?
+                }
+                "d" if *token == "RTR" => rtr = true,
+                // number, reserved, ...
+                _ => (),
+            }
+        }
+        let Some((id, ide)) = id else {
+            continue;
+        };
+        if skip {
+            continue;
+        }
+        if !rtr && data.len() < dlc.min(8) as usize {
+            return Err(parse_error(line_number, "missing data bytes"));
+        }
+        frames.push(network_frame(timestamp, bus_id, id, ide, rtr, &data, dlc));
+    }
+    Ok(frames)
+}
@@ -0,0 +1,101 @@
+use std::time::Duration;
+
+use canzero_canlog::{parse_log, ChannelMap, LogFormat, LogWriter};
+use canzero_common::{CanFrame, NetworkFrame, TNetworkFrame, Timestamped};
+
+fn frames() -> Vec<TNetworkFrame> {
+    vec![
+        Timestamped::new(
+            Duration::from_micros(12_345),
+            NetworkFrame {
+                bus_id: 0,
+                can_frame: CanFrame::new(0x123, false, false, 2, 0xBEEF),
+            },
+        ),
+        Timestamped::new(
+            Duration::from_micros(1_500_001),
+            NetworkFrame {
+                bus_id: 1,
+                can_frame: CanFrame::new(0x1ABCDEF, true, false, 8, 0x0807060504030201),
+            },
+        ),
+        Timestamped::new(
+            Duration::from_micros(2_000_000),
+            NetworkFrame {
+                bus_id: 1,
+                can_frame: CanFrame::new(0x42, false, true, 0, 0),
+            },
+        ),
+    ]
+}
+
+fn roundtrip(format: LogFormat) {
+    let channels = ChannelMap::new([(0, "can0".to_owned()), (1, "can1".to_owned())]);
+    let mut writer = LogWriter::new(vec![], format, channels.clone()).unwrap();
+    for frame in &frames() {
+        writer.write(frame).unwrap();
+    }
+    let log = String::from_utf8(writer.finish().unwrap()).unwrap();
+
+    let parsed = parse_log(&log, format, &channels).unwrap();
+    assert_eq!(parsed.len(), frames().len(), "{log}");
+    for (parsed, expected) in parsed.iter().zip(frames().iter()) {
+        assert_eq!(parsed.timestamp, expected.timestamp, "{format:?}");
+        assert_eq!(parsed.bus_id, expected.bus_id, "{format:?}");
+        assert_eq!(parsed.can_frame.key(), expected.can_frame.key(), "{format:?}");
+        assert_eq!(parsed.can_frame.get_data_u64(), expected.can_frame.get_data_u64());
+        if !expected.can_frame.get_rtr_flag() {
+            assert_eq!(parsed.can_frame.get_dlc(), expected.can_frame.get_dlc());
+        }
+    }
+}
+
+#[test]
+fn candump_roundtrip() {
+    roundtrip(LogFormat::Candump);
+}
+
+#[test]
+fn asc_roundtrip() {
+    roundtrip(LogFormat::Asc);
+}
+
+#[test]
+fn trc_roundtrip() {
+    roundtrip(LogFormat::Trc);
+}
+
+#[test]
+fn candump_from_can_utils() {
+    let log = "(1436509052.249713) vcan0 44C#4F\n(1436509052.449847) vcan0 0C8FC5F2#R\n";
+    let channels = ChannelMap::new([(3, "vcan0".to_owned())]);
+    let frames = parse_log(log, LogFormat::Candump, &channels).unwrap();
+    assert_eq!(frames.len(), 2);
+    assert_eq!(frames[0].bus_id, 3);
+    assert_eq!(frames[0].can_frame.get_id(), 0x44C);
+    assert_eq!(frames[0].can_frame.get_data_u64(), 0x4F);
+    assert!(frames[1].can_frame.get_ide_flag());
+    assert!(frames[1].can_frame.get_rtr_flag());
+    assert!(parse_log(log, LogFormat::Candump, &ChannelMap::default()).is_err());
+}
+
+#[test]
+fn asc_base_dec() {
+    let log = "\
+date Mon Oct 19 08:00:00.000 am 2026
+base dec  timestamps absolute
+   0.012345 1  291             Rx   d 10 1 2 3 4 5 6 7 8
+   1.500000 2  1000x           Rx   d 2 255 16
+";
+    let channels = ChannelMap::new([(0, "can0".to_owned()), (1, "can1".to_owned())]);
+    let frames = parse_log(log, LogFormat::Asc, &channels).unwrap();
+    assert_eq!(frames.len(), 2);
+    assert_eq!(frames[0].can_frame.get_id(), 291);
+    assert_eq!(frames[0].can_frame.get_dlc(), 10);
+    assert_eq!(frames[0].can_frame.get_data_u64(), 0x0807060504030201);
+    assert_eq!(frames[1].bus_id, 1);
+    assert_eq!(frames[1].can_frame.get_id(), 1000);
+    assert!(frames[1].can_frame.get_ide_flag());
+    assert_eq!(frames[1].can_frame.get_dlc(), 2);
+    assert_eq!(frames[1].can_frame.get_data_u64(), 0x10FF);
+}
@@ -0,0 +1,346 @@
+use canzero_config::config::{Message, SignalRef, SignalType, Type, TypeSignalEncoding};
+
+/// A primitive part of a type, object entries are stored as the raw values of their leaves
+/// in the order of `Value::get_as_bin`.
+#[derive(Debug, Clone)]
+pub enum Leaf {
+    Signal(SignalType),
+    Enum { size: u8, entries: Vec<(String, u64)> },
+}
+
+impl Leaf {
+    pub fn size(&self) -> u8 {
+        match self {
+            Leaf::Signal(signal_type) => signal_type.size(),
+            Leaf::Enum { size, entries: _ } => *size,
+        }
+    }
+
+    pub fn physical(&self, raw: u64) -> f64 {
+        match self {
+            Leaf::Signal(SignalType::UnsignedInt { size: _ }) => raw as f64,
+            Leaf::Signal(SignalType::SignedInt { size }) => {
+                let shift = 64 - *size as u32;
+                ((raw << shift) as i64 >> shift) as f64
+            }
+            Leaf::Signal(SignalType::Decimal {
+                size: _,
+                offset,
+                scale,
+            }) => raw as f64 * scale + offset,
+            Leaf::Enum {
+                size: _,
+                entries: _,
+            } => raw as f64,
+        }
+    }
+
+    /// Saturates at the limits of the leaf, enums fall back to their first entry
+    /// if the value is not a discriminant.
+    pub fn raw(&self, value: f64) -> u64 {
+        let max = mask(self.size());
+        match self {
+            Leaf::Signal(SignalType::UnsignedInt { size: _ }) => {
+                value.round().clamp(0.0, max as f64) as u64
+            }
+            Leaf::Signal(SignalType::SignedInt { size }) => {
+                let limit = (1u64 << (*size - 1)) as f64;
+                (value.round().clamp(-limit, limit - 1.0) as i64) as u64 & max
+            }
+            Leaf::Signal(SignalType::Decimal {
+                size: _,
+                offset,
+                scale,
+            }) => ((value - offset) / scale).round().clamp(0.0, max as f64) as u64,
+            Leaf::Enum { size: _, entries } => {
+                let discriminant = value.round() as u64;
+                if entries.iter().any(|(_, entry)| *entry == discriminant) {
+                    discriminant
+                } else {
+                    entries.first().map(|(_, entry)| *entry).unwrap_or(0)
+                }
+            }
+        }
+    }
+}
+
+pub fn mask(size: u8) -> u64 {
+    u64::MAX.checked_shr(64 - size as u32).unwrap_or(0)
+}
+
+/// Leaves of a type with their attribute path relative to the type, primitive types
+/// have a single leaf with a empty path.
+pub fn type_leaves(ty: &Type) -> Vec<(String, Leaf)> {
+    fn collect(ty: &Type, path: String, leaves: &mut Vec<(String, Leaf)>) {
+        match ty {
+            Type::Primitive(signal_type) => leaves.push((path, Leaf::Signal(signal_type.clone()))),
+            Type::Struct {
+                name: _,
+                description: _,
+                attribs,
+                visibility: _,
+            } => {
+                for (name, attrib_ty) in attribs {
+                    let path = if path.is_empty() {
+                        name.clone()
+                    } else {
+                        format!("{path}.{name}")
+                    };
+                    collect(attrib_ty, path, leaves);
+                }
+            }
+            Type::Enum {
+                name: _,
+                description: _,
+                size,
+                entries,
+                visibility: _,
+            } => leaves.push((
+                path,
+                Leaf::Enum {
+                    size: *size,
+                    entries: entries.clone(),
+                },
+            )),
+            Type::Array { len, ty } => {
+                for i in 0..*len {
+                    let path = if path.is_empty() {
+                        i.to_string()
+                    } else {
+                        format!("{path}.{i}")
+                    };
+                    collect(ty, path, leaves);
+                }
+            }
+        }
+    }
+    let mut leaves = vec![];
+    collect(ty, String::new(), &mut leaves);
+    leaves
+}
+
+/// Packs raw leaf values into the 32 bit words of a get response or set request.
+pub fn to_words(leaves: &[(String, Leaf)], raw: &[u64]) -> Vec<u32> {
+    let size: u32 = leaves.iter().map(|(_, leaf)| leaf.size() as u32).sum();
+    let mut words = vec![0u32; size.div_ceil(32).max(1) as usize];
+    let mut offset = 0usize;
+    for ((_, leaf), value) in leaves.iter().zip(raw) {
+        for i in 0..leaf.size() as usize {
+            if (value >> i) & 0x1 != 0 {
+                words[(offset + i) / 32] |= 1 << ((offset + i) % 32);
+            }
+        }
+        offset += leaf.size() as usize;
+    }
+    words
+}
+
+pub fn from_words(leaves: &[(String, Leaf)], words: &[u32]) -> Vec<u64> {
+    let mut offset = 0usize;
+    leaves
+        .iter()
+        .map(|(_, leaf)| {
+            let mut value = 0u64;
+            for i in 0..leaf.size() as usize {
+                let bit = offset + i;
+                if words.get(bit / 32).is_some_and(|word| (word >> (bit % 32)) & 0x1 != 0) {
+                    value |= 1 << i;
+                }
+            }
+            offset += leaf.size() as usize;
+            value
+        })
+        .collect()
+}
+
+/// The signals of a message with the attribute path of the encoding, messages without
+/// encoding are addressed by their signal names.
+pub fn message_signals(message: &Message) -> Vec<(String, SignalRef)> {
+    fn collect(
+        attribute: &TypeSignalEncoding,
+        path: String,
+        signals: &mut Vec<(String, SignalRef)>,
+    ) {
+        match attribute {
+            TypeSignalEncoding::Primitive(primitive) => {
+                signals.push((path, primitive.signal().clone()))
+            }
+            TypeSignalEncoding::Composite(composite) => {
+                for attribute in composite.attributes() {
+                    collect(attribute, format!("{path}.{}", attribute.name()), signals);
+                }
+            }
+        }
+    }
+    match message.encoding() {
+        Some(encoding) => {
+            let mut signals = vec![];
+            for attribute in encoding.attributes() {
+                collect(attribute, attribute.name().to_owned(), &mut signals);
+            }
+            signals
+        }
+        None => message
+            .signals()
+            .iter()
+            .map(|signal| (signal.name().to_owned(), signal.clone()))
+            .collect(),
+    }
+}
+
+/// The signals of each top level attribute of the encoding, a stream maps one object
+/// entry to each of them.
+pub fn attribute_signals(message: &Message) -> Vec<Vec<SignalRef>> {
+    let Some(encoding) = message.encoding() else {
+        return message.signals().iter().map(|signal| vec![signal.clone()]).collect();
+    };
+    encoding
+        .attributes()
+        .iter()
+        .map(|attribute| {
+            let mut signals = vec![];
+            let mut stack = vec![attribute];
+            while let Some(attribute) = stack.pop() {
+                match attribute {
+                    TypeSignalEncoding::Primitive(primitive) => {
+                        signals.push(primitive.signal().clone())
+                    }
+                    TypeSignalEncoding::Composite(composite) => {
+                        stack.extend(composite.attributes().iter().rev())
+                    }
+                }
+            }
+            signals
+        })
+        .collect()
+}
+
+pub fn read_signal(data: u64, signal: &SignalRef) -> u64 {
+    (data >> signal.byte_offset()) & mask(signal.size())
+}
+
+pub fn write_signal(data: &mut u64, signal: &SignalRef, raw: u64) {
+    let mask = mask(signal.size()) << signal.byte_offset();
+    *data = (*data & !mask) | ((raw << signal.byte_offset()) & mask);
+}
+
+/// Looks up signals of a message by attribute path, e.g. `header.od_index`.
+pub struct MessageLayout {
+    signals: Vec<(String, SignalRef)>,
+}
+
+impl MessageLayout {
+    pub fn new(message: &Message) -> Self {
+        Self {
+            signals: message_signals(message),
+        }
+    }
+
+    fn signal(&self, path: &str) -> Option<&SignalRef> {
+        self.signals
+            .iter()
+            .find(|(name, _)| name == path)
+            .map(|(_, signal)| signal)
+    }
+
+    pub fn read(&self, data: u64, path: &str) -> Option<u64> {
+        self.signal(path).map(|signal| read_signal(data, signal))
+    }
+
+    pub fn write(&self, data: &mut u64, path: &str, raw: u64) {
+        if let Some(signal) = self.signal(path) {
+            write_signal(data, signal, raw);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use canzero_config::config::{NetworkRef, ObjectEntryRef};
+    use canzero_yaml::parse_yaml_config_from_file;
+
+    use super::*;
+
+    fn network() -> NetworkRef {
+        parse_yaml_config_from_file("../codegen/examples/conformance.yaml").unwrap()
+    }
+
+    fn object_entry(network: &NetworkRef, name: &str) -> ObjectEntryRef {
+        network.nodes()[0]
+            .object_entries()
+            .iter()
+            .find(|object_entry| object_entry.name() == name)
+            .cloned()
+            .unwrap()
+    }
+
+    #[test]
+    fn words_round_trip() {
+        let network = network();
+        for object_entry in network.nodes()[0].object_entries() {
+            let leaves = type_leaves(object_entry.ty());
+            let raw: Vec<u64> = leaves
+                .iter()
+                .enumerate()
+                .map(|(i, (_, leaf))| {
+                    0xA5C3_5A3C_F00F_0FF0u64.rotate_left(7 * i as u32) & mask(leaf.size())
+                })
+                .collect();
+            let size: u32 = leaves.iter().map(|(_, leaf)| leaf.size() as u32).sum();
+            let words = to_words(&leaves, &raw);
+            assert_eq!(words.len(), size.div_ceil(32) as usize, "{}", object_entry.name());
+            assert_eq!(from_words(&leaves, &words), raw, "{}", object_entry.name());
+        }
+    }
+
+    #[test]
+    fn struct_leaves_are_addressed_by_path() {
+        let network = network();
+        let leaves = type_leaves(object_entry(&network, "pose").ty());
+        let paths: Vec<&str> = leaves.iter().map(|(path, _)| path.as_str()).collect();
+        assert_eq!(
+            paths,
+            vec!["position.x", "position.y", "position.z", "heading", "mode", "valid"]
+        );
+        let leaves = type_leaves(object_entry(&network, "u8_value").ty());
+        assert_eq!(leaves[0].0, "");
+    }
+
+    #[test]
+    fn raw_values_round_trip_and_saturate() {
+        let signed = Leaf::Signal(SignalType::SignedInt { size: 13 });
+        assert_eq!(signed.physical(signed.raw(-5.0)), -5.0);
+        assert_eq!(signed.physical(signed.raw(1e6)), 4095.0);
+        assert_eq!(signed.physical(signed.raw(-1e6)), -4096.0);
+
+        let unsigned = Leaf::Signal(SignalType::UnsignedInt { size: 8 });
+        assert_eq!(unsigned.raw(-1.0), 0);
+        assert_eq!(unsigned.raw(300.0), 255);
+
+        let network = network();
+        let leaves = type_leaves(object_entry(&network, "d16_value").ty());
+        let decimal = &leaves[0].1;
+        assert!((decimal.physical(decimal.raw(12.5)) - 12.5).abs() < 0.01);
+        assert_eq!(decimal.physical(decimal.raw(1000.0)), decimal.physical(mask(16)));
+
+        let leaves = type_leaves(object_entry(&network, "mode").ty());
+        let mode = &leaves[0].1;
+        assert_eq!(mode.raw(7.0), 7);
+        // not a discriminant of drive_mode.
+        assert_eq!(mode.raw(5.0), 0);
+    }
+
+    #[test]
+    fn message_layout_round_trip() {
+        let network = network();
+        let layout = MessageLayout::new(network.get_req_message());
+        let mut data = 0u64;
+        layout.write(&mut data, "header.od_index", 17);
+        layout.write(&mut data, "header.client_id", 3);
+        layout.write(&mut data, "header.server_id", 1);
+        assert_eq!(layout.read(data, "header.od_index"), Some(17));
+        assert_eq!(layout.read(data, "header.client_id"), Some(3));
+        assert_eq!(layout.read(data, "header.server_id"), Some(1));
+        assert_eq!(layout.read(data, "header.unknown"), None);
+    }
+}
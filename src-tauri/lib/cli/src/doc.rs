@@ -0,0 +1,711 @@
+use std::{path::PathBuf, time::Duration};
+
+use canzero_appdata::AppData;
+use canzero_config::config::{
+    self, message::MessageUsage, Message, Network, ObjectEntryAccess, SignalType, Type,
+};
+
+use crate::errors::{Error, Result};
+
+#[derive(Debug, Clone, Copy)]
+enum DocFormat {
+    Markdown,
+    Html,
+}
+
+fn parse_format(format: &str) -> Result<DocFormat> {
+    match format.to_lowercase().as_str() {
+        "markdown" | "md" => Ok(DocFormat::Markdown),
+        "html" | "htm" => Ok(DocFormat::Html),
+        _ => Err(Error::InvalidDocFormat(format.to_owned())),
+    }
+}
+
+pub fn command_config_doc(format: Option<String>, output: Option<PathBuf>) -> Result<()> {
+    let appdata = AppData::read()?;
+    let network = appdata.config()?;
+
+    // without an explicit format we go by the extension of the output file.
+    let format = match format {
+        Some(format) => parse_format(&format)?,
+        None => match output
+            .as_ref()
+            .and_then(|path| path.extension())
+            .and_then(|ext| ext.to_str())
+        {
+            Some(ext) => parse_format(ext).unwrap_or(DocFormat::Markdown),
+            None => DocFormat::Markdown,
+        },
+    };
+
+    let doc = match format {
+        DocFormat::Markdown => render(&network, Markdown::default()),
+        DocFormat::Html => render(&network, Html::default()),
+    };
+
+    match output {
+        Some(path) => std::fs::write(path, doc)?,
+        None => print!("{doc}"),
+    }
+    Ok(())
+}
+
+/// Output primitives of a document format. Text passed to `heading` and
+/// `paragraph` is already formatted with `text`, `code` or `link`.
+trait DocWriter {
+    fn text(&self, text: &str) -> String;
+    fn code(&self, text: &str) -> String;
+    fn link(&self, anchor: &str, text: &str) -> String;
+    fn heading(&mut self, level: usize, anchor: &str, title: &str);
+    fn paragraph(&mut self, text: &str);
+    fn list(&mut self, items: &[String]);
+    fn table(&mut self, header: &[&str], rows: &[Vec<String>]);
+    fn finish(self, title: &str) -> String;
+}
+
+#[derive(Default)]
+struct Markdown {
+    body: String,
+}
+
+impl DocWriter for Markdown {
+    fn text(&self, text: &str) -> String {
+        let mut escaped = String::with_capacity(text.len());
+        for c in text.chars() {
+            match c {
+                '|' | '*' | '_' | '`' | '[' | ']' | '<' | '>' | '\\' => {
+                    escaped.push('\\');
+                    escaped.push(c);
+                }
+                '\n' => escaped.push(' '),
+                _ => escaped.push(c),
+            }
+        }
+        escaped
+    }
+    fn code(&self, text: &str) -> String {
+        format!("`{}`", text.replace('|', "\\|"))
+    }
+    fn link(&self, anchor: &str, text: &str) -> String {
+        format!("[{}](#{anchor})", self.text(text))
+    }
+    fn heading(&mut self, level: usize, anchor: &str, title: &str) {
+        // explicit anchors, because the generated heading ids differ between renderers.
+        self.body.push_str(&format!("<a id=\"{anchor}\"></a>\n\n"));
+        self.body
+            .push_str(&format!("{} {title}\n\n", "#".repeat(level.clamp(1, 6))));
+    }
+    fn paragraph(&mut self, text: &str) {
+        self.body.push_str(&format!("{text}\n\n"));
+    }
+    fn list(&mut self, items: &[String]) {
+        for item in items {
+            self.body.push_str(&format!("- {item}\n"));
+        }
+        self.body.push('\n');
+    }
+    fn table(&mut self, header: &[&str], rows: &[Vec<String>]) {
+        self.body.push_str(&format!("| {} |\n", header.join(" | ")));
+        self.body
+            .push_str(&format!("|{}\n", " --- |".repeat(header.len())));
+        for row in rows {
+            self.body.push_str(&format!("| {} |\n", row.join(" | ")));
+        }
+        self.body.push('\n');
+    }
+    fn finish(self, title: &str) -> String {
+        format!("# {title}\n\n{}", self.body)
+    }
+}
+
+#[derive(Default)]
+struct Html {
+    body: String,
+}
+
+impl DocWriter for Html {
+    fn text(&self, text: &str) -> String {
+        let mut escaped = String::with_capacity(text.len());
+        for c in text.chars() {
+            match c {
+                '&' => escaped.push_str("&amp;"),
+                '<' => escaped.push_str("&lt;"),
+                '>' => escaped.push_str("&gt;"),
+                '"' => escaped.push_str("&quot;"),
+                _ => escaped.push(c),
+            }
+        }
+        escaped
+    }
+    fn code(&self, text: &str) -> String {
+        format!("<code>{}</code>", self.text(text))
+    }
+    fn link(&self, anchor: &str, text: &str) -> String {
+        format!("<a href=\"#{anchor}\">{}</a>", self.text(text))
+    }
+    fn heading(&mut self, level: usize, anchor: &str, title: &str) {
+        let level = level.clamp(1, 6);
+        self.body
+            .push_str(&format!("<h{level} id=\"{anchor}\">{title}</h{level}>\n"));
+    }
+    fn paragraph(&mut self, text: &str) {
+        self.body.push_str(&format!("<p>{text}</p>\n"));
+    }
+    fn list(&mut self, items: &[String]) {
+        self.body.push_str("<ul>\n");
+        for item in items {
+            self.body.push_str(&format!("<li>{item}</li>\n"));
+        }
+        self.body.push_str("</ul>\n");
+    }
+    fn table(&mut self, header: &[&str], rows: &[Vec<String>]) {
+        self.body.push_str("<table>\n<tr>");
+        for h in header {
+            self.body.push_str(&format!("<th>{h}</th>"));
+        }
+        self.body.push_str("</tr>\n");
+        for row in rows {
+            self.body.push_str("<tr>");
+            for cell in row {
+                self.body.push_str(&format!("<td>{cell}</td>"));
+            }
+            self.body.push_str("</tr>\n");
+        }
+        self.body.push_str("</table>\n");
+    }
+    fn finish(self, title: &str) -> String {
+        let title = self.text(title);
+        format!(
+            "<!DOCTYPE html>
+<html>
+<head>
+<meta charset=\"utf-8\">
+<title>{title}</title>
+<style>
+body {{ font-family: sans-serif; max-width: 1200px; margin: auto; }}
+table {{ border-collapse: collapse; margin-bottom: 1em; }}
+th, td {{ border: 1px solid #999; padding: 2px 6px; text-align: left; }}
+code {{ background: #eee; }}
+</style>
+</head>
+<body>
+<h1>{title}</h1>
+{}</body>
+</html>
+",
+            self.body
+        )
+    }
+}
+
+fn node_anchor(name: &str) -> String {
+    format!("node-{name}")
+}
+
+fn message_anchor(name: &str) -> String {
+    format!("message-{name}")
+}
+
+fn type_anchor(name: &str) -> String {
+    format!("type-{name}")
+}
+
+fn bus_anchor(name: &str) -> String {
+    format!("bus-{name}")
+}
+
+fn format_duration(duration: &Duration) -> String {
+    if duration.subsec_micros() % 1000 == 0 {
+        format!("{}ms", duration.as_millis())
+    } else {
+        format!("{}us", duration.as_micros())
+    }
+}
+
+fn format_interval((min, max): &(Duration, Duration)) -> String {
+    if min == max {
+        format_duration(min)
+    } else {
+        format!("{} - {}", format_duration(min), format_duration(max))
+    }
+}
+
+fn format_id(message: &Message) -> String {
+    if message.id().ide() {
+        format!("0x{:08X} (ext)", message.id().as_u32())
+    } else {
+        format!("0x{:03X}", message.id().as_u32())
+    }
+}
+
+fn signal_type_name(ty: &SignalType) -> String {
+    match ty {
+        SignalType::UnsignedInt { size } => format!("u{size}"),
+        SignalType::SignedInt { size } => format!("i{size}"),
+        SignalType::Decimal {
+            size,
+            offset,
+            scale,
+        } => {
+            let max = (u64::MAX >> (64 - *size as u32)) as f64 * scale + offset;
+            format!("d{size}<{offset}..{max}>")
+        }
+    }
+}
+
+/// Type name, which links to the definition for named types.
+fn type_ref(w: &impl DocWriter, ty: &Type) -> String {
+    match ty {
+        Type::Primitive(signal_type) => w.code(&signal_type_name(signal_type)),
+        Type::Struct { name, .. } | Type::Enum { name, .. } => w.link(&type_anchor(name), name),
+        Type::Array { len, ty } => format!("{}{}", type_ref(w, ty), w.code(&format!("[{len}]"))),
+    }
+}
+
+fn message_ref(w: &impl DocWriter, message: &Message) -> String {
+    w.link(&message_anchor(message.name()), message.name())
+}
+
+fn usage_name(w: &impl DocWriter, message: &Message) -> String {
+    match message.usage() {
+        MessageUsage::Stream(stream) => format!("stream {}", w.code(stream.name())),
+        MessageUsage::CommandReq(command) => format!("command request {}", w.code(command.name())),
+        MessageUsage::CommandResp(command) => {
+            format!("command response {}", w.code(command.name()))
+        }
+        MessageUsage::GetResp => "get response".to_owned(),
+        MessageUsage::GetReq => "get request".to_owned(),
+        MessageUsage::SetResp => "set response".to_owned(),
+        MessageUsage::SetReq => "set request".to_owned(),
+        MessageUsage::SetStageReq => "set stage request".to_owned(),
+        MessageUsage::SetCommitReq => "set commit request".to_owned(),
+        MessageUsage::SetCommitResp => "set commit response".to_owned(),
+        MessageUsage::Heartbeat => "heartbeat".to_owned(),
+        MessageUsage::External { interval } => {
+            format!("external ({})", format_duration(interval))
+        }
+    }
+}
+
+fn optional_text(w: &impl DocWriter, text: Option<&str>) -> String {
+    text.map(|t| w.text(t)).unwrap_or_default()
+}
+
+fn render<W: DocWriter>(network: &Network, mut w: W) -> String {
+    let nodes: Vec<String> = network
+        .nodes()
+        .iter()
+        .map(|node| w.link(&node_anchor(node.name()), node.name()))
+        .collect();
+    let toc = [
+        w.link("buses", "Buses"),
+        format!("{} ({})", w.link("nodes", "Nodes"), nodes.join(", ")),
+        w.link("messages", "Messages"),
+        w.link("types", "Types"),
+    ];
+
+    w.paragraph(&format!(
+        "{} {}",
+        w.text("Interface control document generated from the network configuration built at"),
+        w.text(&network.build_time().format("%Y-%m-%d %H:%M:%S").to_string()),
+    ));
+    w.paragraph(&format!(
+        "{} {}",
+        w.text("Configuration hash:"),
+        w.code(&format!("{:016X}", network.portable_hash())),
+    ));
+    w.list(&toc);
+
+    render_buses(network, &mut w);
+    render_nodes(network, &mut w);
+    render_messages(network, &mut w);
+    render_types(network, &mut w);
+
+    w.finish("CANzero network")
+}
+
+fn render_buses(network: &Network, w: &mut impl DocWriter) {
+    w.heading(2, "buses", "Buses");
+    let rows: Vec<Vec<String>> = network
+        .buses()
+        .iter()
+        .map(|bus| {
+            let nodes: Vec<String> = network
+                .nodes()
+                .iter()
+                .filter(|node| node.buses().iter().any(|b| b.id() == bus.id()))
+                .map(|node| w.link(&node_anchor(node.name()), node.name()))
+                .collect();
+            vec![
+                w.link(&bus_anchor(bus.name()), bus.name()),
+                bus.id().to_string(),
+                format!("{} bit/s", bus.baudrate()),
+                nodes.join(", "),
+            ]
+        })
+        .collect();
+    w.table(&["Bus", "ID", "Baudrate", "Nodes"], &rows);
+
+    for bus in network.buses() {
+        w.heading(3, &bus_anchor(bus.name()), &w.text(bus.name()));
+        let mut messages: Vec<&config::MessageRef> = network
+            .messages()
+            .iter()
+            .filter(|message| message.bus().id() == bus.id())
+            .collect();
+        messages.sort_by_key(|message| (message.id().ide(), message.id().as_u32()));
+        let rows: Vec<Vec<String>> = messages
+            .iter()
+            .map(|message| {
+                vec![
+                    w.code(&format_id(message)),
+                    message_ref(w, message),
+                    message.dlc().to_string(),
+                    usage_name(w, message),
+                ]
+            })
+            .collect();
+        w.table(&["ID", "Message", "DLC", "Usage"], &rows);
+    }
+}
+
+fn render_nodes(network: &Network, w: &mut impl DocWriter) {
+    w.heading(2, "nodes", "Nodes");
+    for node in network.nodes() {
+        w.heading(3, &node_anchor(node.name()), &w.text(node.name()));
+        if let Some(description) = node.description() {
+            w.paragraph(&w.text(description));
+        }
+        let buses: Vec<String> = node
+            .buses()
+            .iter()
+            .map(|bus| w.link(&bus_anchor(bus.name()), bus.name()))
+            .collect();
+        w.list(&[
+            format!("{} {}", w.text("Node id:"), node.id()),
+            format!(
+                "{} {}",
+                w.text("Heartbeat timeout:"),
+                format_duration(&node.heartbeat_timeout())
+            ),
+            format!("{} {}", w.text("Buses:"), buses.join(", ")),
+        ]);
+
+        w.heading(4, &format!("{}-od", node_anchor(node.name())), "Object dictionary");
+        let rows: Vec<Vec<String>> = node
+            .object_entries()
+            .iter()
+            .map(|oe| {
+                let access = match oe.access() {
+                    ObjectEntryAccess::Const => "const",
+                    ObjectEntryAccess::Local => "read",
+                    ObjectEntryAccess::Global => "read/write",
+                };
+                vec![
+                    oe.id().to_string(),
+                    w.code(oe.name()),
+                    type_ref(w, oe.ty()),
+                    optional_text(w, oe.unit()),
+                    access.to_owned(),
+                    optional_text(w, oe.description()),
+                ]
+            })
+            .collect();
+        w.table(
+            &["ID", "Name", "Type", "Unit", "Access", "Description"],
+            &rows,
+        );
+
+        for (suffix, title, streams) in [
+            ("tx", "Transmitted streams", node.tx_streams()),
+            ("rx", "Received streams", node.rx_streams()),
+        ] {
+            if streams.is_empty() {
+                continue;
+            }
+            let anchor = format!("{}-{suffix}", node_anchor(node.name()));
+            w.heading(4, &anchor, title);
+            let rows: Vec<Vec<String>> = streams
+                .iter()
+                .map(|stream| {
+                    let entries: Vec<String> = stream
+                        .mapping()
+                        .iter()
+                        .map(|oe| match oe {
+                            Some(oe) => w.code(oe.name()),
+                            None => w.text("-"),
+                        })
+                        .collect();
+                    vec![
+                        w.code(stream.name()),
+                        message_ref(w, stream.message()),
+                        format_interval(stream.interval()),
+                        entries.join(", "),
+                        optional_text(w, stream.description()),
+                    ]
+                })
+                .collect();
+            w.table(
+                &["Stream", "Message", "Interval", "Entries", "Description"],
+                &rows,
+            );
+        }
+
+        if !node.commands().is_empty() {
+            w.heading(4, &format!("{}-commands", node_anchor(node.name())), "Commands");
+            let rows: Vec<Vec<String>> = node
+                .commands()
+                .iter()
+                .map(|command| {
+                    let args: Vec<String> = command
+                        .tx_message()
+                        .encoding()
+                        .map(|encoding| {
+                            encoding
+                                .attributes()
+                                .iter()
+                                .map(|attrib| {
+                                    format!(
+                                        "{} : {}",
+                                        w.code(attrib.name()),
+                                        type_ref(w, attrib.ty())
+                                    )
+                                })
+                                .collect()
+                        })
+                        .unwrap_or_default();
+                    vec![
+                        format!("{}({})", w.code(command.name()), args.join(", ")),
+                        message_ref(w, command.tx_message()),
+                        message_ref(w, command.rx_message()),
+                        optional_text(w, command.description().map(|d| d.as_str())),
+                    ]
+                })
+                .collect();
+            w.table(&["Command", "Request", "Response", "Description"], &rows);
+        }
+
+        if !node.extern_commands().is_empty() {
+            let calls: Vec<String> = node
+                .extern_commands()
+                .iter()
+                .map(|(node_name, command)| {
+                    format!(
+                        "{}::{}",
+                        w.link(&node_anchor(node_name), node_name),
+                        w.code(command.name())
+                    )
+                })
+                .collect();
+            w.paragraph(&format!("{} {}", w.text("Calls commands:"), calls.join(", ")));
+        }
+    }
+}
+
+fn render_messages(network: &Network, w: &mut impl DocWriter) {
+    w.heading(2, "messages", "Messages");
+    for message in network.messages() {
+        w.heading(3, &message_anchor(message.name()), &w.text(message.name()));
+        if let Some(description) = message.description() {
+            w.paragraph(&w.text(description));
+        }
+        w.list(&[
+            format!("{} {}", w.text("ID:"), w.code(&format_id(message))),
+            format!(
+                "{} {}",
+                w.text("Bus:"),
+                w.link(&bus_anchor(message.bus().name()), message.bus().name())
+            ),
+            format!("{} {}", w.text("DLC:"), message.dlc()),
+            format!("{} {}", w.text("Usage:"), usage_name(w, message)),
+        ]);
+        if message.signals().is_empty() {
+            continue;
+        }
+
+        // signals are numbered, the layout grid refers to them by number.
+        let rows: Vec<Vec<String>> = message
+            .signals()
+            .iter()
+            .enumerate()
+            .map(|(i, signal)| {
+                let start = signal.byte_offset();
+                let end = start + signal.size() as usize - 1;
+                vec![
+                    i.to_string(),
+                    w.code(signal.name()),
+                    format!("{start}..{end}"),
+                    w.code(&signal_type_name(signal.ty())),
+                    optional_text(w, signal.description()),
+                ]
+            })
+            .collect();
+        w.table(&["#", "Signal", "Bits", "Type", "Description"], &rows);
+
+        // bit layout (little endian), one row per byte with the LSB first.
+        let mut grid = vec![w.text("-"); message.dlc() as usize * 8];
+        for (i, signal) in message.signals().iter().enumerate() {
+            let start = signal.byte_offset();
+            for bit in start..start + signal.size() as usize {
+                if let Some(cell) = grid.get_mut(bit) {
+                    *cell = i.to_string();
+                }
+            }
+        }
+        let rows: Vec<Vec<String>> = grid
+            .chunks(8)
+            .enumerate()
+            .map(|(byte, bits)| {
+                let mut row = vec![byte.to_string()];
+                row.extend(bits.iter().cloned());
+                row
+            })
+            .collect();
+        w.table(
+            &["Byte", "0", "1", "2", "3", "4", "5", "6", "7"],
+            &rows,
+        );
+    }
+}
+
+fn render_types(network: &Network, w: &mut impl DocWriter) {
+    w.heading(2, "types", "Types");
+    for ty in network.types() {
+        match ty as &Type {
+            Type::Struct {
+                name,
+                description,
+                attribs,
+                visibility: _,
+            } => {
+                w.heading(3, &type_anchor(name), &format!("{} (struct)", w.text(name)));
+                if let Some(description) = description {
+                    w.paragraph(&w.text(description));
+                }
+                let rows: Vec<Vec<String>> = attribs
+                    .iter()
+                    .map(|(attrib_name, attrib_ty)| {
+                        vec![
+                            w.code(attrib_name),
+                            type_ref(w, attrib_ty),
+                            attrib_ty.size().to_string(),
+                        ]
+                    })
+                    .collect();
+                w.table(&["Attribute", "Type", "Bits"], &rows);
+            }
+            Type::Enum {
+                name,
+                description,
+                size,
+                entries,
+                visibility: _,
+            } => {
+                w.heading(
+                    3,
+                    &type_anchor(name),
+                    &format!("{} (enum, {size} bits)", w.text(name)),
+                );
+                if let Some(description) = description {
+                    w.paragraph(&w.text(description));
+                }
+                let rows: Vec<Vec<String>> = entries
+                    .iter()
+                    .map(|(entry_name, value)| vec![w.code(entry_name), value.to_string()])
+                    .collect();
+                w.table(&["Entry", "Value"], &rows);
+            }
+            Type::Primitive(_) | Type::Array { .. } => (),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use canzero_yaml::parse_yaml_config_from_file;
+
+    use super::*;
+
+    fn write_sample(mut w: impl DocWriter) -> String {
+        w.heading(2, "nodes", "Nodes");
+        w.paragraph(&format!("{} {}", w.text("a|b <c>"), w.code("x|y")));
+        w.list(&[w.link("node-a_b", "a_b")]);
+        w.table(&["ID", "Name"], &[vec!["1".to_owned(), w.code("flag")]]);
+        w.finish("Doc & more")
+    }
+
+    #[test]
+    fn markdown_output() {
+        assert_eq!(
+            write_sample(Markdown::default()),
+            r#"# Doc & more
+
+<a id="nodes"></a>
+
+## Nodes
+
+a\|b \<c\> `x\|y`
+
+- [a\_b](#node-a_b)
+
+| ID | Name |
+| --- | --- |
+| 1 | `flag` |
+
+"#
+        );
+    }
+
+    #[test]
+    fn html_output() {
+        assert_eq!(
+            write_sample(Html::default()),
+            r##"<!DOCTYPE html>
+<html>
+<head>
+<meta charset="utf-8">
+<title>Doc &amp; more</title>
+<style>
+body { font-family: sans-serif; max-width: 1200px; margin: auto; }
+table { border-collapse: collapse; margin-bottom: 1em; }
+th, td { border: 1px solid #999; padding: 2px 6px; text-align: left; }
+code { background: #eee; }
+</style>
+</head>
+<body>
+<h1>Doc &amp; more</h1>
+<h2 id="nodes">Nodes</h2>
+<p>a|b &lt;c&gt; <code>x|y</code></p>
+<ul>
+<li><a href="#node-a_b">a_b</a></li>
+</ul>
+<table>
+<tr><th>ID</th><th>Name</th></tr>
+<tr><td>1</td><td><code>flag</code></td></tr>
+</table>
+</body>
+</html>
+"##
+        );
+    }
+
+    #[test]
+    fn network_sections() {
+        let network = parse_yaml_config_from_file("../codegen/examples/conformance.yaml").unwrap();
+        let markdown = render(&network, Markdown::default());
+        for line in [
+            "## Buses",
+            "<a id=\"node-primary\"></a>",
+            "| 2 | `flag` | `u1` |  | read/write |  |",
+            "| `motion` | [primary\\_stream\\_motion](#message-primary_stream_motion) | 10ms | `position` |  |",
+            "<a id=\"type-drive_mode\"></a>",
+            "### drive\\_mode (enum, 3 bits)",
+            "| `brake` | 7 |",
+            "| `position` | [vec3](#type-vec3) | 48 |",
+        ] {
+            assert!(markdown.contains(line), "missing {line:?} in\n{markdown}");
+        }
+        let html = render(&network, Html::default());
+        assert!(html.contains("<h3 id=\"type-pose\">pose (struct)</h3>"));
+    }
+}
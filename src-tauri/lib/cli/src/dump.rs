@@ -1,6 +1,7 @@
-use std::{net::SocketAddr, time::Duration};
+use std::{net::SocketAddr, path::PathBuf, time::Duration};
 
 use canzero_appdata::AppData;
+use canzero_canlog::{ChannelMap, LogFormat, LogWriter};
 use canzero_config::config::MessageId;
 use canzero_tcp::tcpcan::ConnectionId;
 use canzero_udp::{frame::NetworkDescription, scanner::UdpNetworkScanner};
@@ -17,7 +18,12 @@ pub async fn discover() -> Result<NetworkDescription> {
     }
 }
 
-pub async fn command_dump(filter_msg_names: Vec<String>, filter_ids: Vec<String>) -> Result<()> {
+pub async fn command_dump(
+    filter_msg_names: Vec<String>,
+    filter_ids: Vec<String>,
+    record: Option<PathBuf>,
+    format: Option<String>,
+) -> Result<()> {
     if !filter_ids.is_empty() {
         return Err(Error::NotYetImplemented);
     }
@@ -25,6 +31,21 @@ pub async fn command_dump(filter_msg_names: Vec<String>, filter_ids: Vec<String>
     let appdata = AppData::read()?;
     let network_config = appdata.config()?;
 
+    let mut recorder = match &record {
+        Some(path) => {
+            let format = match &format {
+                Some(format) => format.parse::<LogFormat>()?,
+                None => LogFormat::from_path(path).unwrap_or(LogFormat::Candump),
+            };
+            Some(LogWriter::create(
+                path,
+                format,
+                ChannelMap::from_buses(network_config.buses()),
+            )?)
+        }
+        None => None,
+    };
+
     let network = discover().await?;
 
     let connection =
@@ -49,10 +70,20 @@ pub async fn command_dump(filter_msg_names: Vec<String>, filter_ids: Vec<String>
     .await.unwrap();
 
     loop {
-        let Some(frame) = tcpcan.recv().await else {
-            println!("Connection closed");
-            return Ok(());
+        // stopping with Ctrl-C still completes the recording.
+        let frame = tokio::select! {
+            frame = tcpcan.recv() => match frame {
+                Some(frame) => frame,
+                None => {
+                    println!("Connection closed");
+                    break;
+                }
+            },
+            _ = tokio::signal::ctrl_c() => break,
         };
+        if let Some(recorder) = &mut recorder {
+            recorder.write(&frame)?;
+        }
         let timestamp = &frame.timestamp;
         let tsec = timestamp.as_secs_f32();
         let bus_id = &frame.bus_id;
@@ -91,4 +122,10 @@ pub async fn command_dump(filter_msg_names: Vec<String>, filter_ids: Vec<String>
             println!("{tsec:08.2}s : {bus:4} {id:5} [{dlc:1}] {data:016X}  ({msg_name})");
         }
     }
+    if let (Some(recorder), Some(path)) = (recorder, &record) {
+        let count = recorder.count();
+        recorder.finish()?;
+        cprintln!("<green>Recorded {count} frames to {path:?}</green>");
+    }
+    Ok(())
 }
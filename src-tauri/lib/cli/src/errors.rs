@@ -17,6 +17,15 @@ pub enum Error {
     InvalidResponse,
     InvalidNodeName(String),
     InvalidBusName(String),
+    InvalidPlatform(String),
+    InvalidDocFormat(String),
+    InvalidManifest(String),
+    InvalidSimConfig(String),
+    InvalidFrame(String),
+    InvalidMessageName(String),
+    InvalidSignalValue(String),
+    LogError(canzero_canlog::Error),
+    BuildFailed(String),
     NoServerFound,
     NotYetImplemented,
 }
@@ -39,6 +48,11 @@ impl From<canzero_codegen::errors::Error> for Error {
     }
 }
 
+impl From<canzero_canlog::Error> for Error {
+    fn from(value: canzero_canlog::Error) -> Self {
+        Error::LogError(value)
+    }
+}
 
 impl Display for Error {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
@@ -52,6 +66,15 @@ impl Display for Error {
             Error::InvalidResponse => write!(f, "Invalid Response"),
             Error::InvalidNodeName(node_name) => write!(f, "Invalid node name : {node_name}"),
             Error::InvalidBusName(bus_name) => write!(f, "Invalid bus name : {bus_name}"),
+            Error::InvalidPlatform(platform) => write!(f, "Invalid platform : {platform}"),
+            Error::InvalidDocFormat(format) => write!(f, "Invalid document format : {format}"),
+            Error::InvalidManifest(reason) => write!(f, "Invalid manifest : {reason}"),
+            Error::InvalidSimConfig(reason) => write!(f, "Invalid simulation config : {reason}"),
+            Error::InvalidFrame(frame) => write!(f, "Invalid frame : {frame} (expected <id>#<data>)"),
+            Error::InvalidMessageName(message_name) => write!(f, "Invalid message name : {message_name}"),
+            Error::InvalidSignalValue(reason) => write!(f, "Invalid signal value : {reason}"),
+            Error::LogError(err) => write!(f, "Log file : {err}"),
+            Error::BuildFailed(stderr) => write!(f, "Build failed :\n{stderr}"),
             Error::NoServerFound => write!(f, "No server found"),
             Error::NotYetImplemented => write!(f, "Not yet implemented"),
         }
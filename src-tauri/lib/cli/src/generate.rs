@@ -1,8 +1,13 @@
 use std::path::{Path, PathBuf};
 
 use canzero_appdata::AppData;
+use canzero_codegen::options::{Options, Platform};
+use color_print::cprintln;
 
-use crate::errors::{Error, Result};
+use crate::{
+    errors::{Error, Result},
+    manifest::{Manifest, NodeTarget},
+};
 
 fn rec_create_dir(dir : &Path) -> Result<()>{
    if !dir.is_dir() {
@@ -14,7 +19,17 @@ fn rec_create_dir(dir : &Path) -> Result<()>{
     Ok(())
 }
 
-pub fn command_generate(node_name : &str, output_dir : &PathBuf) -> Result<()> {
+fn parse_platform(platform : &str) -> Result<Platform> {
+    match platform.to_lowercase().as_str() {
+        "linux" => Ok(Platform::Linux),
+        "stm32-bxcan" | "bxcan" => Ok(Platform::Stm32BxCan),
+        "stm32-fdcan" | "fdcan" => Ok(Platform::Stm32FdCan),
+        "teensy4-flexcan" | "teensy4" | "flexcan" => Ok(Platform::Teensy4FlexCan),
+        _ => Err(Error::InvalidPlatform(platform.to_owned())),
+    }
+}
+
+pub fn command_generate(node_name : &str, output_dir : &PathBuf, platform : Option<String>, can_clock : Option<u32>, sim : bool) -> Result<()> {
     let appdata = AppData::read()?;
     let network_config = appdata.config()?;
     let output_dir = output_dir.clone();
@@ -24,17 +39,133 @@ pub fn command_generate(node_name : &str, output_dir : &PathBuf) -> Result<()> {
     
     rec_create_dir(&output_dir)?;
 
-    let mut options = canzero_codegen::options::Options::default();
+    let target = NodeTarget {
+        output: output_dir,
+        language: None,
+        platform,
+        namespace: None,
+        indent: None,
+        can_clock,
+        sim,
+        source: None,
+        header: None,
+    };
+    let (options, source_file_path, main_sim_file_path) = node_options(&target)?;
+
+    canzero_codegen::generate(node_name, network_config, options)?;
+
+    if sim {
+        let mut binary_path = target.output.clone();
+        binary_path.push(format!("{node_name}_sim"));
+        build_sim(&source_file_path, &main_sim_file_path, &binary_path)?;
+        println!("Built simulated node {}", binary_path.to_str().unwrap());
+        println!("Run it with --tcp <host>:<port> (see \"canzero server scan\") or --socketcan");
+    }
+
+    Ok(())
+}
+
+/// Regenerates every node listed in the project manifest. Only files with a
+/// changed content are written, such that build systems only rebuild the affected nodes.
+pub fn command_generate_all(manifest_path : Option<PathBuf>) -> Result<()> {
+    let appdata = AppData::read()?;
+    let network_config = appdata.config()?;
+    let manifest_path = match manifest_path {
+        Some(path) => path,
+        None => match appdata.get_config_path() {
+            Some(config_path) => Manifest::default_path(config_path),
+            None => return Err(Error::NoConfigSelected),
+        },
+    };
+    let manifest = Manifest::read(&manifest_path)?;
+
+    for node_name in manifest.nodes.keys() {
+        if !network_config.nodes().iter().any(|node| node.name() == node_name) {
+            return Err(Error::InvalidNodeName(node_name.to_owned()));
+        }
+    }
+
+    for node in network_config.nodes() {
+        let Some(target) = manifest.nodes.get(node.name()) else {
+            cprintln!("{:25} : <dim>{:10}</dim> (not in manifest)", node.name(), "SKIPPED");
+            continue;
+        };
+        rec_create_dir(&target.output)?;
+        let (options, source_file_path, main_sim_file_path) = node_options(target)?;
+        let written = canzero_codegen::generate_if_changed(node.name(), network_config.clone(), options)?;
 
-    let mut source_file_path = output_dir.clone();
-    source_file_path.push("canzero.cpp");
+        let mut binary_path = target.output.clone();
+        binary_path.push(format!("{}_sim", node.name()));
+        if target.sim && (!written.is_empty() || !binary_path.exists()) {
+            build_sim(&source_file_path, &main_sim_file_path, &binary_path)?;
+        }
+
+        if written.is_empty() {
+            cprintln!("{:25} : <green>{:10}</green>", node.name(), "UP-TO-DATE");
+        } else {
+            let files : Vec<&str> = written
+                .iter()
+                .map(|path| {
+                    Path::new(path)
+                        .file_name()
+                        .and_then(|name| name.to_str())
+                        .unwrap_or(path)
+                })
+                .collect();
+            cprintln!("{:25} : <yellow>{:10}</yellow> ({})", node.name(), "REBUILD", files.join(", "));
+        }
+    }
+    Ok(())
+}
+
+/// Codegen options of a node target, returns the options together with
+/// the source and main_sim paths, which are required to build simulated nodes.
+fn node_options(target : &NodeTarget) -> Result<(Options, PathBuf, PathBuf)> {
+    let mut options = Options::default();
+    if let Some(platform) = &target.platform {
+        options.set_platform(parse_platform(platform)?);
+    }
+    if let Some(can_clock) = target.can_clock {
+        options.set_can_clock(can_clock);
+    }
+    if let Some(namespace) = &target.namespace {
+        options.set_namespace(namespace);
+    }
+    if let Some(indent) = target.indent {
+        options.set_indent(indent);
+    }
+    if target.sim {
+        if !matches!(options.platform(), Platform::Linux) {
+            return Err(Error::InvalidPlatform("simulation is only supported for linux".to_owned()));
+        }
+        options.set_simulation(true);
+    }
+
+    let source_file_path = target.output.join(target.source.as_deref().unwrap_or("canzero.cpp"));
     options.set_source_file_path(source_file_path.to_str().unwrap());
 
-    let mut header_file_path = output_dir.clone();
-    header_file_path.push("canzero.h");
+    let header_file_path = target.output.join(target.header.as_deref().unwrap_or("canzero.h"));
     options.set_header_file_path(header_file_path.to_str().unwrap());
 
-    canzero_codegen::generate(node_name, network_config, options)?;
+    let main_sim_file_path = target.output.join("main_sim.cpp");
+    options.set_sim_main_file_path(main_sim_file_path.to_str().unwrap());
 
+    Ok((options, source_file_path, main_sim_file_path))
+}
+
+fn build_sim(source_file_path : &Path, main_sim_file_path : &Path, binary_path : &Path) -> Result<()> {
+    let compiler = std::env::var("CXX").unwrap_or("c++".to_owned());
+    let output = std::process::Command::new(&compiler)
+        .arg("-std=c++17")
+        .arg("-O2")
+        .arg("-o")
+        .arg(binary_path)
+        .arg(source_file_path)
+        .arg(main_sim_file_path)
+        .output()
+        .map_err(|_| Error::MissingDependency(compiler))?;
+    if !output.status.success() {
+        return Err(Error::BuildFailed(String::from_utf8_lossy(&output.stderr).into_owned()));
+    }
     Ok(())
 }
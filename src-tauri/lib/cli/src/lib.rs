@@ -10,24 +10,32 @@ use crate::{
         command_config_nodes_list, command_config_object_entries_list, command_config_set,
         command_config_show,
     },
+    doc::command_config_doc,
     dump::command_dump,
     errors::Error,
-    generate::command_generate,
+    generate::{command_generate, command_generate_all},
     scan::command_scan,
+    send::command_send,
     server::command_server,
+    sim::command_sim,
     ssh::{command_ssh, command_ssh_reboot},
     status::command_status,
     update::{command_update_self, command_update_server},
 };
 
 mod client;
+mod codec;
 mod config;
+mod doc;
 mod dump;
 mod errors;
 mod generate;
 mod get;
+mod manifest;
 mod scan;
+mod send;
 mod server;
+mod sim;
 mod ssh;
 mod status;
 mod update;
@@ -55,8 +63,22 @@ enum Command {
     #[clap(alias = "gen")]
     #[command(about = "Generate c code from the selected network configuration.")]
     Generate {
-        node_name: String,
-        output_dir: PathBuf,
+        #[arg(required_unless_present = "all")]
+        node_name: Option<String>,
+        #[arg(required_unless_present = "all")]
+        output_dir: Option<PathBuf>,
+        #[arg(short, long, help = "linux, stm32-bxcan, stm32-fdcan or teensy4-flexcan")]
+        platform: Option<String>,
+        #[arg(long, help = "Frequency of the CAN peripheral clock in Hz")]
+        can_clock: Option<u32>,
+        #[arg(long, help = "Build a simulated node process for the host", action = clap::ArgAction::SetTrue)]
+        sim: bool,
+        #[arg(long, help = "Generate all nodes listed in the project manifest", action = clap::ArgAction::SetTrue,
+              conflicts_with_all = ["node_name", "output_dir", "platform", "can_clock", "sim"])]
+        all: bool,
+        #[arg(long, help = "Path to the project manifest (default: canzero.toml next to the network configuration)",
+              requires = "all")]
+        manifest: Option<PathBuf>,
     },
     #[command(about = "Start canzero graphical user interface.")]
     Gui,
@@ -75,6 +97,33 @@ enum Command {
         #[clap(alias = "id")]
         #[arg(short, long, num_args=0..)]
         ids: Vec<String>,
+        #[arg(long, help = "Record all frames to a log file (.log, .asc or .trc)")]
+        record: Option<PathBuf>,
+        #[arg(long, help = "candump, asc or trc (default: inferred from the file extension)", requires = "record")]
+        format: Option<String>,
+    },
+    #[command(about = "Send a raw frame or a configured message, once or cyclically.")]
+    Send {
+        #[arg(help = "<id>#<data> like cansend, e.g. 123#DEADBEEF", required_unless_present = "message")]
+        frame: Option<String>,
+        #[arg(short, long, help = "Compose a message of the network configuration", conflicts_with = "frame")]
+        message: Option<String>,
+        #[arg(short, long = "signal", value_name = "SIGNAL=VALUE", requires = "message",
+              help = "Value of a signal of the message, physical values or enum variants, e.g. -s header.od_index=3")]
+        signals: Vec<String>,
+        #[arg(short, long, help = "Name of the bus (default: bus of the message or first bus of the network)")]
+        bus: Option<String>,
+        #[arg(short, long, help = "Send cyclically with this period in ms")]
+        period: Option<u64>,
+        #[arg(short, long, help = "Stop after sending this many frames", requires = "period")]
+        count: Option<usize>,
+    },
+    #[command(about = "Emulate nodes of the network configuration on the server.")]
+    Sim {
+        #[arg(short, long, value_delimiter = ',', help = "Nodes to emulate (default: all)")]
+        nodes: Vec<String>,
+        #[arg(short, long, help = "toml file, which assigns value generators to object entries")]
+        generators: Option<PathBuf>,
     },
     #[command(about = "Check the status of all connected nodes.")]
     Status,
@@ -107,6 +156,16 @@ enum ConfigCommand {
     )]
     Check,
     Where,
+    #[command(
+        about = "Generate an interface control document of the network configuration.",
+        arg_required_else_help = false
+    )]
+    Doc {
+        #[arg(short, long, help = "markdown or html")]
+        format: Option<String>,
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+    },
 }
 
 #[derive(Subcommand, Debug)]
@@ -161,11 +220,24 @@ pub async fn run_cli(armv7_cli_binary : Option<PathBuf>) -> bool {
                 },
                 ConfigCommand::Check => command_config_check(),
                 ConfigCommand::Where => command_config_get(),
+                ConfigCommand::Doc { format, output } => command_config_doc(format, output),
             },
             Command::Generate {
                 node_name,
                 output_dir,
-            } => command_generate(&node_name, &output_dir),
+                platform,
+                can_clock,
+                sim,
+                all,
+                manifest,
+            } => match (all, node_name, output_dir) {
+                (true, _, _) => command_generate_all(manifest),
+                (false, Some(node_name), Some(output_dir)) => {
+                    command_generate(&node_name, &output_dir, platform, can_clock, sim)
+                }
+                // enforced by clap (required_unless_present = "all").
+                (false, _, _) => unreachable!(),
+            },
             Command::Gui => return true,
             Command::Server { command } => match command {
                 ServerCommand::Start => command_server().await,
@@ -178,7 +250,11 @@ pub async fn run_cli(armv7_cli_binary : Option<PathBuf>) -> bool {
                 }
             },
             Command::Connect => command_client().await,
-            Command::Dump { messages, ids } => command_dump(messages, ids).await,
+            Command::Dump { messages, ids, record, format } => command_dump(messages, ids, record, format).await,
+            Command::Send { frame, message, signals, bus, period, count } => {
+                command_send(frame, message, signals, bus, period, count).await
+            }
+            Command::Sim { nodes, generators } => command_sim(nodes, generators).await,
             Command::Status => command_status().await,
             Command::Update { socketcan } => command_update_self(socketcan),
             Command::Version => {
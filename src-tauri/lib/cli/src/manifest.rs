@@ -0,0 +1,80 @@
+use std::{
+    collections::BTreeMap,
+    path::{Path, PathBuf},
+};
+
+use serde::Deserialize;
+
+use crate::errors::{Error, Result};
+
+pub const MANIFEST_FILE_NAME: &str = "canzero.toml";
+
+/// Project manifest, which lists where and how the code of each node is generated.
+///
+/// ```toml
+/// [nodes.motor_driver]
+/// output = "../motor_driver/src/canzero"
+/// platform = "stm32-fdcan"
+/// namespace = "canzero"
+/// language = "c++"
+/// ```
+///
+/// Relative output directories are resolved against the directory of the manifest.
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Manifest {
+    #[serde(default)]
+    pub nodes: BTreeMap<String, NodeTarget>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct NodeTarget {
+    pub output: PathBuf,
+    pub language: Option<String>,
+    pub platform: Option<String>,
+    pub namespace: Option<String>,
+    pub indent: Option<usize>,
+    pub can_clock: Option<u32>,
+    #[serde(default)]
+    pub sim: bool,
+    pub source: Option<String>,
+    pub header: Option<String>,
+}
+
+impl Manifest {
+    pub fn read(path: &Path) -> Result<Self> {
+        if !path.exists() {
+            return Err(Error::FileNotFound(path.to_str().unwrap().to_owned()));
+        }
+        let src = std::fs::read_to_string(path)?;
+        let mut manifest = toml::from_str::<Manifest>(&src)
+            .map_err(|err| Error::InvalidManifest(err.to_string()))?;
+        let dir = path.parent().unwrap_or(Path::new("."));
+        for (node_name, target) in manifest.nodes.iter_mut() {
+            if target.output.is_relative() {
+                target.output = dir.join(&target.output);
+            }
+            // only the c++ backend exists so far.
+            if let Some(language) = &target.language {
+                match language.to_lowercase().as_str() {
+                    "c++" | "cpp" | "cxx" => (),
+                    _ => {
+                        return Err(Error::InvalidManifest(format!(
+                            "unsupported language {language} for node {node_name}"
+                        )))
+                    }
+                }
+            }
+        }
+        Ok(manifest)
+    }
+
+    /// The manifest is expected next to the selected network configuration.
+    pub fn default_path(config_path: &Path) -> PathBuf {
+        config_path
+            .parent()
+            .unwrap_or(Path::new("."))
+            .join(MANIFEST_FILE_NAME)
+    }
+}
@@ -0,0 +1,196 @@
+use std::{net::SocketAddr, time::{Duration, Instant}};
+
+use canzero_appdata::AppData;
+use canzero_common::{CanFrame, NetworkFrame, Timestamped};
+use canzero_tcp::tcpcan::{ConnectionId, TcpCan};
+use color_print::cprintln;
+
+use canzero_config::config::{bus::BusRef, NetworkRef};
+use canzero_serialize::{attributes_from_paths, FrameSerializer, Value};
+
+use crate::{
+    dump::discover,
+    errors::{Error, Result},
+};
+
+/// Parses frames in the notation of can-utils `cansend`, e.g. `123#DEADBEEF` or
+/// `1F334455#11.22`, ids with 8 digits are extended and `#R` sends a remote frame.
+fn parse_frame(frame: &str) -> Result<CanFrame> {
+    let invalid = || Error::InvalidFrame(frame.to_owned());
+    let (id, data) = frame.split_once('#').ok_or_else(invalid)?;
+    let ide = id.len() == 8;
+    let can_id = u32::from_str_radix(id, 16).map_err(|_| invalid())?;
+    if (!ide && id.len() != 3) || can_id > 0x1FFFFFFF {
+        return Err(invalid());
+    }
+    if let Some(dlc) = data.strip_prefix('R') {
+        let dlc = if dlc.is_empty() {
+            0
+        } else {
+            dlc.parse::<u8>().map_err(|_| invalid())?
+        };
+        if dlc > 8 {
+            return Err(invalid());
+        }
+        return Ok(CanFrame::new(can_id, ide, true, dlc, 0));
+    }
+    let digits: String = data.chars().filter(|c| *c != '.').collect();
+    if !digits.is_ascii() || !digits.len().is_multiple_of(2) || digits.len() > 16 {
+        return Err(invalid());
+    }
+    let mut value = 0u64;
+    for i in 0..digits.len() / 2 {
+        let byte = u8::from_str_radix(&digits[2 * i..2 * i + 2], 16).map_err(|_| invalid())?;
+        value |= (byte as u64) << (i * 8);
+    }
+    Ok(CanFrame::new(can_id, ide, false, (digits.len() / 2) as u8, value))
+}
+
+/// Integers are kept as they are, so the serializer can check them against the signal type,
+/// anything which is not a number is an enum variant.
+fn parse_value(value: &str) -> Value {
+    if let Ok(value) = value.parse::<u64>() {
+        Value::UnsignedValue(value)
+    } else if let Ok(value) = value.parse::<i64>() {
+        Value::SignedValue(value)
+    } else if let Ok(value) = value.parse::<f64>() {
+        Value::RealValue(value)
+    } else {
+        Value::EnumValue(value.to_owned())
+    }
+}
+
+/// Composes a configured message from `<signal>=<value>` assignments, signals are addressed
+/// by their attribute path (e.g. `header.od_index`) and take physical values or enum variants.
+/// Every signal of the message has to be assigned, returns the frame with the bus of the message.
+fn compose_message(
+    network_config: &NetworkRef,
+    message_name: &str,
+    assignments: &[String],
+) -> Result<(CanFrame, BusRef)> {
+    let message = network_config
+        .messages()
+        .iter()
+        .find(|message| message.name() == message_name)
+        .ok_or_else(|| Error::InvalidMessageName(message_name.to_owned()))?;
+    let mut values = vec![];
+    for assignment in assignments {
+        let Some((path, value)) = assignment.split_once('=') else {
+            return Err(Error::InvalidSignalValue(format!(
+                "{assignment} : expected <signal>=<value>"
+            )));
+        };
+        values.push((path.trim().to_owned(), parse_value(value.trim())));
+    }
+    let can_frame = attributes_from_paths(values)
+        .and_then(|attributes| FrameSerializer::new(message)?.can_frame(&attributes))
+        .map_err(Error::InvalidSignalValue)?;
+    Ok((can_frame, message.bus().clone()))
+}
+
+pub async fn command_send(
+    frame: Option<String>,
+    message: Option<String>,
+    signals: Vec<String>,
+    bus: Option<String>,
+    period: Option<u64>,
+    count: Option<usize>,
+) -> Result<()> {
+    let appdata = AppData::read()?;
+    let network_config = appdata.config()?;
+    let (frame, can_frame, default_bus) = match (&frame, &message) {
+        (_, Some(message_name)) => {
+            let (can_frame, bus) = compose_message(&network_config, message_name, &signals)?;
+            (message_name.clone(), can_frame, Some(bus))
+        }
+        (Some(frame), None) => (frame.clone(), parse_frame(frame)?, None),
+        (None, None) => return Err(Error::InvalidFrame(String::new())),
+    };
+    let bus = match (&bus, &default_bus) {
+        (Some(bus_name), _) => network_config
+            .buses()
+            .iter()
+            .find(|bus| bus.name() == bus_name)
+            .ok_or_else(|| Error::InvalidBusName(bus_name.clone()))?,
+        (None, Some(bus)) => bus,
+        (None, None) => network_config
+            .buses()
+            .first()
+            .ok_or_else(|| Error::InvalidBusName("<none>".to_owned()))?,
+    };
+
+    let network = discover().await?;
+    let connection =
+        tokio::net::TcpStream::connect(SocketAddr::new(network.server_addr, network.service_port))
+            .await?;
+    let tcpcan = TcpCan::new(
+        connection,
+        ConnectionId::Client {
+            request_id: false,
+            sync_history: false,
+        },
+    )
+    .await?;
+
+    let start = Instant::now();
+    let network_frame = NetworkFrame {
+        bus_id: bus.id(),
+        can_frame,
+    };
+    let Some(period) = period else {
+        tcpcan
+            .send(&Timestamped::now(start, network_frame))
+            .await?;
+        cprintln!("<green>Sent {frame} on {}</green>", bus.name());
+        return Ok(());
+    };
+
+    cprintln!(
+        "<green>Sending {frame} on {} every {period}ms (Ctrl-C to stop)</green>",
+        bus.name()
+    );
+    let mut interval = tokio::time::interval(Duration::from_millis(period.max(1)));
+    let mut sent = 0usize;
+    while count.is_none_or(|count| sent < count) {
+        tokio::select! {
+            _ = interval.tick() => {
+                tcpcan.send(&Timestamped::now(start, network_frame.clone())).await?;
+                sent += 1;
+            }
+            _ = tokio::signal::ctrl_c() => break,
+        }
+    }
+    cprintln!("<green>Sent {sent} frames</green>");
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_data_frames() {
+        let frame = parse_frame("123#DE.AD").unwrap();
+        assert_eq!(frame.get_id(), 0x123);
+        assert!(!frame.get_ide_flag());
+        assert_eq!(frame.get_dlc(), 2);
+        assert_eq!(frame.get_data_u64(), 0xADDE);
+        assert!(parse_frame("1F334455#").unwrap().get_ide_flag());
+    }
+
+    #[test]
+    fn parses_remote_frames() {
+        let frame = parse_frame("123#R8").unwrap();
+        assert!(frame.get_rtr_flag());
+        assert_eq!(frame.get_dlc(), 8);
+        assert_eq!(parse_frame("123#R").unwrap().get_dlc(), 0);
+    }
+
+    #[test]
+    fn rejects_invalid_frames() {
+        assert!(parse_frame("123#R9").is_err());
+        assert!(parse_frame("123#0011223344556677889").is_err());
+        assert!(parse_frame("12#00").is_err());
+        assert!(parse_frame("123").is_err());
+    }
+}
@@ -0,0 +1,140 @@
+use std::{collections::HashMap, f64::consts::PI, path::PathBuf};
+
+use serde::Deserialize;
+
+/// Optional toml file passed with `canzero sim --generators`, e.g.
+///
+/// ```toml
+/// [generators]
+/// "motor_driver.current" = { kind = "sine", offset = 10.0, amplitude = 5.0, period = 2.0 }
+/// "motor_driver.state" = { kind = "constant", value = 1 }
+/// "input_board.position" = { kind = "ramp", from = 0.0, to = 100.0, period = 10.0 }
+/// "input_board.imu.acc_x" = { kind = "noise", mean = 0.0, amplitude = 0.2 }
+/// "input_board.velocity" = { kind = "replay", log = "run.log" }
+/// ```
+///
+/// Keys are `<node>.<object entry>` optionally followed by the path of a struct attribute,
+/// a generator for a struct drives all of its attributes. Times are in seconds and enums
+/// are generated by their discriminant.
+#[derive(Debug, Default, Deserialize)]
+pub struct SimConfig {
+    #[serde(default)]
+    pub generators: HashMap<String, GeneratorConfig>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(tag = "kind", rename_all = "lowercase")]
+pub enum GeneratorConfig {
+    Constant {
+        value: f64,
+    },
+    /// Sawtooth from `from` to `to`.
+    Ramp {
+        from: f64,
+        to: f64,
+        period: f64,
+    },
+    Sine {
+        #[serde(default)]
+        offset: f64,
+        amplitude: f64,
+        period: f64,
+    },
+    /// Uniformly distributed in `mean ± amplitude`.
+    Noise {
+        #[serde(default)]
+        mean: f64,
+        amplitude: f64,
+    },
+    /// Replays the values of the streamed object entry from a log file in a loop.
+    Replay { log: PathBuf },
+}
+
+pub enum Generator {
+    Constant(f64),
+    Ramp { from: f64, to: f64, period: f64 },
+    Sine { offset: f64, amplitude: f64, period: f64 },
+    Noise { mean: f64, amplitude: f64, state: u64 },
+    /// Sorted samples of (seconds since the start of the log, value).
+    Replay { samples: Vec<(f64, f64)> },
+}
+
+impl Generator {
+    /// `seed` decorrelates the noise of different leaves, replays are created
+    /// from the samples of their log with `Generator::replay`.
+    pub fn new(config: &GeneratorConfig, seed: u64) -> Option<Self> {
+        Some(match config {
+            GeneratorConfig::Constant { value } => Generator::Constant(*value),
+            GeneratorConfig::Ramp { from, to, period } => Generator::Ramp {
+                from: *from,
+                to: *to,
+                period: *period,
+            },
+            GeneratorConfig::Sine {
+                offset,
+                amplitude,
+                period,
+            } => Generator::Sine {
+                offset: *offset,
+                amplitude: *amplitude,
+                period: *period,
+            },
+            GeneratorConfig::Noise { mean, amplitude } => Generator::Noise {
+                mean: *mean,
+                amplitude: *amplitude,
+                state: seed.wrapping_mul(0x9E3779B97F4A7C15) | 1,
+            },
+            GeneratorConfig::Replay { log: _ } => return None,
+        })
+    }
+
+    pub fn replay(samples: Vec<(f64, f64)>) -> Self {
+        Generator::Replay { samples }
+    }
+
+    /// Value at `t` seconds after the start of the simulation.
+    pub fn sample(&mut self, t: f64) -> f64 {
+        match self {
+            Generator::Constant(value) => *value,
+            Generator::Ramp { from, to, period } => {
+                if *period <= 0.0 {
+                    return *to;
+                }
+                *from + (*to - *from) * (t / *period).fract()
+            }
+            Generator::Sine {
+                offset,
+                amplitude,
+                period,
+            } => {
+                if *period <= 0.0 {
+                    return *offset;
+                }
+                *offset + *amplitude * (2.0 * PI * t / *period).sin()
+            }
+            Generator::Noise {
+                mean,
+                amplitude,
+                state,
+            } => {
+                // xorshift64, good enough for test signals.
+                *state ^= *state << 13;
+                *state ^= *state >> 7;
+                *state ^= *state << 17;
+                let uniform = (*state >> 11) as f64 / (1u64 << 53) as f64;
+                *mean + *amplitude * (2.0 * uniform - 1.0)
+            }
+            Generator::Replay { samples } => {
+                let Some((duration, last)) = samples.last().copied() else {
+                    return 0.0;
+                };
+                if duration <= 0.0 {
+                    return last;
+                }
+                let t = t % duration;
+                let index = samples.partition_point(|(time, _)| *time <= t);
+                samples[index.saturating_sub(1)].1
+            }
+        }
+    }
+}
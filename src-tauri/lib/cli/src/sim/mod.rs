@@ -0,0 +1,288 @@
+use std::{
+    net::SocketAddr,
+    path::{Path, PathBuf},
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+
+use canzero_appdata::AppData;
+use canzero_canlog::{read_log, ChannelMap};
+use canzero_common::{NetworkFrame, TNetworkFrame, Timestamped};
+use canzero_config::config::{NetworkRef, NodeRef};
+use canzero_tcp::tcpcan::{ConnectionId, TcpCan};
+use color_print::cprintln;
+
+use crate::{
+    codec::{attribute_signals, read_signal},
+    dump::discover,
+    errors::{Error, Result},
+};
+
+use self::{
+    generator::{Generator, GeneratorConfig, SimConfig},
+    node::SimNode,
+};
+
+mod generator;
+mod node;
+
+const HEARTBEAT_INTERVAL: Duration = Duration::from_millis(100);
+
+pub async fn command_sim(node_names: Vec<String>, generators: Option<PathBuf>) -> Result<()> {
+    let appdata = AppData::read()?;
+    let network_config = appdata.config()?;
+
+    let nodes: Vec<NodeRef> = if node_names.is_empty() {
+        network_config.nodes().clone()
+    } else {
+        node_names
+            .iter()
+            .map(|name| {
+                network_config
+                    .nodes()
+                    .iter()
+                    .find(|node| node.name() == name)
+                    .cloned()
+                    .ok_or_else(|| Error::InvalidNodeName(name.clone()))
+            })
+            .collect::<Result<_>>()?
+    };
+
+    let sim_config = match &generators {
+        Some(path) => {
+            let src = std::fs::read_to_string(path)
+                .map_err(|_| Error::FileNotFound(path.to_str().unwrap_or_default().to_owned()))?;
+            toml::from_str::<SimConfig>(&src)
+                .map_err(|err| Error::InvalidSimConfig(err.to_string()))?
+        }
+        None => SimConfig::default(),
+    };
+
+    let mut sim_nodes = vec![];
+    for node in &nodes {
+        let mut sim_node = SimNode::new(&network_config, node);
+        attach_generators(&network_config, &mut sim_node, &sim_config)?;
+        sim_nodes.push(Arc::new(Mutex::new(sim_node)));
+    }
+    for key in sim_config.generators.keys() {
+        if !generator_key_exists(&network_config, key) {
+            return Err(Error::InvalidSimConfig(format!(
+                "{key} is not a object entry of the network"
+            )));
+        }
+    }
+
+    let network = discover().await?;
+    let connection =
+        tokio::net::TcpStream::connect(SocketAddr::new(network.server_addr, network.service_port))
+            .await?;
+    let tcpcan = Arc::new(
+        TcpCan::new(
+            connection,
+            ConnectionId::Client {
+                request_id: false,
+                sync_history: false,
+            },
+        )
+        .await?,
+    );
+    cprintln!(
+        "<green>Simulating {} at {}:{}</green>",
+        nodes
+            .iter()
+            .map(|node| node.name())
+            .collect::<Vec<_>>()
+            .join(", "),
+        network.server_addr,
+        network.service_port
+    );
+
+    let start = Instant::now();
+    let mut tasks = vec![];
+    for sim_node in &sim_nodes {
+        tasks.push(tokio::spawn(heartbeat_task(
+            sim_node.clone(),
+            tcpcan.clone(),
+            start,
+        )));
+        let streams = sim_node.lock().unwrap().node().tx_streams().clone();
+        for stream in streams {
+            let sim_node = sim_node.clone();
+            let tcpcan = tcpcan.clone();
+            tasks.push(tokio::spawn(async move {
+                let mut interval =
+                    tokio::time::interval((*stream.min_interval()).max(Duration::from_millis(1)));
+                interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Skip);
+                loop {
+                    interval.tick().await;
+                    let now = Instant::now() - start;
+                    let frame = {
+                        let mut sim_node = sim_node.lock().unwrap();
+                        sim_node.update(now.as_secs_f64());
+                        sim_node.stream_frame(&stream, now)
+                    };
+                    if let Some(frame) = frame {
+                        if send(&tcpcan, start, frame).await.is_err() {
+                            break;
+                        }
+                    }
+                }
+            }));
+        }
+    }
+
+    loop {
+        let frame = tokio::select! {
+            frame = tcpcan.recv() => match frame {
+                Some(frame) => frame,
+                None => {
+                    cprintln!("<red>Connection closed</red>");
+                    break;
+                }
+            },
+            _ = tokio::signal::ctrl_c() => break,
+        };
+        for sim_node in &sim_nodes {
+            let responses = {
+                let mut sim_node = sim_node.lock().unwrap();
+                sim_node.update((Instant::now() - start).as_secs_f64());
+                sim_node.receive(&frame)
+            };
+            for response in responses {
+                send(&tcpcan, start, response).await?;
+            }
+        }
+    }
+    for task in tasks {
+        task.abort();
+    }
+    Ok(())
+}
+
+async fn send(tcpcan: &TcpCan, start: Instant, frame: NetworkFrame) -> std::io::Result<()> {
+    let frame: TNetworkFrame = Timestamped::now(start, frame);
+    tcpcan.send(&frame).await
+}
+
+async fn heartbeat_task(sim_node: Arc<Mutex<SimNode>>, tcpcan: Arc<TcpCan>, start: Instant) {
+    let mut interval = tokio::time::interval(HEARTBEAT_INTERVAL);
+    loop {
+        interval.tick().await;
+        let frames = sim_node.lock().unwrap().heartbeat_frames();
+        for frame in frames {
+            if send(&tcpcan, start, frame).await.is_err() {
+                return;
+            }
+        }
+    }
+}
+
+fn generator_key_exists(network_config: &NetworkRef, key: &str) -> bool {
+    network_config.nodes().iter().any(|node| {
+        node.object_entries().iter().any(|object_entry| {
+            let name = format!("{}.{}", node.name(), object_entry.name());
+            key == name || key.starts_with(&format!("{name}."))
+        })
+    })
+}
+
+/// Assigns the configured generators to the leaves of the object entries, the most
+/// specific key wins.
+fn attach_generators(
+    network_config: &NetworkRef,
+    sim_node: &mut SimNode,
+    sim_config: &SimConfig,
+) -> Result<()> {
+    let node = sim_node.node().clone();
+    let mut seed = node.id() as u64;
+    for (entry, object_entry) in sim_node
+        .entries_mut()
+        .iter_mut()
+        .zip(node.object_entries())
+    {
+        for leaf_index in 0..entry.leaves().len() {
+            let (leaf_path, leaf) = entry.leaves()[leaf_index].clone();
+            let path = if leaf_path.is_empty() {
+                format!("{}.{}", node.name(), object_entry.name())
+            } else {
+                format!("{}.{}.{leaf_path}", node.name(), object_entry.name())
+            };
+            let Some((_, config)) = sim_config
+                .generators
+                .iter()
+                .filter(|(key, _)| path == **key || path.starts_with(&format!("{key}.")))
+                .max_by_key(|(key, _)| key.len())
+            else {
+                continue;
+            };
+            seed += 1;
+            let generator = match config {
+                GeneratorConfig::Replay { log } => {
+                    let samples =
+                        replay_samples(network_config, &node, object_entry.id(), leaf_index, log)?;
+                    Generator::replay(
+                        samples
+                            .into_iter()
+                            .map(|(t, raw)| (t, leaf.physical(raw)))
+                            .collect(),
+                    )
+                }
+                _ => Generator::new(config, seed).expect("replays are handled above"),
+            };
+            entry.set_generator(leaf_index, generator);
+        }
+    }
+    Ok(())
+}
+
+/// Raw values of a leaf of a object entry in the tx streams of the node found in the log,
+/// with their time since the first frame of the log.
+fn replay_samples(
+    network_config: &NetworkRef,
+    node: &NodeRef,
+    oe_id: u32,
+    leaf_index: usize,
+    log: &Path,
+) -> Result<Vec<(f64, u64)>> {
+    let frames = read_log(log, None, &ChannelMap::from_buses(network_config.buses()))?;
+    let start = frames
+        .iter()
+        .map(|frame| frame.timestamp)
+        .min()
+        .unwrap_or_default();
+    let mut samples = vec![];
+    for stream in node.tx_streams() {
+        let message = stream.message();
+        let Some(signals) = stream
+            .mapping()
+            .iter()
+            .zip(attribute_signals(message))
+            .find(|(object_entry, _)| object_entry.as_ref().is_some_and(|oe| oe.id() == oe_id))
+            .map(|(_, signals)| signals)
+        else {
+            continue;
+        };
+        let Some(signal) = signals.get(leaf_index) else {
+            continue;
+        };
+        for frame in &frames {
+            let can_frame = &frame.can_frame;
+            if can_frame.get_id() == message.id().as_u32()
+                && can_frame.get_ide_flag() == message.id().ide()
+            {
+                samples.push((
+                    (frame.timestamp - start).as_secs_f64(),
+                    read_signal(can_frame.get_data_u64(), signal),
+                ));
+            }
+        }
+    }
+    if samples.is_empty() {
+        return Err(Error::InvalidSimConfig(format!(
+            "{log:?} contains no streamed values of object entry {oe_id} of {}",
+            node.name()
+        )));
+    }
+    samples.sort_by(|a, b| a.0.total_cmp(&b.0));
+    Ok(samples)
+}
@@ -0,0 +1,639 @@
+use std::{
+    collections::HashMap,
+    time::{Duration, Instant},
+};
+
+use canzero_common::{CanFrame, NetworkFrame, TNetworkFrame};
+use canzero_config::builder::network_builder::{MAX_STAGED_ENTRIES, STAGE_TIMEOUT};
+use canzero_config::config::{
+    stream::StreamRef, Message, NetworkRef, NodeRef, ObjectEntryAccess, ObjectEntryRef,
+};
+use color_print::cprintln;
+
+use crate::codec::{
+    attribute_signals, from_words, read_signal, to_words, type_leaves, write_signal, Leaf,
+    MessageLayout,
+};
+
+use super::generator::Generator;
+
+/// Discriminants of the `set_resp_erno`, `command_resp_erno` and `set_commit_erno` enums
+/// of every network.
+const ERNO_SUCCESS: u64 = 0;
+const ERNO_ERROR: u64 = 1;
+const ERNO_ABORTED: u64 = 2;
+/// Discriminant of `set_commit_action::Abort`.
+const ACTION_ABORT: u64 = 1;
+/// Unit of the `ticks_next` field of heartbeats.
+const HEARTBEAT_TICK: Duration = Duration::from_millis(50);
+/// Largest value of the u7 `ticks_next` field.
+const MAX_TICKS_NEXT: u128 = 0x7F;
+
+pub struct SimEntry {
+    object_entry: ObjectEntryRef,
+    leaves: Vec<(String, Leaf)>,
+    raw: Vec<u64>,
+    generators: Vec<Option<Generator>>,
+}
+
+impl SimEntry {
+    fn new(object_entry: &ObjectEntryRef) -> Self {
+        let leaves = type_leaves(object_entry.ty());
+        Self {
+            object_entry: object_entry.clone(),
+            raw: leaves
+                .iter()
+                .map(|(_, leaf)| leaf.raw(0.0))
+                .collect(),
+            generators: leaves.iter().map(|_| None).collect(),
+            leaves,
+        }
+    }
+
+    pub fn leaves(&self) -> &[(String, Leaf)] {
+        &self.leaves
+    }
+
+    pub fn set_generator(&mut self, leaf: usize, generator: Generator) {
+        self.generators[leaf] = Some(generator);
+    }
+}
+
+struct Protocol {
+    get_req: MessageLayout,
+    get_resp: MessageLayout,
+    set_req: MessageLayout,
+    set_resp: MessageLayout,
+    set_stage_req: MessageLayout,
+    set_commit_req: MessageLayout,
+    set_commit_resp: MessageLayout,
+}
+
+/// Entries staged by a client, rejected entries are staged without words.
+struct Stage {
+    client_id: u64,
+    entries: Vec<(u64, Option<Vec<u32>>)>,
+    last_staged: Instant,
+}
+
+/// A node emulated from its definition in the network config.
+///
+/// Object entries start at zero and follow their generators, a set request takes over
+/// the entry and stops its generators.
+pub struct SimNode {
+    network: NetworkRef,
+    node: NodeRef,
+    entries: Vec<SimEntry>,
+    protocol: Protocol,
+    // fragments of set requests by (client_id, od_index).
+    set_buffers: HashMap<(u64, u64), Vec<u32>>,
+    stage_buffers: HashMap<(u64, u64), Vec<u32>>,
+    stage: Option<Stage>,
+    // last sent data of each stream and when it was sent.
+    stream_state: HashMap<String, (u64, Duration)>,
+}
+
+impl SimNode {
+    pub fn new(network: &NetworkRef, node: &NodeRef) -> Self {
+        Self {
+            network: network.clone(),
+            node: node.clone(),
+            entries: node.object_entries().iter().map(SimEntry::new).collect(),
+            protocol: Protocol {
+                get_req: MessageLayout::new(network.get_req_message()),
+                get_resp: MessageLayout::new(network.get_resp_message()),
+                set_req: MessageLayout::new(network.set_req_message()),
+                set_resp: MessageLayout::new(network.set_resp_message()),
+                set_stage_req: MessageLayout::new(network.set_stage_req_message()),
+                set_commit_req: MessageLayout::new(network.set_commit_req_message()),
+                set_commit_resp: MessageLayout::new(network.set_commit_resp_message()),
+            },
+            set_buffers: HashMap::new(),
+            stage_buffers: HashMap::new(),
+            stage: None,
+            stream_state: HashMap::new(),
+        }
+    }
+
+    pub fn node(&self) -> &NodeRef {
+        &self.node
+    }
+
+    pub fn entries_mut(&mut self) -> &mut [SimEntry] {
+        &mut self.entries
+    }
+
+    fn entry(&self, od_index: u64) -> Option<&SimEntry> {
+        self.entries
+            .iter()
+            .find(|entry| entry.object_entry.id() as u64 == od_index)
+    }
+
+    fn entry_mut(&mut self, od_index: u64) -> Option<&mut SimEntry> {
+        self.entries
+            .iter_mut()
+            .find(|entry| entry.object_entry.id() as u64 == od_index)
+    }
+
+    /// Advances all generators to `t` seconds after the start of the simulation.
+    pub fn update(&mut self, t: f64) {
+        for entry in &mut self.entries {
+            for ((raw, generator), (_, leaf)) in entry
+                .raw
+                .iter_mut()
+                .zip(entry.generators.iter_mut())
+                .zip(entry.leaves.iter())
+            {
+                if let Some(generator) = generator {
+                    *raw = leaf.raw(generator.sample(t));
+                }
+            }
+        }
+    }
+
+    /// Like the generated code, the next heartbeat is announced within the heartbeat
+    /// timeout of the node.
+    pub fn heartbeat_frames(&self) -> Vec<NetworkFrame> {
+        let ticks_next = self
+            .node
+            .heartbeat_timeout()
+            .as_millis()
+            .div_ceil(HEARTBEAT_TICK.as_millis())
+            .min(MAX_TICKS_NEXT);
+        let data = self.node.id() as u64 | (ticks_next as u64) << 9;
+        self.node
+            .buses()
+            .iter()
+            .filter_map(|bus| {
+                let message = self
+                    .network
+                    .heartbeat_messages()
+                    .iter()
+                    .find(|message| message.bus().id() == bus.id())?;
+                Some(NetworkFrame {
+                    bus_id: bus.id(),
+                    can_frame: CanFrame::new(
+                        message.id().as_u32(),
+                        message.id().ide(),
+                        false,
+                        2,
+                        data,
+                    ),
+                })
+            })
+            .collect()
+    }
+
+    /// Like the generated code, a stream is sent if its data changed or
+    /// the max interval elapsed since it was sent last.
+    pub fn stream_frame(&mut self, stream: &StreamRef, now: Duration) -> Option<NetworkFrame> {
+        let message = stream.message();
+        let mut data = 0u64;
+        for (signals, object_entry) in attribute_signals(message).iter().zip(stream.mapping()) {
+            let Some(object_entry) = object_entry else {
+                continue;
+            };
+            let Some(entry) = self.entry(object_entry.id() as u64) else {
+                continue;
+            };
+            for (signal, raw) in signals.iter().zip(&entry.raw) {
+                write_signal(&mut data, signal, *raw);
+            }
+        }
+        let send = match self.stream_state.get(stream.name()) {
+            Some((prev, sent)) => *prev != data || now - *sent >= *stream.max_interval(),
+            None => true,
+        };
+        if !send {
+            return None;
+        }
+        self.stream_state
+            .insert(stream.name().to_owned(), (data, now));
+        Some(message_frame(message, data))
+    }
+
+    /// Handles a received frame and returns the responses.
+    pub fn receive(&mut self, frame: &TNetworkFrame) -> Vec<NetworkFrame> {
+        let mut responses = self.handle(&frame.can_frame);
+        // like the generated nodes, requests received on a fallback bus are answered on that bus.
+        let fallback = self.network.messages().iter().any(|message| {
+            message.usage().is_configuration()
+                && matches(&frame.can_frame, message)
+                && message.bus().id() != frame.bus_id
+        });
+        if fallback {
+            for response in &mut responses {
+                response.bus_id = frame.bus_id;
+            }
+        }
+        responses
+    }
+
+    fn handle(&mut self, can_frame: &CanFrame) -> Vec<NetworkFrame> {
+        if matches(can_frame, self.network.get_req_message()) {
+            self.get_request(can_frame.get_data_u64())
+        } else if matches(can_frame, self.network.set_req_message()) {
+            self.set_request(can_frame.get_data_u64())
+        } else if matches(can_frame, self.network.set_stage_req_message()) {
+            self.stage_request(can_frame.get_data_u64())
+        } else if matches(can_frame, self.network.set_commit_req_message()) {
+            self.commit_request(can_frame.get_data_u64())
+        } else {
+            let command = self
+                .node
+                .commands()
+                .iter()
+                .find(|command| matches(can_frame, command.tx_message()))
+                .cloned();
+            match command {
+                Some(command) => {
+                    let arguments: Vec<String> = attribute_signals(command.tx_message())
+                        .iter()
+                        .flatten()
+                        .map(|signal| {
+                            format!(
+                                "{}={}",
+                                signal.name(),
+                                read_signal(can_frame.get_data_u64(), signal)
+                            )
+                        })
+                        .collect();
+                    cprintln!(
+                        "<blue>{}::{}</blue>({})",
+                        self.node.name(),
+                        command.name(),
+                        arguments.join(", ")
+                    );
+                    let resp = command.rx_message();
+                    let mut data = 0u64;
+                    MessageLayout::new(resp).write(&mut data, "erno", ERNO_SUCCESS);
+                    vec![message_frame(resp, data)]
+                }
+                None => vec![],
+            }
+        }
+    }
+
+    fn get_request(&mut self, data: u64) -> Vec<NetworkFrame> {
+        let layout = &self.protocol.get_req;
+        let (Some(od_index), Some(client_id), Some(server_id)) = (
+            layout.read(data, "header.od_index"),
+            layout.read(data, "header.client_id"),
+            layout.read(data, "header.server_id"),
+        ) else {
+            return vec![];
+        };
+        if server_id != self.node.id() as u64 {
+            return vec![];
+        }
+        let Some(entry) = self.entry(od_index) else {
+            return vec![];
+        };
+        let words = to_words(&entry.leaves, &entry.raw);
+        let resp = self.network.get_resp_message();
+        let layout = &self.protocol.get_resp;
+        words
+            .iter()
+            .enumerate()
+            .map(|(i, word)| {
+                let mut data = 0u64;
+                layout.write(&mut data, "header.sof", (i == 0) as u64);
+                layout.write(&mut data, "header.eof", (i == words.len() - 1) as u64);
+                layout.write(&mut data, "header.toggle", (i % 2) as u64);
+                layout.write(&mut data, "header.od_index", od_index);
+                layout.write(&mut data, "header.client_id", client_id);
+                layout.write(&mut data, "header.server_id", server_id);
+                layout.write(&mut data, "data", *word as u64);
+                message_frame(resp, data)
+            })
+            .collect()
+    }
+
+    fn set_request(&mut self, data: u64) -> Vec<NetworkFrame> {
+        let Some((od_index, client_id, words)) = reassemble(
+            &self.protocol.set_req,
+            &mut self.set_buffers,
+            self.node.id() as u64,
+            data,
+        ) else {
+            return vec![];
+        };
+        let erno = if self.accepts(od_index, &words) {
+            self.apply(od_index, &words);
+            ERNO_SUCCESS
+        } else {
+            ERNO_ERROR
+        };
+
+        let resp = self.network.set_resp_message();
+        let layout = &self.protocol.set_resp;
+        let mut data = 0u64;
+        layout.write(&mut data, "header.od_index", od_index);
+        layout.write(&mut data, "header.client_id", client_id);
+        layout.write(&mut data, "header.server_id", self.node.id() as u64);
+        layout.write(&mut data, "header.erno", erno);
+        vec![message_frame(resp, data)]
+    }
+
+    /// Like the generated code, only one client can stage at a time and
+    /// entries beyond the failed bitmap are ignored.
+    fn stage_request(&mut self, data: u64) -> Vec<NetworkFrame> {
+        let Some((od_index, client_id, words)) = reassemble(
+            &self.protocol.set_stage_req,
+            &mut self.stage_buffers,
+            self.node.id() as u64,
+            data,
+        ) else {
+            return vec![];
+        };
+        let words = self.accepts(od_index, &words).then_some(words);
+        self.expire_stage();
+        let stage = self.stage.get_or_insert(Stage {
+            client_id,
+            entries: vec![],
+            last_staged: Instant::now(),
+        });
+        if stage.client_id != client_id || stage.entries.len() >= MAX_STAGED_ENTRIES {
+            return vec![];
+        }
+        stage.entries.push((od_index, words));
+        stage.last_staged = Instant::now();
+        vec![]
+    }
+
+    /// Discards the stage of a client, which stopped staging.
+    fn expire_stage(&mut self) {
+        if let Some(stage) = &self.stage {
+            if stage.last_staged.elapsed() > STAGE_TIMEOUT {
+                self.stage = None;
+            }
+        }
+    }
+
+    fn commit_request(&mut self, data: u64) -> Vec<NetworkFrame> {
+        let layout = &self.protocol.set_commit_req;
+        let field = |name: &str| layout.read(data, &format!("header.{name}"));
+        let (Some(client_id), Some(server_id), Some(action), Some(entries)) = (
+            field("client_id"),
+            field("server_id"),
+            field("action"),
+            field("entries"),
+        ) else {
+            return vec![];
+        };
+        if server_id != self.node.id() as u64 {
+            return vec![];
+        }
+        self.expire_stage();
+        let (erno, staged, failed) = match self.stage.take() {
+            // another client is staging, its transaction is left untouched.
+            Some(stage) if stage.client_id != client_id => {
+                self.stage = Some(stage);
+                (ERNO_ERROR, 0, 0)
+            }
+            stage => {
+                let staged = stage.map(|stage| stage.entries).unwrap_or_default();
+                let failed = staged
+                    .iter()
+                    .enumerate()
+                    .filter(|(_, (_, words))| words.is_none())
+                    .fold(0u64, |failed, (i, _)| failed | 1 << i);
+                let erno = if action == ACTION_ABORT {
+                    ERNO_ABORTED
+                } else if staged.len() as u64 != entries || failed != 0 {
+                    ERNO_ERROR
+                } else {
+                    for (od_index, words) in &staged {
+                        if let Some(words) = words {
+                            self.apply(*od_index, words);
+                        }
+                    }
+                    ERNO_SUCCESS
+                };
+                (erno, staged.len() as u64, failed)
+            }
+        };
+
+        let resp = self.network.set_commit_resp_message();
+        let layout = &self.protocol.set_commit_resp;
+        let mut data = 0u64;
+        layout.write(&mut data, "header.client_id", client_id);
+        layout.write(&mut data, "header.server_id", server_id);
+        layout.write(&mut data, "header.erno", erno);
+        layout.write(&mut data, "header.entries", staged);
+        layout.write(&mut data, "failed", failed);
+        vec![message_frame(resp, data)]
+    }
+
+    fn accepts(&self, od_index: u64, words: &[u32]) -> bool {
+        match self.entry(od_index) {
+            Some(entry) => {
+                matches!(entry.object_entry.access(), ObjectEntryAccess::Global)
+                    && words.len() == to_words(&entry.leaves, &entry.raw).len()
+            }
+            None => false,
+        }
+    }
+
+    fn apply(&mut self, od_index: u64, words: &[u32]) {
+        let node_name = self.node.name().to_owned();
+        let Some(entry) = self.entry_mut(od_index) else {
+            return;
+        };
+        entry.raw = from_words(&entry.leaves, words);
+        entry.generators.iter_mut().for_each(|generator| *generator = None);
+        cprintln!(
+            "<blue>{node_name}::{}</blue> was set",
+            entry.object_entry.name()
+        );
+    }
+}
+
+/// Collects the fragments of a set or stage request,
+/// returns the od_index, client_id and words after the last fragment.
+fn reassemble(
+    layout: &MessageLayout,
+    buffers: &mut HashMap<(u64, u64), Vec<u32>>,
+    node_id: u64,
+    data: u64,
+) -> Option<(u64, u64, Vec<u32>)> {
+    let field = |name: &str| layout.read(data, &format!("header.{name}"));
+    let (Some(sof), Some(eof), Some(od_index), Some(client_id), Some(server_id), Some(word)) = (
+        field("sof"),
+        field("eof"),
+        field("od_index"),
+        field("client_id"),
+        field("server_id"),
+        layout.read(data, "data"),
+    ) else {
+        return None;
+    };
+    if server_id != node_id {
+        return None;
+    }
+    let key = (client_id, od_index);
+    if sof != 0 {
+        buffers.insert(key, vec![]);
+    }
+    // None if the start of the request was missed.
+    let buffer = buffers.get_mut(&key)?;
+    buffer.push(word as u32);
+    if eof == 0 {
+        return None;
+    }
+    let words = buffers.remove(&key).unwrap_or_default();
+    Some((od_index, client_id, words))
+}
+
+fn matches(frame: &CanFrame, message: &Message) -> bool {
+    frame.get_id() == message.id().as_u32() && frame.get_ide_flag() == message.id().ide()
+}
+
+fn message_frame(message: &Message, data: u64) -> NetworkFrame {
+    NetworkFrame {
+        bus_id: message.bus().id(),
+        can_frame: CanFrame::new(
+            message.id().as_u32(),
+            message.id().ide(),
+            false,
+            message.dlc(),
+            data,
+        ),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use canzero_yaml::parse_yaml_config_from_file;
+
+    use super::*;
+
+    const CLIENT_ID: u64 = 5;
+
+    fn network() -> NetworkRef {
+        parse_yaml_config_from_file("../codegen/examples/conformance.yaml").unwrap()
+    }
+
+    fn od_index(node: &SimNode, name: &str) -> u64 {
+        node.entries
+            .iter()
+            .find(|entry| entry.object_entry.name() == name)
+            .map(|entry| entry.object_entry.id() as u64)
+            .unwrap()
+    }
+
+    fn get_request(node: &mut SimNode, od_index: u64) -> Vec<u64> {
+        let mut data = 0u64;
+        let layout = &node.protocol.get_req;
+        layout.write(&mut data, "header.od_index", od_index);
+        layout.write(&mut data, "header.client_id", CLIENT_ID);
+        layout.write(&mut data, "header.server_id", node.node.id() as u64);
+        node.get_request(data)
+            .iter()
+            .map(|frame| frame.can_frame.get_data_u64())
+            .collect()
+    }
+
+    /// Fragments of a set request, like the cnl sends them.
+    fn set_request(node: &SimNode, od_index: u64, words: &[u32]) -> Vec<u64> {
+        let layout = &node.protocol.set_req;
+        words
+            .iter()
+            .enumerate()
+            .map(|(i, word)| {
+                let mut data = 0u64;
+                layout.write(&mut data, "header.sof", (i == 0) as u64);
+                layout.write(&mut data, "header.eof", (i == words.len() - 1) as u64);
+                layout.write(&mut data, "header.toggle", (i % 2) as u64);
+                layout.write(&mut data, "header.od_index", od_index);
+                layout.write(&mut data, "header.client_id", CLIENT_ID);
+                layout.write(&mut data, "header.server_id", node.node.id() as u64);
+                layout.write(&mut data, "data", *word as u64);
+                data
+            })
+            .collect()
+    }
+
+    fn set_erno(node: &SimNode, responses: &[NetworkFrame]) -> Option<u64> {
+        let [response] = responses else {
+            return None;
+        };
+        node.protocol
+            .set_resp
+            .read(response.can_frame.get_data_u64(), "header.erno")
+    }
+
+    #[test]
+    fn get_response_fragments_round_trip_through_set_requests() {
+        let network = network();
+        let primary = &network.nodes()[0];
+        let mut server = SimNode::new(&network, primary);
+        let index = od_index(&server, "wide");
+        let raw = vec![0xAB_CDEF_0123, 0x8_1234, 0xABC];
+        server.entry_mut(index).unwrap().raw = raw.clone();
+
+        let fragments = get_request(&mut server, index);
+        let layout = &server.protocol.get_resp;
+        let header = |data: u64, name: &str| layout.read(data, &format!("header.{name}")).unwrap();
+        // 40 + 20 + 12 bits
+        assert_eq!(fragments.len(), 3);
+        for (i, data) in fragments.iter().enumerate() {
+            assert_eq!(header(*data, "sof"), (i == 0) as u64);
+            assert_eq!(header(*data, "eof"), (i == 2) as u64);
+            assert_eq!(header(*data, "toggle"), (i % 2) as u64);
+            assert_eq!(header(*data, "od_index"), index);
+            assert_eq!(header(*data, "client_id"), CLIENT_ID);
+        }
+        let words: Vec<u32> = fragments
+            .iter()
+            .map(|data| layout.read(*data, "data").unwrap() as u32)
+            .collect();
+
+        let mut client = SimNode::new(&network, primary);
+        let mut responses = vec![];
+        for data in set_request(&client, index, &words) {
+            responses = client.set_request(data);
+        }
+        assert_eq!(set_erno(&client, &responses), Some(ERNO_SUCCESS));
+        assert_eq!(client.entry(index).unwrap().raw, raw);
+    }
+
+    #[test]
+    fn set_requests_without_start_of_frame_are_ignored() {
+        let network = network();
+        let mut node = SimNode::new(&network, &network.nodes()[0]);
+        let index = od_index(&node, "u64_value");
+        let fragments = set_request(&node, index, &[0xDEADBEEF, 0x01234567]);
+        assert!(node.set_request(fragments[1]).is_empty());
+
+        assert!(node.set_request(fragments[0]).is_empty());
+        let responses = node.set_request(fragments[1]);
+        assert_eq!(set_erno(&node, &responses), Some(ERNO_SUCCESS));
+        assert_eq!(node.entry(index).unwrap().raw, vec![0x01234567_DEADBEEF]);
+    }
+
+    #[test]
+    fn set_requests_of_the_wrong_size_are_rejected() {
+        let network = network();
+        let mut node = SimNode::new(&network, &network.nodes()[0]);
+        let index = od_index(&node, "u64_value");
+        let fragments = set_request(&node, index, &[0xDEADBEEF]);
+        let responses = node.set_request(fragments[0]);
+        assert_eq!(set_erno(&node, &responses), Some(ERNO_ERROR));
+        assert_eq!(node.entry(index).unwrap().raw, vec![0]);
+    }
+
+    #[test]
+    fn heartbeats_announce_the_heartbeat_timeout() {
+        let network = network();
+        let node_ref = &network.nodes()[0];
+        let node = SimNode::new(&network, node_ref);
+        let frames = node.heartbeat_frames();
+        assert_eq!(frames.len(), node_ref.buses().len());
+        let ticks_next = (frames[0].can_frame.get_data_u64() >> 9) & 0x7F;
+        let expected = node_ref.heartbeat_timeout().as_millis().div_ceil(50);
+        assert!(expected > 0);
+        assert_eq!(ticks_next as u128, expected);
+        assert_eq!(frames[0].can_frame.get_data_u64() & 0xFF, node_ref.id() as u64);
+    }
+}
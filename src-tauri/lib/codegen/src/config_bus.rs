@@ -0,0 +1,62 @@
+use canzero_config::config::{self, bus::BusRef, MessageRef};
+
+use crate::errors::Result;
+use crate::options::Options;
+
+/// Messages which are received on the bus. Configuration requests are received on
+/// every bus the node is attached to, so that clients can fall back to another bus.
+pub fn bus_rx_messages(node_config: &config::NodeRef, bus: &BusRef) -> Vec<MessageRef> {
+    if !node_config.buses().iter().any(|b| b.id() == bus.id()) {
+        return vec![];
+    }
+    node_config
+        .rx_messages()
+        .iter()
+        .filter(|message| message.bus().id() == bus.id() || message.usage().is_configuration())
+        .cloned()
+        .collect()
+}
+
+/// `__config_bus` holds the bus of the configuration request, which is currently handled,
+/// if it was received on another bus than the bus of its message, otherwise -1.
+/// Responses are sent with `{namespace}_config_send({namespace}_config_bus(bus_id), ...)`.
+pub fn generate_config_bus(
+    node_config: &config::NodeRef,
+    source: &mut String,
+    options: &Options,
+) -> Result<()> {
+    let namespace = options.namespace();
+    let mut indent = String::new();
+    for _ in 0..options.indent() {
+        indent.push(' ');
+    }
+    let indent2 = format!("{indent}{indent}");
+
+    let mut send_cases = String::new();
+    for bus in node_config.buses() {
+        let bus_id = bus.id();
+        let bus_name = bus.name();
+        send_cases.push_str(&format!(
+            "{indent}case {bus_id}:
+{indent2}{namespace}_{bus_name}_send(frame);
+{indent2}break;
+"
+        ));
+    }
+
+    source.push_str(&format!(
+        "static int32_t __config_bus = -1;
+static uint32_t {namespace}_config_bus(uint32_t bus_id) {{
+{indent}if (__config_bus < 0) {{
+{indent2}return bus_id;
+{indent}}}
+{indent}return (uint32_t)__config_bus;
+}}
+static void {namespace}_config_send(uint32_t bus_id, {namespace}_frame* frame) {{
+{indent}switch (bus_id) {{
+{send_cases}{indent}}}
+}}
+"
+    ));
+    Ok(())
+}
@@ -6,6 +6,9 @@ pub type Result<T> = std::result::Result<T, Error>;
 pub enum Error {
     InvalidNodeName,
     DuplicatedBlockIdentifier,
+    UnsupportedBaudrate { bus_name : String, baudrate : u32, can_clock : u32 },
+    UnsupportedBusCount(usize),
+    SimulationRequiresLinux,
     Io(std::io::Error),
 }
 
@@ -1,6 +1,6 @@
 use std::str::FromStr;
 
-use crate::{errors::Result, options::Options};
+use crate::{errors::Result, options::{Options, Platform}};
 
 pub fn generate_includes(
     source: &mut String,
@@ -16,7 +16,19 @@ pub fn generate_includes(
         .unwrap()
         .to_owned();
     source.push_str(&format!("#include \"{header_name}\"\n"));
-    source.push_str("#include <avr/pgmspace.h>\n");
+    match options.platform() {
+        Platform::Linux if !options.simulation() => {
+            source.push_str("#include <avr/pgmspace.h>\n");
+        }
+        Platform::Teensy4FlexCan => {
+            source.push_str("#include <avr/pgmspace.h>\n");
+        }
+        Platform::Linux | Platform::Stm32BxCan | Platform::Stm32FdCan => {
+            // no dedicated memory regions on STM32 or a simulated node.
+            source.push_str("#ifndef DMAMEM\n#define DMAMEM\n#endif\n");
+            source.push_str("#ifndef PROGMEM\n#define PROGMEM\n#endif\n");
+        }
+    }
     
     header.push_str("#include <cinttypes>\n");
     header.push_str("#include <cstddef>\n");
@@ -1,16 +1,19 @@
 use canzero_config::config;
 use can_module_hooks::generate_hooks;
 use command_hooks::generate_command_hooks;
+use config_bus::generate_config_bus;
 use errors::{Error, Result};
 use header_guard::{generate_header_guard_top, generate_header_guard_bottom};
 use includes::generate_includes;
-use options::Options;
+use options::{Options, Platform};
 use pil::generate_pil;
+use platform::generate_platform;
 use rx_handlers::generate_rx_handlers;
 use scheduler::generate_scheduler;
 use senders::generate_senders;
-use setters::generate_setters;
+use setters::{generate_setters, generate_staged_setters};
 use setup::generate_setup;
+use sim::{generate_sim_main, generate_sim_pil};
 use types::generate_types;
 use update::generate_update;
 
@@ -20,15 +23,18 @@ use crate::{
 
 mod includes;
 mod can_module_hooks;
-mod command_hooks;
+mod config_bus;
+pub mod command_hooks;
 pub mod errors;
 mod messages;
 mod object_entries;
 pub mod options;
 mod pil;
+mod platform;
 mod poll;
 mod rx_handlers;
 mod setup;
+mod sim;
 mod types;
 mod update;
 mod header_guard;
@@ -36,11 +42,60 @@ mod scheduler;
 mod setters;
 pub mod senders;
 
+/// A generated file and the path it should be written to.
+pub struct GeneratedFile {
+    pub path: String,
+    pub content: String,
+}
+
+impl GeneratedFile {
+    /// Writes the file unless it already exists with the same content,
+    /// which keeps the timestamps of unchanged files and with that
+    /// avoids needless rebuilds. Returns true iff. the file was written.
+    pub fn write_if_changed(&self) -> Result<bool> {
+        if let Ok(existing) = std::fs::read_to_string(&self.path) {
+            if existing == self.content {
+                return Ok(false);
+            }
+        }
+        std::fs::write(&self.path, &self.content)?;
+        Ok(true)
+    }
+}
+
 pub fn generate(
     node_name: &str,
     network_config: config::NetworkRef,
     options: Options,
 ) -> Result<()> {
+    for file in generate_files(node_name, network_config, &options)? {
+        std::fs::write(&file.path, &file.content)
+            .expect(&format!("failed to write to {}", file.path));
+    }
+    Ok(())
+}
+
+/// Same as `generate`, but only writes files whose content changed.
+/// Returns the paths of the written files.
+pub fn generate_if_changed(
+    node_name: &str,
+    network_config: config::NetworkRef,
+    options: Options,
+) -> Result<Vec<String>> {
+    let mut written = vec![];
+    for file in generate_files(node_name, network_config, &options)? {
+        if file.write_if_changed()? {
+            written.push(file.path);
+        }
+    }
+    Ok(written)
+}
+
+pub fn generate_files(
+    node_name: &str,
+    network_config: config::NetworkRef,
+    options: &Options,
+) -> Result<Vec<GeneratedFile>> {
     let Some(node_config) = network_config
         .nodes()
         .iter()
@@ -48,6 +103,9 @@ pub fn generate(
     else {
         return Err(Error::InvalidNodeName);
     };
+    if options.simulation() && !matches!(options.platform(), Platform::Linux) {
+        return Err(Error::SimulationRequiresLinux);
+    }
 
     // TODO setup paths relativ to the workspace directory!
 
@@ -55,64 +113,78 @@ pub fn generate(
     let mut header = String::new();
 
     generate_header_guard_top(&mut header)?;
-    generate_includes(&mut src, &mut header, &options)?;
+    generate_includes(&mut src, &mut header, options)?;
     header += "#define MAX_DYN_HEARTBEATS 10\n";
-    generate_types(node_config, &mut header, &options)?;
+    generate_types(node_config, &mut header, options)?;
 
     header += &format!("static const node_id CANZERO_NODE_ID = node_id_{};\n", node_name);
 
-    generate_pil(&mut src, &mut header, &options)?;
-    generate_hooks(network_config.buses(), &mut src, &mut header, &options)?;
-    generate_command_hooks(node_config.commands(), &mut src, &mut header, &options)?;
+    generate_pil(&mut src, &mut header, options)?;
+    generate_hooks(network_config.buses(), &mut src, &mut header, options)?;
+    generate_platform(node_config, network_config.buses(), &mut src, &mut header, options)?;
+    if options.simulation() {
+        generate_sim_pil(network_config.buses(), &mut src, &mut header, options)?;
+    }
+    generate_command_hooks(node_config.commands(), &mut src, &mut header, options)?;
 
     // generate_extern_guard_top(&mut header)?;
     generate_object_entries(
         node_config.object_entries(),
         &mut header,
         &mut src,
-        &options,
+        options,
     )?;
     generate_messages(
         node_config.tx_messages(),
         node_config.rx_messages(),
         &mut header,
         &mut src,
-        &options,
+        options,
     )?;
-    // generate_rx_queue(&mut header, &mut src, &options)?;
-    generate_scheduler(&network_config, node_config, &mut src, &mut header, &options)?;
+    // generate_rx_queue(&mut header, &mut src, options)?;
+    generate_config_bus(node_config, &mut src, options)?;
+    generate_scheduler(&network_config, node_config, &mut src, &mut header, options)?;
+    generate_staged_setters(node_config, &mut src, options)?;
     generate_rx_handlers(
         &network_config,
         node_config,
         &mut src,
         &mut header,
-        &options,
+        options,
     )?;
     generate_poll(
         node_config,
         network_config.buses(),
         &mut header,
         &mut src,
-        &options,
+        options,
     )?;
-    generate_update(&mut src, &mut header, &options)?;
-    generate_setup(node_config, &network_config, &mut src, &mut header, &options)?;
+    generate_update(&mut src, &mut header, options)?;
+    generate_setup(node_config, &network_config, &mut src, &mut header, options)?;
     
-    generate_setters(node_config, &mut header, &mut src, &options)?;
+    generate_setters(node_config, &mut header, &mut src, options)?;
 
-    generate_senders(node_config, &network_config, &mut header, &mut src, &options)?;
+    generate_senders(node_config, &network_config, &mut header, &mut src, options)?;
 
     // generate_extern_guard_bottom(&mut header)?;
     generate_header_guard_bottom(&mut header)?;
 
-    std::fs::write(options.source_file_path(), &src).expect(&format!(
-        "failed to write to {}",
-        options.source_file_path()
-    ));
-    std::fs::write(options.header_file_path(), &header).expect(&format!(
-        "failed to write to {}",
-        options.header_file_path()
-    ));
+    let mut files = vec![
+        GeneratedFile {
+            path: options.source_file_path().to_owned(),
+            content: src,
+        },
+        GeneratedFile {
+            path: options.header_file_path().to_owned(),
+            content: header,
+        },
+    ];
+    if options.simulation() {
+        files.push(GeneratedFile {
+            path: options.sim_main_file_path().to_owned(),
+            content: generate_sim_main(node_config, network_config.buses(), options)?,
+        });
+    }
 
     // src.include_file_buffer(&header);
     //
@@ -123,5 +195,5 @@ pub fn generate(
     // println!("SOURCE:");
     // println!("{src:?}");
 
-    Ok(())
+    Ok(files)
 }
@@ -147,22 +147,24 @@ pub fn generate_messages(
                                         }
                                     };
                                     let size = signal_type.size();
+                                    // NOTE the casts of the full width types prevent signed
+                                    // values from being sign extended into the following attributes.
                                     let val = if size < 8 {
                                         format!("(uint8_t)({var} & (0xFF >> (8 - {size})))")
                                     } else if size == 8 {
-                                        format!("{var}")
+                                        format!("(uint8_t)({var})")
                                     } else if size < 16 {
                                         format!("(uint16_t)({var} & (0xFFFF >> (16 - {size})))")
                                     } else if size == 16 {
-                                        format!("{var}")
+                                        format!("(uint16_t)({var})")
                                     } else if size < 32 {
                                         format!("(uint32_t)({var} & (0xFFFFFFFF >> (32 - {size})))")
                                     } else if size == 32 {
-                                        format!("{var}")
+                                        format!("(uint32_t)({var})")
                                     } else if size < 64 {
                                         format!("(uint64_t)({var} & (0xFFFFFFFFFFFFFFFF >> (64 - {size})))")
                                     } else if size == 64 {
-                                        format!("{var}")
+                                        format!("(uint64_t)({var})")
                                     } else {
                                         panic!("primitive data types larger than 64 bit are not supported");
                                     };
@@ -418,10 +420,10 @@ pub fn generate_messages(
                                         SignalType::SignedInt { size } => {
                                             if *size <= 32 {
                                                 deserialized_def.push_str(&format!("{indent}uint32_t tmp_{attrib_bit_offset} = {val_bits};\n"));
-                                                format!("(tmp_{attrib_bit_offset} & (((uint32_t)0x1) << ({size} - 1)) != 0) ? (tmp_{attrib_bit_offset} | (((uint32_t)0xFFFFFFFFul) << ({size} - 1))) : tmp_{attrib_bit_offset}")
+                                                format!("((tmp_{attrib_bit_offset} & (((uint32_t)0x1) << ({size} - 1))) != 0) ? (tmp_{attrib_bit_offset} | (((uint32_t)0xFFFFFFFFul) << ({size} - 1))) : tmp_{attrib_bit_offset}")
                                             }else {
                                                 deserialized_def.push_str(&format!("{indent}uint64_t tmp_{attrib_bit_offset} = {val_bits};\n"));
-                                                format!("(tmp_{attrib_bit_offset} & (((uint64_t)0x1) << ({size} - 1)) != 0) ? (tmp_{attrib_bit_offset} | (((uint64_t)0xFFFFFFFFFFFFFFFFull) << ({size} - 1))) : tmp_{attrib_bit_offset}")
+                                                format!("((tmp_{attrib_bit_offset} & (((uint64_t)0x1) << ({size} - 1))) != 0) ? (tmp_{attrib_bit_offset} | (((uint64_t)0xFFFFFFFFFFFFFFFFull) << ({size} - 1))) : tmp_{attrib_bit_offset}")
                                             }
                                         }
                                         SignalType::Decimal {
@@ -1,6 +1,25 @@
 
 pub enum Platform {
     Linux,
+    /// STM32 with the bxCAN peripheral (e.g. STM32F4), CAN1/CAN2.
+    Stm32BxCan,
+    /// STM32 with the FDCAN peripheral (e.g. STM32G4) in classic CAN mode.
+    Stm32FdCan,
+    /// Teensy 4.x (i.MX RT1062) FlexCAN1-3.
+    Teensy4FlexCan,
+}
+
+impl Platform {
+    /// Default frequency of the clock feeding the CAN peripheral,
+    /// used to derive the bit timing from the bus baudrate.
+    pub fn default_can_clock(&self) -> u32 {
+        match self {
+            Platform::Linux => 0,
+            Platform::Stm32BxCan => 42_000_000,
+            Platform::Stm32FdCan => 170_000_000,
+            Platform::Teensy4FlexCan => 24_000_000,
+        }
+    }
 }
 
 pub struct Options {
@@ -9,6 +28,9 @@ pub struct Options {
     platform : Platform,
     indent : usize,
     namespace : String,
+    can_clock : Option<u32>,
+    simulation : bool,
+    sim_main_file_path : String,
 }
 
 impl Default for Options {
@@ -19,6 +41,9 @@ impl Default for Options {
             platform : Platform::Linux,
             indent : 2,
             namespace : "canzero".to_owned(),
+            can_clock : None,
+            simulation : false,
+            sim_main_file_path : "main_sim.cpp".to_owned(),
         }
     }
 }
@@ -54,4 +79,24 @@ impl Options {
     pub fn set_namespace(&mut self, namespace : &str) {
         self.namespace = namespace.to_owned();
     }
+    pub fn can_clock(&self) -> u32 {
+        self.can_clock.unwrap_or(self.platform.default_can_clock())
+    }
+    pub fn set_can_clock(&mut self, can_clock : u32) {
+        self.can_clock = Some(can_clock);
+    }
+    /// Emits a Linux PIL (SocketCAN or TCP) and a main function
+    /// to run the node as a host process.
+    pub fn simulation(&self) -> bool {
+        self.simulation
+    }
+    pub fn set_simulation(&mut self, simulation : bool) {
+        self.simulation = simulation;
+    }
+    pub fn sim_main_file_path(&self) -> &str {
+        &self.sim_main_file_path
+    }
+    pub fn set_sim_main_file_path(&mut self, path : &str) {
+        self.sim_main_file_path = path.to_owned();
+    }
 }
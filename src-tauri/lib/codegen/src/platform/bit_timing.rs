@@ -0,0 +1,77 @@
+use std::ops::RangeInclusive;
+
+use crate::errors::{Error, Result};
+
+// sample point recommended by CiA 301 for all baudrates up to 1Mbit/s.
+const SAMPLE_POINT_PERMILLE: u32 = 875;
+
+/// Register limits of a CAN peripheral. All values are in time quanta
+/// (or plain dividers) and not in the "value - 1" encoding of the registers.
+pub struct BitTimingLimits {
+    pub prescaler: RangeInclusive<u32>,
+    pub tseg1: RangeInclusive<u32>,
+    pub tseg2: RangeInclusive<u32>,
+    pub sjw_max: u32,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct BitTiming {
+    pub prescaler: u32,
+    pub tseg1: u32,
+    pub tseg2: u32,
+    pub sjw: u32,
+}
+
+pub fn compute_bit_timing(
+    bus_name: &str,
+    baudrate: u32,
+    can_clock: u32,
+    limits: &BitTimingLimits,
+) -> Result<BitTiming> {
+    let unsupported = || Error::UnsupportedBaudrate {
+        bus_name: bus_name.to_owned(),
+        baudrate,
+        can_clock,
+    };
+    if baudrate == 0 || can_clock == 0 {
+        return Err(unsupported());
+    }
+
+    let mut best: Option<(u32, BitTiming)> = None;
+    for prescaler in limits.prescaler.clone() {
+        let Some(bit_clock) = baudrate.checked_mul(prescaler) else {
+            break;
+        };
+        if bit_clock > can_clock {
+            break;
+        }
+        if can_clock % bit_clock != 0 {
+            continue;
+        }
+        let tq = can_clock / bit_clock;
+        let ideal_tseg2 = (tq * (1000 - SAMPLE_POINT_PERMILLE) + 500) / 1000;
+        let tseg2 = ideal_tseg2.clamp(*limits.tseg2.start(), *limits.tseg2.end());
+        let Some(tseg1) = tq.checked_sub(1 + tseg2) else {
+            continue;
+        };
+        // prefer a later sample point over an invalid segment.
+        let tseg1 = tseg1.min(*limits.tseg1.end());
+        let tseg2 = tq - 1 - tseg1;
+        if !limits.tseg1.contains(&tseg1) || !limits.tseg2.contains(&tseg2) {
+            continue;
+        }
+        let sample_point = ((1 + tseg1) * 1000) / tq;
+        let error = sample_point.abs_diff(SAMPLE_POINT_PERMILLE);
+        let timing = BitTiming {
+            prescaler,
+            tseg1,
+            tseg2,
+            sjw: tseg2.min(limits.sjw_max),
+        };
+        match best {
+            Some((best_error, _)) if best_error <= error => (),
+            _ => best = Some((error, timing)),
+        }
+    }
+    best.map(|(_, timing)| timing).ok_or_else(unsupported)
+}
@@ -0,0 +1,223 @@
+use canzero_config::config::bus::BusRef;
+
+use crate::{errors::Result, options::Options};
+
+use super::bit_timing::{compute_bit_timing, BitTimingLimits};
+
+pub const MAX_INSTANCES: usize = 2;
+// CAN1 and CAN2 share 28 filter banks, CAN2 starts at bank 14.
+pub const FILTER_BANKS_PER_INSTANCE: usize = 14;
+const BASE_ADDRESSES: [u32; MAX_INSTANCES] = [0x40006400, 0x40006800];
+
+const LIMITS: BitTimingLimits = BitTimingLimits {
+    prescaler: 1..=1024,
+    tseg1: 1..=16,
+    tseg2: 1..=8,
+    sjw_max: 4,
+};
+
+pub fn generate_bxcan(buses: &Vec<BusRef>, source: &mut String, options: &Options) -> Result<()> {
+    let namespace = options.namespace();
+    let ns_upper = namespace.to_uppercase();
+    let mut indent = String::new();
+    for _ in 0..options.indent() {
+        indent.push(' ');
+    }
+    let indent2 = format!("{indent}{indent}");
+    let indent3 = format!("{indent2}{indent}");
+
+    source.push_str(&format!(
+        "typedef struct {{
+{indent}volatile uint32_t TIR;
+{indent}volatile uint32_t TDTR;
+{indent}volatile uint32_t TDLR;
+{indent}volatile uint32_t TDHR;
+}} {namespace}_bxcan_tx_mailbox;
+typedef struct {{
+{indent}volatile uint32_t RIR;
+{indent}volatile uint32_t RDTR;
+{indent}volatile uint32_t RDLR;
+{indent}volatile uint32_t RDHR;
+}} {namespace}_bxcan_rx_mailbox;
+typedef struct {{
+{indent}volatile uint32_t FR1;
+{indent}volatile uint32_t FR2;
+}} {namespace}_bxcan_filter_bank;
+typedef struct {{
+{indent}volatile uint32_t MCR;
+{indent}volatile uint32_t MSR;
+{indent}volatile uint32_t TSR;
+{indent}volatile uint32_t RF0R;
+{indent}volatile uint32_t RF1R;
+{indent}volatile uint32_t IER;
+{indent}volatile uint32_t ESR;
+{indent}volatile uint32_t BTR;
+{indent}uint32_t RESERVED0[88];
+{indent}{namespace}_bxcan_tx_mailbox TX[3];
+{indent}{namespace}_bxcan_rx_mailbox RX[2];
+{indent}uint32_t RESERVED1[12];
+{indent}volatile uint32_t FMR;
+{indent}volatile uint32_t FM1R;
+{indent}uint32_t RESERVED2;
+{indent}volatile uint32_t FS1R;
+{indent}uint32_t RESERVED3;
+{indent}volatile uint32_t FFA1R;
+{indent}uint32_t RESERVED4;
+{indent}volatile uint32_t FA1R;
+{indent}uint32_t RESERVED5[8];
+{indent}{namespace}_bxcan_filter_bank FB[28];
+}} {namespace}_bxcan_regs;
+static_assert(offsetof({namespace}_bxcan_regs, TX) == 0x180, \"invalid bxCAN register layout\");
+static_assert(offsetof({namespace}_bxcan_regs, FMR) == 0x200, \"invalid bxCAN register layout\");
+static_assert(offsetof({namespace}_bxcan_regs, FB) == 0x240, \"invalid bxCAN register layout\");
+// filter banks are only accessible through the registers of CAN1.
+#ifndef {ns_upper}_BXCAN_FILTER_BASE
+#define {ns_upper}_BXCAN_FILTER_BASE 0x{:08X}u
+#endif
+static uint32_t {namespace}_bxcan_id_to_reg(uint32_t id) {{
+{indent}uint32_t rtr = (id & {ns_upper}_FRAME_RTR_BIT) ? 0x2 : 0x0;
+{indent}if (id & {ns_upper}_FRAME_IDE_BIT) {{
+{indent2}return ((id & 0x1FFFFFFF) << 3) | 0x4 | rtr;
+{indent}}}
+{indent}return ((id & 0x7FF) << 21) | rtr;
+}}
+static uint32_t {namespace}_bxcan_filter_mask_to_reg({namespace}_can_filter* filter) {{
+{indent}uint32_t flags = ((filter->mask & {ns_upper}_FRAME_IDE_BIT) ? 0x4 : 0x0)
+{indent2}| ((filter->mask & {ns_upper}_FRAME_RTR_BIT) ? 0x2 : 0x0);
+{indent}if (filter->id & {ns_upper}_FRAME_IDE_BIT) {{
+{indent2}return ((filter->mask & 0x1FFFFFFF) << 3) | flags;
+{indent}}}
+{indent}return ((filter->mask & 0x7FF) << 21) | flags;
+}}
+",
+        BASE_ADDRESSES[0]
+    ));
+
+    for bus in buses {
+        let bus_name = bus.name();
+        let bus_upper = bus_name.to_uppercase();
+        let instance = bus.id() as usize;
+        let base_address = BASE_ADDRESSES[instance];
+        let first_bank = instance * FILTER_BANKS_PER_INSTANCE;
+        let timing = compute_bit_timing(bus_name, bus.baudrate(), options.can_clock(), &LIMITS)?;
+        let btr = ((timing.sjw - 1) << 24)
+            | ((timing.tseg2 - 1) << 20)
+            | ((timing.tseg1 - 1) << 16)
+            | (timing.prescaler - 1);
+        let regs = format!("(({namespace}_bxcan_regs*)({ns_upper}_{bus_upper}_BASE))");
+        let filter_regs = format!("(({namespace}_bxcan_regs*)({ns_upper}_BXCAN_FILTER_BASE))");
+        let rx_queue = format!("{namespace}_{bus_name}_rx_queue");
+        let tx_queue = format!("{namespace}_{bus_name}_tx_queue");
+
+        source.push_str(&format!(
+            "#ifndef {ns_upper}_{bus_upper}_BASE
+#define {ns_upper}_{bus_upper}_BASE 0x{base_address:08X}u
+#endif
+void {namespace}_{bus_name}_setup(uint32_t baudrate, {namespace}_can_filter* filters, int filter_count) {{
+{indent}// bit timing is derived from the configured baudrate at generation time.
+{indent}(void)baudrate;
+{indent}{namespace}_bxcan_regs* can = {regs};
+{indent}{namespace}_bxcan_regs* filter_can = {filter_regs};
+{indent}{namespace}_{bus_name}_platform_init();
+{indent}can->MCR &= ~0x2u; // leave sleep mode
+{indent}can->MCR |= 0x1u; // INRQ
+{indent}while ((can->MSR & 0x1u) == 0) {{}}
+{indent}// automatic bus-off recovery and transmission in chronological order.
+{indent}can->MCR |= (0x1u << 6) | (0x1u << 2);
+{indent}// prescaler = {}, tseg1 = {}, tseg2 = {}, sjw = {}
+{indent}can->BTR = 0x{btr:08X};
+
+{indent}filter_can->FMR |= 0x1u; // FINIT
+{indent}filter_can->FMR = (filter_can->FMR & ~(0x3Fu << 8)) | ({FILTER_BANKS_PER_INSTANCE}u << 8);
+{indent}bool accept_all = filter_count <= 0 || filter_count > {FILTER_BANKS_PER_INSTANCE};
+{indent}for (int i = 0; i < {FILTER_BANKS_PER_INSTANCE}; ++i) {{
+{indent2}uint32_t bank = {first_bank} + i;
+{indent2}uint32_t bank_bit = 0x1u << bank;
+{indent2}filter_can->FA1R &= ~bank_bit;
+{indent2}if (!accept_all && i >= filter_count) {{
+{indent3}continue;
+{indent2}}}
+{indent2}if (accept_all && i > 0) {{
+{indent3}continue;
+{indent2}}}
+{indent2}filter_can->FM1R &= ~bank_bit; // identifier mask mode
+{indent2}filter_can->FS1R |= bank_bit; // single 32-bit scale
+{indent2}filter_can->FFA1R &= ~bank_bit; // assigned to fifo 0
+{indent2}if (accept_all) {{
+{indent3}filter_can->FB[bank].FR1 = 0;
+{indent3}filter_can->FB[bank].FR2 = 0;
+{indent2}}} else {{
+{indent3}filter_can->FB[bank].FR1 = {namespace}_bxcan_id_to_reg(filters[i].id);
+{indent3}filter_can->FB[bank].FR2 = {namespace}_bxcan_filter_mask_to_reg(&filters[i]);
+{indent2}}}
+{indent2}filter_can->FA1R |= bank_bit;
+{indent}}}
+{indent}filter_can->FMR &= ~0x1u;
+
+{indent}// TMEIE, FMPIE0, FOVIE0
+{indent}can->IER = 0x1u | 0x2u | 0x8u;
+{indent}can->MCR &= ~0x1u;
+{indent}while (can->MSR & 0x1u) {{}}
+}}
+static void {namespace}_{bus_name}_write_mailbox(uint32_t mailbox, const {namespace}_frame* frame) {{
+{indent}{namespace}_bxcan_tx_mailbox* tx_mailbox = &{regs}->TX[mailbox];
+{indent}tx_mailbox->TDTR = frame->dlc & 0xF;
+{indent}tx_mailbox->TDLR = (uint32_t)frame->data[0] | ((uint32_t)frame->data[1] << 8)
+{indent2}| ((uint32_t)frame->data[2] << 16) | ((uint32_t)frame->data[3] << 24);
+{indent}tx_mailbox->TDHR = (uint32_t)frame->data[4] | ((uint32_t)frame->data[5] << 8)
+{indent2}| ((uint32_t)frame->data[6] << 16) | ((uint32_t)frame->data[7] << 24);
+{indent}tx_mailbox->TIR = {namespace}_bxcan_id_to_reg(frame->id) | 0x1u; // TXRQ
+}}
+void {namespace}_{bus_name}_send({namespace}_frame* frame) {{
+{indent}{namespace}_enter_critical();
+{indent}uint32_t tsr = {regs}->TSR;
+{indent}// TME0-2, CODE contains the next empty mailbox
+{indent}if ({namespace}_frame_queue_empty(&{tx_queue}) && (tsr & (0x7u << 26))) {{
+{indent2}{namespace}_{bus_name}_write_mailbox((tsr >> 24) & 0x3, frame);
+{indent}}} else {{
+{indent2}{namespace}_frame_queue_push(&{tx_queue}, frame);
+{indent}}}
+{indent}{namespace}_exit_critical();
+}}
+void {namespace}_{bus_name}_isr() {{
+{indent}{namespace}_bxcan_regs* can = {regs};
+{indent}{namespace}_frame frame;
+{indent}while (can->RF0R & 0x3u) {{
+{indent2}{namespace}_bxcan_rx_mailbox* rx_mailbox = &can->RX[0];
+{indent2}uint32_t rir = rx_mailbox->RIR;
+{indent2}if (rir & 0x4u) {{
+{indent3}frame.id = ((rir >> 3) & 0x1FFFFFFF) | {ns_upper}_FRAME_IDE_BIT;
+{indent2}}} else {{
+{indent3}frame.id = (rir >> 21) & 0x7FF;
+{indent2}}}
+{indent2}if (rir & 0x2u) {{
+{indent3}frame.id |= {ns_upper}_FRAME_RTR_BIT;
+{indent2}}}
+{indent2}frame.dlc = rx_mailbox->RDTR & 0xF;
+{indent2}uint32_t low = rx_mailbox->RDLR;
+{indent2}uint32_t high = rx_mailbox->RDHR;
+{indent2}for (int i = 0; i < 4; ++i) {{
+{indent3}frame.data[i] = (uint8_t)(low >> (8 * i));
+{indent3}frame.data[i + 4] = (uint8_t)(high >> (8 * i));
+{indent2}}}
+{indent2}can->RF0R = 0x20u; // RFOM0 releases the output mailbox
+{indent2}{namespace}_frame_queue_push(&{rx_queue}, &frame);
+{indent}}}
+{indent}if (can->RF0R & 0x10u) {{
+{indent2}can->RF0R = 0x10u; // FOVR0
+{indent2}{rx_queue}.overflow_count = {rx_queue}.overflow_count + 1;
+{indent}}}
+{indent}uint32_t request_completed = can->TSR & (0x1u | (0x1u << 8) | (0x1u << 16));
+{indent}if (request_completed) {{
+{indent2}can->TSR = request_completed;
+{indent2}while ((can->TSR & (0x7u << 26)) && {namespace}_frame_queue_pop(&{tx_queue}, &frame)) {{
+{indent3}{namespace}_{bus_name}_write_mailbox((can->TSR >> 24) & 0x3, &frame);
+{indent2}}}
+{indent}}}
+}}
+",
+            timing.prescaler, timing.tseg1, timing.tseg2, timing.sjw
+        ));
+    }
+    Ok(())
+}
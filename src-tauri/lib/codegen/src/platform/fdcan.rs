@@ -0,0 +1,240 @@
+use canzero_config::config::bus::BusRef;
+
+use crate::{errors::Result, options::Options};
+
+use super::bit_timing::{compute_bit_timing, BitTimingLimits};
+
+pub const MAX_INSTANCES: usize = 3;
+pub const STD_FILTER_COUNT: usize = 28;
+pub const EXT_FILTER_COUNT: usize = 8;
+const BASE_ADDRESSES: [u32; MAX_INSTANCES] = [0x40006400, 0x40006800, 0x40006C00];
+const SRAM_BASE_ADDRESS: u32 = 0x4000A400;
+
+// fixed message ram layout of the STM32G4 (in words, per instance).
+const SRAM_INSTANCE_SIZE: u32 = 212;
+const SRAM_STD_FILTER_OFFSET: u32 = 0;
+const SRAM_EXT_FILTER_OFFSET: u32 = 28;
+const SRAM_RX_FIFO0_OFFSET: u32 = 44;
+const SRAM_TX_BUFFER_OFFSET: u32 = 158;
+const SRAM_ELEMENT_SIZE: u32 = 18;
+
+const LIMITS: BitTimingLimits = BitTimingLimits {
+    prescaler: 1..=512,
+    tseg1: 2..=256,
+    tseg2: 2..=128,
+    sjw_max: 128,
+};
+
+pub fn generate_fdcan(buses: &Vec<BusRef>, source: &mut String, options: &Options) -> Result<()> {
+    let namespace = options.namespace();
+    let ns_upper = namespace.to_uppercase();
+    let mut indent = String::new();
+    for _ in 0..options.indent() {
+        indent.push(' ');
+    }
+    let indent2 = format!("{indent}{indent}");
+    let indent3 = format!("{indent2}{indent}");
+
+    source.push_str(&format!(
+        "typedef struct {{
+{indent}volatile uint32_t CREL;
+{indent}volatile uint32_t ENDN;
+{indent}uint32_t RESERVED1;
+{indent}volatile uint32_t DBTP;
+{indent}volatile uint32_t TEST;
+{indent}volatile uint32_t RWD;
+{indent}volatile uint32_t CCCR;
+{indent}volatile uint32_t NBTP;
+{indent}volatile uint32_t TSCC;
+{indent}volatile uint32_t TSCV;
+{indent}volatile uint32_t TOCC;
+{indent}volatile uint32_t TOCV;
+{indent}uint32_t RESERVED2[4];
+{indent}volatile uint32_t ECR;
+{indent}volatile uint32_t PSR;
+{indent}volatile uint32_t TDCR;
+{indent}uint32_t RESERVED3;
+{indent}volatile uint32_t IR;
+{indent}volatile uint32_t IE;
+{indent}volatile uint32_t ILS;
+{indent}volatile uint32_t ILE;
+{indent}uint32_t RESERVED4[8];
+{indent}volatile uint32_t RXGFC;
+{indent}volatile uint32_t XIDAM;
+{indent}volatile uint32_t HPMS;
+{indent}uint32_t RESERVED5;
+{indent}volatile uint32_t RXF0S;
+{indent}volatile uint32_t RXF0A;
+{indent}volatile uint32_t RXF1S;
+{indent}volatile uint32_t RXF1A;
+{indent}uint32_t RESERVED6[8];
+{indent}volatile uint32_t TXBC;
+{indent}volatile uint32_t TXFQS;
+{indent}volatile uint32_t TXBRP;
+{indent}volatile uint32_t TXBAR;
+{indent}volatile uint32_t TXBCR;
+{indent}volatile uint32_t TXBTO;
+{indent}volatile uint32_t TXBCF;
+{indent}volatile uint32_t TXBTIE;
+{indent}volatile uint32_t TXBCIE;
+{indent}volatile uint32_t TXEFS;
+{indent}volatile uint32_t TXEFA;
+}} {namespace}_fdcan_regs;
+static_assert(offsetof({namespace}_fdcan_regs, RXGFC) == 0x80, \"invalid FDCAN register layout\");
+static_assert(offsetof({namespace}_fdcan_regs, TXBC) == 0xC0, \"invalid FDCAN register layout\");
+#ifndef {ns_upper}_FDCAN_SRAM_BASE
+#define {ns_upper}_FDCAN_SRAM_BASE 0x{SRAM_BASE_ADDRESS:08X}u
+#endif
+"
+    ));
+
+    for bus in buses {
+        let bus_name = bus.name();
+        let bus_upper = bus_name.to_uppercase();
+        let instance = bus.id();
+        let base_address = BASE_ADDRESSES[instance as usize];
+        let timing = compute_bit_timing(bus_name, bus.baudrate(), options.can_clock(), &LIMITS)?;
+        let nbtp = ((timing.sjw - 1) << 25)
+            | ((timing.prescaler - 1) << 16)
+            | ((timing.tseg1 - 1) << 8)
+            | (timing.tseg2 - 1);
+        let regs = format!("(({namespace}_fdcan_regs*)({ns_upper}_{bus_upper}_BASE))");
+        let sram = format!("((volatile uint32_t*)({ns_upper}_FDCAN_SRAM_BASE) + {})", instance * SRAM_INSTANCE_SIZE);
+        let rx_queue = format!("{namespace}_{bus_name}_rx_queue");
+        let tx_queue = format!("{namespace}_{bus_name}_tx_queue");
+
+        source.push_str(&format!(
+            "#ifndef {ns_upper}_{bus_upper}_BASE
+#define {ns_upper}_{bus_upper}_BASE 0x{base_address:08X}u
+#endif
+void {namespace}_{bus_name}_setup(uint32_t baudrate, {namespace}_can_filter* filters, int filter_count) {{
+{indent}// bit timing is derived from the configured baudrate at generation time.
+{indent}(void)baudrate;
+{indent}{namespace}_fdcan_regs* can = {regs};
+{indent}volatile uint32_t* sram = {sram};
+{indent}{namespace}_{bus_name}_platform_init();
+{indent}can->CCCR |= 0x1u; // INIT
+{indent}while ((can->CCCR & 0x1u) == 0) {{}}
+{indent}can->CCCR |= 0x2u; // CCE
+{indent}// classic CAN without bit rate switching, automatic retransmission enabled.
+{indent}can->CCCR &= ~((0x1u << 8) | (0x1u << 9) | (0x1u << 6));
+{indent}// prescaler = {}, tseg1 = {}, tseg2 = {}, sjw = {}
+{indent}can->NBTP = 0x{nbtp:08X};
+{indent}for (uint32_t i = 0; i < {SRAM_INSTANCE_SIZE}; ++i) {{
+{indent2}sram[i] = 0;
+{indent}}}
+{indent}can->TXBC = 0; // tx fifo mode
+
+{indent}uint32_t std_count = 0;
+{indent}uint32_t ext_count = 0;
+{indent}for (int i = 0; i < filter_count; ++i) {{
+{indent2}if (filters[i].id & {ns_upper}_FRAME_IDE_BIT) {{
+{indent3}ext_count++;
+{indent2}}} else {{
+{indent3}std_count++;
+{indent2}}}
+{indent}}}
+{indent}if (filter_count <= 0 || std_count > {STD_FILTER_COUNT} || ext_count > {EXT_FILTER_COUNT}) {{
+{indent2}// accept all non matching frames into fifo 0.
+{indent2}can->RXGFC = 0;
+{indent}}} else {{
+{indent2}std_count = 0;
+{indent2}ext_count = 0;
+{indent2}for (int i = 0; i < filter_count; ++i) {{
+{indent3}{namespace}_can_filter* filter = &filters[i];
+{indent3}if (filter->id & {ns_upper}_FRAME_IDE_BIT) {{
+{indent3}{indent}volatile uint32_t* element = sram + {SRAM_EXT_FILTER_OFFSET} + 2 * ext_count++;
+{indent3}{indent}element[0] = (0x1u << 29) | (filter->id & 0x1FFFFFFF); // EFEC: store in fifo 0
+{indent3}{indent}element[1] = (0x2u << 30) | (filter->mask & 0x1FFFFFFF); // EFT: classic filter
+{indent3}}} else {{
+{indent3}{indent}// SFT: classic filter, SFEC: store in fifo 0
+{indent3}{indent}sram[{SRAM_STD_FILTER_OFFSET} + std_count++] = (0x2u << 30) | (0x1u << 27)
+{indent3}{indent}{indent}| ((filter->id & 0x7FF) << 16) | (filter->mask & 0x7FF);
+{indent3}}}
+{indent2}}}
+{indent2}// reject non matching frames and remote frames.
+{indent2}can->RXGFC = (ext_count << 24) | (std_count << 16) | (0x2u << 4) | (0x2u << 2) | 0x3u;
+{indent}}}
+
+{indent}// RF0NE, RF0LE, TCE all on interrupt line 0
+{indent}can->IE = 0x1u | 0x4u | (0x1u << 7);
+{indent}can->ILS = 0;
+{indent}can->TXBTIE = 0x7u;
+{indent}can->ILE = 0x1u;
+{indent}can->CCCR &= ~0x1u;
+{indent}while (can->CCCR & 0x1u) {{}}
+}}
+static void {namespace}_{bus_name}_write_tx_fifo(const {namespace}_frame* frame) {{
+{indent}{namespace}_fdcan_regs* can = {regs};
+{indent}uint32_t index = (can->TXFQS >> 16) & 0x3;
+{indent}volatile uint32_t* element = {sram} + {SRAM_TX_BUFFER_OFFSET} + index * {SRAM_ELEMENT_SIZE};
+{indent}uint32_t t0;
+{indent}if (frame->id & {ns_upper}_FRAME_IDE_BIT) {{
+{indent2}t0 = (0x1u << 30) | (frame->id & 0x1FFFFFFF);
+{indent}}} else {{
+{indent2}t0 = (frame->id & 0x7FF) << 18;
+{indent}}}
+{indent}if (frame->id & {ns_upper}_FRAME_RTR_BIT) {{
+{indent2}t0 |= 0x1u << 29;
+{indent}}}
+{indent}element[0] = t0;
+{indent}element[1] = (uint32_t)(frame->dlc & 0xF) << 16;
+{indent}element[2] = (uint32_t)frame->data[0] | ((uint32_t)frame->data[1] << 8)
+{indent2}| ((uint32_t)frame->data[2] << 16) | ((uint32_t)frame->data[3] << 24);
+{indent}element[3] = (uint32_t)frame->data[4] | ((uint32_t)frame->data[5] << 8)
+{indent2}| ((uint32_t)frame->data[6] << 16) | ((uint32_t)frame->data[7] << 24);
+{indent}can->TXBAR = 0x1u << index;
+}}
+void {namespace}_{bus_name}_send({namespace}_frame* frame) {{
+{indent}{namespace}_enter_critical();
+{indent}// TFQF: tx fifo full
+{indent}if ({namespace}_frame_queue_empty(&{tx_queue}) && !({regs}->TXFQS & (0x1u << 21))) {{
+{indent2}{namespace}_{bus_name}_write_tx_fifo(frame);
+{indent}}} else {{
+{indent2}{namespace}_frame_queue_push(&{tx_queue}, frame);
+{indent}}}
+{indent}{namespace}_exit_critical();
+}}
+void {namespace}_{bus_name}_isr() {{
+{indent}{namespace}_fdcan_regs* can = {regs};
+{indent}{namespace}_frame frame;
+{indent}uint32_t ir = can->IR;
+{indent}can->IR = ir;
+{indent}if (ir & 0x4u) {{
+{indent2}// RF0L: message lost
+{indent2}{rx_queue}.overflow_count = {rx_queue}.overflow_count + 1;
+{indent}}}
+{indent}while (can->RXF0S & 0xFu) {{
+{indent2}uint32_t index = (can->RXF0S >> 8) & 0x3;
+{indent2}volatile uint32_t* element = {sram} + {SRAM_RX_FIFO0_OFFSET} + index * {SRAM_ELEMENT_SIZE};
+{indent2}uint32_t r0 = element[0];
+{indent2}if (r0 & (0x1u << 30)) {{
+{indent3}frame.id = (r0 & 0x1FFFFFFF) | {ns_upper}_FRAME_IDE_BIT;
+{indent2}}} else {{
+{indent3}frame.id = (r0 >> 18) & 0x7FF;
+{indent2}}}
+{indent2}if (r0 & (0x1u << 29)) {{
+{indent3}frame.id |= {ns_upper}_FRAME_RTR_BIT;
+{indent2}}}
+{indent2}frame.dlc = (element[1] >> 16) & 0xF;
+{indent2}uint32_t low = element[2];
+{indent2}uint32_t high = element[3];
+{indent2}for (int i = 0; i < 4; ++i) {{
+{indent3}frame.data[i] = (uint8_t)(low >> (8 * i));
+{indent3}frame.data[i + 4] = (uint8_t)(high >> (8 * i));
+{indent2}}}
+{indent2}can->RXF0A = index;
+{indent2}{namespace}_frame_queue_push(&{rx_queue}, &frame);
+{indent}}}
+{indent}if (ir & (0x1u << 7)) {{
+{indent2}while (!(can->TXFQS & (0x1u << 21)) && {namespace}_frame_queue_pop(&{tx_queue}, &frame)) {{
+{indent3}{namespace}_{bus_name}_write_tx_fifo(&frame);
+{indent2}}}
+{indent}}}
+}}
+",
+            timing.prescaler, timing.tseg1, timing.tseg2, timing.sjw
+        ));
+    }
+    Ok(())
+}
@@ -0,0 +1,101 @@
+use canzero_config::config::{self, bus::BusRef};
+
+use crate::config_bus::bus_rx_messages;
+use crate::options::Options;
+
+const STANDARD_ID_MASK: u32 = 0x7FF;
+const EXTENDED_ID_MASK: u32 = 0x1FFFFFFF;
+
+/// A hardware acceptance filter. `id` and `mask` only cover the
+/// identifier bits, the IDE and RTR bits are always matched exactly.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct CanFilter {
+    pub id: u32,
+    pub mask: u32,
+    pub ide: bool,
+}
+
+impl CanFilter {
+    fn exact(message_id: &config::MessageId) -> Self {
+        match message_id {
+            config::MessageId::StandardId(id) => CanFilter {
+                id: *id,
+                mask: STANDARD_ID_MASK,
+                ide: false,
+            },
+            config::MessageId::ExtendedId(id) => CanFilter {
+                id: *id,
+                mask: EXTENDED_ID_MASK,
+                ide: true,
+            },
+        }
+    }
+
+    fn merge(&self, other: &CanFilter) -> CanFilter {
+        let mask = self.mask & other.mask & !(self.id ^ other.id);
+        CanFilter {
+            id: self.id & mask,
+            mask,
+            ide: self.ide,
+        }
+    }
+
+    /// Renders the filter as a {namespace}_can_filter initializer.
+    pub fn to_c_initializer(&self, options: &Options) -> String {
+        let ns_upper = options.namespace().to_uppercase();
+        let mask = self.mask;
+        let id = self.id;
+        if self.ide {
+            format!(
+                "{{ 0x{mask:X} | {ns_upper}_FRAME_IDE_BIT | {ns_upper}_FRAME_RTR_BIT, 0x{id:X} | {ns_upper}_FRAME_IDE_BIT }}"
+            )
+        } else {
+            format!(
+                "{{ 0x{mask:X} | {ns_upper}_FRAME_IDE_BIT | {ns_upper}_FRAME_RTR_BIT, 0x{id:X} }}"
+            )
+        }
+    }
+}
+
+/// Exact match filters for every message the node receives on the bus,
+/// including the configuration requests of the other buses.
+pub fn rx_filters(node_config: &config::NodeRef, bus: &BusRef) -> Vec<CanFilter> {
+    let mut filters: Vec<CanFilter> = vec![];
+    for message in bus_rx_messages(node_config, bus) {
+        let filter = CanFilter::exact(message.id());
+        if !filters.contains(&filter) {
+            filters.push(filter);
+        }
+    }
+    filters
+}
+
+/// Merges filters until at most `capacity` remain. Every merge picks the pair
+/// whose combined mask keeps the most bits, so the additional frames that
+/// pass the hardware filter stay as few as possible. Frames that are accepted
+/// by accident are dropped by the poll function anyway.
+pub fn reduce_filters(mut filters: Vec<CanFilter>, capacity: usize) -> Vec<CanFilter> {
+    while filters.len() > capacity {
+        let mut best: Option<(usize, usize, u32)> = None;
+        for i in 0..filters.len() {
+            for j in (i + 1)..filters.len() {
+                if filters[i].ide != filters[j].ide {
+                    continue;
+                }
+                let kept_bits = filters[i].merge(&filters[j]).mask.count_ones();
+                match best {
+                    Some((_, _, best_bits)) if best_bits >= kept_bits => (),
+                    _ => best = Some((i, j, kept_bits)),
+                }
+            }
+        }
+        let Some((i, j, _)) = best else {
+            // only one filter per frame format left, which can't be merged.
+            break;
+        };
+        let merged = filters[i].merge(&filters[j]);
+        filters.remove(j);
+        filters[i] = merged;
+    }
+    filters
+}
@@ -0,0 +1,224 @@
+use canzero_config::config::bus::BusRef;
+
+use crate::{errors::Result, options::Options};
+
+use super::bit_timing::{compute_bit_timing, BitTimingLimits};
+
+pub const MAX_INSTANCES: usize = 3;
+// legacy rx fifo with RFFN = 0 provides 8 id filter elements (MB6-7),
+// the fifo itself occupies MB0-5.
+pub const RX_FIFO_FILTER_COUNT: usize = 8;
+// a single tx mailbox keeps the frames in chronological order, which the
+// fragmentation protocol depends on.
+const TX_MAILBOX: u32 = 8;
+const BASE_ADDRESSES: [u32; MAX_INSTANCES] = [0x401D0000, 0x401D4000, 0x401D8000];
+
+// tseg1 = PROPSEG + PSEG1, both in 1..=8.
+const LIMITS: BitTimingLimits = BitTimingLimits {
+    prescaler: 1..=256,
+    tseg1: 2..=16,
+    tseg2: 2..=8,
+    sjw_max: 4,
+};
+
+pub fn generate_flexcan(buses: &Vec<BusRef>, source: &mut String, options: &Options) -> Result<()> {
+    let namespace = options.namespace();
+    let ns_upper = namespace.to_uppercase();
+    let mut indent = String::new();
+    for _ in 0..options.indent() {
+        indent.push(' ');
+    }
+    let indent2 = format!("{indent}{indent}");
+    let indent3 = format!("{indent2}{indent}");
+
+    source.push_str(&format!(
+        "typedef struct {{
+{indent}volatile uint32_t CS;
+{indent}volatile uint32_t ID;
+{indent}volatile uint32_t WORD0;
+{indent}volatile uint32_t WORD1;
+}} {namespace}_flexcan_mb;
+typedef struct {{
+{indent}volatile uint32_t MCR;
+{indent}volatile uint32_t CTRL1;
+{indent}volatile uint32_t TIMER;
+{indent}uint32_t RESERVED0;
+{indent}volatile uint32_t RXMGMASK;
+{indent}volatile uint32_t RX14MASK;
+{indent}volatile uint32_t RX15MASK;
+{indent}volatile uint32_t ECR;
+{indent}volatile uint32_t ESR1;
+{indent}volatile uint32_t IMASK2;
+{indent}volatile uint32_t IMASK1;
+{indent}volatile uint32_t IFLAG2;
+{indent}volatile uint32_t IFLAG1;
+{indent}volatile uint32_t CTRL2;
+{indent}volatile uint32_t ESR2;
+{indent}uint32_t RESERVED1[2];
+{indent}volatile uint32_t CRCR;
+{indent}volatile uint32_t RXFGMASK;
+{indent}volatile uint32_t RXFIR;
+{indent}uint32_t RESERVED2[12];
+{indent}{namespace}_flexcan_mb MB[64];
+{indent}uint32_t RESERVED3[256];
+{indent}volatile uint32_t RXIMR[64];
+}} {namespace}_flexcan_regs;
+static_assert(offsetof({namespace}_flexcan_regs, MB) == 0x80, \"invalid FlexCAN register layout\");
+static_assert(offsetof({namespace}_flexcan_regs, RXIMR) == 0x880, \"invalid FlexCAN register layout\");
+// rx fifo id filter table element, format A.
+static uint32_t {namespace}_flexcan_filter_id_to_reg(uint32_t id) {{
+{indent}uint32_t flags = ((id & {ns_upper}_FRAME_RTR_BIT) ? (0x1u << 31) : 0x0)
+{indent2}| ((id & {ns_upper}_FRAME_IDE_BIT) ? (0x1u << 30) : 0x0);
+{indent}if (id & {ns_upper}_FRAME_IDE_BIT) {{
+{indent2}return flags | ((id & 0x1FFFFFFF) << 1);
+{indent}}}
+{indent}return flags | ((id & 0x7FF) << 19);
+}}
+static uint32_t {namespace}_flexcan_filter_mask_to_reg({namespace}_can_filter* filter) {{
+{indent}uint32_t flags = ((filter->mask & {ns_upper}_FRAME_RTR_BIT) ? (0x1u << 31) : 0x0)
+{indent2}| ((filter->mask & {ns_upper}_FRAME_IDE_BIT) ? (0x1u << 30) : 0x0);
+{indent}if (filter->id & {ns_upper}_FRAME_IDE_BIT) {{
+{indent2}return flags | ((filter->mask & 0x1FFFFFFF) << 1);
+{indent}}}
+{indent}return flags | ((filter->mask & 0x7FF) << 19);
+}}
+"
+    ));
+
+    for bus in buses {
+        let bus_name = bus.name();
+        let bus_upper = bus_name.to_uppercase();
+        let base_address = BASE_ADDRESSES[bus.id() as usize];
+        let timing = compute_bit_timing(bus_name, bus.baudrate(), options.can_clock(), &LIMITS)?;
+        let propseg = (timing.tseg1 + 1) / 2;
+        let pseg1 = timing.tseg1 - propseg;
+        let ctrl1 = ((timing.prescaler - 1) << 24)
+            | ((timing.sjw - 1) << 22)
+            | ((pseg1 - 1) << 19)
+            | ((timing.tseg2 - 1) << 16)
+            | (propseg - 1);
+        let regs = format!("(({namespace}_flexcan_regs*)({ns_upper}_{bus_upper}_BASE))");
+        let rx_queue = format!("{namespace}_{bus_name}_rx_queue");
+        let tx_queue = format!("{namespace}_{bus_name}_tx_queue");
+
+        source.push_str(&format!(
+            "#ifndef {ns_upper}_{bus_upper}_BASE
+#define {ns_upper}_{bus_upper}_BASE 0x{base_address:08X}u
+#endif
+void {namespace}_{bus_name}_setup(uint32_t baudrate, {namespace}_can_filter* filters, int filter_count) {{
+{indent}// bit timing is derived from the configured baudrate at generation time.
+{indent}(void)baudrate;
+{indent}{namespace}_flexcan_regs* can = {regs};
+{indent}{namespace}_{bus_name}_platform_init();
+{indent}can->MCR &= ~(0x1u << 31); // MDIS
+{indent}while (can->MCR & (0x1u << 20)) {{}} // LPMACK
+{indent}can->MCR |= 0x1u << 25; // SOFTRST
+{indent}while (can->MCR & (0x1u << 25)) {{}}
+{indent}can->MCR |= (0x1u << 30) | (0x1u << 28); // FRZ, HALT
+{indent}while ((can->MCR & (0x1u << 24)) == 0) {{}} // FRZACK
+{indent}// rx fifo, individual rx masks, no self reception, last mailbox is the tx mailbox.
+{indent}can->MCR = (can->MCR & ~(0x7Fu | (0x3u << 8)))
+{indent2}| (0x1u << 29) | (0x1u << 16) | (0x1u << 17) | {TX_MAILBOX}u;
+{indent}// prescaler = {}, propseg = {propseg}, pseg1 = {pseg1}, pseg2 = {}, rjw = {}
+{indent}can->CTRL1 = 0x{ctrl1:08X};
+{indent}can->CTRL2 &= ~(0xFu << 24); // RFFN = 0
+
+{indent}volatile uint32_t* filter_table = &can->MB[6].CS;
+{indent}bool accept_all = filter_count <= 0 || filter_count > {RX_FIFO_FILTER_COUNT};
+{indent}for (int i = 0; i < {RX_FIFO_FILTER_COUNT}; ++i) {{
+{indent2}if (accept_all) {{
+{indent3}filter_table[i] = 0;
+{indent3}can->RXIMR[i] = 0;
+{indent2}}} else {{
+{indent3}// unused elements repeat the first filter.
+{indent3}{namespace}_can_filter* filter = &filters[i < filter_count ? i : 0];
+{indent3}filter_table[i] = {namespace}_flexcan_filter_id_to_reg(filter->id);
+{indent3}can->RXIMR[i] = {namespace}_flexcan_filter_mask_to_reg(filter);
+{indent2}}}
+{indent}}}
+{indent}can->MB[{TX_MAILBOX}].CS = 0x8u << 24; // tx inactive
+
+{indent}// rx fifo frame available, rx fifo overflow, tx mailbox
+{indent}can->IMASK1 = (0x1u << 5) | (0x1u << 7) | (0x1u << {TX_MAILBOX});
+{indent}can->MCR &= ~((0x1u << 30) | (0x1u << 28));
+{indent}while (can->MCR & (0x1u << 24)) {{}}
+{indent}while (can->MCR & (0x1u << 27)) {{}} // NOTRDY
+}}
+static bool {namespace}_{bus_name}_tx_mailbox_free() {{
+{indent}return (({regs}->MB[{TX_MAILBOX}].CS >> 24) & 0xF) == 0x8;
+}}
+static void {namespace}_{bus_name}_write_tx_mailbox(const {namespace}_frame* frame) {{
+{indent}{namespace}_flexcan_mb* mb = &{regs}->MB[{TX_MAILBOX}];
+{indent}mb->CS = 0x8u << 24;
+{indent}uint32_t cs = (0xCu << 24) | ((uint32_t)(frame->dlc & 0xF) << 16);
+{indent}if (frame->id & {ns_upper}_FRAME_IDE_BIT) {{
+{indent2}mb->ID = frame->id & 0x1FFFFFFF;
+{indent2}cs |= (0x1u << 22) | (0x1u << 21); // SRR, IDE
+{indent}}} else {{
+{indent2}mb->ID = (frame->id & 0x7FF) << 18;
+{indent}}}
+{indent}if (frame->id & {ns_upper}_FRAME_RTR_BIT) {{
+{indent2}cs |= 0x1u << 20;
+{indent}}}
+{indent}mb->WORD0 = ((uint32_t)frame->data[0] << 24) | ((uint32_t)frame->data[1] << 16)
+{indent2}| ((uint32_t)frame->data[2] << 8) | (uint32_t)frame->data[3];
+{indent}mb->WORD1 = ((uint32_t)frame->data[4] << 24) | ((uint32_t)frame->data[5] << 16)
+{indent2}| ((uint32_t)frame->data[6] << 8) | (uint32_t)frame->data[7];
+{indent}mb->CS = cs;
+}}
+void {namespace}_{bus_name}_send({namespace}_frame* frame) {{
+{indent}{namespace}_enter_critical();
+{indent}if ({namespace}_frame_queue_empty(&{tx_queue}) && {namespace}_{bus_name}_tx_mailbox_free()) {{
+{indent2}{namespace}_{bus_name}_write_tx_mailbox(frame);
+{indent}}} else {{
+{indent2}{namespace}_frame_queue_push(&{tx_queue}, frame);
+{indent}}}
+{indent}{namespace}_exit_critical();
+}}
+void {namespace}_{bus_name}_isr() {{
+{indent}{namespace}_flexcan_regs* can = {regs};
+{indent}{namespace}_frame frame;
+{indent}uint32_t iflag = can->IFLAG1;
+{indent}while (can->IFLAG1 & (0x1u << 5)) {{
+{indent2}{namespace}_flexcan_mb* mb = &can->MB[0];
+{indent2}uint32_t cs = mb->CS;
+{indent2}uint32_t id = mb->ID;
+{indent2}if (cs & (0x1u << 21)) {{
+{indent3}frame.id = (id & 0x1FFFFFFF) | {ns_upper}_FRAME_IDE_BIT;
+{indent2}}} else {{
+{indent3}frame.id = (id >> 18) & 0x7FF;
+{indent2}}}
+{indent2}if (cs & (0x1u << 20)) {{
+{indent3}frame.id |= {ns_upper}_FRAME_RTR_BIT;
+{indent2}}}
+{indent2}frame.dlc = (cs >> 16) & 0xF;
+{indent2}uint32_t word0 = mb->WORD0;
+{indent2}uint32_t word1 = mb->WORD1;
+{indent2}for (int i = 0; i < 4; ++i) {{
+{indent3}frame.data[i] = (uint8_t)(word0 >> (24 - 8 * i));
+{indent3}frame.data[i + 4] = (uint8_t)(word1 >> (24 - 8 * i));
+{indent2}}}
+{indent2}(void)can->TIMER; // unlocks the mailbox
+{indent2}can->IFLAG1 = 0x1u << 5;
+{indent2}{namespace}_frame_queue_push(&{rx_queue}, &frame);
+{indent}}}
+{indent}if (iflag & (0x1u << 7)) {{
+{indent2}can->IFLAG1 = 0x1u << 7;
+{indent2}{rx_queue}.overflow_count = {rx_queue}.overflow_count + 1;
+{indent}}}
+{indent}if (iflag & (0x1u << 6)) {{
+{indent2}can->IFLAG1 = 0x1u << 6; // rx fifo warning
+{indent}}}
+{indent}if (iflag & (0x1u << {TX_MAILBOX})) {{
+{indent2}can->IFLAG1 = 0x1u << {TX_MAILBOX};
+{indent2}if ({namespace}_frame_queue_pop(&{tx_queue}, &frame)) {{
+{indent3}{namespace}_{bus_name}_write_tx_mailbox(&frame);
+{indent2}}}
+{indent}}}
+}}
+",
+            timing.prescaler, timing.tseg2, timing.sjw
+        ));
+    }
+    Ok(())
+}
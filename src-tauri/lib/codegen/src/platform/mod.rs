@@ -0,0 +1,198 @@
+use canzero_config::config::{self, bus::BusRef};
+
+use crate::{
+    errors::{Error, Result},
+    options::{Options, Platform},
+};
+
+use self::filters::{reduce_filters, rx_filters, CanFilter};
+
+mod bit_timing;
+mod bxcan;
+mod fdcan;
+mod filters;
+mod flexcan;
+
+/// Emits the implementation of the can module hooks
+/// ({namespace}_{bus}_setup/send/recv) for MCU platforms.
+/// For Linux the hooks are left to hand written glue code.
+pub fn generate_platform(
+    node_config: &config::NodeRef,
+    buses: &Vec<BusRef>,
+    source: &mut String,
+    header: &mut String,
+    options: &Options,
+) -> Result<()> {
+    let max_buses = match options.platform() {
+        Platform::Linux => return Ok(()),
+        Platform::Stm32BxCan => bxcan::MAX_INSTANCES,
+        Platform::Stm32FdCan => fdcan::MAX_INSTANCES,
+        Platform::Teensy4FlexCan => flexcan::MAX_INSTANCES,
+    };
+    if buses.len() > max_buses {
+        return Err(Error::UnsupportedBusCount(buses.len()));
+    }
+
+    let namespace = options.namespace();
+    generate_frame_queue(source, options)?;
+
+    for bus in buses {
+        let bus_name = bus.name();
+        header.push_str(&format!("void {namespace}_{bus_name}_isr();\n"));
+        header.push_str(&format!(
+            "uint32_t {namespace}_{bus_name}_rx_overflow_count();\n"
+        ));
+        // enables the peripheral clock, muxes the pins and enables the
+        // interrupts, which call {namespace}_{bus_name}_isr.
+        header.push_str(&format!(
+            "extern void {namespace}_{bus_name}_platform_init();\n"
+        ));
+
+        let filters = platform_filters(node_config, bus, options);
+        if !filters.is_empty() {
+            let mut filter_table = format!(
+                "static {namespace}_can_filter {namespace}_{bus_name}_filters[{}] = {{\n",
+                filters.len()
+            );
+            for filter in &filters {
+                filter_table.push_str(&format!(
+                    "{}{},\n",
+                    " ".repeat(options.indent()),
+                    filter.to_c_initializer(options)
+                ));
+            }
+            filter_table.push_str("};\n");
+            source.push_str(&filter_table);
+        }
+        generate_bus_queues(bus, source, options)?;
+    }
+
+    match options.platform() {
+        Platform::Linux => (),
+        Platform::Stm32BxCan => bxcan::generate_bxcan(buses, source, options)?,
+        Platform::Stm32FdCan => fdcan::generate_fdcan(buses, source, options)?,
+        Platform::Teensy4FlexCan => flexcan::generate_flexcan(buses, source, options)?,
+    }
+    Ok(())
+}
+
+/// Arguments of the {namespace}_{bus}_setup call in {namespace}_init.
+pub fn setup_filter_args(
+    node_config: &config::NodeRef,
+    bus: &BusRef,
+    options: &Options,
+) -> String {
+    if let Platform::Linux = options.platform() {
+        return "NULL, 0".to_owned();
+    }
+    let filters = platform_filters(node_config, bus, options);
+    if filters.is_empty() {
+        "NULL, 0".to_owned()
+    } else {
+        format!(
+            "{}_{}_filters, {}",
+            options.namespace(),
+            bus.name(),
+            filters.len()
+        )
+    }
+}
+
+fn platform_filters(node_config: &config::NodeRef, bus: &BusRef, options: &Options) -> Vec<CanFilter> {
+    let filters = rx_filters(node_config, bus);
+    match options.platform() {
+        Platform::Linux => filters,
+        Platform::Stm32BxCan => reduce_filters(filters, bxcan::FILTER_BANKS_PER_INSTANCE),
+        Platform::Stm32FdCan => {
+            // standard and extended filters are stored in separate lists.
+            let (ext_filters, std_filters): (Vec<CanFilter>, Vec<CanFilter>) =
+                filters.into_iter().partition(|f| f.ide);
+            let mut filters = reduce_filters(std_filters, fdcan::STD_FILTER_COUNT);
+            filters.extend(reduce_filters(ext_filters, fdcan::EXT_FILTER_COUNT));
+            filters
+        }
+        Platform::Teensy4FlexCan => reduce_filters(filters, flexcan::RX_FIFO_FILTER_COUNT),
+    }
+}
+
+fn generate_frame_queue(source: &mut String, options: &Options) -> Result<()> {
+    let namespace = options.namespace();
+    let ns_upper = namespace.to_uppercase();
+    let mut indent = String::new();
+    for _ in 0..options.indent() {
+        indent.push(' ');
+    }
+    let indent2 = format!("{indent}{indent}");
+
+    // single producer single consumer queue, the receive queue is filled by the
+    // isr and drained by the poll function, the transmit queue is filled by
+    // send (within a critical section) and drained by the isr.
+    source.push_str(&format!(
+        "#ifndef {ns_upper}_RX_QUEUE_SIZE
+#define {ns_upper}_RX_QUEUE_SIZE 32
+#endif
+#ifndef {ns_upper}_TX_QUEUE_SIZE
+#define {ns_upper}_TX_QUEUE_SIZE 32
+#endif
+static_assert(({ns_upper}_RX_QUEUE_SIZE & ({ns_upper}_RX_QUEUE_SIZE - 1)) == 0, \"{ns_upper}_RX_QUEUE_SIZE must be a power of two\");
+static_assert(({ns_upper}_TX_QUEUE_SIZE & ({ns_upper}_TX_QUEUE_SIZE - 1)) == 0, \"{ns_upper}_TX_QUEUE_SIZE must be a power of two\");
+typedef struct {{
+{indent}volatile uint32_t head;
+{indent}volatile uint32_t tail;
+{indent}volatile uint32_t overflow_count;
+{indent}uint32_t mask;
+{indent}{namespace}_frame* buffer;
+}} {namespace}_frame_queue;
+static bool {namespace}_frame_queue_empty({namespace}_frame_queue* queue) {{
+{indent}return queue->head == queue->tail;
+}}
+static bool {namespace}_frame_queue_push({namespace}_frame_queue* queue, const {namespace}_frame* frame) {{
+{indent}uint32_t head = queue->head;
+{indent}if (head - queue->tail > queue->mask) {{
+{indent2}queue->overflow_count = queue->overflow_count + 1;
+{indent2}return false;
+{indent}}}
+{indent}queue->buffer[head & queue->mask] = *frame;
+{indent}__atomic_thread_fence(__ATOMIC_RELEASE);
+{indent}queue->head = head + 1;
+{indent}return true;
+}}
+static bool {namespace}_frame_queue_pop({namespace}_frame_queue* queue, {namespace}_frame* frame) {{
+{indent}uint32_t tail = queue->tail;
+{indent}if (queue->head == tail) {{
+{indent2}return false;
+{indent}}}
+{indent}__atomic_thread_fence(__ATOMIC_ACQUIRE);
+{indent}*frame = queue->buffer[tail & queue->mask];
+{indent}__atomic_thread_fence(__ATOMIC_RELEASE);
+{indent}queue->tail = tail + 1;
+{indent}return true;
+}}
+"
+    ));
+    Ok(())
+}
+
+fn generate_bus_queues(bus: &BusRef, source: &mut String, options: &Options) -> Result<()> {
+    let namespace = options.namespace();
+    let ns_upper = namespace.to_uppercase();
+    let bus_name = bus.name();
+    let mut indent = String::new();
+    for _ in 0..options.indent() {
+        indent.push(' ');
+    }
+    source.push_str(&format!(
+        "static {namespace}_frame {namespace}_{bus_name}_rx_buffer[{ns_upper}_RX_QUEUE_SIZE];
+static {namespace}_frame_queue {namespace}_{bus_name}_rx_queue = {{ 0, 0, 0, {ns_upper}_RX_QUEUE_SIZE - 1, {namespace}_{bus_name}_rx_buffer }};
+static {namespace}_frame {namespace}_{bus_name}_tx_buffer[{ns_upper}_TX_QUEUE_SIZE];
+static {namespace}_frame_queue {namespace}_{bus_name}_tx_queue = {{ 0, 0, 0, {ns_upper}_TX_QUEUE_SIZE - 1, {namespace}_{bus_name}_tx_buffer }};
+int {namespace}_{bus_name}_recv({namespace}_frame* frame) {{
+{indent}return {namespace}_frame_queue_pop(&{namespace}_{bus_name}_rx_queue, frame);
+}}
+uint32_t {namespace}_{bus_name}_rx_overflow_count() {{
+{indent}return {namespace}_{bus_name}_rx_queue.overflow_count;
+}}
+"
+    ));
+    Ok(())
+}
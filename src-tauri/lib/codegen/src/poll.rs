@@ -1,6 +1,7 @@
 use canzero_config::config;
 use canzero_config::config::bus::BusRef;
 
+use crate::config_bus::bus_rx_messages;
 use crate::options::Options;
 
 use crate::errors::Result;
@@ -36,21 +37,29 @@ pub fn generate_poll(
         poll_func_def.push_str(&format!(
             "{indent2}switch (frame.id) {{\n"
         ));
-        for message in node_config.rx_messages() {
-            if message.bus().id() != bus_id {
-                continue;
-            }
+        for message in bus_rx_messages(node_config, bus) {
             let message_name = message.name();
             // first bit stands for ide bit
             let id = match message.id() {
                 config::MessageId::StandardId(id) => format!("0x{id:X}"),
                 config::MessageId::ExtendedId(id) => format!("(0x{id:X} | {}_FRAME_IDE_BIT)", namespace.to_uppercase()),
             };
-            poll_func_def.push_str(&format!("{indent3}case {id}:
+            if message.bus().id() == bus_id {
+                poll_func_def.push_str(&format!("{indent3}case {id}:
 {indent4}{namespace}_handle_{message_name}(&frame);
 {indent4}break;
 "
-            ));
+                ));
+            } else {
+                // configuration request on a fallback bus, the response is sent on this bus.
+                poll_func_def.push_str(&format!("{indent3}case {id}:
+{indent4}__config_bus = {bus_id};
+{indent4}{namespace}_handle_{message_name}(&frame);
+{indent4}__config_bus = -1;
+{indent4}break;
+"
+                ));
+            }
         }
         poll_func_def.push_str(&format!("{indent2}}}\n"));
         poll_func_def.push_str(&format!("{indent}}}\n"));
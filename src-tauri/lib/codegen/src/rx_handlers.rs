@@ -1,4 +1,4 @@
-use canzero_config::config::{self, message, Type};
+use canzero_config::config::{self, message, ObjectEntryAccess, Type};
 
 use crate::{errors::Result, options::Options, types::to_c_type_name};
 
@@ -67,7 +67,7 @@ pub fn generate_rx_handlers(
                         attribute_list += ", ";
                     }
                     let name = attrib.name();
-                    attribute_list += &format!("msg.{name}");
+                    attribute_list += &format!("msg.m_{name}");
                 }
                 let resp_msg = command.rx_message();
                 let resp_msg_name = resp_msg.name();
@@ -77,10 +77,10 @@ pub fn generate_rx_handlers(
                 (
                     format!(
                         "{indent}{namespace}_message_{resp_msg_name} resp{{}};
-{indent}resp.erno = {namespace}_{command_name}({attribute_list});
+{indent}resp.m_erno = {namespace}_{command_name}({attribute_list});
 {indent}{frame_type_name} resp_frame;
 {indent}{namespace}_serialize_{namespace}_message_{resp_msg_name}(&resp, &resp_frame);
-{indent}{namespace}_can{resp_bus_id}_send(&resp_frame);
+{indent}{namespace}_config_send({namespace}_config_bus({resp_bus_id}), &resp_frame);
 "
                     ),
                     "static",
@@ -90,6 +90,7 @@ pub fn generate_rx_handlers(
             message::MessageUsage::GetResp => panic!(),
             message::MessageUsage::GetReq => {
                 let mut logic = String::new();
+                let get_resp_bus_id = network_config.get_resp_message().bus().id();
                 let mut case_logic = format!("{indent}switch (msg.m_header.m_od_index) {{\n");
                 for object_entry in node_config.object_entries() {
                     let name = object_entry.name();
@@ -490,7 +491,7 @@ pub fn generate_rx_handlers(
 {indent2}resp.m_header.m_sof = 1;
 {indent2}resp.m_header.m_eof = 0;
 {indent2}resp.m_header.m_toggle = 0;
-{indent2}schedule_get_resp_fragmentation_job({buffer_name}, {buffer_size}, {od_index}, msg.m_header.m_client_id);
+{indent2}schedule_get_resp_fragmentation_job({buffer_name}, {buffer_size}, {od_index}, msg.m_header.m_client_id, {namespace}_config_bus({get_resp_bus_id}));
 {indent2}break;
 {indent}}}\n"
                         );
@@ -498,7 +499,6 @@ pub fn generate_rx_handlers(
                 }
                 case_logic += &format!("{indent}}}\n");
                 let node_name = node_config.name();
-                let resp_bus_name = network_config.get_resp_message().bus().name();
                 logic += &format!(
                     "{indent}if (msg.m_header.m_server_id != node_id_{node_name}) {{
 {indent2}return;
@@ -509,7 +509,7 @@ pub fn generate_rx_handlers(
 {indent}resp.m_header.m_server_id = msg.m_header.m_server_id;
 {indent}{frame_type_name} resp_frame;
 {indent}{namespace}_serialize_{namespace}_message_get_resp(&resp, &resp_frame);
-{indent}{namespace}_{resp_bus_name}_send(&resp_frame);
+{indent}{namespace}_config_send({namespace}_config_bus({get_resp_bus_id}), &resp_frame);
 "
                 );
                 (logic, "static PROGMEM")
@@ -517,288 +517,8 @@ pub fn generate_rx_handlers(
             message::MessageUsage::SetResp => panic!(),
             message::MessageUsage::SetReq => {
                 let node_id = node_config.id();
-                let mut case_logic = format!("{indent}switch (msg.m_header.m_od_index) {{\n");
-                for object_entry in node_config.object_entries() {
-                    let od_index = object_entry.id();
-                    let size = ty_size(object_entry.ty());
-                    let oe_name = object_entry.name();
-                    let ty = to_c_type_name(object_entry.ty());
-                    let oe_tmp_var = format!("{ty} {oe_name}_tmp;\n");
-                    let mut parse_logic = format!("{oe_tmp_var}");
-                    let oe_var = format!("{oe_name}_tmp");
-                    if size <= 32 {
-                        fn generate_parse_logic(
-                            parse_logic: &mut String,
-                            ty: &Type,
-                            var: &str,
-                            attrib_offset: &mut usize,
-                        ) {
-                            match ty {
-                                Type::Primitive(signal_type) => {
-                                    let size = signal_type.size() as usize;
-
-                                    let masked_val =
-                                        format!("((msg.m_data >> {attrib_offset}) & (0xFFFFFFFF >> (32 - {size})))");
-
-                                    let parsed_val = match signal_type {
-                                        config::SignalType::UnsignedInt { size } => {
-                                            if *size <= 8 {
-                                                format!("((uint8_t)({masked_val}))")
-                                            } else if *size <= 16 {
-                                                format!("((uint16_t)({masked_val}))")
-                                            } else if *size <= 32 {
-                                                format!("((uint32_t)({masked_val}))")
-                                            } else if *size <= 64 {
-                                                format!("((uint64_t)({masked_val}))")
-                                            } else {
-                                                panic!("unsigned integers larger than 64 bit are not supported");
-                                            }
-                                        }
-                                        config::SignalType::SignedInt { size } => {
-                                            // if (masked[*size-1] is set) {
-                                            // pad with ones
-                                            // }
-                                            if *size <= 8 {
-                                                format!("({masked_val} & (0x1 << ({size} - 1))) != 0 ? (int8_t) ({masked_val} | (0xFFFFFFFF << ({size} - 1))) : (int8_t){masked_val}")
-                                            } else if *size <= 16 {
-                                                format!("({masked_val} & (0x1 << ({size} - 1))) != 0 ? (int16_t) ({masked_val} | (0xFFFFFFFF << ({size} - 1))) : (int16_t){masked_val}")
-                                            } else if *size <= 32 {
-                                                format!("({masked_val} & (0x1 << ({size} - 1))) != 0 ? (int32_t) ({masked_val} | (0xFFFFFFFF << ({size} - 1))) : (int32_t){masked_val}")
-                                            } else if *size <= 64 {
-                                                panic!("unsigned integers larger than 32 bit are not supported in non fragmented set requests");
-                                            } else {
-                                                panic!("unsigned integers larger than 64 bit are not supported");
-                                            }
-                                        }
-                                        config::SignalType::Decimal {
-                                            size,
-                                            offset,
-                                            scale,
-                                        } => {
-                                            if *size <= 32 {
-                                                format!(
-                                                    "(float)({masked_val} * {scale} + {offset})"
-                                                )
-                                            } else if *size <= 64 {
-                                                format!(
-                                                    "(double)({masked_val} * {scale} + {offset})"
-                                                )
-                                            } else {
-                                                panic!("decimal data types larger than 64 bit are not supported");
-                                            }
-                                        }
-                                    };
-                                    parse_logic.push_str(&format!("{var} = {parsed_val};\n"));
-                                    *attrib_offset += size as usize;
-                                }
-                                Type::Struct {
-                                    name: _,
-                                    description: _,
-                                    attribs,
-                                    visibility: _,
-                                } => {
-                                    for (attrib_name, attrib_ty) in attribs {
-                                        generate_parse_logic(
-                                            parse_logic,
-                                            attrib_ty,
-                                            &format!("{var}.m_{attrib_name}"),
-                                            attrib_offset,
-                                        );
-                                    }
-                                }
-                                Type::Enum {
-                                    name,
-                                    description: _,
-                                    size,
-                                    entries: _,
-                                    visibility: _,
-                                } => {
-                                    let size = *size as usize;
-
-                                    let masked_val =
-                                        format!("((msg.m_data >> {attrib_offset}) & (0xFFFFFFFF >> (32 - {size})))");
-
-                                    let parsed_val = format!("(({name}){masked_val})");
-                                    parse_logic.push_str(&format!("{var} = {parsed_val};\n"));
-                                    *attrib_offset += size as usize;
-                                }
-                                Type::Array { len: _, ty: _ } => todo!(),
-                            }
-                        }
-                        generate_parse_logic(
-                            &mut parse_logic,
-                            object_entry.ty(),
-                            &format!("{indent2}{oe_var}"),
-                            &mut 0,
-                        );
-                        case_logic.push_str(&format!(
-                            "{indent}case {od_index} : {{
-{indent2}if (msg.m_header.m_sof != 1 || msg.m_header.m_toggle != 0 || msg.m_header.m_eof != 1) {{
-{indent3}return;
-{indent2}}}
-{indent2}{parse_logic}{indent2}{namespace}_set_{oe_name}({oe_var});
-{indent2}break;
-{indent}}}
-"
-                        ));
-                    } else {
-                        let word_size = size.div_ceil(32);
-                        let buffer_name = format!("{oe_var}_tx_fragmentation_buffer");
-                        let buffer_offset = format!("{oe_var}_tx_fragmentation_offset");
-                        source.push_str(&format!("static uint32_t DMAMEM {buffer_name}[{word_size}];\n"));
-                        source.push_str(&format!("static uint32_t DMAMEM {buffer_offset} = 0;\n"));
-
-                        let mut write_logic = String::new();
-                        fn generate_write_logic(
-                            write_logic: &mut String,
-                            ty: &Type,
-                            bit_offset: &mut usize,
-                            buffer_name: &str,
-                            var: &str,
-                            indent: &str,
-                        ) {
-                            match ty {
-                                Type::Primitive(signal_type) => {
-                                    let size = signal_type.size() as usize;
-                                    let bit_word_offset = *bit_offset % 32;
-                                    let word_offset = *bit_offset / 32;
-                                    let val_bits = if bit_word_offset == 0 && size <= 32 {
-                                        format!("({buffer_name}[{word_offset}] & (0xFFFFFFFF >> (32 - {size})))")
-                                    } else if bit_word_offset == 0 && size > 32 {
-                                        let upper_word_offset = word_offset + 1;
-                                        let upper_word_bit_offset = size - 32;
-                                        format!("(uint64_t){buffer_name}[{word_offset}] | (((uint64_t)({buffer_name}[{upper_word_offset}] & (0xFFFFFFFF >> (32 - {upper_word_bit_offset})))) << 32)")
-                                    } else if bit_word_offset + size <= 32 {
-                                        format!("({buffer_name}[{word_offset}] >> {bit_word_offset}) & (0xFFFFFFFF >> (32 - {size}))")
-                                    } else if bit_word_offset + size <= 64 {
-                                        let upper_word_offset = word_offset + 1;
-                                        let upper_word_bit_offset = (bit_word_offset + size) - 32;
-                                        let upper_word_shift = 32 - bit_word_offset;
-                                        format!("(uint64_t)({buffer_name}[{word_offset}] >> {bit_word_offset}) | ((uint64_t)({buffer_name}[{upper_word_offset}] & (0xFFFFFFFF >> (32 - {upper_word_bit_offset}))) << {upper_word_shift})")
-                                    } else { 
-                                        let middle_word = word_offset + 1;
-                                        let upper_word = word_offset + 2;
-                                        let middle_shift = 32 - bit_word_offset;
-                                        let upper_len = size + bit_word_offset - 64;
-                                        let upper_mask = u32::MAX.checked_shr(32 - upper_len as u32).unwrap_or(0);
-                                        let upper_shift = 64 - bit_word_offset;
-                                        format!("((uint64_t)({buffer_name}[{word_offset}]) >> {bit_word_offset}) | ((uint64_t)({buffer_name}[{middle_word}]) << {middle_shift}) | ((uint64_t)({buffer_name}[{upper_word}] & 0x{upper_mask:X}) << {upper_shift})")
-                                    };
-                                    let val = match signal_type {
-                                        config::SignalType::UnsignedInt { size: _ } => {
-                                            format!("{val_bits}")
-                                        }
-                                        config::SignalType::SignedInt { size } => {
-                                            if *size <= 32 {
-                                                write_logic.push_str(&format!("{indent}uint32_t tmp_{bit_offset} = {val_bits};\n"));
-                                                format!("(tmp_{bit_offset} & (0x1 << ({size} - 1))) != 0 ? (int32_t) (tmp_{bit_offset} | (0xFFFFFFFF << ({size} - 1))) : (int32_t)tmp_{bit_offset}")
-                                            }else {
-                                                write_logic.push_str(&format!("{indent}uint64_t tmp_{bit_offset} = {val_bits};\n"));
-                                                format!("(tmp_{bit_offset} & (((uint64_t)0x1ull) << ({size} - 1))) != 0 ? (int64_t) (tmp_{bit_offset} | (((uint64_t)0xFFFFFFFFFFFFFFFFull) << ({size} - 1))) : (int64_t)tmp_{bit_offset}")
-                                            }
-                                        }
-                                        config::SignalType::Decimal {
-                                            size: _,
-                                            offset,
-                                            scale,
-                                        } => format!("({val_bits}) * {scale} + {offset}"),
-                                    };
-                                    write_logic.push_str(&format!("{indent}{var} = {val};\n"));
-                                    *bit_offset += signal_type.size() as usize;
-                                }
-                                Type::Struct {
-                                    name: _,
-                                    description: _,
-                                    attribs,
-                                    visibility: _,
-                                } => {
-                                    for (attrib_name, attrib_ty) in attribs {
-                                        generate_write_logic(
-                                            write_logic,
-                                            attrib_ty,
-                                            bit_offset,
-                                            buffer_name,
-                                            &format!("{var}.m_{attrib_name}"),
-                                            indent,
-                                        )
-                                    }
-                                }
-                                Type::Enum {
-                                    name,
-                                    description: _,
-                                    size,
-                                    entries: _,
-                                    visibility: _,
-                                } => {
-                                    let size = *size as usize;
-                                    let bit_word_offset = *bit_offset % 32;
-                                    let word_offset = *bit_offset / 32;
-                                    let val_bits = if bit_word_offset == 0 && size <= 32 {
-                                        format!("({buffer_name}[{word_offset}] & (0xFFFFFFFF >> (32 - {size})))")
-                                    } else if bit_word_offset == 0 && size > 32 {
-                                        let upper_word_offset = word_offset + 1;
-                                        let upper_word_bit_offset = size - 32;
-                                        format!("(uint64_t){buffer_name}[{word_offset}] | (((uint64_t)({buffer_name}[{upper_word_offset}] & (0xFFFFFFFF >> (32 - {upper_word_bit_offset})))) << 32)")
-                                    } else if bit_word_offset + size <= 32 {
-                                        format!("({buffer_name}[{word_offset}] >> {bit_word_offset}) & (0xFFFFFFFF >> (32 - {size}))")
-                                    } else if bit_word_offset + size <= 64 {
-                                        let upper_word_offset = word_offset + 1;
-                                        let upper_word_bit_offset = (bit_word_offset + size) - 32;
-                                        let upper_word_shift = 32 - bit_word_offset;
-                                        format!("(uint64_t)({buffer_name}[{word_offset}] >> {bit_word_offset}) | ((uint64_t)({buffer_name}[{upper_word_offset}] & (0xFFFFFFFF >> (32 - {upper_word_bit_offset}))) << {upper_word_shift})")
-                                    } else { 
-                                        let middle_word = word_offset + 1;
-                                        let upper_word = word_offset + 2;
-                                        let middle_shift = 32 - bit_word_offset;
-                                        let upper_len = size + bit_word_offset - 64;
-                                        let upper_mask = u32::MAX.checked_shr(32 - upper_len as u32).unwrap_or(0);
-                                        let upper_shift = 64 - bit_word_offset;
-                                        format!("((uint64_t)({buffer_name}[{word_offset}]) >> {bit_word_offset}) | ((uint64_t)({buffer_name}[{middle_word}]) << {middle_shift}) | ((uint64_t)({buffer_name}[{upper_word}] & 0x{upper_mask:X}) << {upper_shift})")
-                                    };
-                                    let val = format!("(({name})({val_bits}))");
-                                    write_logic.push_str(&format!("{indent}{var} = {val};\n"));
-                                    *bit_offset += size;
-                                }
-                                Type::Array { len: _, ty: _ } => todo!(),
-                            }
-                        }
-                        generate_write_logic(
-                            &mut write_logic,
-                            object_entry.ty(),
-                            &mut 0,
-                            &buffer_name,
-                            &oe_var,
-                            &indent2,
-                        );
-                        let oe_ty = to_c_type_name(object_entry.ty());
-
-                        case_logic.push_str(&format!(
-                            "{indent}case {od_index} : {{
-{indent2}if (msg.m_header.m_sof == 1) {{
-{indent3}if (msg.m_header.m_toggle != 0 || msg.m_header.m_eof != 0) {{
-{indent4}return; //TODO proper error response frame!
-{indent3}}}
-{indent3}{buffer_offset} = 0;
-{indent2}}}else {{
-{indent3}{buffer_offset} += 1;
-{indent3}if ({buffer_offset} >= {word_size}) {{
-{indent4}return;
-{indent3}}}
-{indent2}}}
-{indent2}{buffer_name}[{buffer_offset}] = msg.m_data;
-{indent2}if (msg.m_header.m_eof == 0) {{
-{indent3}return;
-{indent2}}}
-{indent2}{oe_ty} {oe_var};
-{write_logic}{indent2}{namespace}_set_{oe_name}({oe_var});
-{indent2}break;
-{indent}}}
-"
-                        ));
-                    }
-                }
-                case_logic.push_str(&format!("{indent}default:\n{indent2}return;\n{indent}}}"));
-                let resp_bus_name = network_config.set_resp_message().bus().name();
+                let case_logic = generate_set_req_cases(node_config, source, &namespace, &indent, false);
+                let resp_bus_id = network_config.set_resp_message().bus().id();
                 let logic = format!(
                     "{indent}if (msg.m_header.m_server_id != {node_id}) {{
 {indent2}return;
@@ -811,12 +531,61 @@ pub fn generate_rx_handlers(
 {indent}resp.m_header.m_erno = set_resp_erno_Success;
 {indent}canzero_frame resp_frame;
 {indent}{namespace}_serialize_{namespace}_message_set_resp(&resp, &resp_frame);
-{indent}{namespace}_{resp_bus_name}_send(&resp_frame);\n
+{indent}{namespace}_config_send({namespace}_config_bus({resp_bus_id}), &resp_frame);\n
 "
                 );
 
                 (logic, "static PROGMEM")
             },
+            message::MessageUsage::SetStageReq => {
+                let node_id = node_config.id();
+                let case_logic = generate_set_req_cases(node_config, source, &namespace, &indent, true);
+                let logic = format!(
+                    "{indent}if (msg.m_header.m_server_id != {node_id}) {{
+{indent2}return;
+{indent}}}
+{case_logic}
+"
+                );
+                (logic, "static PROGMEM")
+            },
+            message::MessageUsage::SetCommitReq => {
+                let node_id = node_config.id();
+                let resp_bus_id = network_config.set_commit_resp_message().bus().id();
+                let logic = format!(
+                    "{indent}if (msg.m_header.m_server_id != {node_id}) {{
+{indent2}return;
+{indent}}}
+{indent}{namespace}_message_set_commit_resp resp{{}};
+{indent}resp.m_header.m_client_id = msg.m_header.m_client_id;
+{indent}resp.m_header.m_server_id = msg.m_header.m_server_id;
+{indent}{namespace}_expire_staged();
+{indent}// another client is staging, its transaction is left untouched.
+{indent}if (__stage_count != 0 && __stage_client_id != msg.m_header.m_client_id) {{
+{indent2}resp.m_header.m_entries = 0;
+{indent2}resp.m_failed = 0;
+{indent2}resp.m_header.m_erno = set_commit_erno_Error;
+{indent}}} else {{
+{indent2}resp.m_header.m_entries = __stage_count;
+{indent2}resp.m_failed = __stage_failed;
+{indent2}if (msg.m_header.m_action == set_commit_action_Abort) {{
+{indent3}resp.m_header.m_erno = set_commit_erno_Aborted;
+{indent2}}} else if (__stage_count != msg.m_header.m_entries || __stage_failed != 0) {{
+{indent3}resp.m_header.m_erno = set_commit_erno_Error;
+{indent2}}} else {{
+{indent3}{namespace}_commit_staged();
+{indent3}resp.m_header.m_erno = set_commit_erno_Success;
+{indent2}}}
+{indent2}{namespace}_clear_staged();
+{indent}}}
+{indent}canzero_frame resp_frame;
+{indent}{namespace}_serialize_{namespace}_message_set_commit_resp(&resp, &resp_frame);
+{indent}{namespace}_config_send({namespace}_config_bus({resp_bus_id}), &resp_frame);\n
+"
+                );
+                (logic, "static PROGMEM")
+            },
+            message::MessageUsage::SetCommitResp => panic!(),
             message::MessageUsage::Heartbeat => {
                 let mut logic = String::new();
                 logic.push_str(&format!(
@@ -886,6 +655,321 @@ pub fn generate_rx_handlers(
     Ok(())
 }
 
+/// Cases of the od_index switch of set requests, which parse the value of the object entry.
+/// Staged values are stored for the next commit instead of being set.
+fn generate_set_req_cases(
+    node_config: &config::NodeRef,
+    source: &mut String,
+    namespace: &str,
+    indent: &str,
+    staged: bool,
+) -> String {
+    let indent2 = format!("{indent}{indent}");
+    let indent3 = format!("{indent2}{indent}");
+    let indent4 = format!("{indent2}{indent2}");
+    let mut case_logic = format!("{indent}switch (msg.m_header.m_od_index) {{\n");
+    for object_entry in node_config.object_entries() {
+        if staged && !matches!(object_entry.access(), ObjectEntryAccess::Global) {
+            continue;
+        }
+        let od_index = object_entry.id();
+        let size = ty_size(object_entry.ty());
+        let oe_name = object_entry.name();
+        let ty = to_c_type_name(object_entry.ty());
+        let oe_var = if staged {
+            format!("{oe_name}_staged_tmp")
+        } else {
+            format!("{oe_name}_tmp")
+        };
+        let mut parse_logic = format!("{ty} {oe_var};\n");
+        // staged values are applied by the set_commit_req.
+        let apply = if staged {
+            format!("if ({namespace}_stage(msg.m_header.m_od_index, msg.m_header.m_client_id, 0)) {{\n{indent3}__oe_{oe_name}_staged = {oe_var};\n{indent2}}}")
+        } else {
+            format!("{namespace}_set_{oe_name}({oe_var});")
+        };
+        if size <= 32 {
+            fn generate_parse_logic(
+                parse_logic: &mut String,
+                ty: &Type,
+                var: &str,
+                attrib_offset: &mut usize,
+            ) {
+                match ty {
+                    Type::Primitive(signal_type) => {
+                        let size = signal_type.size() as usize;
+
+                        let masked_val =
+                            format!("((msg.m_data >> {attrib_offset}) & (0xFFFFFFFF >> (32 - {size})))");
+
+                        let parsed_val = match signal_type {
+                            config::SignalType::UnsignedInt { size } => {
+                                if *size <= 8 {
+                                    format!("((uint8_t)({masked_val}))")
+                                } else if *size <= 16 {
+                                    format!("((uint16_t)({masked_val}))")
+                                } else if *size <= 32 {
+                                    format!("((uint32_t)({masked_val}))")
+                                } else if *size <= 64 {
+                                    format!("((uint64_t)({masked_val}))")
+                                } else {
+                                    panic!("unsigned integers larger than 64 bit are not supported");
+                                }
+                            }
+                            config::SignalType::SignedInt { size } => {
+                                // if (masked[*size-1] is set) {
+                                // pad with ones
+                                // }
+                                if *size <= 8 {
+                                    format!("({masked_val} & (0x1 << ({size} - 1))) != 0 ? (int8_t) ({masked_val} | (0xFFFFFFFF << ({size} - 1))) : (int8_t){masked_val}")
+                                } else if *size <= 16 {
+                                    format!("({masked_val} & (0x1 << ({size} - 1))) != 0 ? (int16_t) ({masked_val} | (0xFFFFFFFF << ({size} - 1))) : (int16_t){masked_val}")
+                                } else if *size <= 32 {
+                                    format!("({masked_val} & (0x1 << ({size} - 1))) != 0 ? (int32_t) ({masked_val} | (0xFFFFFFFF << ({size} - 1))) : (int32_t){masked_val}")
+                                } else if *size <= 64 {
+                                    panic!("unsigned integers larger than 32 bit are not supported in non fragmented set requests");
+                                } else {
+                                    panic!("unsigned integers larger than 64 bit are not supported");
+                                }
+                            }
+                            config::SignalType::Decimal {
+                                size,
+                                offset,
+                                scale,
+                            } => {
+                                if *size <= 32 {
+                                    format!(
+                                        "(float)({masked_val} * {scale} + {offset})"
+                                    )
+                                } else if *size <= 64 {
+                                    format!(
+                                        "(double)({masked_val} * {scale} + {offset})"
+                                    )
+                                } else {
+                                    panic!("decimal data types larger than 64 bit are not supported");
+                                }
+                            }
+                        };
+                        parse_logic.push_str(&format!("{var} = {parsed_val};\n"));
+                        *attrib_offset += size as usize;
+                    }
+                    Type::Struct {
+                        name: _,
+                        description: _,
+                        attribs,
+                        visibility: _,
+                    } => {
+                        for (attrib_name, attrib_ty) in attribs {
+                            generate_parse_logic(
+                                parse_logic,
+                                attrib_ty,
+                                &format!("{var}.m_{attrib_name}"),
+                                attrib_offset,
+                            );
+                        }
+                    }
+                    Type::Enum {
+                        name,
+                        description: _,
+                        size,
+                        entries: _,
+                        visibility: _,
+                    } => {
+                        let size = *size as usize;
+
+                        let masked_val =
+                            format!("((msg.m_data >> {attrib_offset}) & (0xFFFFFFFF >> (32 - {size})))");
+
+                        let parsed_val = format!("(({name}){masked_val})");
+                        parse_logic.push_str(&format!("{var} = {parsed_val};\n"));
+                        *attrib_offset += size as usize;
+                    }
+                    Type::Array { len: _, ty: _ } => todo!(),
+                }
+            }
+            generate_parse_logic(
+                &mut parse_logic,
+                object_entry.ty(),
+                &format!("{indent2}{oe_var}"),
+                &mut 0,
+            );
+            case_logic.push_str(&format!(
+                "{indent}case {od_index} : {{
+{indent2}if (msg.m_header.m_sof != 1 || msg.m_header.m_toggle != 0 || msg.m_header.m_eof != 1) {{
+{indent3}return;
+{indent2}}}
+{indent2}{parse_logic}{indent2}{apply}
+{indent2}break;
+{indent}}}
+"
+            ));
+        } else {
+            let word_size = size.div_ceil(32);
+            let buffer_name = format!("{oe_var}_tx_fragmentation_buffer");
+            let buffer_offset = format!("{oe_var}_tx_fragmentation_offset");
+            source.push_str(&format!("static uint32_t DMAMEM {buffer_name}[{word_size}];\n"));
+            source.push_str(&format!("static uint32_t DMAMEM {buffer_offset} = 0;\n"));
+
+            let mut write_logic = String::new();
+            fn generate_write_logic(
+                write_logic: &mut String,
+                ty: &Type,
+                bit_offset: &mut usize,
+                buffer_name: &str,
+                var: &str,
+                indent: &str,
+            ) {
+                match ty {
+                    Type::Primitive(signal_type) => {
+                        let size = signal_type.size() as usize;
+                        let bit_word_offset = *bit_offset % 32;
+                        let word_offset = *bit_offset / 32;
+                        let val_bits = if bit_word_offset == 0 && size <= 32 {
+                            format!("({buffer_name}[{word_offset}] & (0xFFFFFFFF >> (32 - {size})))")
+                        } else if bit_word_offset == 0 && size > 32 {
+                            let upper_word_offset = word_offset + 1;
+                            let upper_word_bit_offset = size - 32;
+                            format!("(uint64_t){buffer_name}[{word_offset}] | (((uint64_t)({buffer_name}[{upper_word_offset}] & (0xFFFFFFFF >> (32 - {upper_word_bit_offset})))) << 32)")
+                        } else if bit_word_offset + size <= 32 {
+                            format!("({buffer_name}[{word_offset}] >> {bit_word_offset}) & (0xFFFFFFFF >> (32 - {size}))")
+                        } else if bit_word_offset + size <= 64 {
+                            let upper_word_offset = word_offset + 1;
+                            let upper_word_bit_offset = (bit_word_offset + size) - 32;
+                            let upper_word_shift = 32 - bit_word_offset;
+                            format!("(uint64_t)({buffer_name}[{word_offset}] >> {bit_word_offset}) | ((uint64_t)({buffer_name}[{upper_word_offset}] & (0xFFFFFFFF >> (32 - {upper_word_bit_offset}))) << {upper_word_shift})")
+                        } else { 
+                            let middle_word = word_offset + 1;
+                            let upper_word = word_offset + 2;
+                            let middle_shift = 32 - bit_word_offset;
+                            let upper_len = size + bit_word_offset - 64;
+                            let upper_mask = u32::MAX.checked_shr(32 - upper_len as u32).unwrap_or(0);
+                            let upper_shift = 64 - bit_word_offset;
+                            format!("((uint64_t)({buffer_name}[{word_offset}]) >> {bit_word_offset}) | ((uint64_t)({buffer_name}[{middle_word}]) << {middle_shift}) | ((uint64_t)({buffer_name}[{upper_word}] & 0x{upper_mask:X}) << {upper_shift})")
+                        };
+                        let val = match signal_type {
+                            config::SignalType::UnsignedInt { size: _ } => {
+                                format!("{val_bits}")
+                            }
+                            config::SignalType::SignedInt { size } => {
+                                if *size <= 32 {
+                                    write_logic.push_str(&format!("{indent}uint32_t tmp_{bit_offset} = {val_bits};\n"));
+                                    format!("(tmp_{bit_offset} & (0x1 << ({size} - 1))) != 0 ? (int32_t) (tmp_{bit_offset} | (0xFFFFFFFF << ({size} - 1))) : (int32_t)tmp_{bit_offset}")
+                                }else {
+                                    write_logic.push_str(&format!("{indent}uint64_t tmp_{bit_offset} = {val_bits};\n"));
+                                    format!("(tmp_{bit_offset} & (((uint64_t)0x1ull) << ({size} - 1))) != 0 ? (int64_t) (tmp_{bit_offset} | (((uint64_t)0xFFFFFFFFFFFFFFFFull) << ({size} - 1))) : (int64_t)tmp_{bit_offset}")
+                                }
+                            }
+                            config::SignalType::Decimal {
+                                size: _,
+                                offset,
+                                scale,
+                            } => format!("({val_bits}) * {scale} + {offset}"),
+                        };
+                        write_logic.push_str(&format!("{indent}{var} = {val};\n"));
+                        *bit_offset += signal_type.size() as usize;
+                    }
+                    Type::Struct {
+                        name: _,
+                        description: _,
+                        attribs,
+                        visibility: _,
+                    } => {
+                        for (attrib_name, attrib_ty) in attribs {
+                            generate_write_logic(
+                                write_logic,
+                                attrib_ty,
+                                bit_offset,
+                                buffer_name,
+                                &format!("{var}.m_{attrib_name}"),
+                                indent,
+                            )
+                        }
+                    }
+                    Type::Enum {
+                        name,
+                        description: _,
+                        size,
+                        entries: _,
+                        visibility: _,
+                    } => {
+                        let size = *size as usize;
+                        let bit_word_offset = *bit_offset % 32;
+                        let word_offset = *bit_offset / 32;
+                        let val_bits = if bit_word_offset == 0 && size <= 32 {
+                            format!("({buffer_name}[{word_offset}] & (0xFFFFFFFF >> (32 - {size})))")
+                        } else if bit_word_offset == 0 && size > 32 {
+                            let upper_word_offset = word_offset + 1;
+                            let upper_word_bit_offset = size - 32;
+                            format!("(uint64_t){buffer_name}[{word_offset}] | (((uint64_t)({buffer_name}[{upper_word_offset}] & (0xFFFFFFFF >> (32 - {upper_word_bit_offset})))) << 32)")
+                        } else if bit_word_offset + size <= 32 {
+                            format!("({buffer_name}[{word_offset}] >> {bit_word_offset}) & (0xFFFFFFFF >> (32 - {size}))")
+                        } else if bit_word_offset + size <= 64 {
+                            let upper_word_offset = word_offset + 1;
+                            let upper_word_bit_offset = (bit_word_offset + size) - 32;
+                            let upper_word_shift = 32 - bit_word_offset;
+                            format!("(uint64_t)({buffer_name}[{word_offset}] >> {bit_word_offset}) | ((uint64_t)({buffer_name}[{upper_word_offset}] & (0xFFFFFFFF >> (32 - {upper_word_bit_offset}))) << {upper_word_shift})")
+                        } else { 
+                            let middle_word = word_offset + 1;
+                            let upper_word = word_offset + 2;
+                            let middle_shift = 32 - bit_word_offset;
+                            let upper_len = size + bit_word_offset - 64;
+                            let upper_mask = u32::MAX.checked_shr(32 - upper_len as u32).unwrap_or(0);
+                            let upper_shift = 64 - bit_word_offset;
+                            format!("((uint64_t)({buffer_name}[{word_offset}]) >> {bit_word_offset}) | ((uint64_t)({buffer_name}[{middle_word}]) << {middle_shift}) | ((uint64_t)({buffer_name}[{upper_word}] & 0x{upper_mask:X}) << {upper_shift})")
+                        };
+                        let val = format!("(({name})({val_bits}))");
+                        write_logic.push_str(&format!("{indent}{var} = {val};\n"));
+                        *bit_offset += size;
+                    }
+                    Type::Array { len: _, ty: _ } => todo!(),
+                }
+            }
+            generate_write_logic(
+                &mut write_logic,
+                object_entry.ty(),
+                &mut 0,
+                &buffer_name,
+                &oe_var,
+                &indent2,
+            );
+            let oe_ty = to_c_type_name(object_entry.ty());
+
+            case_logic.push_str(&format!(
+                "{indent}case {od_index} : {{
+{indent2}if (msg.m_header.m_sof == 1) {{
+{indent3}if (msg.m_header.m_toggle != 0 || msg.m_header.m_eof != 0) {{
+{indent4}return; //TODO proper error response frame!
+{indent3}}}
+{indent3}{buffer_offset} = 0;
+{indent2}}}else {{
+{indent3}{buffer_offset} += 1;
+{indent3}if ({buffer_offset} >= {word_size}) {{
+{indent4}return;
+{indent3}}}
+{indent2}}}
+{indent2}{buffer_name}[{buffer_offset}] = msg.m_data;
+{indent2}if (msg.m_header.m_eof == 0) {{
+{indent3}return;
+{indent2}}}
+{indent2}{oe_ty} {oe_var};
+{write_logic}{indent2}{apply}
+{indent2}break;
+{indent}}}
+"
+            ));
+        }
+    }
+    if staged {
+        // entries, which can't be staged, fail the transaction.
+        case_logic.push_str(&format!(
+            "{indent}default:\n{indent2}{namespace}_stage(msg.m_header.m_od_index, msg.m_header.m_client_id, 1);\n{indent2}return;\n{indent}}}"
+        ));
+    } else {
+        case_logic.push_str(&format!("{indent}default:\n{indent2}return;\n{indent}}}"));
+    }
+    case_logic
+}
+
 fn ty_size(ty: &Type) -> usize {
     match ty {
         Type::Primitive(signal_type) => signal_type.size() as usize,
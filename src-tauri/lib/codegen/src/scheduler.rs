@@ -122,7 +122,6 @@ static void schedule_{stream_name}_interval_job(){{
         stream_id += 1;
     }
 
-    let get_resp_bus_name = network_config.get_resp_message().bus().name();
     for heartbeat in network_config.heartbeat_messages() {
         source.push_str(&format!(
 "__attribute__((weak)) void {namespace}_{0}_wdg_timeout(uint8_t node_id) {{}}
@@ -147,6 +146,7 @@ typedef struct {{
   uint8_t size;
   uint8_t od_index;
   uint8_t client_id;
+  uint32_t bus_id;
 }} get_resp_fragmentation_job;
 
 typedef struct {{
@@ -290,7 +290,7 @@ static void scheduler_unschedule() {{
 {indent}scheduler_reschedule(scheduler.heap[0]->climax);
 }}
 static const uint32_t get_resp_fragmentation_interval = {GET_RESP_FRAGMENTATION_INTERVAL};
-static void schedule_get_resp_fragmentation_job(uint32_t *fragmentation_buffer, uint8_t size, uint8_t od_index, uint8_t client_id) {{
+static void schedule_get_resp_fragmentation_job(uint32_t *fragmentation_buffer, uint8_t size, uint8_t od_index, uint8_t client_id, uint32_t bus_id) {{
 {indent}job_t *fragmentation_job = job_pool_allocator_alloc();
 {indent}fragmentation_job->climax = canzero_get_time() + get_resp_fragmentation_interval;
 {indent}fragmentation_job->tag = GET_RESP_FRAGMENTATION_JOB_TAG;
@@ -299,6 +299,7 @@ static void schedule_get_resp_fragmentation_job(uint32_t *fragmentation_buffer,
 {indent}fragmentation_job->job.get_fragmentation_job.size = size;
 {indent}fragmentation_job->job.get_fragmentation_job.od_index = od_index;
 {indent}fragmentation_job->job.get_fragmentation_job.client_id = client_id;
+{indent}fragmentation_job->job.get_fragmentation_job.bus_id = bus_id;
 {indent}scheduler_schedule(fragmentation_job);
 }}
 
@@ -490,7 +491,7 @@ static void schedule_jobs(uint32_t time) {{
 {indent4}{namespace}_exit_critical();
 {indent4}canzero_frame fragmentation_frame;
 {indent4}{namespace}_serialize_{namespace}_message_get_resp(&fragmentation_response, &fragmentation_frame);
-{indent4}{namespace}_{get_resp_bus_name}_send(&fragmentation_frame);
+{indent4}{namespace}_config_send(fragmentation_job->bus_id, &fragmentation_frame);
 {indent4}break;
 {indent3}}}
 {indent3}default: {{
@@ -26,6 +26,7 @@ pub fn generate_senders(
     let get_resp_msg_name = network_config.get_resp_message().name();
     let frame_type_name = format!("{namespace}_message_{get_resp_msg_name}");
     let get_resp_bus_name = network_config.get_resp_message().bus().name();
+    let get_resp_bus_id = network_config.get_resp_message().bus().id();
     let mut sender_def = String::new();
     let node_name = node_config.name();
 
@@ -433,7 +434,7 @@ pub fn generate_senders(
 {indent}msg.m_header.m_eof = 0;
 "           );
             String::from(format!(
-"{indent}schedule_get_resp_fragmentation_job({buffer_name}, {buffer_size}, {od_index}, {UNSOLICITED_ID});
+"{indent}schedule_get_resp_fragmentation_job({buffer_name}, {buffer_size}, {od_index}, {UNSOLICITED_ID}, {get_resp_bus_id});
 
 "
             ))
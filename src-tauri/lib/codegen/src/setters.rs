@@ -1,5 +1,6 @@
+use canzero_config::builder::network_builder::{MAX_STAGED_ENTRIES, STAGE_TIMEOUT};
 use canzero_config::config::stream::StreamRef;
-use canzero_config::config::{NodeRef, Type};
+use canzero_config::config::{NodeRef, ObjectEntryAccess, Type};
 
 use crate::errors::Result;
 use crate::options::Options;
@@ -96,3 +97,96 @@ pub fn generate_setters(
 
     Ok(())
 }
+
+/// Buffers of the set transaction. set_stage_req frames store the value of an object entry
+/// in its staged variable, a set_commit_req applies all staged values at once.
+pub fn generate_staged_setters(
+    node_config: &NodeRef,
+    source: &mut String,
+    options: &Options,
+) -> Result<()> {
+    let namespace = options.namespace();
+    let mut indent = String::new();
+    for _ in 0..options.indent() {
+        indent.push(' ');
+    }
+    let indent2 = format!("{indent}{indent}");
+    let indent3 = format!("{indent}{indent2}");
+
+    let staged_entries: Vec<_> = node_config
+        .object_entries()
+        .iter()
+        .filter(|object_entry| matches!(object_entry.access(), ObjectEntryAccess::Global))
+        .collect();
+
+    source.push_str(&format!(
+"static uint16_t DMAMEM __stage_od_index[{MAX_STAGED_ENTRIES}];
+static uint8_t DMAMEM __stage_count = 0;
+static uint32_t DMAMEM __stage_failed = 0;
+static uint8_t DMAMEM __stage_client_id = 0;
+static uint32_t DMAMEM __stage_time = 0;
+"));
+    for object_entry in &staged_entries {
+        let oe_name = object_entry.name();
+        let type_name = to_c_type_name(object_entry.ty());
+        source.push_str(&format!("static {type_name} DMAMEM __oe_{oe_name}_staged;\n"));
+    }
+
+    // only one client can stage at a time, frames of other clients are ignored
+    // until the transaction is committed, aborted or expired.
+    // _stage returns 0 if the entry is ignored, the staged value must not be written then.
+    let stage_timeout = STAGE_TIMEOUT.as_millis();
+    source.push_str(&format!(
+"static void {namespace}_clear_staged() {{
+{indent}__stage_count = 0;
+{indent}__stage_failed = 0;
+}}
+static void {namespace}_expire_staged() {{
+{indent}if (__stage_count != 0 && {namespace}_get_time() - __stage_time > {stage_timeout}) {{
+{indent2}{namespace}_clear_staged();
+{indent}}}
+}}
+static int {namespace}_stage(uint16_t od_index, uint8_t client_id, int failed) {{
+{indent}{namespace}_expire_staged();
+{indent}if (__stage_count != 0 && __stage_client_id != client_id) {{
+{indent2}return 0;
+{indent}}}
+{indent}if (__stage_count >= {MAX_STAGED_ENTRIES}) {{
+{indent2}return 0;
+{indent}}}
+{indent}__stage_client_id = client_id;
+{indent}__stage_time = {namespace}_get_time();
+{indent}if (failed) {{
+{indent2}__stage_failed |= ((uint32_t)1u << __stage_count);
+{indent}}}
+{indent}__stage_od_index[__stage_count] = od_index;
+{indent}__stage_count += 1;
+{indent}return 1;
+}}
+"));
+
+    let mut commit_def = format!(
+"static void {namespace}_commit_staged() {{
+{indent}for (uint8_t i = 0; i < __stage_count; ++i) {{
+{indent2}switch (__stage_od_index[i]) {{
+");
+    for object_entry in &staged_entries {
+        let oe_name = object_entry.name();
+        let od_index = object_entry.id();
+        commit_def.push_str(&format!(
+"{indent2}case {od_index}:
+{indent3}{namespace}_set_{oe_name}(__oe_{oe_name}_staged);
+{indent3}break;
+"));
+    }
+    commit_def.push_str(&format!(
+"{indent2}default:
+{indent3}break;
+{indent2}}}
+{indent}}}
+}}
+"));
+    source.push_str(&commit_def);
+
+    Ok(())
+}
@@ -1,6 +1,7 @@
 use canzero_config::config;
 
 use crate::options::Options;
+use crate::platform::setup_filter_args;
 
 use crate::errors::Result;
 
@@ -23,7 +24,8 @@ pub fn generate_setup(node_config : &config::NodeRef, network_config : &config::
     for bus in network_config.buses() {
         let bus_name = bus.name();
         let baudrate = bus.baudrate();
-        setup_cans.push_str(&format!("{indent}{namespace}_{bus_name}_setup({baudrate}, NULL, 0);\n"));
+        let filter_args = setup_filter_args(node_config, bus, options);
+        setup_cans.push_str(&format!("{indent}{namespace}_{bus_name}_setup({baudrate}, {filter_args});\n"));
     }
 
     let mut schedule_stream_jobs_logic = String::new();
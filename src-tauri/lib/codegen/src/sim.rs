@@ -0,0 +1,454 @@
+use canzero_config::config::{self, bus::BusRef};
+
+use crate::{command_hooks::command_hook_signature, errors::Result, options::Options};
+
+// frames buffered per bus, when the TCP backend is used.
+const SIM_RX_QUEUE_SIZE: usize = 64;
+
+/// Linux PIL for simulated nodes. Implements the can module hooks with either
+/// SocketCAN (one socket per bus, the bus name is the interface name) or the
+/// CANzero TCP protocol (all buses multiplexed over one connection to a server).
+pub fn generate_sim_pil(
+    buses: &Vec<BusRef>,
+    source: &mut String,
+    header: &mut String,
+    options: &Options,
+) -> Result<()> {
+    let namespace = options.namespace();
+    let ns_upper = namespace.to_uppercase();
+    let mut indent = String::new();
+    for _ in 0..options.indent() {
+        indent.push(' ');
+    }
+    let indent2 = format!("{indent}{indent}");
+    let indent3 = format!("{indent2}{indent}");
+    let indent4 = format!("{indent2}{indent2}");
+    let bus_count = buses.len();
+
+    header.push_str(&format!("int {namespace}_sim_connect_socketcan();\n"));
+    header.push_str(&format!(
+        "int {namespace}_sim_connect_tcp(const char* host, uint16_t port);\n"
+    ));
+    header.push_str(&format!("uint32_t {namespace}_sim_take_requested_update();\n"));
+    header.push_str(&format!("void {namespace}_sim_wait(uint32_t timeout);\n"));
+
+    source.push_str(&format!(
+        "#include <arpa/inet.h>
+#include <cerrno>
+#include <cstdio>
+#include <cstdlib>
+#include <cstring>
+#include <fcntl.h>
+#include <linux/can.h>
+#include <linux/can/raw.h>
+#include <net/if.h>
+#include <netdb.h>
+#include <netinet/in.h>
+#include <netinet/tcp.h>
+#include <poll.h>
+#include <sys/ioctl.h>
+#include <sys/socket.h>
+#include <time.h>
+#include <unistd.h>
+#define {ns_upper}_SIM_BUS_COUNT {bus_count}
+#define {ns_upper}_SIM_RX_QUEUE_SIZE {SIM_RX_QUEUE_SIZE}
+// the TCP protocol uses the SocketCAN layout of the flag bits.
+#define {ns_upper}_SIM_TCP_IDE_BIT 0x80000000u
+#define {ns_upper}_SIM_TCP_RTR_BIT 0x40000000u
+#define {ns_upper}_SIM_TCP_FRAME_SIZE 24
+typedef enum {{
+{indent}{ns_upper}_SIM_BACKEND_SOCKETCAN,
+{indent}{ns_upper}_SIM_BACKEND_TCP,
+}} {namespace}_sim_backend;
+typedef struct {{
+{indent}uint32_t head;
+{indent}uint32_t tail;
+{indent}{namespace}_frame buffer[{ns_upper}_SIM_RX_QUEUE_SIZE];
+}} {namespace}_sim_rx_queue;
+static {namespace}_sim_backend {namespace}_sim_backend_kind = {ns_upper}_SIM_BACKEND_SOCKETCAN;
+static int {namespace}_sim_bus_fds[{ns_upper}_SIM_BUS_COUNT];
+static int {namespace}_sim_tcp_fd = -1;
+static uint8_t {namespace}_sim_tcp_rx_buffer[{ns_upper}_SIM_TCP_FRAME_SIZE];
+static size_t {namespace}_sim_tcp_rx_fill = 0;
+static {namespace}_sim_rx_queue {namespace}_sim_rx_queues[{ns_upper}_SIM_BUS_COUNT];
+static struct timespec {namespace}_sim_start;
+static bool {namespace}_sim_clock_started = false;
+static uint32_t {namespace}_sim_requested_update = UINT32_MAX;
+
+static uint64_t {namespace}_sim_time_us() {{
+{indent}struct timespec now;
+{indent}clock_gettime(CLOCK_MONOTONIC, &now);
+{indent}if (!{namespace}_sim_clock_started) {{
+{indent2}{namespace}_sim_start = now;
+{indent2}{namespace}_sim_clock_started = true;
+{indent}}}
+{indent}int64_t sec = now.tv_sec - {namespace}_sim_start.tv_sec;
+{indent}int64_t nsec = now.tv_nsec - {namespace}_sim_start.tv_nsec;
+{indent}return (uint64_t)(sec * 1000000 + nsec / 1000);
+}}
+uint32_t {namespace}_get_time() {{
+{indent}return (uint32_t)({namespace}_sim_time_us() / 1000);
+}}
+void {namespace}_request_update(uint32_t time) {{
+{indent}if (time < {namespace}_sim_requested_update) {{
+{indent2}{namespace}_sim_requested_update = time;
+{indent}}}
+}}
+uint32_t {namespace}_sim_take_requested_update() {{
+{indent}uint32_t time = {namespace}_sim_requested_update;
+{indent}{namespace}_sim_requested_update = UINT32_MAX;
+{indent}return time;
+}}
+// the simulation is single threaded, there is nothing to protect against.
+void {namespace}_enter_critical() {{}}
+void {namespace}_exit_critical() {{}}
+
+int {namespace}_sim_connect_socketcan() {{
+{indent}{namespace}_sim_time_us();
+{indent}{namespace}_sim_backend_kind = {ns_upper}_SIM_BACKEND_SOCKETCAN;
+{indent}for (int i = 0; i < {ns_upper}_SIM_BUS_COUNT; ++i) {{
+{indent2}{namespace}_sim_bus_fds[i] = -1;
+{indent}}}
+{indent}return 0;
+}}
+int {namespace}_sim_connect_tcp(const char* host, uint16_t port) {{
+{indent}{namespace}_sim_time_us();
+{indent}struct addrinfo hints;
+{indent}memset(&hints, 0, sizeof(hints));
+{indent}hints.ai_family = AF_UNSPEC;
+{indent}hints.ai_socktype = SOCK_STREAM;
+{indent}char service[8];
+{indent}snprintf(service, sizeof(service), \"%u\", (unsigned)port);
+{indent}struct addrinfo* addresses;
+{indent}if (getaddrinfo(host, service, &hints, &addresses) != 0) {{
+{indent2}fprintf(stderr, \"failed to resolve %s:%u\\n\", host, (unsigned)port);
+{indent2}return -1;
+{indent}}}
+{indent}int fd = -1;
+{indent}for (struct addrinfo* address = addresses; address != NULL; address = address->ai_next) {{
+{indent2}fd = socket(address->ai_family, address->ai_socktype, address->ai_protocol);
+{indent2}if (fd < 0) {{
+{indent3}continue;
+{indent2}}}
+{indent2}if (connect(fd, address->ai_addr, address->ai_addrlen) == 0) {{
+{indent3}break;
+{indent2}}}
+{indent2}close(fd);
+{indent2}fd = -1;
+{indent}}}
+{indent}freeaddrinfo(addresses);
+{indent}if (fd < 0) {{
+{indent2}fprintf(stderr, \"failed to connect to %s:%u\\n\", host, (unsigned)port);
+{indent2}return -1;
+{indent}}}
+{indent}int nodelay = 1;
+{indent}setsockopt(fd, IPPROTO_TCP, TCP_NODELAY, &nodelay, sizeof(nodelay));
+{indent}// connection handshake: neither request a node id nor a history sync.
+{indent}uint8_t handshake[2] = {{0x0, 0x0}};
+{indent}if (write(fd, handshake, sizeof(handshake)) != sizeof(handshake)) {{
+{indent2}close(fd);
+{indent2}return -1;
+{indent}}}
+{indent}fcntl(fd, F_SETFL, fcntl(fd, F_GETFL) | O_NONBLOCK);
+{indent}{namespace}_sim_tcp_fd = fd;
+{indent}{namespace}_sim_backend_kind = {ns_upper}_SIM_BACKEND_TCP;
+{indent}return 0;
+}}
+
+static void {namespace}_sim_socketcan_open(int bus_id, const char* ifname) {{
+{indent}int fd = socket(PF_CAN, SOCK_RAW, CAN_RAW);
+{indent}if (fd < 0) {{
+{indent2}perror(\"failed to open SocketCAN socket\");
+{indent2}exit(EXIT_FAILURE);
+{indent}}}
+{indent}struct ifreq ifr;
+{indent}memset(&ifr, 0, sizeof(ifr));
+{indent}strncpy(ifr.ifr_name, ifname, IFNAMSIZ - 1);
+{indent}if (ioctl(fd, SIOCGIFINDEX, &ifr) < 0) {{
+{indent2}fprintf(stderr, \"SocketCAN interface %s not found\\n\", ifname);
+{indent2}exit(EXIT_FAILURE);
+{indent}}}
+{indent}struct sockaddr_can addr;
+{indent}memset(&addr, 0, sizeof(addr));
+{indent}addr.can_family = AF_CAN;
+{indent}addr.can_ifindex = ifr.ifr_ifindex;
+{indent}if (bind(fd, (struct sockaddr*)&addr, sizeof(addr)) < 0) {{
+{indent2}perror(\"failed to bind SocketCAN socket\");
+{indent2}exit(EXIT_FAILURE);
+{indent}}}
+{indent}fcntl(fd, F_SETFL, fcntl(fd, F_GETFL) | O_NONBLOCK);
+{indent}{namespace}_sim_bus_fds[bus_id] = fd;
+}}
+static void {namespace}_sim_socketcan_send(int bus_id, const {namespace}_frame* frame) {{
+{indent}struct can_frame raw;
+{indent}memset(&raw, 0, sizeof(raw));
+{indent}if (frame->id & {ns_upper}_FRAME_IDE_BIT) {{
+{indent2}raw.can_id = (frame->id & CAN_EFF_MASK) | CAN_EFF_FLAG;
+{indent}}} else {{
+{indent2}raw.can_id = frame->id & CAN_SFF_MASK;
+{indent}}}
+{indent}if (frame->id & {ns_upper}_FRAME_RTR_BIT) {{
+{indent2}raw.can_id |= CAN_RTR_FLAG;
+{indent}}}
+{indent}raw.can_dlc = frame->dlc;
+{indent}memcpy(raw.data, frame->data, 8);
+{indent}if (write({namespace}_sim_bus_fds[bus_id], &raw, sizeof(raw)) != sizeof(raw)) {{
+{indent2}perror(\"failed to write to SocketCAN socket\");
+{indent}}}
+}}
+static int {namespace}_sim_socketcan_recv(int bus_id, {namespace}_frame* frame) {{
+{indent}struct can_frame raw;
+{indent}while (read({namespace}_sim_bus_fds[bus_id], &raw, sizeof(raw)) == sizeof(raw)) {{
+{indent2}if (raw.can_id & CAN_ERR_FLAG) {{
+{indent3}continue;
+{indent2}}}
+{indent2}if (raw.can_id & CAN_EFF_FLAG) {{
+{indent3}frame->id = (raw.can_id & CAN_EFF_MASK) | {ns_upper}_FRAME_IDE_BIT;
+{indent2}}} else {{
+{indent3}frame->id = raw.can_id & CAN_SFF_MASK;
+{indent2}}}
+{indent2}if (raw.can_id & CAN_RTR_FLAG) {{
+{indent3}frame->id |= {ns_upper}_FRAME_RTR_BIT;
+{indent2}}}
+{indent2}frame->dlc = raw.can_dlc;
+{indent2}memcpy(frame->data, raw.data, 8);
+{indent2}return 1;
+{indent}}}
+{indent}return 0;
+}}
+
+static void {namespace}_sim_tcp_send(int bus_id, const {namespace}_frame* frame) {{
+{indent}uint8_t buffer[{ns_upper}_SIM_TCP_FRAME_SIZE];
+{indent}memset(buffer, 0, sizeof(buffer));
+{indent}buffer[0] = 0x1;
+{indent}buffer[1] = (uint8_t)bus_id;
+{indent}buffer[2] = frame->dlc;
+{indent}uint32_t key;
+{indent}if (frame->id & {ns_upper}_FRAME_IDE_BIT) {{
+{indent2}key = (frame->id & 0x1FFFFFFF) | {ns_upper}_SIM_TCP_IDE_BIT;
+{indent}}} else {{
+{indent2}key = frame->id & 0x7FF;
+{indent}}}
+{indent}if (frame->id & {ns_upper}_FRAME_RTR_BIT) {{
+{indent2}key |= {ns_upper}_SIM_TCP_RTR_BIT;
+{indent}}}
+{indent}uint64_t timestamp = {namespace}_sim_time_us();
+{indent}memcpy(buffer + 4, &key, sizeof(key));
+{indent}memcpy(buffer + 8, &timestamp, sizeof(timestamp));
+{indent}memcpy(buffer + 16, frame->data, 8);
+{indent}size_t written = 0;
+{indent}while (written < sizeof(buffer)) {{
+{indent2}ssize_t n = write({namespace}_sim_tcp_fd, buffer + written, sizeof(buffer) - written);
+{indent2}if (n > 0) {{
+{indent3}written += (size_t)n;
+{indent2}}} else if (n < 0 && (errno == EAGAIN || errno == EWOULDBLOCK || errno == EINTR)) {{
+{indent3}struct pollfd pfd = {{{namespace}_sim_tcp_fd, POLLOUT, 0}};
+{indent3}poll(&pfd, 1, -1);
+{indent2}}} else {{
+{indent3}fprintf(stderr, \"TCP connection closed\\n\");
+{indent3}exit(EXIT_FAILURE);
+{indent2}}}
+{indent}}}
+}}
+static void {namespace}_sim_tcp_pump() {{
+{indent}while (true) {{
+{indent2}ssize_t n = read({namespace}_sim_tcp_fd,
+{indent4}{namespace}_sim_tcp_rx_buffer + {namespace}_sim_tcp_rx_fill,
+{indent4}{ns_upper}_SIM_TCP_FRAME_SIZE - {namespace}_sim_tcp_rx_fill);
+{indent2}if (n == 0) {{
+{indent3}fprintf(stderr, \"TCP connection closed by the server\\n\");
+{indent3}exit(EXIT_FAILURE);
+{indent2}}}
+{indent2}if (n < 0) {{
+{indent3}return;
+{indent2}}}
+{indent2}{namespace}_sim_tcp_rx_fill += (size_t)n;
+{indent2}if ({namespace}_sim_tcp_rx_fill < {ns_upper}_SIM_TCP_FRAME_SIZE) {{
+{indent3}continue;
+{indent2}}}
+{indent2}{namespace}_sim_tcp_rx_fill = 0;
+{indent2}const uint8_t* buffer = {namespace}_sim_tcp_rx_buffer;
+{indent2}// 0x0 keep alive, 0x2 end of history sync
+{indent2}if (buffer[0] != 0x1 || buffer[1] >= {ns_upper}_SIM_BUS_COUNT) {{
+{indent3}continue;
+{indent2}}}
+{indent2}{namespace}_sim_rx_queue* queue = &{namespace}_sim_rx_queues[buffer[1]];
+{indent2}if (queue->head - queue->tail >= {ns_upper}_SIM_RX_QUEUE_SIZE) {{
+{indent3}fprintf(stderr, \"rx queue of bus %u overflowed\\n\", (unsigned)buffer[1]);
+{indent3}continue;
+{indent2}}}
+{indent2}{namespace}_frame* frame = &queue->buffer[queue->head % {ns_upper}_SIM_RX_QUEUE_SIZE];
+{indent2}uint32_t key;
+{indent2}memcpy(&key, buffer + 4, sizeof(key));
+{indent2}if (key & {ns_upper}_SIM_TCP_IDE_BIT) {{
+{indent3}frame->id = (key & 0x1FFFFFFF) | {ns_upper}_FRAME_IDE_BIT;
+{indent2}}} else {{
+{indent3}frame->id = key & 0x7FF;
+{indent2}}}
+{indent2}if (key & {ns_upper}_SIM_TCP_RTR_BIT) {{
+{indent3}frame->id |= {ns_upper}_FRAME_RTR_BIT;
+{indent2}}}
+{indent2}frame->dlc = buffer[2];
+{indent2}memcpy(frame->data, buffer + 16, 8);
+{indent2}queue->head += 1;
+{indent}}}
+}}
+static int {namespace}_sim_tcp_recv(int bus_id, {namespace}_frame* frame) {{
+{indent}{namespace}_sim_tcp_pump();
+{indent}{namespace}_sim_rx_queue* queue = &{namespace}_sim_rx_queues[bus_id];
+{indent}if (queue->head == queue->tail) {{
+{indent2}return 0;
+{indent}}}
+{indent}*frame = queue->buffer[queue->tail % {ns_upper}_SIM_RX_QUEUE_SIZE];
+{indent}queue->tail += 1;
+{indent}return 1;
+}}
+void {namespace}_sim_wait(uint32_t timeout) {{
+{indent}struct pollfd fds[{ns_upper}_SIM_BUS_COUNT + 1];
+{indent}int count = 0;
+{indent}if ({namespace}_sim_backend_kind == {ns_upper}_SIM_BACKEND_TCP) {{
+{indent2}fds[count++] = {{{namespace}_sim_tcp_fd, POLLIN, 0}};
+{indent}}} else {{
+{indent2}for (int i = 0; i < {ns_upper}_SIM_BUS_COUNT; ++i) {{
+{indent3}if ({namespace}_sim_bus_fds[i] >= 0) {{
+{indent4}fds[count++] = {{{namespace}_sim_bus_fds[i], POLLIN, 0}};
+{indent3}}}
+{indent2}}}
+{indent}}}
+{indent}poll(fds, count, (int)timeout);
+}}
+"
+    ));
+
+    for bus in buses {
+        let bus_id = bus.id();
+        let bus_name = bus.name();
+        source.push_str(&format!(
+            "void {namespace}_{bus_name}_setup(uint32_t baudrate, {namespace}_can_filter* filters, int filter_count) {{
+{indent}// the bitrate of a (v)can interface is configured with ip link.
+{indent}(void)baudrate;
+{indent}(void)filters;
+{indent}(void)filter_count;
+{indent}if ({namespace}_sim_backend_kind == {ns_upper}_SIM_BACKEND_SOCKETCAN) {{
+{indent2}{namespace}_sim_socketcan_open({bus_id}, \"{bus_name}\");
+{indent}}}
+}}
+void {namespace}_{bus_name}_send({namespace}_frame* frame) {{
+{indent}if ({namespace}_sim_backend_kind == {ns_upper}_SIM_BACKEND_TCP) {{
+{indent2}{namespace}_sim_tcp_send({bus_id}, frame);
+{indent}}} else {{
+{indent2}{namespace}_sim_socketcan_send({bus_id}, frame);
+{indent}}}
+}}
+int {namespace}_{bus_name}_recv({namespace}_frame* frame) {{
+{indent}if ({namespace}_sim_backend_kind == {ns_upper}_SIM_BACKEND_TCP) {{
+{indent2}return {namespace}_sim_tcp_recv({bus_id}, frame);
+{indent}}}
+{indent}return {namespace}_sim_socketcan_recv({bus_id}, frame);
+}}
+"
+        ));
+    }
+    Ok(())
+}
+
+/// Entry point of the simulated node, which runs the poll/update loop.
+pub fn generate_sim_main(
+    node_config: &config::NodeRef,
+    buses: &Vec<BusRef>,
+    options: &Options,
+) -> Result<String> {
+    let namespace = options.namespace();
+    let mut indent = String::new();
+    for _ in 0..options.indent() {
+        indent.push(' ');
+    }
+    let indent2 = format!("{indent}{indent}");
+    let indent3 = format!("{indent2}{indent}");
+    let node_name = node_config.name();
+
+    let header_name = std::path::Path::new(options.header_file_path())
+        .file_name()
+        .and_then(|name| name.to_str())
+        .unwrap_or(options.header_file_path())
+        .to_owned();
+
+    let mut main_sim = format!(
+        "#include \"{header_name}\"
+#include <cstdio>
+#include <cstdlib>
+#include <cstring>
+
+"
+    );
+
+    // commands are acknowledged by default, link a definition to override them.
+    for command in node_config.commands() {
+        let command_name = command.name();
+        let hook_signature = command_hook_signature(command, options);
+        main_sim.push_str(&format!(
+            "__attribute__((weak)) {hook_signature} {{
+{indent}printf(\"command {command_name} received\\n\");
+{indent}return command_resp_erno_Success;
+}}
+"
+        ));
+    }
+
+    let mut poll_logic = String::new();
+    for bus in buses {
+        let bus_id = bus.id();
+        poll_logic.push_str(&format!("{indent2}{namespace}_can{bus_id}_poll();\n"));
+    }
+
+    main_sim.push_str(&format!(
+        "
+static void print_usage(const char* program) {{
+{indent}fprintf(stderr, \"usage: %s [--socketcan | --tcp <host>:<port>]\\n\", program);
+}}
+
+int main(int argc, char** argv) {{
+{indent}const char* tcp_address = NULL;
+{indent}for (int i = 1; i < argc; ++i) {{
+{indent2}if (strcmp(argv[i], \"--tcp\") == 0 && i + 1 < argc) {{
+{indent3}tcp_address = argv[++i];
+{indent2}}} else if (strcmp(argv[i], \"--socketcan\") == 0) {{
+{indent3}tcp_address = NULL;
+{indent2}}} else {{
+{indent3}print_usage(argv[0]);
+{indent3}return EXIT_FAILURE;
+{indent2}}}
+{indent}}}
+
+{indent}if (tcp_address != NULL) {{
+{indent2}char host[256];
+{indent2}const char* port = strrchr(tcp_address, ':');
+{indent2}if (port == NULL || (size_t)(port - tcp_address) >= sizeof(host)) {{
+{indent3}print_usage(argv[0]);
+{indent3}return EXIT_FAILURE;
+{indent2}}}
+{indent2}memcpy(host, tcp_address, port - tcp_address);
+{indent2}host[port - tcp_address] = '\\0';
+{indent2}if ({namespace}_sim_connect_tcp(host, (uint16_t)atoi(port + 1)) != 0) {{
+{indent3}return EXIT_FAILURE;
+{indent2}}}
+{indent}}} else {{
+{indent2}{namespace}_sim_connect_socketcan();
+{indent}}}
+
+{indent}{namespace}_init();
+{indent}printf(\"simulating node {node_name}\\n\");
+{indent}while (true) {{
+{poll_logic}{indent2}uint32_t next = {namespace}_update_continue({namespace}_get_time());
+{indent2}uint32_t requested = {namespace}_sim_take_requested_update();
+{indent2}if (requested < next) {{
+{indent3}next = requested;
+{indent2}}}
+{indent2}uint32_t now = {namespace}_get_time();
+{indent2}{namespace}_sim_wait(next > now ? next - now : 0);
+{indent}}}
+}}
+"
+    ));
+    Ok(main_sim)
+}
@@ -0,0 +1,29 @@
+use canzero_codegen::{generate_if_changed, options::Options};
+use canzero_yaml::parse_yaml_config_from_file;
+
+fn options(dir: &std::path::Path) -> Options {
+    let mut options = Options::default();
+    options.set_source_file_path(dir.join("canzero.cpp").to_str().unwrap());
+    options.set_header_file_path(dir.join("canzero.h").to_str().unwrap());
+    options
+}
+
+#[test]
+fn unchanged_files_are_not_rewritten() {
+    let dir = std::env::temp_dir().join("canzero-codegen-if-changed");
+    let _ = std::fs::remove_dir_all(&dir);
+    std::fs::create_dir_all(&dir).unwrap();
+
+    let network_config = parse_yaml_config_from_file("./examples/simple.yaml").unwrap();
+    let written = generate_if_changed("simple", network_config.clone(), options(&dir)).unwrap();
+    assert_eq!(written.len(), 2);
+
+    let written = generate_if_changed("simple", network_config.clone(), options(&dir)).unwrap();
+    assert!(written.is_empty());
+
+    // a different namespace changes both files.
+    let mut renamed = options(&dir);
+    renamed.set_namespace("other");
+    let written = generate_if_changed("simple", network_config, renamed).unwrap();
+    assert_eq!(written.len(), 2);
+}
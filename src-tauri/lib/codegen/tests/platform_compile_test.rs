@@ -0,0 +1,90 @@
+use std::{path::PathBuf, process::Command};
+
+use canzero_codegen::{
+    generate,
+    options::{Options, Platform},
+};
+use canzero_yaml::parse_yaml_config_from_file;
+
+// registers and message ram are backed by plain arrays, so the generated
+// drivers can be compiled (not executed) on the host.
+const REGISTER_STUBS: &str = "#include <cstdint>
+static uint32_t stub_can0_regs[1024];
+static uint32_t stub_can2_regs[1024];
+static uint32_t stub_fdcan_sram[1024];
+#define CANZERO_CAN0_BASE ((uintptr_t)stub_can0_regs)
+#define CANZERO_CAN2_BASE ((uintptr_t)stub_can2_regs)
+#define CANZERO_BXCAN_FILTER_BASE ((uintptr_t)stub_can0_regs)
+#define CANZERO_FDCAN_SRAM_BASE ((uintptr_t)stub_fdcan_sram)
+#define DMAMEM
+";
+
+const PGMSPACE_STUB: &str = "#pragma once
+#define PROGMEM
+";
+
+fn host_compile(platform: Platform, simulation: bool, name: &str) {
+    let dir = std::env::temp_dir().join(format!("canzero-codegen-{name}"));
+    std::fs::create_dir_all(dir.join("avr")).unwrap();
+    std::fs::write(dir.join("avr").join("pgmspace.h"), PGMSPACE_STUB).unwrap();
+    std::fs::write(dir.join("register_stubs.h"), REGISTER_STUBS).unwrap();
+
+    let network_config = parse_yaml_config_from_file("./examples/simple.yaml").unwrap();
+    let source_file_path: PathBuf = dir.join("canzero.cpp");
+    let header_file_path: PathBuf = dir.join("canzero.h");
+    let sim_main_file_path: PathBuf = dir.join("main_sim.cpp");
+    let mut options = Options::default();
+    options.set_platform(platform);
+    options.set_simulation(simulation);
+    options.set_source_file_path(source_file_path.to_str().unwrap());
+    options.set_header_file_path(header_file_path.to_str().unwrap());
+    options.set_sim_main_file_path(sim_main_file_path.to_str().unwrap());
+    generate("simple", network_config, options).unwrap();
+
+    let compiler = std::env::var("CXX").unwrap_or("c++".to_owned());
+    let mut command = Command::new(&compiler);
+    command
+        .arg("-std=c++17")
+        .arg("-fsyntax-only")
+        .arg("-I")
+        .arg(&dir)
+        .arg("-include")
+        .arg(dir.join("register_stubs.h"))
+        .arg(&source_file_path);
+    if simulation {
+        command.arg(&sim_main_file_path);
+    }
+    let output = match command.output()
+    {
+        Ok(output) => output,
+        Err(err) => {
+            eprintln!("skipping host compile test, {compiler} is not available : {err:?}");
+            return;
+        }
+    };
+    assert!(
+        output.status.success(),
+        "generated {name} code failed to compile:\n{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+}
+
+#[test]
+fn stm32_bxcan_compiles_on_host() {
+    host_compile(Platform::Stm32BxCan, false, "stm32-bxcan");
+}
+
+#[test]
+fn stm32_fdcan_compiles_on_host() {
+    host_compile(Platform::Stm32FdCan, false, "stm32-fdcan");
+}
+
+#[test]
+fn teensy4_flexcan_compiles_on_host() {
+    host_compile(Platform::Teensy4FlexCan, false, "teensy4-flexcan");
+}
+
+#[test]
+fn linux_simulation_compiles_on_host() {
+    host_compile(Platform::Linux, true, "linux-sim");
+}
@@ -51,16 +51,30 @@ pub fn import_dbc(network_builder: &NetworkBuilder, bus: &str, dbc_path: &str) -
             }
         }
         for rx in receives {
-            message_builder.add_receiver(&rx);
+            // Vector__XXX is the placeholder of dbc files for no node.
+            if rx != "Vector__XXX" {
+                message_builder.add_receiver(&rx);
+            }
         }
 
-        for msg_transmitter in dbc.message_transmitters().iter().filter(|transmitter| transmitter.message_id() == message.message_id()) {
-            let transmitters = msg_transmitter.transmitter();
-            for tx in transmitters {
-                match tx {
-                    can_dbc::Transmitter::NodeName(node_name) => message_builder.add_receiver(node_name),
-                    can_dbc::Transmitter::VectorXXX => (),
+        // the transmitter of the BO_ line, BO_TX_BU_ lists additional transmitters.
+        let mut transmitters = vec![message.transmitter()];
+        transmitters.extend(
+            dbc.message_transmitters()
+                .iter()
+                .filter(|transmitter| transmitter.message_id() == message.message_id())
+                .flat_map(|msg_transmitter| msg_transmitter.transmitter()),
+        );
+        let mut transmitter_names: Vec<&str> = vec![];
+        for tx in transmitters {
+            match tx {
+                can_dbc::Transmitter::NodeName(node_name) if node_name != "Vector__XXX" => {
+                    if !transmitter_names.contains(&node_name.as_str()) {
+                        transmitter_names.push(node_name);
+                        message_builder.add_transmitter(node_name);
+                    }
                 }
+                _ => (),
             }
         }
     }
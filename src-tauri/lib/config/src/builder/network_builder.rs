@@ -29,6 +29,12 @@ use super::{
     StructBuilder, TypeBuilder,
 };
 
+/// Width of the failed bitmap of the set_commit_resp, a node stages at most this many entries.
+pub const MAX_STAGED_ENTRIES: usize = 32;
+/// A node discards the staged entries of a client, which didn't stage or commit for this long,
+/// so that a client which died while staging doesn't block the transactions of the others.
+pub const STAGE_TIMEOUT: Duration = Duration::from_millis(1000);
+
 #[derive(Debug, Clone)]
 pub struct NetworkBuilder(pub BuilderRef<NetworkData>);
 
@@ -41,6 +47,9 @@ pub struct NetworkData {
     pub get_resp_message: OnceCell<MessageBuilder>,
     pub set_req_message: OnceCell<MessageBuilder>,
     pub set_resp_message: OnceCell<MessageBuilder>,
+    pub set_stage_req_message: OnceCell<MessageBuilder>,
+    pub set_commit_req_message: OnceCell<MessageBuilder>,
+    pub set_commit_resp_message: OnceCell<MessageBuilder>,
     pub buses: BuilderRef<Vec<BusBuilder>>,
 }
 
@@ -56,6 +65,9 @@ impl NetworkBuilder {
             get_resp_message: OnceCell::new(),
             set_req_message: OnceCell::new(),
             set_resp_message: OnceCell::new(),
+            set_stage_req_message: OnceCell::new(),
+            set_commit_req_message: OnceCell::new(),
+            set_commit_resp_message: OnceCell::new(),
             buses: make_builder_ref(vec![]),
         }));
 
@@ -149,6 +161,71 @@ impl NetworkBuilder {
             .set(set_resp_message)
             .unwrap();
 
+        // Transactions: set_stage_req frames are staged by the node and applied
+        // together by a set_commit_req, which is answered by a single set_commit_resp.
+        let set_commit_action = network_builder.define_enum("set_commit_action");
+        set_commit_action.add_entry("Commit", Some(0)).unwrap();
+        set_commit_action.add_entry("Abort", Some(1)).unwrap();
+
+        let set_commit_erno = network_builder.define_enum("set_commit_erno");
+        set_commit_erno.add_entry("Success", Some(0)).unwrap();
+        set_commit_erno.add_entry("Error", Some(1)).unwrap();
+        set_commit_erno.add_entry("Aborted", Some(2)).unwrap();
+
+        let set_stage_req_message = network_builder.create_message("set_stage_req", None);
+        set_stage_req_message.set_any_std_id(MessagePriority::Low);
+        set_stage_req_message.__assign_to_configuration();
+        let set_stage_req_format = set_stage_req_message.make_type_format();
+        set_stage_req_format.add_type("set_req_header", "header");
+        set_stage_req_format.add_type("u32", "data");
+        network_builder
+            .0
+            .borrow_mut()
+            .set_stage_req_message
+            .set(set_stage_req_message)
+            .unwrap();
+
+        let set_commit_req_message = network_builder.create_message("set_commit_req", None);
+        set_commit_req_message.set_any_std_id(MessagePriority::Low);
+        set_commit_req_message.__assign_to_configuration();
+        let set_commit_req_format = set_commit_req_message.make_type_format();
+        let set_commit_req_header = network_builder.define_struct("set_commit_req_header");
+        set_commit_req_header.add_attribute(client_id_name, "u8").unwrap();
+        set_commit_req_header.add_attribute(server_id_name, "u8").unwrap();
+        set_commit_req_header
+            .add_attribute("action", "set_commit_action")
+            .unwrap();
+        // number of staged entries, the node rejects the commit if it staged a different number.
+        set_commit_req_header.add_attribute("entries", "u6").unwrap();
+        set_commit_req_format.add_type("set_commit_req_header", "header");
+        network_builder
+            .0
+            .borrow_mut()
+            .set_commit_req_message
+            .set(set_commit_req_message)
+            .unwrap();
+
+        let set_commit_resp_message = network_builder.create_message("set_commit_resp", None);
+        set_commit_resp_message.set_any_std_id(MessagePriority::Low);
+        set_commit_resp_message.__assign_to_configuration();
+        let set_commit_resp_format = set_commit_resp_message.make_type_format();
+        let set_commit_resp_header = network_builder.define_struct("set_commit_resp_header");
+        set_commit_resp_header.add_attribute(client_id_name, "u8").unwrap();
+        set_commit_resp_header.add_attribute(server_id_name, "u8").unwrap();
+        set_commit_resp_header
+            .add_attribute("erno", "set_commit_erno")
+            .unwrap();
+        set_commit_resp_header.add_attribute("entries", "u6").unwrap();
+        set_commit_resp_format.add_type("set_commit_resp_header", "header");
+        // bit i is set if the i-th staged entry was rejected.
+        set_commit_resp_format.add_type(&format!("u{MAX_STAGED_ENTRIES}"), "failed");
+        network_builder
+            .0
+            .borrow_mut()
+            .set_commit_resp_message
+            .set(set_commit_resp_message)
+            .unwrap();
+
         let command_resp = network_builder.define_enum("command_resp_erno");
         command_resp.add_entry("Success", Some(0)).unwrap();
         command_resp.add_entry("Error", Some(1)).unwrap();
@@ -243,6 +320,18 @@ impl NetworkBuilder {
     pub fn _set_resp_message(&self) -> MessageBuilder {
         self.0.borrow().set_resp_message.get().unwrap().clone()
     }
+
+    pub fn _set_stage_req_message(&self) -> MessageBuilder {
+        self.0.borrow().set_stage_req_message.get().unwrap().clone()
+    }
+
+    pub fn _set_commit_req_message(&self) -> MessageBuilder {
+        self.0.borrow().set_commit_req_message.get().unwrap().clone()
+    }
+
+    pub fn _set_commit_resp_message(&self) -> MessageBuilder {
+        self.0.borrow().set_commit_resp_message.get().unwrap().clone()
+    }
 }
 
 impl NetworkBuilder {
@@ -778,6 +867,24 @@ impl NetworkBuilder {
             .unwrap()
             .clone();
         set_req_message.__set_usage(MessageUsage::SetReq);
+        let set_stage_req_message = messages
+            .iter()
+            .find(|m| m.name() == builder.set_stage_req_message.get().unwrap().0.borrow().name)
+            .unwrap()
+            .clone();
+        set_stage_req_message.__set_usage(MessageUsage::SetStageReq);
+        let set_commit_req_message = messages
+            .iter()
+            .find(|m| m.name() == builder.set_commit_req_message.get().unwrap().0.borrow().name)
+            .unwrap()
+            .clone();
+        set_commit_req_message.__set_usage(MessageUsage::SetCommitReq);
+        let set_commit_resp_message = messages
+            .iter()
+            .find(|m| m.name() == builder.set_commit_resp_message.get().unwrap().0.borrow().name)
+            .unwrap()
+            .clone();
+        set_commit_resp_message.__set_usage(MessageUsage::SetCommitResp);
         let heartbeat_messages = messages
             .iter()
             .filter(|message| message.name().starts_with("heartbeat_"))
@@ -1226,6 +1333,9 @@ impl NetworkBuilder {
             get_resp_message,
             set_req_message,
             set_resp_message,
+            set_stage_req_message,
+            set_commit_req_message,
+            set_commit_resp_message,
             heartbeat_messages,
             buses,
         ));
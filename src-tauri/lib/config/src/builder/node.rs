@@ -46,6 +46,9 @@ impl NodeBuilder {
         node_builder.add_tx_message(&network_builder._get_resp_message());
         node_builder.add_rx_message(&network_builder._set_req_message());
         node_builder.add_tx_message(&network_builder._set_resp_message());
+        node_builder.add_rx_message(&network_builder._set_stage_req_message());
+        node_builder.add_rx_message(&network_builder._set_commit_req_message());
+        node_builder.add_tx_message(&network_builder._set_commit_resp_message());
 
         let config_hash = node_builder.create_object_entry("config_hash", "u64");
         config_hash.add_description("Hash of the local network configuration");
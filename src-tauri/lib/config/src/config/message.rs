@@ -12,10 +12,34 @@ pub enum MessageUsage {
     GetReq,
     SetResp,
     SetReq,
+    SetStageReq,
+    SetCommitReq,
+    SetCommitResp,
     Heartbeat,
     External{interval : Duration},
 }
 
+impl MessageUsage {
+    /// Requests and responses of the object dictionary and commands.
+    /// Nodes accept them on every bus they are attached to and respond on the
+    /// bus the request arrived on, which allows a client to fall back to
+    /// another bus if the bus of the message fails.
+    pub fn is_configuration(&self) -> bool {
+        matches!(
+            self,
+            MessageUsage::CommandReq(_)
+                | MessageUsage::CommandResp(_)
+                | MessageUsage::GetResp
+                | MessageUsage::GetReq
+                | MessageUsage::SetResp
+                | MessageUsage::SetReq
+                | MessageUsage::SetStageReq
+                | MessageUsage::SetCommitReq
+                | MessageUsage::SetCommitResp
+        )
+    }
+}
+
 #[derive(Debug, Eq, PartialEq, Copy, Clone)]
 pub enum MessageId {
     StandardId(u32),
@@ -15,6 +15,9 @@ pub struct Network {
     get_req_message : MessageRef,
     set_resp_message : MessageRef,
     set_req_message : MessageRef,
+    set_stage_req_message : MessageRef,
+    set_commit_req_message : MessageRef,
+    set_commit_resp_message : MessageRef,
     heartbeat_messages : Vec<MessageRef>,
     buses : Vec<BusRef>,
 }
@@ -53,6 +56,9 @@ impl Network {
         get_resp_message : MessageRef,
         set_req_message : MessageRef,
         set_resp_message : MessageRef,
+        set_stage_req_message : MessageRef,
+        set_commit_req_message : MessageRef,
+        set_commit_resp_message : MessageRef,
         heartbeat_messages : Vec<MessageRef>,
         buses : Vec<BusRef>,
     ) -> Network {
@@ -65,6 +71,9 @@ impl Network {
             get_resp_message,
             set_req_message,
             set_resp_message,
+            set_stage_req_message,
+            set_commit_req_message,
+            set_commit_resp_message,
             heartbeat_messages,
             buses
         }
@@ -96,6 +105,15 @@ impl Network {
     pub fn set_req_message(&self) -> &MessageRef {
         &self.set_req_message
     }
+    pub fn set_stage_req_message(&self) -> &MessageRef {
+        &self.set_stage_req_message
+    }
+    pub fn set_commit_req_message(&self) -> &MessageRef {
+        &self.set_commit_req_message
+    }
+    pub fn set_commit_resp_message(&self) -> &MessageRef {
+        &self.set_commit_resp_message
+    }
     pub fn heartbeat_messages(&self) -> &Vec<MessageRef> {
         &self.heartbeat_messages
     }
@@ -0,0 +1,101 @@
+use canzero_config::config::{SignalRef, Type, TypeSignalEncoding};
+
+use crate::{
+    serialize_attributes, signal_serializer::SignalSerializer, AttributeSerializer, Value,
+};
+
+pub enum EncodedSerializer {
+    Primitive(SignalSerializer),
+    Composite(Vec<AttributeSerializer>),
+    Enum(EnumSerializer),
+}
+
+pub struct EnumSerializer {
+    entries: Vec<(String, u64)>,
+    signal_serializer: SignalSerializer,
+}
+
+impl EncodedSerializer {
+    pub fn new(encoding: &TypeSignalEncoding) -> Result<Self, String> {
+        Ok(match encoding {
+            TypeSignalEncoding::Primitive(prim) => match prim.ty() as &Type {
+                // NOTE: config refers to unsigned, signed and decimal values as primitives
+                Type::Primitive(_) => {
+                    Self::Primitive(SignalSerializer::new(prim.signal()))
+                }
+                Type::Enum {
+                    name: _,
+                    description: _,
+                    size: _,
+                    entries,
+                    visibility: _,
+                } => Self::Enum(EnumSerializer {
+                    entries: entries.clone(),
+                    signal_serializer: SignalSerializer::new(prim.signal()),
+                }),
+                Type::Struct {
+                    name: _,
+                    description: _,
+                    attribs: _,
+                    visibility: _,
+                } => panic!(
+                    "DETECTED INVALID CONFIG : primitive encoding associated with a structured type!"
+                ),
+                Type::Array { len: _, ty: _ } => {
+                    return Err(format!(
+                        "{} has an array type, which is not supported by the encoding",
+                        encoding.name()
+                    ))
+                }
+            },
+            TypeSignalEncoding::Composite(comp) => Self::Composite(
+                comp.attributes()
+                    .iter()
+                    .map(|attribute_encoding| {
+                        Ok(AttributeSerializer::new(
+                            attribute_encoding.name(),
+                            EncodedSerializer::new(attribute_encoding)?,
+                        ))
+                    })
+                    .collect::<Result<_, String>>()?,
+            ),
+        })
+    }
+
+    pub fn new_from_signal(signal: &SignalRef) -> Self {
+        Self::Primitive(SignalSerializer::new(signal))
+    }
+
+    /// `path` is the dotted name of the attribute and only used for error messages.
+    pub fn serialize(&self, path: &str, value: &Value) -> Result<u64, String> {
+        match &self {
+            Self::Primitive(signal_serializer) => signal_serializer.serialize(path, value),
+            Self::Composite(attribute_serializers) => {
+                let Value::StructValue(attributes) = value else {
+                    return Err(format!("{path} expects a struct"));
+                };
+                serialize_attributes(attribute_serializers, attributes, Some(path))
+            }
+            Self::Enum(EnumSerializer {
+                entries,
+                signal_serializer,
+            }) => {
+                let Value::EnumValue(variant) = value else {
+                    return Err(format!("{path} expects an enum variant"));
+                };
+                let Some((_, discriminant)) = entries.iter().find(|(name, _)| name == variant)
+                else {
+                    return Err(format!(
+                        "{variant} is not a variant of {path}, expected one of {}",
+                        entries
+                            .iter()
+                            .map(|(name, _)| name.as_str())
+                            .collect::<Vec<_>>()
+                            .join(", ")
+                    ));
+                };
+                Ok(signal_serializer.place(*discriminant))
+            }
+        }
+    }
+}
@@ -0,0 +1,229 @@
+use canzero_common::CanFrame;
+use canzero_config::config::MessageRef;
+
+use self::encoding_serializer::EncodedSerializer;
+
+pub use value::{attributes_from_paths, Attribute, Value};
+
+mod encoding_serializer;
+mod signal_serializer;
+mod value;
+
+/// Inverse of the `FrameDeserializer`, composes the payload of a message from the values of
+/// its attributes. Messages without encoding are composed from their signals by name.
+pub struct FrameSerializer {
+    message_config: MessageRef,
+    value_serializer: FrameValueSerializer,
+}
+
+impl FrameSerializer {
+    /// Fails if the encoding of the message contains types, which can not be encoded,
+    /// e.g. arrays.
+    pub fn new(message_config: &MessageRef) -> Result<Self, String> {
+        Ok(Self {
+            message_config: message_config.clone(),
+            value_serializer: FrameValueSerializer::new(message_config)?,
+        })
+    }
+
+    pub fn serialize(&self, attributes: &[Attribute]) -> Result<u64, String> {
+        self.value_serializer.serialize(attributes)
+    }
+
+    pub fn can_frame(&self, attributes: &[Attribute]) -> Result<CanFrame, String> {
+        Ok(CanFrame::new(
+            self.message_config.id().as_u32(),
+            self.message_config.id().ide(),
+            false,
+            self.message_config.dlc(),
+            self.serialize(attributes)?,
+        ))
+    }
+}
+
+pub struct FrameValueSerializer {
+    attribute_serializer: Vec<AttributeSerializer>,
+}
+
+struct AttributeSerializer {
+    attribute_name: String,
+    encoded_serializer: EncodedSerializer,
+}
+
+impl AttributeSerializer {
+    pub fn new(name: &str, encoded_serializer: EncodedSerializer) -> Self {
+        Self {
+            attribute_name: name.to_owned(),
+            encoded_serializer,
+        }
+    }
+}
+
+impl FrameValueSerializer {
+    pub fn new(message_config: &MessageRef) -> Result<FrameValueSerializer, String> {
+        Ok(match message_config.encoding() {
+            Some(message_encoding) => Self {
+                attribute_serializer: message_encoding
+                    .attributes()
+                    .iter()
+                    .map(|type_encoding| {
+                        Ok(AttributeSerializer::new(
+                            type_encoding.name(),
+                            EncodedSerializer::new(type_encoding)?,
+                        ))
+                    })
+                    .collect::<Result<_, String>>()?,
+            },
+            None => Self {
+                attribute_serializer: message_config
+                    .signals()
+                    .iter()
+                    .map(|signal| {
+                        AttributeSerializer::new(
+                            signal.name(),
+                            EncodedSerializer::new_from_signal(signal),
+                        )
+                    })
+                    .collect(),
+            },
+        })
+    }
+
+    pub fn serialize(&self, attributes: &[Attribute]) -> Result<u64, String> {
+        serialize_attributes(&self.attribute_serializer, attributes, None)
+    }
+}
+
+/// Every attribute has to be given exactly once, unknown attributes are rejected.
+fn serialize_attributes(
+    attribute_serializers: &[AttributeSerializer],
+    attributes: &[Attribute],
+    parent: Option<&str>,
+) -> Result<u64, String> {
+    let path = |name: &str| match parent {
+        Some(parent) => format!("{parent}.{name}"),
+        None => name.to_owned(),
+    };
+    if let Some(unknown) = attributes.iter().find(|attribute| {
+        !attribute_serializers
+            .iter()
+            .any(|serializer| serializer.attribute_name == attribute.name())
+    }) {
+        return Err(format!("Unknown attribute {}", path(unknown.name())));
+    }
+    let mut data = 0u64;
+    for serializer in attribute_serializers {
+        let name = path(&serializer.attribute_name);
+        let mut matching = attributes
+            .iter()
+            .filter(|attribute| attribute.name() == serializer.attribute_name);
+        let Some(attribute) = matching.next() else {
+            return Err(format!("Missing attribute {name}"));
+        };
+        if matching.next().is_some() {
+            return Err(format!("Duplicate attribute {name}"));
+        }
+        data |= serializer
+            .encoded_serializer
+            .serialize(&name, attribute.value())?;
+    }
+    Ok(data)
+}
+
+#[cfg(test)]
+mod tests {
+    use canzero_config::config::NetworkRef;
+    use canzero_yaml::parse_yaml_config_from_file;
+
+    use super::*;
+
+    fn network() -> NetworkRef {
+        parse_yaml_config_from_file("../codegen/examples/conformance.yaml").unwrap()
+    }
+
+    fn serializer(network: &NetworkRef, name: &str) -> FrameSerializer {
+        let message = network
+            .messages()
+            .iter()
+            .find(|message| message.name() == name)
+            .unwrap();
+        FrameSerializer::new(message).unwrap()
+    }
+
+    fn configure(target: &str) -> Vec<Attribute> {
+        vec![
+            Attribute::new("gain", Value::RealValue(10.0)),
+            Attribute::new("offset", Value::SignedValue(-2)),
+            Attribute::new("target", Value::EnumValue(target.to_owned())),
+        ]
+    }
+
+    fn header(od_index: u64) -> Value {
+        Value::StructValue(vec![
+            Attribute::new("od_index", Value::UnsignedValue(od_index)),
+            Attribute::new("client_id", Value::UnsignedValue(1)),
+            Attribute::new("server_id", Value::UnsignedValue(2)),
+        ])
+    }
+
+    #[test]
+    fn enum_variants_by_name() {
+        let network = network();
+        let serializer = serializer(&network, "primary_configure_command_req");
+        let data = serializer.serialize(&configure("brake")).unwrap();
+        assert_eq!(data & 0xFFFF, 0xFFFF);
+        assert_eq!((data >> 16) & 0xFFFF, 0xFFFE);
+        assert_eq!((data >> 32) & 0x7, 7);
+        let err = serializer.serialize(&configure("reverse")).unwrap_err();
+        assert!(err.contains("reverse is not a variant of target"), "{err}");
+        let mut attributes = configure("idle");
+        attributes[2] = Attribute::new("target", Value::UnsignedValue(0));
+        assert!(serializer.serialize(&attributes).is_err());
+    }
+
+    #[test]
+    fn missing_duplicate_and_unknown_attributes() {
+        let network = network();
+        let serializer = serializer(&network, "primary_configure_command_req");
+        let mut attributes = configure("idle");
+        attributes.pop();
+        assert_eq!(
+            serializer.serialize(&attributes),
+            Err("Missing attribute target".to_owned())
+        );
+        let mut attributes = configure("idle");
+        attributes.push(Attribute::new("offset", Value::SignedValue(1)));
+        assert_eq!(
+            serializer.serialize(&attributes),
+            Err("Duplicate attribute offset".to_owned())
+        );
+        let mut attributes = configure("idle");
+        attributes.push(Attribute::new("scale", Value::SignedValue(1)));
+        assert_eq!(
+            serializer.serialize(&attributes),
+            Err("Unknown attribute scale".to_owned())
+        );
+    }
+
+    #[test]
+    fn nested_attributes_report_their_path() {
+        let network = network();
+        let serializer = serializer(&network, "get_req");
+        let data = serializer
+            .serialize(&[Attribute::new("header", header(5))])
+            .unwrap();
+        assert_eq!(data, 5 | (1 << 13) | (2 << 21));
+        let Value::StructValue(mut attributes) = header(5) else {
+            unreachable!()
+        };
+        attributes.remove(1);
+        assert_eq!(
+            serializer.serialize(&[Attribute::new("header", Value::StructValue(attributes))]),
+            Err("Missing attribute header.client_id".to_owned())
+        );
+        assert_eq!(
+            serializer.serialize(&[Attribute::new("header", header(1 << 13))]),
+            Err("header.od_index = 8192 is out of range [0, 8191]".to_owned())
+        );
+    }
+}
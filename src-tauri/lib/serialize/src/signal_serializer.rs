@@ -0,0 +1,204 @@
+use canzero_config::config::{self, SignalRef};
+
+use crate::Value;
+
+pub struct SignalSerializer {
+    bit_mask: u64,
+    bit_offset: u32,
+    bit_size: u8,
+    type_info: SignalSerializerTypeInfo,
+}
+
+enum SignalSerializerTypeInfo {
+    Decimal { offset: f64, scale: f64 },
+    Unsigned,
+    Signed,
+}
+
+impl SignalSerializer {
+    pub fn new(signal: &SignalRef) -> Self {
+        let bit_offset = signal.byte_offset() as u32;
+        let bit_size = signal.size() as u32;
+        Self {
+            bit_offset,
+            bit_mask: (u64::MAX >> (u64::BITS - bit_size)),
+            bit_size: bit_size as u8,
+            type_info: match signal.ty() {
+                config::SignalType::UnsignedInt { size: _ } => SignalSerializerTypeInfo::Unsigned,
+                config::SignalType::SignedInt { size: _ } => SignalSerializerTypeInfo::Signed,
+                config::SignalType::Decimal {
+                    size: _,
+                    offset,
+                    scale,
+                } => SignalSerializerTypeInfo::Decimal {
+                    offset: *offset,
+                    scale: *scale,
+                },
+            },
+        }
+    }
+
+    /// Places the raw (unsigned) bits of the signal into the frame data.
+    pub fn place(&self, unsigned_bits: u64) -> u64 {
+        (unsigned_bits & self.bit_mask) << self.bit_offset
+    }
+
+    /// Encodes a value, integer signals accept any numeric value without a fractional part
+    /// and decimals are given in physical units. Values outside of the range of the signal
+    /// are rejected instead of truncated.
+    pub fn serialize(&self, name: &str, value: &Value) -> Result<u64, String> {
+        let unsigned_bits = match &self.type_info {
+            SignalSerializerTypeInfo::Unsigned => {
+                let Some(value) = integer(value) else {
+                    return Err(format!("{name} expects an unsigned integer"));
+                };
+                if value < 0 || value > self.bit_mask as i128 {
+                    return Err(format!(
+                        "{name} = {value} is out of range [0, {}]",
+                        self.bit_mask
+                    ));
+                }
+                value as u64
+            }
+            SignalSerializerTypeInfo::Signed => {
+                let Some(value) = integer(value) else {
+                    return Err(format!("{name} expects a signed integer"));
+                };
+                let max = (1i128 << (self.bit_size - 1)) - 1;
+                let min = -(1i128 << (self.bit_size - 1));
+                if value < min || value > max {
+                    return Err(format!("{name} = {value} is out of range [{min}, {max}]"));
+                }
+                // two's complement, truncated to the size of the signal.
+                value as i64 as u64 & self.bit_mask
+            }
+            SignalSerializerTypeInfo::Decimal { offset, scale } => {
+                let value = match value {
+                    Value::RealValue(value) => *value,
+                    Value::UnsignedValue(value) => *value as f64,
+                    Value::SignedValue(value) => *value as f64,
+                    _ => return Err(format!("{name} expects a number")),
+                };
+                let max = self.bit_mask as f64 * scale + offset;
+                if !value.is_finite() || value < *offset || value > max {
+                    return Err(format!(
+                        "{name} = {value} is out of range [{offset}, {max}]"
+                    ));
+                }
+                (((value - offset) / scale).round() as u64).min(self.bit_mask)
+            }
+        };
+        Ok(self.place(unsigned_bits))
+    }
+}
+
+fn integer(value: &Value) -> Option<i128> {
+    match value {
+        Value::UnsignedValue(value) => Some(*value as i128),
+        Value::SignedValue(value) => Some(*value as i128),
+        Value::RealValue(value) if value.is_finite() && value.fract() == 0.0 => {
+            Some(*value as i128)
+        }
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use canzero_config::config::{make_config_ref, signal::Signal, SignalType};
+
+    use super::*;
+
+    fn serializer(ty: SignalType, offset: usize) -> SignalSerializer {
+        SignalSerializer::new(&make_config_ref(Signal::new("value", None, ty, offset)))
+    }
+
+    #[test]
+    fn unsigned_range() {
+        let signal = serializer(SignalType::UnsignedInt { size: 8 }, 0);
+        assert_eq!(
+            signal.serialize("value", &Value::UnsignedValue(255)),
+            Ok(0xFF)
+        );
+        assert_eq!(signal.serialize("value", &Value::RealValue(3.0)), Ok(3));
+        assert!(signal
+            .serialize("value", &Value::UnsignedValue(256))
+            .is_err());
+        assert!(signal.serialize("value", &Value::SignedValue(-1)).is_err());
+        assert!(signal.serialize("value", &Value::RealValue(1.5)).is_err());
+    }
+
+    #[test]
+    fn signed_range() {
+        let signal = serializer(SignalType::SignedInt { size: 8 }, 8);
+        assert_eq!(
+            signal.serialize("value", &Value::SignedValue(-128)),
+            Ok(0x8000)
+        );
+        assert_eq!(
+            signal.serialize("value", &Value::SignedValue(-1)),
+            Ok(0xFF00)
+        );
+        assert_eq!(
+            signal.serialize("value", &Value::UnsignedValue(127)),
+            Ok(0x7F00)
+        );
+        assert!(signal.serialize("value", &Value::SignedValue(128)).is_err());
+        assert!(signal
+            .serialize("value", &Value::SignedValue(-129))
+            .is_err());
+    }
+
+    #[test]
+    fn decimal_range() {
+        // [-1, 24.5] in steps of 0.1.
+        let signal = serializer(
+            SignalType::Decimal {
+                size: 8,
+                offset: -1.0,
+                scale: 0.1,
+            },
+            0,
+        );
+        assert!(signal.serialize("value", &Value::RealValue(-1.0)).is_ok());
+        assert!(signal.serialize("value", &Value::RealValue(24.5)).is_ok());
+        assert!(signal.serialize("value", &Value::RealValue(-1.1)).is_err());
+        assert!(signal.serialize("value", &Value::RealValue(24.6)).is_err());
+        assert!(signal
+            .serialize("value", &Value::RealValue(f64::NAN))
+            .is_err());
+        assert!(signal
+            .serialize("value", &Value::EnumValue("one".to_owned()))
+            .is_err());
+    }
+
+    #[test]
+    fn decimal_scaling_and_rounding() {
+        let signal = serializer(
+            SignalType::Decimal {
+                size: 8,
+                offset: -1.0,
+                scale: 0.1,
+            },
+            4,
+        );
+        assert_eq!(
+            signal.serialize("value", &Value::RealValue(0.0)),
+            Ok(10 << 4)
+        );
+        assert_eq!(
+            signal.serialize("value", &Value::RealValue(0.04)),
+            Ok(10 << 4)
+        );
+        assert_eq!(
+            signal.serialize("value", &Value::RealValue(0.06)),
+            Ok(11 << 4)
+        );
+        // integers are physical values as well.
+        assert_eq!(
+            signal.serialize("value", &Value::UnsignedValue(2)),
+            Ok(30 << 4)
+        );
+        assert_eq!(signal.serialize("value", &Value::SignedValue(-1)), Ok(0));
+    }
+}
@@ -0,0 +1,362 @@
+use std::{
+    sync::{Arc, Mutex, Weak},
+    time::Duration,
+};
+
+use canzero_appdata::{AlarmCondition, AlarmRule};
+use canzero_config::config::Type;
+use color_print::cprintln;
+
+use super::{
+    error_observable::{ErrorLevel, ErrorLog, ErrorState, ErrorTaskMsg, Friend},
+    frame::Value,
+    network::{
+        object_entry_object::{
+            database::value::ObjectEntryValue, vlistener::ObjectEntryListener, ObjectEntryObject,
+        },
+        NetworkObject,
+    },
+};
+
+const STALE_CHECK_INTERVAL: Duration = Duration::from_millis(100);
+
+struct AlarmEval {
+    active: bool,
+    previous: Option<(f64, Duration)>,
+    last_update: Duration,
+}
+
+impl AlarmEval {
+    fn new(last_update: Duration) -> Self {
+        Self {
+            active: false,
+            previous: None,
+            last_update,
+        }
+    }
+
+    /// Whether the alarm is active after the object entry received `value` at `timestamp`.
+    fn evaluate(&mut self, condition: &AlarmCondition, value: &Value, timestamp: Duration) -> bool {
+        self.last_update = timestamp;
+        let number = as_f64(value);
+        let active = match condition {
+            AlarmCondition::Above {
+                threshold,
+                hysteresis,
+            } => match number {
+                Some(number) if self.active => number >= threshold - hysteresis,
+                Some(number) => number > *threshold,
+                None => self.active,
+            },
+            AlarmCondition::Below {
+                threshold,
+                hysteresis,
+            } => match number {
+                Some(number) if self.active => number <= threshold + hysteresis,
+                Some(number) => number < *threshold,
+                None => self.active,
+            },
+            AlarmCondition::RateOfChange { max_rate } => {
+                let active = match (self.previous, number) {
+                    (Some((previous, previous_timestamp)), Some(number))
+                        if timestamp > previous_timestamp =>
+                    {
+                        let dt = (timestamp - previous_timestamp).as_secs_f64();
+                        ((number - previous) / dt).abs() > *max_rate
+                    }
+                    _ => self.active,
+                };
+                self.previous = number.map(|number| (number, timestamp));
+                active
+            }
+            AlarmCondition::Stale { timeout_ms: _ } => false,
+            AlarmCondition::EnumEquals { variant } => {
+                matches!(value, Value::EnumValue(v) if v == variant)
+            }
+        };
+        self.active = active;
+        active
+    }
+
+    /// Whether no value was received for longer than `timeout_ms` at `now`.
+    fn check_stale(&mut self, timeout_ms: u64, now: Duration) -> bool {
+        self.active = now.saturating_sub(self.last_update) > Duration::from_millis(timeout_ms);
+        self.active
+    }
+}
+
+/// Evaluates an `AlarmRule` on the values of its object entry and reports
+/// the result to the `ErrorState` of the rule.
+pub struct AlarmListener {
+    condition: AlarmCondition,
+    level: ErrorLevel,
+    state: Arc<ErrorState>,
+    eval: Mutex<AlarmEval>,
+}
+
+impl AlarmListener {
+    fn level(&self, active: bool) -> ErrorLevel {
+        if active {
+            self.level
+        } else {
+            ErrorLevel::OK
+        }
+    }
+
+    fn check_stale(&self, now: Duration) {
+        let AlarmCondition::Stale { timeout_ms } = self.condition else {
+            return;
+        };
+        let active = self.eval.lock().unwrap().check_stale(timeout_ms, now);
+        self.state.update(self.level(active), now);
+    }
+}
+
+impl ObjectEntryListener for AlarmListener {
+    fn notify(&self, value: &ObjectEntryValue) {
+        let active =
+            self.eval
+                .lock()
+                .unwrap()
+                .evaluate(&self.condition, &value.value, value.timestamp);
+        self.state.update(self.level(active), value.timestamp);
+    }
+}
+
+/// Registers a listener for every valid rule, invalid rules are skipped with a warning.
+pub async fn register_alarms(
+    alarms: &[AlarmRule],
+    network_object: &NetworkObject,
+    tx: &tokio::sync::mpsc::Sender<ErrorTaskMsg>,
+    log: &ErrorLog,
+) -> Vec<Arc<ErrorState>> {
+    let mut states = vec![];
+    let mut stale_listeners: Vec<(Weak<ObjectEntryObject>, Weak<AlarmListener>)> = vec![];
+    for rule in alarms {
+        let Some(object_entry) = network_object
+            .nodes()
+            .iter()
+            .find(|node| node.name() == rule.node)
+            .and_then(|node| {
+                node.object_entries()
+                    .iter()
+                    .find(|object_entry| object_entry.name() == rule.object_entry)
+            })
+        else {
+            cprintln!(
+                "<yellow>Ignoring alarm {} : unknown object entry {}::{}</yellow>",
+                rule.name,
+                rule.node,
+                rule.object_entry
+            );
+            continue;
+        };
+        if let Err(err) = validate(rule, object_entry) {
+            cprintln!("<yellow>Ignoring alarm {} : {err}</yellow>", rule.name);
+            continue;
+        }
+        let state = Arc::new(ErrorState::new(
+            rule.name.clone(),
+            Some(rule.description.clone().unwrap_or_else(|| describe(rule))),
+            Some(Friend::new(&rule.node, &rule.object_entry)),
+            tx.clone(),
+            log,
+        ));
+        let listener = Arc::new(AlarmListener {
+            condition: rule.condition.clone(),
+            level: ErrorLevel::from(rule.level),
+            state: state.clone(),
+            // entries, which never send a value, become stale after the timeout.
+            eval: Mutex::new(AlarmEval::new(object_entry.now())),
+        });
+        object_entry.vlisten(listener.clone()).await;
+        if let AlarmCondition::Stale { timeout_ms: _ } = rule.condition {
+            stale_listeners.push((Arc::downgrade(object_entry), Arc::downgrade(&listener)));
+        }
+        states.push(state);
+    }
+
+    if !stale_listeners.is_empty() {
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(STALE_CHECK_INTERVAL);
+            loop {
+                interval.tick().await;
+                let mut alive = false;
+                for (object_entry, listener) in &stale_listeners {
+                    if let (Some(object_entry), Some(listener)) =
+                        (object_entry.upgrade(), listener.upgrade())
+                    {
+                        listener.check_stale(object_entry.now());
+                        alive = true;
+                    }
+                }
+                // the network was dropped.
+                if !alive {
+                    break;
+                }
+            }
+        });
+    }
+    states
+}
+
+fn validate(rule: &AlarmRule, object_entry: &ObjectEntryObject) -> Result<(), String> {
+    match (&rule.condition, object_entry.ty() as &Type) {
+        (AlarmCondition::Stale { timeout_ms: _ }, _) => Ok(()),
+        (AlarmCondition::EnumEquals { variant }, Type::Enum { entries, .. }) => {
+            if entries.iter().any(|(name, _)| name == variant) {
+                Ok(())
+            } else {
+                Err(format!(
+                    "{variant} is not a variant of {}",
+                    rule.object_entry
+                ))
+            }
+        }
+        (AlarmCondition::EnumEquals { variant: _ }, _) => {
+            Err(format!("{} is not an enum", rule.object_entry))
+        }
+        (_, Type::Primitive(_)) => Ok(()),
+        _ => Err(format!("{} is not a numeric value", rule.object_entry)),
+    }
+}
+
+fn describe(rule: &AlarmRule) -> String {
+    let name = format!("{}::{}", rule.node, rule.object_entry);
+    match &rule.condition {
+        AlarmCondition::Above { threshold, .. } => format!("{name} above {threshold}"),
+        AlarmCondition::Below { threshold, .. } => format!("{name} below {threshold}"),
+        AlarmCondition::RateOfChange { max_rate } => {
+            format!("{name} changes faster than {max_rate}/s")
+        }
+        AlarmCondition::Stale { timeout_ms } => {
+            format!("{name} not received for {timeout_ms}ms")
+        }
+        AlarmCondition::EnumEquals { variant } => format!("{name} is {variant}"),
+    }
+}
+
+fn as_f64(value: &Value) -> Option<f64> {
+    match value {
+        Value::UnsignedValue(value) => Some(*value as f64),
+        Value::SignedValue(value) => Some(*value as f64),
+        Value::RealValue(value) => Some(*value),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ms(ms: u64) -> Duration {
+        Duration::from_millis(ms)
+    }
+
+    /// Evaluates the values, which are received 100ms apart.
+    fn evaluate(condition: AlarmCondition, values: &[f64]) -> Vec<bool> {
+        let mut eval = AlarmEval::new(Duration::ZERO);
+        values
+            .iter()
+            .enumerate()
+            .map(|(i, value)| {
+                eval.evaluate(&condition, &Value::RealValue(*value), ms(100 * i as u64))
+            })
+            .collect()
+    }
+
+    #[test]
+    fn above_clears_below_the_hysteresis() {
+        let condition = AlarmCondition::Above {
+            threshold: 50.0,
+            hysteresis: 2.0,
+        };
+        assert_eq!(
+            evaluate(condition, &[49.0, 50.0, 51.0, 49.0, 48.0, 47.9, 49.0, 50.1]),
+            vec![false, false, true, true, true, false, false, true]
+        );
+    }
+
+    #[test]
+    fn below_clears_above_the_hysteresis() {
+        let condition = AlarmCondition::Below {
+            threshold: 10.0,
+            hysteresis: 1.0,
+        };
+        assert_eq!(
+            evaluate(condition, &[11.0, 9.0, 10.5, 11.0, 11.1, 10.0]),
+            vec![false, true, true, true, false, false]
+        );
+    }
+
+    #[test]
+    fn without_hysteresis_the_threshold_clears() {
+        let condition = AlarmCondition::Above {
+            threshold: 50.0,
+            hysteresis: 0.0,
+        };
+        assert_eq!(
+            evaluate(condition, &[51.0, 50.0, 49.9]),
+            vec![true, true, false]
+        );
+    }
+
+    #[test]
+    fn rate_of_change_per_second() {
+        // 100ms apart, a step of 1.0 is a rate of 10/s.
+        let condition = AlarmCondition::RateOfChange { max_rate: 10.0 };
+        assert_eq!(
+            evaluate(condition, &[0.0, 1.0, 2.5, 2.0, 2.0]),
+            vec![false, false, true, false, false]
+        );
+    }
+
+    #[test]
+    fn rate_of_change_keeps_the_state_for_equal_timestamps() {
+        let condition = AlarmCondition::RateOfChange { max_rate: 1.0 };
+        let mut eval = AlarmEval::new(Duration::ZERO);
+        assert!(!eval.evaluate(&condition, &Value::RealValue(0.0), ms(0)));
+        assert!(eval.evaluate(&condition, &Value::RealValue(10.0), ms(100)));
+        assert!(eval.evaluate(&condition, &Value::RealValue(10.0), ms(100)));
+        assert!(!eval.evaluate(&condition, &Value::RealValue(10.0), ms(200)));
+    }
+
+    #[test]
+    fn non_numeric_values_keep_the_state() {
+        let condition = AlarmCondition::Above {
+            threshold: 0.0,
+            hysteresis: 0.0,
+        };
+        let mut eval = AlarmEval::new(Duration::ZERO);
+        assert!(eval.evaluate(&condition, &Value::UnsignedValue(1), ms(0)));
+        let variant = Value::EnumValue("OK".to_owned());
+        assert!(eval.evaluate(&condition, &variant, ms(100)));
+    }
+
+    #[test]
+    fn enum_equals_the_variant() {
+        let condition = AlarmCondition::EnumEquals {
+            variant: "ERROR".to_owned(),
+        };
+        let mut eval = AlarmEval::new(Duration::ZERO);
+        let error = Value::EnumValue("ERROR".to_owned());
+        let ok = Value::EnumValue("OK".to_owned());
+        assert!(eval.evaluate(&condition, &error, ms(0)));
+        assert!(!eval.evaluate(&condition, &ok, ms(100)));
+    }
+
+    #[test]
+    fn stale_after_the_timeout() {
+        let condition = AlarmCondition::Stale { timeout_ms: 500 };
+        let mut eval = AlarmEval::new(ms(1000));
+        assert!(!eval.check_stale(500, ms(1500)));
+        assert!(eval.check_stale(500, ms(1501)));
+
+        // a value clears the alarm until the next timeout.
+        assert!(!eval.evaluate(&condition, &Value::UnsignedValue(0), ms(2000)));
+        assert!(!eval.check_stale(500, ms(2400)));
+        assert!(eval.check_stale(500, ms(2600)));
+        // values from before the start are not stale.
+        assert!(!AlarmEval::new(ms(1000)).check_stale(500, ms(0)));
+    }
+}
@@ -0,0 +1,138 @@
+use std::{
+    sync::Mutex,
+    time::{Duration, Instant},
+};
+
+use canzero_common::CanFrame;
+
+const WINDOW: Duration = Duration::from_millis(100);
+
+struct Window {
+    start: Instant,
+    bits: u64,
+    load: f64,
+}
+
+/// Estimates the load of a bus from the frames passing the adapter, the load
+/// is the fraction of the baudrate used in the last window of 100ms.
+pub struct BusLoadMeter {
+    baudrate: u32,
+    window: Mutex<Window>,
+}
+
+impl BusLoadMeter {
+    pub fn new(baudrate: u32) -> Self {
+        Self {
+            baudrate,
+            window: Mutex::new(Window {
+                start: Instant::now(),
+                bits: 0,
+                load: 0.0,
+            }),
+        }
+    }
+
+    pub fn record(&self, frame: &CanFrame) {
+        self.record_at(frame, Instant::now());
+    }
+
+    pub fn load(&self) -> f64 {
+        self.load_at(Instant::now())
+    }
+
+    fn record_at(&self, frame: &CanFrame, now: Instant) {
+        let mut window = self.window.lock().unwrap();
+        self.rotate(&mut window, now);
+        window.bits += frame_bits(frame);
+    }
+
+    fn load_at(&self, now: Instant) -> f64 {
+        let mut window = self.window.lock().unwrap();
+        self.rotate(&mut window, now);
+        window.load
+    }
+
+    fn rotate(&self, window: &mut Window, now: Instant) {
+        let elapsed = now.saturating_duration_since(window.start);
+        if elapsed < WINDOW || self.baudrate == 0 {
+            return;
+        }
+        window.load = if elapsed < WINDOW * 2 {
+            (window.bits as f64 / (self.baudrate as f64 * elapsed.as_secs_f64())).min(1.0)
+        } else {
+            // nothing was recorded in the last window.
+            0.0
+        };
+        window.start = now;
+        window.bits = 0;
+    }
+}
+
+/// Bits on the wire including the worst case of stuff bits.
+fn frame_bits(frame: &CanFrame) -> u64 {
+    let overhead = if frame.get_ide_flag() { 67 } else { 47 };
+    let bits = overhead + 8 * frame.get_dlc().min(8) as u64;
+    bits + bits / 5
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // 47 + 64 bits and 22 stuff bits.
+    const FRAME_BITS: u64 = 133;
+
+    fn frame() -> CanFrame {
+        CanFrame::new(0x10, false, false, 8, 0)
+    }
+
+    fn start(meter: &BusLoadMeter) -> Instant {
+        meter.window.lock().unwrap().start
+    }
+
+    #[test]
+    fn frame_bits_include_stuff_bits() {
+        assert_eq!(frame_bits(&frame()), FRAME_BITS);
+        assert_eq!(frame_bits(&CanFrame::new(0x10, true, false, 0, 0)), 80);
+        // dlc above 8 carries 8 bytes.
+        assert_eq!(frame_bits(&CanFrame::new(0x10, false, false, 15, 0)), FRAME_BITS);
+    }
+
+    #[test]
+    fn load_of_the_last_window() {
+        let meter = BusLoadMeter::new(125_000);
+        let t0 = start(&meter);
+        for _ in 0..10 {
+            meter.record_at(&frame(), t0 + Duration::from_millis(50));
+        }
+        // the window is still open.
+        assert_eq!(meter.load_at(t0 + Duration::from_millis(99)), 0.0);
+        let load = meter.load_at(t0 + WINDOW);
+        assert!((load - 10.0 * FRAME_BITS as f64 / 12_500.0).abs() < 1e-9);
+        // the load is kept until the next window ends.
+        assert_eq!(meter.load_at(t0 + Duration::from_millis(150)), load);
+        assert_eq!(meter.load_at(t0 + WINDOW * 2), 0.0);
+    }
+
+    #[test]
+    fn idle_bus_has_no_load() {
+        let meter = BusLoadMeter::new(125_000);
+        let t0 = start(&meter);
+        meter.record_at(&frame(), t0);
+        assert_eq!(meter.load_at(t0 + WINDOW * 3), 0.0);
+    }
+
+    #[test]
+    fn load_saturates() {
+        let meter = BusLoadMeter::new(1_000);
+        let t0 = start(&meter);
+        for _ in 0..10 {
+            meter.record_at(&frame(), t0);
+        }
+        assert_eq!(meter.load_at(t0 + WINDOW), 1.0);
+
+        let meter = BusLoadMeter::new(0);
+        meter.record_at(&frame(), t0);
+        assert_eq!(meter.load_at(t0 + WINDOW), 0.0);
+    }
+}
@@ -1,23 +1,36 @@
+use std::{collections::HashMap, sync::Arc, time::Instant};
+
 use canzero_config::config::{bus::BusRef, NetworkRef};
 use canzero_udp::frame::NetworkDescription;
 
-use self::tcp::TcpCanAdapter;
-use canzero_common::{CanFrame, TCanFrame, TCanError};
+use self::{
+    bus_load::BusLoadMeter,
+    replay::{Replay, ReplayCanAdapter},
+    tcp::TcpCanAdapter,
+    virtual_can::{VirtualCanAdapter, VirtualCanNetwork},
+};
+use canzero_common::{CanFrame, TCanFrame, TCanError, TNetworkFrame};
 
+mod bus_load;
 #[cfg(feature = "socket-can")]
 mod socket_can;
 
+pub mod replay;
 mod tcp;
+pub mod virtual_can;
 
 pub struct CanAdapter {
     bus: BusRef,
     imp: CanAdapterImpl,
+    load: BusLoadMeter,
 }
 
 pub enum CanAdapterImpl {
     TcpCanAdapter(TcpCanAdapter),
     #[cfg(feature = "socket-can")]
     SocketCanAdapter(socket_can::SocketCanAdapter),
+    ReplayCanAdapter(ReplayCanAdapter),
+    Virtual(VirtualCanAdapter),
 }
 
 impl CanAdapter {
@@ -54,6 +67,7 @@ impl CanAdapter {
         {
             adapters.push(Self {
                 bus: bus.clone(),
+                load: BusLoadMeter::new(bus.baudrate()),
                 imp: CanAdapterImpl::TcpCanAdapter(TcpCanAdapter::create(
                     tcp_client.clone(),
                     bus.id(),
@@ -76,6 +90,7 @@ impl CanAdapter {
         for bus in network_config.buses() {
             adapters.push(Self {
                 bus: bus.clone(),
+                load: BusLoadMeter::new(bus.baudrate()),
                 imp: CanAdapterImpl::SocketCanAdapter(socket_can::SocketCanAdapter::create(
                     bus,
                     start_of_run,
@@ -86,23 +101,89 @@ impl CanAdapter {
         Ok(adapters)
     }
 
-    pub async fn receive(&self) -> std::io::Result<Result<TCanFrame, TCanError>> {
+    /// Adapters attached to a in memory network, which doesn't require a server or
+    /// a vcan interface.
+    pub fn create_virtual_adapters(
+        network_config: &NetworkRef,
+        network: &Arc<VirtualCanNetwork>,
+    ) -> Vec<Self> {
+        network_config
+            .buses()
+            .iter()
+            .map(|bus| Self {
+                bus: bus.clone(),
+                load: BusLoadMeter::new(bus.baudrate()),
+                imp: CanAdapterImpl::Virtual(VirtualCanAdapter::create(network, bus.id())),
+            })
+            .collect()
+    }
+
+    /// Adapters, which replay a recorded log instead of connecting to a network.
+    pub fn create_replay_adapters(
+        network_config: &NetworkRef,
+        app_handle: &tauri::AppHandle,
+        frames: Vec<TNetworkFrame>,
+        timebase: Instant,
+    ) -> Vec<Self> {
+        let mut bus_channels = HashMap::new();
+        let mut receivers = vec![];
+        for bus in network_config.buses() {
+            let (tx, rx) = tokio::sync::mpsc::channel(16);
+            bus_channels.insert(bus.id(), tx);
+            receivers.push((bus, rx));
+        }
+        let replay = Replay::create(frames, bus_channels, timebase, app_handle);
+        receivers
+            .into_iter()
+            .map(|(bus, rx)| Self {
+                bus: bus.clone(),
+                load: BusLoadMeter::new(bus.baudrate()),
+                imp: CanAdapterImpl::ReplayCanAdapter(ReplayCanAdapter::create(&replay, rx)),
+            })
+            .collect()
+    }
+
+    pub fn replay(&self) -> Option<&Arc<Replay>> {
         match &self.imp {
+            CanAdapterImpl::ReplayCanAdapter(adapter) => Some(adapter.replay()),
+            _ => None,
+        }
+    }
+
+    pub async fn receive(&self) -> std::io::Result<Result<TCanFrame, TCanError>> {
+        let received = match &self.imp {
             CanAdapterImpl::TcpCanAdapter(adapter) => adapter.receive().await,
             #[cfg(feature = "socket-can")]
             CanAdapterImpl::SocketCanAdapter(adapter) => adapter.receive().await,
+            CanAdapterImpl::ReplayCanAdapter(adapter) => adapter.receive().await,
+            CanAdapterImpl::Virtual(adapter) => adapter.receive().await,
+        };
+        if let Ok(Ok(frame)) = &received {
+            self.load.record(frame);
         }
+        received
     }
 
     pub async fn send(&self, frame: CanFrame, loopback:  bool) -> std::io::Result<()> {
+        // loopback frames are recorded when they are received.
+        if !loopback {
+            self.load.record(&frame);
+        }
         match &self.imp {
             CanAdapterImpl::TcpCanAdapter(adapter) => adapter.send(frame, loopback).await,
             #[cfg(feature = "socket-can")]
             CanAdapterImpl::SocketCanAdapter(adapter) => adapter.send(frame).await,
+            CanAdapterImpl::ReplayCanAdapter(adapter) => adapter.send(frame).await,
+            CanAdapterImpl::Virtual(adapter) => adapter.send(frame, loopback).await,
         }
     }
 
     pub fn bus(&self) -> &BusRef {
         &self.bus
     }
+
+    /// Fraction of the baudrate, which was used in the last 100ms.
+    pub fn bus_load(&self) -> f64 {
+        self.load.load()
+    }
 }
@@ -0,0 +1,437 @@
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex, Weak},
+    time::{Duration, Instant},
+};
+
+use canzero_common::{CanFrame, TCanError, TCanFrame, TNetworkFrame, Timestamped};
+use serde::Serialize;
+use tauri::Manager;
+use tokio::sync::{mpsc, watch, Notify};
+
+const REPLAY_STATUS_EVENT_NAME: &str = "replay-status";
+
+#[derive(Serialize, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct ReplayStatus {
+    playing: bool,
+    speed: f64,
+    position_ms: u64,
+    duration_ms: u64,
+    frame_index: usize,
+    frame_count: usize,
+}
+
+/// The position of the playback in the log, every method takes the instant it happens at
+/// to keep the timing independent of the clock.
+struct ReplayState {
+    // index of the next frame.
+    position: usize,
+    playing: bool,
+    speed: f64,
+    // log time at the instant, where playback was (re)started.
+    anchor: (Instant, Duration),
+}
+
+impl ReplayState {
+    fn new(now: Instant) -> Self {
+        Self {
+            position: 0,
+            playing: false,
+            speed: 1.0,
+            anchor: (now, Duration::ZERO),
+        }
+    }
+
+    fn current_time(&self, frames: &[TNetworkFrame], now: Instant) -> Duration {
+        if self.playing {
+            let elapsed = now.saturating_duration_since(self.anchor.0);
+            (self.anchor.1 + elapsed.mul_f64(self.speed)).min(log_duration(frames))
+        } else {
+            self.anchor.1
+        }
+    }
+
+    /// Starts over, if the end of the log was reached.
+    fn play(&mut self, frames: &[TNetworkFrame], now: Instant) {
+        if self.position >= frames.len() {
+            self.seek(frames, Duration::ZERO, now);
+        }
+        self.anchor = (now, self.current_time(frames, now));
+        self.playing = true;
+    }
+
+    fn pause(&mut self, frames: &[TNetworkFrame], now: Instant) {
+        self.anchor = (now, self.current_time(frames, now));
+        self.playing = false;
+    }
+
+    fn seek(&mut self, frames: &[TNetworkFrame], time: Duration, now: Instant) {
+        let time = time.min(log_duration(frames));
+        self.position = frames.partition_point(|frame| frame.timestamp < time);
+        self.anchor = (now, time);
+    }
+
+    fn set_speed(
+        &mut self,
+        frames: &[TNetworkFrame],
+        speed: f64,
+        now: Instant,
+    ) -> Result<(), String> {
+        if !speed.is_finite() || speed <= 0.0 {
+            return Err(format!("invalid replay speed {speed}"));
+        }
+        self.anchor = (now, self.current_time(frames, now));
+        self.speed = speed;
+        Ok(())
+    }
+
+    /// The index of the next frame and the instant it is due at.
+    fn next_due(&self, frames: &[TNetworkFrame]) -> Option<(usize, Instant)> {
+        if self.playing && self.position < frames.len() {
+            let log_time = frames[self.position].timestamp;
+            let wait = log_time.saturating_sub(self.anchor.1).div_f64(self.speed);
+            Some((self.position, self.anchor.0 + wait))
+        } else {
+            None
+        }
+    }
+
+    /// Moves past the frame at `index`, returns None if the state changed since it was
+    /// due and whether it was the last frame otherwise.
+    fn advance(&mut self, frames: &[TNetworkFrame], index: usize, now: Instant) -> Option<bool> {
+        if !self.playing || self.position != index {
+            return None;
+        }
+        self.position += 1;
+        let finished = self.position == frames.len();
+        if finished {
+            self.anchor = (now, log_duration(frames));
+            self.playing = false;
+        }
+        Some(finished)
+    }
+}
+
+fn log_duration(frames: &[TNetworkFrame]) -> Duration {
+    frames
+        .last()
+        .map(|frame| frame.timestamp)
+        .unwrap_or_default()
+}
+
+/// Plays a recorded log to the `ReplayCanAdapter`s of all buses.
+///
+/// Log times are relative to the first frame of the log. Replayed frames are stamped
+/// with the time they are played at on the timebase of the CNL, so seeking backwards
+/// replays frames again, but the history of object entries stays ordered.
+pub struct Replay {
+    frames: Vec<TNetworkFrame>,
+    timebase: Instant,
+    state: Mutex<ReplayState>,
+    changed: Notify,
+    // true while the replay is paused, see `Replay::suspended`.
+    suspended: watch::Sender<bool>,
+}
+
+impl Replay {
+    pub fn create(
+        mut frames: Vec<TNetworkFrame>,
+        bus_channels: HashMap<u32, mpsc::Sender<TCanFrame>>,
+        timebase: Instant,
+        app_handle: &tauri::AppHandle,
+    ) -> Arc<Self> {
+        frames.sort_by_key(|frame| frame.timestamp);
+        let start = frames.first().map(|frame| frame.timestamp).unwrap_or_default();
+        for frame in &mut frames {
+            frame.timestamp -= start;
+        }
+        let replay = Arc::new(Self {
+            frames,
+            timebase,
+            state: Mutex::new(ReplayState::new(Instant::now())),
+            changed: Notify::new(),
+            suspended: watch::channel(true).0,
+        });
+        tokio::spawn(player_task(replay.clone(), bus_channels));
+        tokio::spawn(status_task(Arc::downgrade(&replay), app_handle.clone()));
+        replay
+    }
+
+    /// Heartbeats stop while the replay is paused and jump on a seek, so the heartbeat
+    /// watchdogs are suspended while this is true and restarted on every change.
+    pub fn suspended(&self) -> watch::Receiver<bool> {
+        self.suspended.subscribe()
+    }
+
+    fn notify_changed(&self, playing: bool) {
+        self.suspended.send_replace(!playing);
+        self.changed.notify_one();
+    }
+
+    pub fn duration(&self) -> Duration {
+        log_duration(&self.frames)
+    }
+
+    pub fn play(&self) {
+        let mut state = self.state.lock().unwrap();
+        state.play(&self.frames, Instant::now());
+        drop(state);
+        self.notify_changed(true);
+    }
+
+    pub fn pause(&self) {
+        let mut state = self.state.lock().unwrap();
+        state.pause(&self.frames, Instant::now());
+        drop(state);
+        self.notify_changed(false);
+    }
+
+    pub fn seek(&self, time: Duration) {
+        let mut state = self.state.lock().unwrap();
+        state.seek(&self.frames, time, Instant::now());
+        let playing = state.playing;
+        drop(state);
+        self.notify_changed(playing);
+    }
+
+    pub fn set_speed(&self, speed: f64) -> Result<(), String> {
+        let mut state = self.state.lock().unwrap();
+        state.set_speed(&self.frames, speed, Instant::now())?;
+        let playing = state.playing;
+        drop(state);
+        self.notify_changed(playing);
+        Ok(())
+    }
+
+    pub fn status(&self) -> ReplayStatus {
+        let state = self.state.lock().unwrap();
+        ReplayStatus {
+            playing: state.playing,
+            speed: state.speed,
+            position_ms: state.current_time(&self.frames, Instant::now()).as_millis() as u64,
+            duration_ms: self.duration().as_millis() as u64,
+            frame_index: state.position,
+            frame_count: self.frames.len(),
+        }
+    }
+}
+
+async fn player_task(replay: Arc<Replay>, bus_channels: HashMap<u32, mpsc::Sender<TCanFrame>>) {
+    // the receivers of all buses are dropped together with the CNL.
+    let Some(cnl_channel) = bus_channels.values().next().cloned() else {
+        return;
+    };
+    loop {
+        let next = replay.state.lock().unwrap().next_due(&replay.frames);
+        let Some((index, due)) = next else {
+            tokio::select! {
+                _ = replay.changed.notified() => continue,
+                _ = cnl_channel.closed() => break,
+            }
+        };
+        tokio::select! {
+            _ = tokio::time::sleep_until(due.into()) => (),
+            // the state changed, recompute the next frame.
+            _ = replay.changed.notified() => continue,
+            _ = cnl_channel.closed() => break,
+        }
+        let (bus_id, frame, finished) = {
+            let mut state = replay.state.lock().unwrap();
+            let Some(finished) = state.advance(&replay.frames, index, Instant::now()) else {
+                continue;
+            };
+            let frame = &replay.frames[index];
+            (
+                frame.bus_id,
+                Timestamped::new(
+                    due.saturating_duration_since(replay.timebase),
+                    frame.can_frame.clone(),
+                ),
+                finished,
+            )
+        };
+        if finished {
+            replay.notify_changed(false);
+        }
+        if let Some(tx) = bus_channels.get(&bus_id) {
+            if tx.send(frame).await.is_err() {
+                // the CNL was dropped.
+                break;
+            }
+        }
+    }
+}
+
+async fn status_task(replay: Weak<Replay>, app_handle: tauri::AppHandle) {
+    let mut interval = tokio::time::interval(Duration::from_millis(250));
+    let mut prev: Option<ReplayStatus> = None;
+    loop {
+        interval.tick().await;
+        let Some(replay) = replay.upgrade() else {
+            break;
+        };
+        let status = replay.status();
+        if prev.as_ref() != Some(&status) {
+            let _ = app_handle.emit_all(REPLAY_STATUS_EVENT_NAME, status.clone());
+            prev = Some(status);
+        }
+    }
+}
+
+pub struct ReplayCanAdapter {
+    replay: Arc<Replay>,
+    rx: tokio::sync::Mutex<mpsc::Receiver<TCanFrame>>,
+}
+
+impl ReplayCanAdapter {
+    pub fn create(replay: &Arc<Replay>, rx: mpsc::Receiver<TCanFrame>) -> Self {
+        Self {
+            replay: replay.clone(),
+            rx: tokio::sync::Mutex::new(rx),
+        }
+    }
+
+    pub fn replay(&self) -> &Arc<Replay> {
+        &self.replay
+    }
+
+    pub async fn receive(&self) -> std::io::Result<Result<TCanFrame, TCanError>> {
+        match self.rx.lock().await.recv().await {
+            Some(frame) => Ok(Ok(frame)),
+            None => Err(std::io::Error::new(
+                std::io::ErrorKind::ConnectionAborted,
+                "Replay stopped".to_owned(),
+            )),
+        }
+    }
+
+    /// There is no network during a replay.
+    pub async fn send(&self, _frame: CanFrame) -> std::io::Result<()> {
+        Err(std::io::Error::new(
+            std::io::ErrorKind::Unsupported,
+            "Frames can not be sent during a replay".to_owned(),
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use canzero_common::NetworkFrame;
+
+    use super::*;
+
+    fn frames(times_ms: &[u64]) -> Vec<TNetworkFrame> {
+        times_ms
+            .iter()
+            .map(|time| {
+                Timestamped::new(
+                    Duration::from_millis(*time),
+                    NetworkFrame {
+                        bus_id: 0,
+                        can_frame: CanFrame::new(0x10, false, false, 0, 0),
+                    },
+                )
+            })
+            .collect()
+    }
+
+    fn ms(millis: u64) -> Duration {
+        Duration::from_millis(millis)
+    }
+
+    #[test]
+    fn frames_are_due_at_their_log_time() {
+        let frames = frames(&[0, 100, 200]);
+        let t0 = Instant::now();
+        let mut state = ReplayState::new(t0);
+        assert_eq!(state.next_due(&frames), None);
+
+        state.play(&frames, t0);
+        assert_eq!(state.next_due(&frames), Some((0, t0)));
+        assert_eq!(state.advance(&frames, 0, t0), Some(false));
+        assert_eq!(state.next_due(&frames), Some((1, t0 + ms(100))));
+        assert_eq!(state.current_time(&frames, t0 + ms(50)), ms(50));
+
+        state.pause(&frames, t0 + ms(50));
+        assert_eq!(state.next_due(&frames), None);
+        assert_eq!(state.current_time(&frames, t0 + ms(500)), ms(50));
+        // the frame, which was due before the pause, is not played.
+        assert_eq!(state.advance(&frames, 1, t0 + ms(100)), None);
+
+        state.play(&frames, t0 + ms(500));
+        assert_eq!(state.next_due(&frames), Some((1, t0 + ms(550))));
+    }
+
+    #[test]
+    fn seeking_backwards_replays_frames() {
+        let frames = frames(&[0, 100, 200, 300]);
+        let t0 = Instant::now();
+        let mut state = ReplayState::new(t0);
+        state.play(&frames, t0);
+        for (index, time) in [(0, 0), (1, 100), (2, 200)] {
+            assert_eq!(state.advance(&frames, index, t0 + ms(time)), Some(false));
+        }
+
+        state.seek(&frames, ms(50), t0 + ms(250));
+        assert!(state.playing);
+        assert_eq!(state.current_time(&frames, t0 + ms(250)), ms(50));
+        assert_eq!(state.next_due(&frames), Some((1, t0 + ms(300))));
+        // the frame, which was due before the seek, is not played.
+        assert_eq!(state.advance(&frames, 3, t0 + ms(300)), None);
+
+        // frames at the time of the seek are played again.
+        state.seek(&frames, ms(100), t0 + ms(300));
+        assert_eq!(state.next_due(&frames), Some((1, t0 + ms(300))));
+
+        // seeking past the end stops at the last frame.
+        state.seek(&frames, ms(1000), t0 + ms(300));
+        assert_eq!(state.current_time(&frames, t0 + ms(300)), ms(300));
+        assert_eq!(state.next_due(&frames), Some((3, t0 + ms(300))));
+    }
+
+    #[test]
+    fn changing_speed_mid_playback() {
+        let frames = frames(&[0, 300]);
+        let t0 = Instant::now();
+        let mut state = ReplayState::new(t0);
+        state.play(&frames, t0);
+        assert_eq!(state.advance(&frames, 0, t0), Some(false));
+
+        state.set_speed(&frames, 2.0, t0 + ms(100)).unwrap();
+        assert_eq!(state.current_time(&frames, t0 + ms(100)), ms(100));
+        assert_eq!(state.current_time(&frames, t0 + ms(150)), ms(200));
+        assert_eq!(state.next_due(&frames), Some((1, t0 + ms(200))));
+
+        state.set_speed(&frames, 0.5, t0 + ms(150)).unwrap();
+        assert_eq!(state.current_time(&frames, t0 + ms(250)), ms(250));
+        assert_eq!(state.next_due(&frames), Some((1, t0 + ms(350))));
+
+        assert!(state.set_speed(&frames, 0.0, t0 + ms(250)).is_err());
+        assert!(state.set_speed(&frames, f64::NAN, t0 + ms(250)).is_err());
+        assert_eq!(state.speed, 0.5);
+    }
+
+    #[test]
+    fn playback_stops_at_the_end_of_the_log() {
+        let frames = frames(&[0, 100]);
+        let t0 = Instant::now();
+        let mut state = ReplayState::new(t0);
+        state.play(&frames, t0);
+        assert_eq!(state.advance(&frames, 0, t0), Some(false));
+        // the time doesn't run past the last frame.
+        assert_eq!(state.current_time(&frames, t0 + ms(90)), ms(90));
+        assert_eq!(state.current_time(&frames, t0 + ms(150)), ms(100));
+
+        assert_eq!(state.advance(&frames, 1, t0 + ms(150)), Some(true));
+        assert!(!state.playing);
+        assert_eq!(state.next_due(&frames), None);
+        assert_eq!(state.current_time(&frames, t0 + ms(500)), ms(100));
+
+        // playing again starts over.
+        state.play(&frames, t0 + ms(500));
+        assert_eq!(state.position, 0);
+        assert_eq!(state.current_time(&frames, t0 + ms(500)), Duration::ZERO);
+        assert_eq!(state.next_due(&frames), Some((0, t0 + ms(500))));
+    }
+}
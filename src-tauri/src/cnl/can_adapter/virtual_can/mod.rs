@@ -0,0 +1,254 @@
+use std::{
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc, OnceLock,
+    },
+    time::Instant,
+};
+
+use canzero_common::{CanFrame, TCanError, TCanFrame};
+use color_print::cprintln;
+use tokio::sync::broadcast;
+
+const VIRTUAL_BUS_CAPACITY: usize = 1024;
+
+#[derive(Clone)]
+struct VirtualFrame {
+    source: usize,
+    loopback: bool,
+    bus_id: u32,
+    frame: TCanFrame,
+}
+
+/// In memory CAN network, every frame sent by a adapter is received by all other
+/// adapters attached to the same bus id.
+pub struct VirtualCanNetwork {
+    tx: broadcast::Sender<VirtualFrame>,
+    next_adapter_id: AtomicUsize,
+    timebase: Instant,
+}
+
+impl VirtualCanNetwork {
+    pub fn new(timebase: Instant) -> Arc<Self> {
+        let (tx, _) = broadcast::channel(VIRTUAL_BUS_CAPACITY);
+        Arc::new(Self {
+            tx,
+            next_adapter_id: AtomicUsize::new(0),
+            timebase,
+        })
+    }
+
+    /// The network shared by everything in this process.
+    pub fn global() -> &'static Arc<Self> {
+        static GLOBAL: OnceLock<Arc<VirtualCanNetwork>> = OnceLock::new();
+        GLOBAL.get_or_init(|| VirtualCanNetwork::new(Instant::now()))
+    }
+
+    pub fn timebase(&self) -> Instant {
+        self.timebase
+    }
+}
+
+pub struct VirtualCanAdapter {
+    network: Arc<VirtualCanNetwork>,
+    id: usize,
+    bus_id: u32,
+    rx: tokio::sync::Mutex<broadcast::Receiver<VirtualFrame>>,
+}
+
+impl VirtualCanAdapter {
+    pub fn create(network: &Arc<VirtualCanNetwork>, bus_id: u32) -> Self {
+        Self {
+            network: network.clone(),
+            id: network.next_adapter_id.fetch_add(1, Ordering::SeqCst),
+            bus_id,
+            rx: tokio::sync::Mutex::new(network.tx.subscribe()),
+        }
+    }
+
+    pub async fn receive(&self) -> std::io::Result<Result<TCanFrame, TCanError>> {
+        let mut rx = self.rx.lock().await;
+        loop {
+            match rx.recv().await {
+                Ok(virtual_frame) => {
+                    if virtual_frame.bus_id != self.bus_id {
+                        continue;
+                    }
+                    if virtual_frame.source == self.id && !virtual_frame.loopback {
+                        continue;
+                    }
+                    return Ok(Ok(virtual_frame.frame));
+                }
+                Err(broadcast::error::RecvError::Lagged(count)) => {
+                    cprintln!("<yellow>Virtual CAN adapter dropped {count} frames</yellow>");
+                }
+                Err(broadcast::error::RecvError::Closed) => {
+                    return Err(std::io::Error::new(
+                        std::io::ErrorKind::ConnectionAborted,
+                        "Virtual CAN network closed".to_owned(),
+                    ))
+                }
+            }
+        }
+    }
+
+    /// Like the tcp adapter, the frame is only received by the sender itself if
+    /// `loopback` is set.
+    pub async fn send(&self, frame: CanFrame, loopback: bool) -> std::io::Result<()> {
+        // sending without any receivers is not a error on a real bus either.
+        let _ = self.network.tx.send(VirtualFrame {
+            source: self.id,
+            loopback,
+            bus_id: self.bus_id,
+            frame: TCanFrame::now(self.network.timebase, frame),
+        });
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use canzero_config::config;
+    use canzero_yaml::parse_yaml_config_from_file;
+    use serde_json::json;
+
+    use super::*;
+    use crate::cnl::{
+        can_adapter::CanAdapter,
+        deserialize::FrameDeserializer,
+        frame::Value,
+        serialize::{attributes_from_json, FrameSerializer},
+        tx::get_request_frame,
+    };
+
+    async fn receive_id(adapter: &VirtualCanAdapter) -> Option<u32> {
+        match tokio::time::timeout(Duration::from_millis(50), adapter.receive()).await {
+            Ok(frame) => Some(frame.unwrap().unwrap().get_id()),
+            Err(_) => None,
+        }
+    }
+
+    fn adapter_of<'a>(adapters: &'a [CanAdapter], message: &config::MessageRef) -> &'a CanAdapter {
+        adapters
+            .iter()
+            .find(|adapter| adapter.bus().id() == message.bus().id())
+            .unwrap()
+    }
+
+    async fn receive_message(adapter: &CanAdapter, message: &config::MessageRef) -> TCanFrame {
+        loop {
+            let frame = tokio::time::timeout(Duration::from_millis(50), adapter.receive())
+                .await
+                .expect("frame was not received")
+                .unwrap()
+                .unwrap();
+            if frame.get_id() == message.id().as_u32() {
+                return frame;
+            }
+        }
+    }
+
+    fn header_field(frame: &crate::cnl::frame::Frame, name: &str) -> u64 {
+        match frame
+            .attribute("header")
+            .and_then(|header| header.attribute(name))
+        {
+            Some(Value::UnsignedValue(value)) => *value,
+            _ => panic!("{} header without {name}", frame.name()),
+        }
+    }
+
+    #[tokio::test]
+    async fn frames_are_broadcast_to_other_adapters_of_the_bus() {
+        let network = VirtualCanNetwork::new(Instant::now());
+        let a = VirtualCanAdapter::create(&network, 0);
+        let b = VirtualCanAdapter::create(&network, 0);
+        let c = VirtualCanAdapter::create(&network, 1);
+
+        a.send(CanFrame::new(0x10, false, false, 0, 0), false)
+            .await
+            .unwrap();
+        assert_eq!(receive_id(&b).await, Some(0x10));
+        assert_eq!(receive_id(&a).await, None);
+        assert_eq!(receive_id(&c).await, None);
+
+        a.send(CanFrame::new(0x11, false, false, 0, 0), true)
+            .await
+            .unwrap();
+        assert_eq!(receive_id(&a).await, Some(0x11));
+        assert_eq!(receive_id(&b).await, Some(0x11));
+    }
+
+    #[tokio::test]
+    async fn get_request_is_answered_over_a_second_adapter() {
+        let network_config =
+            parse_yaml_config_from_file("lib/codegen/examples/conformance.yaml").unwrap();
+        let network = VirtualCanNetwork::new(Instant::now());
+        let panel = CanAdapter::create_virtual_adapters(&network_config, &network);
+        let node = CanAdapter::create_virtual_adapters(&network_config, &network);
+
+        let get_req = network_config.get_req_message();
+        let get_resp = network_config.get_resp_message();
+        let server = &network_config.nodes()[0];
+        let object_entry = &server.object_entries()[0];
+        let client_id = network_config.nodes().len() as u64;
+
+        adapter_of(&panel, get_req)
+            .send(
+                get_request_frame(&network_config, server.id() as u8, object_entry.id() as u16),
+                false,
+            )
+            .await
+            .unwrap();
+
+        let request = receive_message(adapter_of(&node, get_req), get_req).await;
+        let request = FrameDeserializer::new(get_req).deserialize(request.get_data_u64());
+        assert_eq!(header_field(&request, "od_index"), object_entry.id() as u64);
+        assert_eq!(header_field(&request, "server_id"), server.id() as u64);
+        assert_eq!(header_field(&request, "client_id"), client_id);
+
+        let attributes = attributes_from_json(&json!({
+            "header" : {
+                "sof" : 1,
+                "eof" : 1,
+                "toggle" : 0,
+                "od_index" : header_field(&request, "od_index"),
+                "client_id" : header_field(&request, "client_id"),
+                "server_id" : header_field(&request, "server_id"),
+            },
+            "data" : 0x2A,
+        }))
+        .unwrap();
+        let data = FrameSerializer::new(get_resp)
+            .and_then(|serializer| serializer.serialize(&attributes))
+            .unwrap();
+        adapter_of(&node, get_resp)
+            .send(
+                CanFrame::new(
+                    get_resp.id().as_u32(),
+                    get_resp.id().ide(),
+                    false,
+                    get_resp.dlc(),
+                    data,
+                ),
+                false,
+            )
+            .await
+            .unwrap();
+
+        let response = receive_message(adapter_of(&panel, get_resp), get_resp).await;
+        let response = FrameDeserializer::new(get_resp).deserialize(response.get_data_u64());
+        assert_eq!(
+            header_field(&response, "od_index"),
+            object_entry.id() as u64
+        );
+        assert_eq!(header_field(&response, "server_id"), server.id() as u64);
+        assert_eq!(header_field(&response, "client_id"), client_id);
+        assert!(matches!(
+            response.attribute("data"),
+            Some(Value::UnsignedValue(0x2A))
+        ));
+    }
+}
@@ -0,0 +1,594 @@
+use std::{
+    collections::{BTreeMap, VecDeque},
+    path::{Path, PathBuf},
+    sync::{Arc, Mutex, Weak},
+    time::{Duration, Instant},
+};
+
+use canzero_common::{CanFrame, TCanError, TCanFrame};
+use canzero_config::config::{self, Type};
+use serde::Serialize;
+use tauri::Manager;
+
+use crate::notification::notify_error;
+
+use self::trigger::{TriggerDefinition, TriggerListener};
+
+use super::{
+    frame::{Attribute, Frame, Value},
+    network::{
+        object_entry_object::{
+            database::value::ObjectEntryValue, vlistener::ObjectEntryListener, ObjectEntryObject,
+        },
+        NetworkObject,
+    },
+};
+
+pub mod trigger;
+
+const CAPTURE_TRIGGER_EVENT: &str = "capture-trigger";
+
+// samples are kept a bit longer than the largest window, the post trigger
+// window is only collected after the timer of the capture task elapsed.
+const RETENTION_MARGIN: Duration = Duration::from_secs(1);
+// upper bound for the ring buffer, a busy network with long windows could
+// otherwise grow it without limit.
+const MAX_SAMPLES: usize = 500_000;
+
+#[derive(Serialize, Clone, Copy, PartialEq, Debug)]
+#[serde(rename_all = "lowercase")]
+pub enum TriggerStatus {
+    Armed,
+    Capturing,
+    Fired,
+}
+
+#[derive(Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct TriggerInfo {
+    trigger_id: u32,
+    name: String,
+    status: TriggerStatus,
+    captures: usize,
+    last_capture: Option<String>,
+}
+
+struct ArmedTrigger {
+    definition: TriggerDefinition,
+    status: TriggerStatus,
+    captures: usize,
+    last_capture: Option<PathBuf>,
+    listener: Option<(Arc<ObjectEntryObject>, usize)>,
+}
+
+impl ArmedTrigger {
+    fn info(&self, trigger_id: u32) -> TriggerInfo {
+        TriggerInfo {
+            trigger_id,
+            name: self.definition.name.clone(),
+            status: self.status,
+            captures: self.captures,
+            last_capture: self
+                .last_capture
+                .as_ref()
+                .map(|path| path.to_string_lossy().into_owned()),
+        }
+    }
+}
+
+enum SampleData {
+    Frame(CanFrame, Option<Frame>),
+    Error(u64),
+    // index of the object entry in `CaptureState::object_entries`.
+    Value(usize, Value),
+}
+
+struct CaptureSample {
+    timestamp: Duration,
+    bus_id: u32,
+    data: SampleData,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct CapturedFrame<'a> {
+    // relative to the trigger, negative for the pre trigger window.
+    time_us: i64,
+    bus: &'a str,
+    id: Option<u32>,
+    ide: Option<bool>,
+    data: Option<Vec<u8>>,
+    message: Option<&'a str>,
+    attributes: Option<&'a Vec<Attribute>>,
+    error: Option<u64>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct CapturedValue<'a> {
+    // relative to the trigger, negative for the pre trigger window.
+    time_us: i64,
+    node: &'a str,
+    object_entry: &'a str,
+    value: &'a Value,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct CaptureFile<'a> {
+    trigger: &'a TriggerDefinition,
+    trigger_time_ms: f64,
+    captured_at: String,
+    frames: Vec<CapturedFrame<'a>>,
+    values: Vec<CapturedValue<'a>>,
+}
+
+#[derive(Default)]
+struct CaptureInner {
+    // shared with the capture tasks, which serialize them without holding the lock.
+    samples: VecDeque<Arc<CaptureSample>>,
+    retention: Option<Duration>,
+    triggers: BTreeMap<u32, ArmedTrigger>,
+    next_trigger_id: u32,
+}
+
+impl CaptureInner {
+    fn update_retention(&mut self) {
+        self.retention = self
+            .triggers
+            .values()
+            .filter(|trigger| trigger.status != TriggerStatus::Fired)
+            .map(|trigger| {
+                Duration::from_millis(
+                    trigger.definition.pre_trigger_ms + trigger.definition.post_trigger_ms,
+                ) + RETENTION_MARGIN
+            })
+            .max();
+        if self.retention.is_none() {
+            self.samples.clear();
+        }
+    }
+}
+
+pub struct CaptureState {
+    inner: Mutex<CaptureInner>,
+    buses: Vec<(String, u32)>,
+    // (node, object entry) of every object entry of the network.
+    object_entries: Vec<(String, String)>,
+    timebase: Instant,
+    app_handle: tauri::AppHandle,
+}
+
+impl CaptureState {
+    fn push(self: &Arc<Self>, sample: CaptureSample) {
+        let mut inner = self.inner.lock().unwrap();
+        let Some(retention) = inner.retention else {
+            return;
+        };
+        let fired: Vec<u32> = inner
+            .triggers
+            .iter()
+            .filter(|(_, trigger)| trigger.status == TriggerStatus::Armed)
+            .filter(|(_, trigger)| match &sample.data {
+                SampleData::Frame(frame, _) => {
+                    trigger
+                        .definition
+                        .condition
+                        .matches_frame(frame, sample.bus_id, &self.buses)
+                }
+                SampleData::Error(_) => trigger
+                    .definition
+                    .condition
+                    .matches_error(sample.bus_id, &self.buses),
+                // value conditions are evaluated by the TriggerListener of the object entry.
+                SampleData::Value(..) => false,
+            })
+            .map(|(trigger_id, _)| *trigger_id)
+            .collect();
+        let timestamp = sample.timestamp;
+        while inner.samples.len() >= MAX_SAMPLES
+            || inner
+                .samples
+                .front()
+                .is_some_and(|oldest| oldest.timestamp + retention < timestamp)
+        {
+            inner.samples.pop_front();
+        }
+        inner.samples.push_back(Arc::new(sample));
+        drop(inner);
+        for trigger_id in fired {
+            self.fire(trigger_id, timestamp);
+        }
+    }
+
+    /// Freezes the window around `time`, the capture is written once the
+    /// post trigger window has passed.
+    fn fire(self: &Arc<Self>, trigger_id: u32, time: Duration) {
+        let mut inner = self.inner.lock().unwrap();
+        let Some(trigger) = inner.triggers.get_mut(&trigger_id) else {
+            return;
+        };
+        if trigger.status != TriggerStatus::Armed {
+            return;
+        }
+        trigger.status = TriggerStatus::Capturing;
+        let definition = trigger.definition.clone();
+        let info = trigger.info(trigger_id);
+        drop(inner);
+        self.emit(info);
+
+        let state = self.clone();
+        tokio::spawn(async move {
+            let complete =
+                state.timebase + time + Duration::from_millis(definition.post_trigger_ms);
+            tokio::time::sleep_until(complete.into()).await;
+            let result = state.write_capture(&definition, time).await;
+            let mut inner = state.inner.lock().unwrap();
+            let Some(trigger) = inner.triggers.get_mut(&trigger_id) else {
+                // disarmed while capturing.
+                return;
+            };
+            trigger.status = if definition.rearm {
+                TriggerStatus::Armed
+            } else {
+                TriggerStatus::Fired
+            };
+            if let Ok(path) = &result {
+                trigger.captures += 1;
+                trigger.last_capture = Some(path.clone());
+            }
+            let info = trigger.info(trigger_id);
+            inner.update_retention();
+            drop(inner);
+            state.emit(info);
+            if let Err(err) = result {
+                notify_error(
+                    &state.app_handle,
+                    "Capture failed",
+                    &format!("Failed to write the capture of {} : {err}", definition.name),
+                    chrono::Local::now(),
+                );
+            }
+        });
+    }
+
+    async fn write_capture(
+        &self,
+        definition: &TriggerDefinition,
+        time: Duration,
+    ) -> Result<PathBuf, String> {
+        let now = chrono::Local::now();
+        let file_name = format!(
+            "{}_{}.json",
+            definition
+                .name
+                .chars()
+                .map(|c| if c.is_ascii_alphanumeric() { c } else { '_' })
+                .collect::<String>(),
+            now.format("%Y%m%d_%H%M%S%.3f")
+        );
+        let path = Path::new(&definition.directory).join(file_name);
+        let start = time.saturating_sub(Duration::from_millis(definition.pre_trigger_ms));
+        let end = time + Duration::from_millis(definition.post_trigger_ms);
+
+        let samples: Vec<Arc<CaptureSample>> = self
+            .inner
+            .lock()
+            .unwrap()
+            .samples
+            .iter()
+            .filter(|sample| sample.timestamp >= start && sample.timestamp <= end)
+            .cloned()
+            .collect();
+        let mut frames = vec![];
+        let mut values = vec![];
+        for sample in &samples {
+            let time_us = relative_time_us(sample.timestamp, time);
+            match &sample.data {
+                SampleData::Value(index, value) => {
+                    let (node, object_entry) = &self.object_entries[*index];
+                    values.push(CapturedValue {
+                        time_us,
+                        node,
+                        object_entry,
+                        value,
+                    });
+                }
+                _ => frames.push(self.captured_frame(sample, time_us)),
+            }
+        }
+        let content = serde_json::to_vec_pretty(&CaptureFile {
+            trigger: definition,
+            trigger_time_ms: time.as_secs_f64() * 1000.0,
+            captured_at: now.to_rfc3339(),
+            frames,
+            values,
+        })
+        .map_err(|err| err.to_string())?;
+        tokio::fs::write(&path, content)
+            .await
+            .map_err(|err| err.to_string())?;
+        Ok(path)
+    }
+
+    fn captured_frame<'a>(&'a self, sample: &'a CaptureSample, time_us: i64) -> CapturedFrame<'a> {
+        let bus = self
+            .buses
+            .iter()
+            .find(|(_, bus_id)| *bus_id == sample.bus_id)
+            .map_or("unknown", |(name, _)| name.as_str());
+        match &sample.data {
+            SampleData::Frame(can_frame, frame) => CapturedFrame {
+                time_us,
+                bus,
+                id: Some(can_frame.get_id()),
+                ide: Some(can_frame.get_ide_flag()),
+                data: Some(
+                    can_frame.get_data_8u8()[..(can_frame.get_dlc() as usize).min(8)].to_vec(),
+                ),
+                message: frame.as_ref().map(Frame::name),
+                attributes: frame.as_ref().map(Frame::attributes),
+                error: None,
+            },
+            SampleData::Error(erno) => CapturedFrame {
+                time_us,
+                bus,
+                id: None,
+                ide: None,
+                data: None,
+                message: None,
+                attributes: None,
+                error: Some(*erno),
+            },
+            SampleData::Value(..) => unreachable!("values are not captured as frames"),
+        }
+    }
+
+    fn emit(&self, info: TriggerInfo) {
+        let _ = self.app_handle.emit_all(CAPTURE_TRIGGER_EVENT, info);
+    }
+}
+
+fn relative_time_us(timestamp: Duration, time: Duration) -> i64 {
+    if timestamp >= time {
+        (timestamp - time).as_micros() as i64
+    } else {
+        -((time - timestamp).as_micros() as i64)
+    }
+}
+
+/// Records the values of an object entry, while a trigger is armed.
+struct ValueRecorder {
+    index: usize,
+    // weak, the object entry outlives the capture object.
+    state: Weak<CaptureState>,
+}
+
+impl ObjectEntryListener for ValueRecorder {
+    fn notify(&self, value: &ObjectEntryValue) {
+        if let Some(state) = self.state.upgrade() {
+            state.push(CaptureSample {
+                timestamp: value.timestamp,
+                bus_id: 0,
+                data: SampleData::Value(self.index, value.value.clone()),
+            });
+        }
+    }
+}
+
+/// Oscilloscope like capture of the received frames and object entry values.
+///
+/// While at least one trigger is armed all received frames and values are kept in a
+/// ring buffer, when a trigger fires the samples within its pre and post trigger window
+/// are written to a json file in the directory of the trigger.
+pub struct CaptureObject {
+    state: Arc<CaptureState>,
+    network: Arc<NetworkObject>,
+}
+
+impl CaptureObject {
+    pub async fn create(
+        network_config: &config::NetworkRef,
+        network: &Arc<NetworkObject>,
+        app_handle: &tauri::AppHandle,
+        timebase: Instant,
+    ) -> Self {
+        let object_entries: Vec<&Arc<ObjectEntryObject>> = network
+            .nodes()
+            .iter()
+            .flat_map(|node| node.object_entries())
+            .collect();
+        let state = Arc::new(CaptureState {
+            inner: Mutex::new(CaptureInner::default()),
+            buses: network_config
+                .buses()
+                .iter()
+                .map(|bus| (bus.name().to_owned(), bus.id()))
+                .collect(),
+            object_entries: object_entries
+                .iter()
+                .map(|object_entry| {
+                    (
+                        object_entry.node_ref().name().to_owned(),
+                        object_entry.name().to_owned(),
+                    )
+                })
+                .collect(),
+            timebase,
+            app_handle: app_handle.clone(),
+        });
+        for (index, object_entry) in object_entries.into_iter().enumerate() {
+            object_entry
+                .vlisten(Arc::new(ValueRecorder {
+                    index,
+                    state: Arc::downgrade(&state),
+                }))
+                .await;
+        }
+        Self {
+            state,
+            network: network.clone(),
+        }
+    }
+
+    /// `frame` is the decoded frame, if the id belongs to a message of the network.
+    pub fn push_frame(&self, can_frame: &TCanFrame, frame: Option<&Frame>, bus_id: u32) {
+        self.state.push(CaptureSample {
+            timestamp: can_frame.timestamp,
+            bus_id,
+            data: SampleData::Frame(can_frame.value.clone(), frame.cloned()),
+        });
+    }
+
+    pub fn push_error(&self, error: &TCanError, bus_id: u32) {
+        self.state.push(CaptureSample {
+            timestamp: error.timestamp,
+            bus_id,
+            data: SampleData::Error(error.erno()),
+        });
+    }
+
+    pub async fn arm(&self, definition: TriggerDefinition) -> Result<TriggerInfo, String> {
+        if !Path::new(&definition.directory).is_dir() {
+            return Err(format!("{} is not a directory", definition.directory));
+        }
+        if let Some(bus) = match &definition.condition {
+            trigger::TriggerCondition::FrameId { bus, .. } => bus.as_ref(),
+            trigger::TriggerCondition::ErrorFrame { bus } => bus.as_ref(),
+            _ => None,
+        } {
+            if !self.state.buses.iter().any(|(name, _)| name == bus) {
+                return Err(format!("Unknown bus {bus}"));
+            }
+        }
+        let object_entry = match definition.condition.object_entry() {
+            Some((node, object_entry)) => {
+                let object_entry = self.object_entry(node, object_entry)?;
+                validate_type(&definition.condition, &object_entry)?;
+                Some(object_entry)
+            }
+            None => None,
+        };
+        let initial = match &object_entry {
+            Some(object_entry) => object_entry.latest_value().await,
+            None => None,
+        };
+
+        let condition = definition.condition.clone();
+        let (trigger_id, info) = {
+            let mut inner = self.state.inner.lock().unwrap();
+            let trigger_id = inner.next_trigger_id;
+            inner.next_trigger_id += 1;
+            let trigger = ArmedTrigger {
+                definition,
+                status: TriggerStatus::Armed,
+                captures: 0,
+                last_capture: None,
+                listener: None,
+            };
+            let info = trigger.info(trigger_id);
+            inner.triggers.insert(trigger_id, trigger);
+            inner.update_retention();
+            (trigger_id, info)
+        };
+
+        if let Some(object_entry) = object_entry {
+            let listener = Arc::new(TriggerListener::new(
+                trigger_id,
+                &condition,
+                initial,
+                &self.state,
+            ));
+            let listener_id = object_entry.vlisten(listener).await;
+            let disarmed = match self
+                .state
+                .inner
+                .lock()
+                .unwrap()
+                .triggers
+                .get_mut(&trigger_id)
+            {
+                Some(trigger) => {
+                    trigger.listener = Some((object_entry.clone(), listener_id));
+                    false
+                }
+                None => true,
+            };
+            if disarmed {
+                // disarmed before the listener was registered.
+                object_entry.vunlisten(listener_id).await;
+            }
+        }
+        Ok(info)
+    }
+
+    pub async fn disarm(&self, trigger_id: u32) -> Result<(), String> {
+        let trigger = {
+            let mut inner = self.state.inner.lock().unwrap();
+            let Some(trigger) = inner.triggers.remove(&trigger_id) else {
+                return Err(format!("No capture trigger with id {trigger_id}"));
+            };
+            inner.update_retention();
+            trigger
+        };
+        if let Some((object_entry, listener_id)) = trigger.listener {
+            object_entry.vunlisten(listener_id).await;
+        }
+        Ok(())
+    }
+
+    pub fn triggers(&self) -> Vec<TriggerInfo> {
+        self.state
+            .inner
+            .lock()
+            .unwrap()
+            .triggers
+            .iter()
+            .map(|(trigger_id, trigger)| trigger.info(*trigger_id))
+            .collect()
+    }
+
+    fn object_entry(&self, node_name: &str, name: &str) -> Result<Arc<ObjectEntryObject>, String> {
+        let Some(node) = self
+            .network
+            .nodes()
+            .iter()
+            .find(|node| node.name() == node_name)
+        else {
+            return Err(format!("Unknown node {node_name}"));
+        };
+        node.object_entries()
+            .iter()
+            .find(|object_entry| object_entry.name() == name)
+            .cloned()
+            .ok_or_else(|| format!("Unknown object entry {node_name}.{name}"))
+    }
+}
+
+fn validate_type(
+    condition: &trigger::TriggerCondition,
+    object_entry: &ObjectEntryObject,
+) -> Result<(), String> {
+    match (condition, object_entry.ty() as &Type) {
+        (trigger::TriggerCondition::Threshold { .. }, Type::Primitive(_)) => Ok(()),
+        (trigger::TriggerCondition::Threshold { .. }, _) => Err(format!(
+            "{} is not a numeric object entry",
+            object_entry.name()
+        )),
+        (trigger::TriggerCondition::EnumValue { variant, .. }, Type::Enum { entries, .. }) => {
+            if entries.iter().any(|(name, _)| name == variant) {
+                Ok(())
+            } else {
+                Err(format!(
+                    "{variant} is not a variant of {}",
+                    object_entry.name()
+                ))
+            }
+        }
+        (trigger::TriggerCondition::EnumValue { .. }, _) => Err(format!(
+            "{} is not an enum object entry",
+            object_entry.name()
+        )),
+        _ => Ok(()),
+    }
+}
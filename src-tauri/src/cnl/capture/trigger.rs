@@ -0,0 +1,281 @@
+use std::sync::{Arc, Mutex, Weak};
+
+use canzero_common::CanFrame;
+use serde::{Deserialize, Serialize};
+
+use crate::cnl::{
+    frame::Value,
+    network::object_entry_object::{
+        database::value::ObjectEntryValue, vlistener::ObjectEntryListener,
+    },
+};
+
+use super::CaptureState;
+
+#[derive(Deserialize, Serialize, Clone, Copy, Debug, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum Edge {
+    Rising,
+    Falling,
+    Both,
+}
+
+#[derive(Deserialize, Serialize, Clone, Debug)]
+#[serde(tag = "kind", rename_all = "camelCase")]
+pub enum TriggerCondition {
+    /// A numeric object entry crosses the threshold.
+    #[serde(rename_all = "camelCase")]
+    Threshold {
+        node: String,
+        object_entry: String,
+        threshold: f64,
+        edge: Edge,
+    },
+    /// An enum object entry changes to the variant.
+    #[serde(rename_all = "camelCase")]
+    EnumValue {
+        node: String,
+        object_entry: String,
+        variant: String,
+    },
+    /// A frame with the id is received, on any bus if `bus` is None.
+    #[serde(rename_all = "camelCase")]
+    FrameId {
+        id: u32,
+        #[serde(default)]
+        ide: bool,
+        bus: Option<String>,
+    },
+    /// An error frame is received, on any bus if `bus` is None.
+    #[serde(rename_all = "camelCase")]
+    ErrorFrame { bus: Option<String> },
+}
+
+#[derive(Deserialize, Serialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct TriggerDefinition {
+    pub name: String,
+    pub condition: TriggerCondition,
+    pub pre_trigger_ms: u64,
+    pub post_trigger_ms: u64,
+    /// Directory the capture files are written to.
+    pub directory: String,
+    /// Rearms the trigger after a capture was written, otherwise it only fires once.
+    #[serde(default)]
+    pub rearm: bool,
+}
+
+impl TriggerCondition {
+    /// Object entry the condition observes as `(node, object_entry)`.
+    pub fn object_entry(&self) -> Option<(&str, &str)> {
+        match self {
+            TriggerCondition::Threshold {
+                node, object_entry, ..
+            }
+            | TriggerCondition::EnumValue {
+                node, object_entry, ..
+            } => Some((node, object_entry)),
+            _ => None,
+        }
+    }
+
+    pub fn matches_frame(&self, frame: &CanFrame, bus_id: u32, bus_ids: &[(String, u32)]) -> bool {
+        match self {
+            TriggerCondition::FrameId { id, ide, bus } => {
+                frame.get_id() == *id
+                    && frame.get_ide_flag() == *ide
+                    && matches_bus(bus.as_deref(), bus_id, bus_ids)
+            }
+            _ => false,
+        }
+    }
+
+    pub fn matches_error(&self, bus_id: u32, bus_ids: &[(String, u32)]) -> bool {
+        match self {
+            TriggerCondition::ErrorFrame { bus } => matches_bus(bus.as_deref(), bus_id, bus_ids),
+            _ => false,
+        }
+    }
+
+    /// Checks if the transition from `previous` to `value` fires the trigger,
+    /// without a previous value only enum triggers can fire.
+    pub fn matches_value(&self, previous: Option<&Value>, value: &Value) -> bool {
+        match self {
+            TriggerCondition::Threshold {
+                threshold, edge, ..
+            } => {
+                let (Some(previous), Some(value)) = (previous.and_then(as_f64), as_f64(value))
+                else {
+                    return false;
+                };
+                let rising = previous < *threshold && value >= *threshold;
+                let falling = previous >= *threshold && value < *threshold;
+                match edge {
+                    Edge::Rising => rising,
+                    Edge::Falling => falling,
+                    Edge::Both => rising || falling,
+                }
+            }
+            TriggerCondition::EnumValue { variant, .. } => {
+                let is_variant =
+                    |value: &Value| matches!(value, Value::EnumValue(v) if v == variant);
+                is_variant(value) && !previous.is_some_and(is_variant)
+            }
+            _ => false,
+        }
+    }
+}
+
+fn matches_bus(bus: Option<&str>, bus_id: u32, bus_ids: &[(String, u32)]) -> bool {
+    match bus {
+        Some(bus) => bus_ids
+            .iter()
+            .any(|(name, id)| name == bus && *id == bus_id),
+        None => true,
+    }
+}
+
+fn as_f64(value: &Value) -> Option<f64> {
+    match value {
+        Value::UnsignedValue(value) => Some(*value as f64),
+        Value::SignedValue(value) => Some(*value as f64),
+        Value::RealValue(value) => Some(*value),
+        _ => None,
+    }
+}
+
+/// Evaluates value conditions on every value pushed to the object entry.
+pub struct TriggerListener {
+    trigger_id: u32,
+    condition: TriggerCondition,
+    previous: Mutex<Option<Value>>,
+    // weak, the object entry outlives the capture object.
+    state: Weak<CaptureState>,
+}
+
+impl TriggerListener {
+    pub fn new(
+        trigger_id: u32,
+        condition: &TriggerCondition,
+        initial: Option<Value>,
+        state: &Arc<CaptureState>,
+    ) -> Self {
+        Self {
+            trigger_id,
+            condition: condition.clone(),
+            previous: Mutex::new(initial),
+            state: Arc::downgrade(state),
+        }
+    }
+}
+
+impl ObjectEntryListener for TriggerListener {
+    fn notify(&self, value: &ObjectEntryValue) {
+        let mut previous = self.previous.lock().unwrap();
+        let fired = self
+            .condition
+            .matches_value(previous.as_ref(), &value.value);
+        *previous = Some(value.value.clone());
+        drop(previous);
+        if fired {
+            if let Some(state) = self.state.upgrade() {
+                state.fire(self.trigger_id, value.timestamp);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn threshold(edge: Edge) -> TriggerCondition {
+        TriggerCondition::Threshold {
+            node: "node".to_owned(),
+            object_entry: "position".to_owned(),
+            threshold: 10.0,
+            edge,
+        }
+    }
+
+    fn buses() -> Vec<(String, u32)> {
+        vec![("can0".to_owned(), 0), ("can1".to_owned(), 1)]
+    }
+
+    #[test]
+    fn threshold_edges() {
+        let below = Value::RealValue(9.5);
+        let above = Value::UnsignedValue(10);
+        let rising = threshold(Edge::Rising);
+        assert!(rising.matches_value(Some(&below), &above));
+        assert!(!rising.matches_value(Some(&above), &below));
+        assert!(!rising.matches_value(Some(&above), &above));
+
+        let falling = threshold(Edge::Falling);
+        assert!(falling.matches_value(Some(&above), &below));
+        assert!(!falling.matches_value(Some(&below), &above));
+
+        let both = threshold(Edge::Both);
+        assert!(both.matches_value(Some(&below), &above));
+        assert!(both.matches_value(Some(&above), &below));
+        assert!(!both.matches_value(Some(&below), &below));
+    }
+
+    #[test]
+    fn threshold_requires_a_previous_numeric_value() {
+        let rising = threshold(Edge::Rising);
+        assert!(!rising.matches_value(None, &Value::SignedValue(20)));
+        assert!(!rising.matches_value(
+            Some(&Value::EnumValue("Idle".to_owned())),
+            &Value::SignedValue(20)
+        ));
+    }
+
+    #[test]
+    fn enum_value_fires_on_change_to_the_variant() {
+        let condition = TriggerCondition::EnumValue {
+            node: "node".to_owned(),
+            object_entry: "state".to_owned(),
+            variant: "Error".to_owned(),
+        };
+        let idle = Value::EnumValue("Idle".to_owned());
+        let error = Value::EnumValue("Error".to_owned());
+        assert!(condition.matches_value(None, &error));
+        assert!(condition.matches_value(Some(&idle), &error));
+        assert!(!condition.matches_value(Some(&error), &error));
+        assert!(!condition.matches_value(Some(&error), &idle));
+    }
+
+    #[test]
+    fn frame_id_matches_id_ide_and_bus() {
+        let frame = CanFrame::new(0x123, false, false, 8, 0);
+        let any_bus = TriggerCondition::FrameId {
+            id: 0x123,
+            ide: false,
+            bus: None,
+        };
+        assert!(any_bus.matches_frame(&frame, 1, &buses()));
+        assert!(!any_bus.matches_frame(&CanFrame::new(0x123, true, false, 8, 0), 1, &buses()));
+        assert!(!any_bus.matches_frame(&CanFrame::new(0x124, false, false, 8, 0), 1, &buses()));
+
+        let can0 = TriggerCondition::FrameId {
+            id: 0x123,
+            ide: false,
+            bus: Some("can0".to_owned()),
+        };
+        assert!(can0.matches_frame(&frame, 0, &buses()));
+        assert!(!can0.matches_frame(&frame, 1, &buses()));
+        assert!(!can0.matches_error(0, &buses()));
+    }
+
+    #[test]
+    fn error_frame_matches_bus() {
+        let can1 = TriggerCondition::ErrorFrame {
+            bus: Some("can1".to_owned()),
+        };
+        assert!(can1.matches_error(1, &buses()));
+        assert!(!can1.matches_error(0, &buses()));
+        assert!(TriggerCondition::ErrorFrame { bus: None }.matches_error(0, &buses()));
+        assert!(!can1.matches_frame(&CanFrame::new(0x1, false, false, 0, 0), 1, &buses()));
+    }
+}
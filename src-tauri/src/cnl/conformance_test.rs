@@ -0,0 +1,727 @@
+// Round trips randomized values through the C code generated by canzero-codegen and
+// the rust side of the cnl (deserialize, Value::get_as_bin and the tx framing).
+//
+// Every node of the example configs is generated for the linux platform and linked
+// against a small harness, which feeds frames from stdin into the poll functions and
+// prints every frame the node sends. The test then
+// - sets every global object entry with set requests encoded by get_as_bin and reads
+//   it back through (fragmented) get responses decoded by the TypeDeserializer.
+// - decodes the tx streams of the node with the FrameDeserializer and encodes them
+//   again with the FrameSerializer.
+// - forwards these streams to the receiving nodes and reads back the mapped entries.
+// - calls every command with arguments encoded by get_as_bin.
+// - stages every global object entry and commits them in a single transaction.
+//
+// NOTE arrays are not supported by codegen or the deserializers yet, object entries and
+// commands with array types are skipped (see random_value).
+
+use std::{
+    collections::HashMap,
+    io::Write,
+    path::{Path, PathBuf},
+    process::{Command, Stdio},
+};
+
+use bitvec::view::AsBits;
+use canzero_codegen::{command_hooks::command_hook_signature, generate, options::Options};
+use canzero_common::CanFrame;
+use canzero_config::builder::network_builder::MAX_STAGED_ENTRIES;
+use canzero_config::config::{self, NetworkRef, NodeRef, ObjectEntryAccess, SignalType, Type};
+use canzero_yaml::parse_yaml_config_from_file;
+use rand::{rngs::StdRng, Rng, SeedableRng};
+
+use super::{
+    deserialize::{type_deserializer::TypeDeserializer, FrameDeserializer},
+    frame::{Attribute, Value},
+    serialize::FrameSerializer,
+    tx::{
+        commit_request_frame, get_request_frame, set_request_frames, stage_request_frames,
+        CommitAction,
+    },
+};
+
+const ROUNDS: usize = 8;
+const SEED: u64 = 0xCA7_2E20;
+
+const PGMSPACE_STUB: &str = "#pragma once
+#define PROGMEM
+#define DMAMEM
+";
+
+const HARNESS_PRELUDE: &str = r#"#include "canzero.cpp"
+#include <cstdio>
+#include <cstring>
+
+static canzero_frame rx_frame;
+static int rx_pending = 0;
+static uint32_t now = 0;
+
+uint32_t canzero_get_time() { return now; }
+void canzero_request_update(uint32_t time) {}
+void canzero_enter_critical() {}
+void canzero_exit_critical() {}
+
+static int take_rx(canzero_frame* frame) {
+  if (!rx_pending) {
+    return 0;
+  }
+  *frame = rx_frame;
+  rx_pending = 0;
+  return 1;
+}
+
+static void print_frame(unsigned bus_id, canzero_frame* frame) {
+  uint64_t data;
+  memcpy(&data, frame->data, sizeof(data));
+  printf("F %u %" PRIx32 " %u %" PRIx64 "\n", bus_id, frame->id, (unsigned)frame->dlc, data);
+}
+
+static void print_arg(uint8_t v) { printf(" %" PRIu64, (uint64_t)v); }
+static void print_arg(uint16_t v) { printf(" %" PRIu64, (uint64_t)v); }
+static void print_arg(uint32_t v) { printf(" %" PRIu64, (uint64_t)v); }
+static void print_arg(uint64_t v) { printf(" %" PRIu64, v); }
+static void print_arg(float v) { printf(" %.17g", (double)v); }
+static void print_arg(double v) { printf(" %.17g", v); }
+template <typename T> static void print_arg(T v) { printf(" %" PRId64, (int64_t)v); }
+"#;
+
+fn example_configs() -> Vec<PathBuf> {
+    let examples = Path::new(env!("CARGO_MANIFEST_DIR")).join("lib/codegen/examples");
+    vec![examples.join("simple.yaml"), examples.join("conformance.yaml")]
+}
+
+fn c_frame_id(frame: &CanFrame) -> u32 {
+    if frame.get_ide_flag() {
+        frame.get_id() | 0x40000000
+    } else {
+        frame.get_id()
+    }
+}
+
+fn frame_matches(frame: &CanFrame, message: &config::MessageRef) -> bool {
+    frame.get_id() == message.id().as_u32() && frame.get_ide_flag() == message.id().ide()
+}
+
+fn generate_harness(network: &NetworkRef, node: &NodeRef, options: &Options) -> String {
+    let namespace = options.namespace();
+    let mut harness = HARNESS_PRELUDE.to_owned();
+    for bus in node.buses() {
+        let bus_name = bus.name();
+        let bus_id = bus.id();
+        harness.push_str(&format!(
+            "void {namespace}_{bus_name}_setup(uint32_t baudrate, {namespace}_can_filter* filters, int filter_count) {{}}
+void {namespace}_{bus_name}_send({namespace}_frame* frame) {{ print_frame({bus_id}, frame); }}
+int {namespace}_{bus_name}_recv({namespace}_frame* frame) {{ return take_rx(frame); }}
+"
+        ));
+    }
+    for command in node.commands() {
+        let command_name = command.name();
+        let signature = command_hook_signature(command, options);
+        harness.push_str(&format!("{signature} {{\n  printf(\"C {command_name}\");\n"));
+        let encoding = command.tx_message().encoding().expect("commands are encoded");
+        for attribute in encoding.attributes() {
+            harness.push_str(&format!("  print_arg({});\n", attribute.name()));
+        }
+        harness.push_str("  printf(\"\\n\");\n  return command_resp_erno_Success;\n}\n");
+    }
+    harness.push_str(&format!(
+        "int main() {{
+  {namespace}_init();
+  char cmd;
+  while (scanf(\" %c\", &cmd) == 1) {{
+    if (cmd == 'R') {{
+      unsigned bus_id, dlc;
+      uint32_t id;
+      uint64_t data;
+      if (scanf(\"%u %\" SCNx32 \" %u %\" SCNx64, &bus_id, &id, &dlc, &data) != 4) {{
+        return 1;
+      }}
+      rx_frame.id = id;
+      rx_frame.dlc = dlc;
+      memcpy(rx_frame.data, &data, sizeof(data));
+      rx_pending = 1;
+      switch (bus_id) {{
+"
+    ));
+    for bus in network.buses() {
+        if node.buses().iter().any(|b| b.id() == bus.id()) {
+            let bus_id = bus.id();
+            harness.push_str(&format!(
+                "      case {bus_id}:\n        {namespace}_can{bus_id}_poll();\n        break;\n"
+            ));
+        }
+    }
+    harness.push_str(&format!(
+        "      }}
+    }} else if (cmd == 'T') {{
+      if (scanf(\"%\" SCNu32, &now) != 1) {{
+        return 1;
+      }}
+      {namespace}_update_continue(now);
+    }}
+    fflush(stdout);
+  }}
+  return 0;
+}}
+"
+    ));
+    harness
+}
+
+/// Generates and compiles the harness of a node, returns None if no C++ compiler is available.
+fn build_harness(config_path: &Path, node_name: &str) -> Option<PathBuf> {
+    let config_name = config_path.file_stem().unwrap().to_str().unwrap();
+    let dir = std::env::temp_dir().join(format!("canzero-conformance-{config_name}-{node_name}"));
+    std::fs::create_dir_all(dir.join("avr")).unwrap();
+    std::fs::write(dir.join("avr").join("pgmspace.h"), PGMSPACE_STUB).unwrap();
+
+    let network = parse_yaml_config_from_file(config_path.to_str().unwrap()).unwrap();
+    let node = network
+        .nodes()
+        .iter()
+        .find(|node| node.name() == node_name)
+        .unwrap()
+        .clone();
+    let mut options = Options::default();
+    options.set_source_file_path(dir.join("canzero.cpp").to_str().unwrap());
+    options.set_header_file_path(dir.join("canzero.h").to_str().unwrap());
+    let harness = generate_harness(&network, &node, &options);
+    generate(node_name, network, options).unwrap();
+    std::fs::write(dir.join("harness.cpp"), harness).unwrap();
+
+    let binary = dir.join("harness");
+    let compiler = std::env::var("CXX").unwrap_or("c++".to_owned());
+    let output = match Command::new(&compiler)
+        .arg("-std=c++17")
+        .arg("-I")
+        .arg(&dir)
+        .arg(dir.join("harness.cpp"))
+        .arg("-o")
+        .arg(&binary)
+        .output()
+    {
+        Ok(output) => output,
+        Err(err) => {
+            eprintln!("skipping conformance test, {compiler} is not available : {err:?}");
+            return None;
+        }
+    };
+    assert!(
+        output.status.success(),
+        "generated code of {config_name}::{node_name} failed to compile:\n{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    Some(binary)
+}
+
+/// Script of frames and time steps, which is fed into a harness.
+struct Session {
+    script: String,
+    now: u32,
+}
+
+impl Session {
+    fn new() -> Self {
+        Self {
+            script: String::new(),
+            now: 0,
+        }
+    }
+
+    fn receive(&mut self, bus_id: u32, frame: &CanFrame) {
+        self.script.push_str(&format!(
+            "R {bus_id} {:x} {} {:x}\n",
+            c_frame_id(frame),
+            frame.get_dlc(),
+            frame.get_data_u64()
+        ));
+    }
+
+    fn advance(&mut self, millis: u32) {
+        self.now += millis;
+        self.script.push_str(&format!("T {}\n", self.now));
+    }
+
+    fn run(self, binary: &Path) -> (Vec<CanFrame>, Vec<String>) {
+        let mut child = Command::new(binary)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .spawn()
+            .unwrap();
+        child
+            .stdin
+            .take()
+            .unwrap()
+            .write_all(self.script.as_bytes())
+            .unwrap();
+        let output = child.wait_with_output().unwrap();
+        assert!(output.status.success(), "harness {binary:?} failed");
+
+        let mut frames = vec![];
+        let mut calls = vec![];
+        for line in String::from_utf8(output.stdout).unwrap().lines() {
+            let mut tokens = line.split_whitespace();
+            match tokens.next() {
+                Some("F") => {
+                    let _bus_id: u32 = tokens.next().unwrap().parse().unwrap();
+                    let id = u32::from_str_radix(tokens.next().unwrap(), 16).unwrap();
+                    let dlc: u8 = tokens.next().unwrap().parse().unwrap();
+                    let data = u64::from_str_radix(tokens.next().unwrap(), 16).unwrap();
+                    frames.push(CanFrame::new(
+                        id & 0x1FFFFFFF,
+                        id & 0x40000000 != 0,
+                        id & 0x80000000 != 0,
+                        dlc,
+                        data,
+                    ));
+                }
+                Some("C") => calls.push(line[2..].to_owned()),
+                _ => panic!("unexpected harness output {line:?}"),
+            }
+        }
+        (frames, calls)
+    }
+}
+
+/// A random value of the type, None if the type contains an array.
+fn random_value(rng: &mut StdRng, ty: &Type) -> Option<Value> {
+    let value = match ty {
+        Type::Primitive(SignalType::UnsignedInt { size }) => {
+            Value::UnsignedValue(rng.gen::<u64>() >> (64 - *size as u32))
+        }
+        Type::Primitive(SignalType::SignedInt { size }) => {
+            Value::SignedValue(rng.gen::<i64>() >> (64 - *size as u32))
+        }
+        Type::Primitive(SignalType::Decimal {
+            size,
+            offset,
+            scale,
+        }) => {
+            let raw = rng.gen::<u64>() >> (64 - *size as u32);
+            Value::RealValue(raw as f64 * scale + offset)
+        }
+        Type::Struct {
+            name: _,
+            description: _,
+            attribs,
+            visibility: _,
+        } => Value::StructValue(
+            attribs
+                .iter()
+                .map(|(name, ty)| Some(Attribute::new(name, random_value(rng, ty)?)))
+                .collect::<Option<_>>()?,
+        ),
+        Type::Enum {
+            name: _,
+            description: _,
+            size: _,
+            entries,
+            visibility: _,
+        } => Value::EnumValue(entries[rng.gen_range(0..entries.len())].0.clone()),
+        Type::Array { len: _, ty: _ } => return None,
+    };
+    Some(value)
+}
+
+fn assert_conforms(expected: &Value, actual: &Value, ty: &Type, context: &str) {
+    match (expected, actual, ty) {
+        (Value::UnsignedValue(e), Value::UnsignedValue(a), _) => {
+            assert_eq!(e, a, "{context}");
+        }
+        (Value::SignedValue(e), Value::SignedValue(a), _) => {
+            assert_eq!(e, a, "{context}");
+        }
+        (
+            Value::RealValue(e),
+            Value::RealValue(a),
+            Type::Primitive(SignalType::Decimal {
+                size,
+                offset,
+                scale,
+            }),
+        ) => {
+            // the generated code stores decimals of up to 32 bits as floats.
+            let epsilon = if *size <= 32 {
+                f32::EPSILON as f64
+            } else {
+                f64::EPSILON
+            };
+            let range = scale * (u64::MAX >> (64 - *size as u32)) as f64;
+            let tolerance = scale + 4.0 * epsilon * (e.abs() + offset.abs() + range);
+            assert!(
+                (e - a).abs() <= tolerance,
+                "{context}: expected {e}, got {a} (tolerance {tolerance})"
+            );
+        }
+        (
+            Value::StructValue(e),
+            Value::StructValue(a),
+            Type::Struct {
+                name: _,
+                description: _,
+                attribs,
+                visibility: _,
+            },
+        ) => {
+            assert_eq!(e.len(), a.len(), "{context}");
+            for ((e, a), (name, ty)) in e.iter().zip(a).zip(attribs) {
+                assert_eq!(a.name(), name, "{context}");
+                assert_conforms(e.value(), a.value(), ty, &format!("{context}.{name}"));
+            }
+        }
+        (Value::EnumValue(e), Value::EnumValue(a), _) => {
+            assert_eq!(e, a, "{context}");
+        }
+        _ => panic!("{context}: expected {expected:?}, got {actual:?}"),
+    }
+}
+
+/// Collects the values of all completed get responses of a node.
+fn get_responses(network: &NetworkRef, node: &NodeRef, frames: &[CanFrame]) -> HashMap<u32, Value> {
+    let get_resp = network.get_resp_message();
+    let deserializer = FrameDeserializer::new(get_resp);
+    let mut buffers: HashMap<u32, Vec<u32>> = HashMap::new();
+    let mut values = HashMap::new();
+    for frame in frames.iter().filter(|frame| frame_matches(frame, get_resp)) {
+        let frame = deserializer.deserialize(frame.get_data_u64());
+        let header = frame.attribute("header").unwrap();
+        let field = |name: &str| match header.attribute(name) {
+            Some(Value::UnsignedValue(value)) => *value,
+            _ => panic!("get_resp header without {name}"),
+        };
+        let Some(Value::UnsignedValue(data)) = frame.attribute("data") else {
+            panic!("get_resp without data");
+        };
+        if field("server_id") != node.id() as u64 {
+            continue;
+        }
+        let od_index = field("od_index") as u32;
+        let buffer = buffers.entry(od_index).or_default();
+        if field("sof") != 0 {
+            buffer.clear();
+        }
+        buffer.push(*data as u32);
+        if field("eof") != 0 {
+            let object_entry = node
+                .object_entries()
+                .iter()
+                .find(|oe| oe.id() == od_index)
+                .unwrap();
+            assert_eq!(
+                buffer.len(),
+                object_entry.ty().size().div_ceil(32) as usize,
+                "{}::{} responded with the wrong number of fragments",
+                node.name(),
+                object_entry.name()
+            );
+            let value = TypeDeserializer::new(object_entry.ty()).deserialize(buffer.as_bits());
+            values.insert(od_index, value);
+        }
+    }
+    values
+}
+
+/// Requests every given object entry and checks the responses against the expected values,
+/// returns all frames sent by the node.
+fn check_object_entries(
+    network: &NetworkRef,
+    node: &NodeRef,
+    binary: &Path,
+    mut session: Session,
+    expected: &HashMap<u32, Value>,
+    context: &str,
+) -> Vec<CanFrame> {
+    let get_req_bus = network.get_req_message().bus().id();
+    for object_entry in node.object_entries() {
+        if !expected.contains_key(&object_entry.id()) {
+            continue;
+        }
+        session.receive(
+            get_req_bus,
+            &get_request_frame(network, node.id(), object_entry.id() as u16),
+        );
+        // remaining fragments are sent by the scheduler.
+        for _ in 0..object_entry.ty().size().div_ceil(32) {
+            session.advance(100);
+        }
+    }
+    let (frames, _) = session.run(binary);
+    let responses = get_responses(network, node, &frames);
+    for object_entry in node.object_entries() {
+        let Some(expected) = expected.get(&object_entry.id()) else {
+            continue;
+        };
+        let oe_context = format!("{context} {}::{}", node.name(), object_entry.name());
+        let Some(actual) = responses.get(&object_entry.id()) else {
+            panic!("{oe_context}: no get response");
+        };
+        assert_conforms(expected, actual, object_entry.ty(), &oe_context);
+    }
+    frames
+}
+
+fn check_round(
+    network: &NetworkRef,
+    harnesses: &HashMap<String, PathBuf>,
+    rng: &mut StdRng,
+    context: &str,
+) {
+    let set_req_bus = network.set_req_message().bus().id();
+    let client_id = network.nodes().len() as u8;
+    let mut stream_frames = vec![];
+
+    for node in network.nodes() {
+        let binary = &harnesses[node.name()];
+
+        // set requests + get requests
+        let mut session = Session::new();
+        let mut values = HashMap::new();
+        for object_entry in node.object_entries() {
+            if !matches!(object_entry.access(), ObjectEntryAccess::Global) {
+                continue;
+            }
+            let Some(value) = random_value(rng, object_entry.ty()) else {
+                continue;
+            };
+            let (bits, last_fill) = value.get_as_bin::<u32>(object_entry.ty());
+            for frame in set_request_frames(
+                network,
+                client_id,
+                node.id(),
+                object_entry.id(),
+                &bits,
+                last_fill,
+            ) {
+                session.receive(set_req_bus, &frame);
+            }
+            values.insert(object_entry.id(), value);
+        }
+        // let every stream run at least once with the new values.
+        session.advance(1000);
+        let frames = check_object_entries(network, node, binary, session, &values, context);
+
+        let set_resp = network.set_resp_message();
+        let set_resp_deserializer = FrameDeserializer::new(set_resp);
+        for frame in frames.iter().filter(|frame| frame_matches(frame, set_resp)) {
+            let frame = set_resp_deserializer.deserialize(frame.get_data_u64());
+            let erno = frame.attribute("header").and_then(|h| h.attribute("erno"));
+            assert!(
+                matches!(erno, Some(Value::EnumValue(erno)) if erno == "Success"),
+                "{context} {}: set request failed {erno:?}",
+                node.name()
+            );
+        }
+        for stream in node.tx_streams() {
+            let message = stream.message();
+            let frame = frames
+                .iter()
+                .rev()
+                .find(|frame| frame_matches(frame, message))
+                .unwrap_or_else(|| panic!("{context}: stream {} was not sent", stream.name()));
+            let decoded = FrameDeserializer::new(message).deserialize(frame.get_data_u64());
+            let encoded = FrameSerializer::new(message)
+                .and_then(|serializer| serializer.serialize(decoded.attributes()))
+                .unwrap_or_else(|err| panic!("{context}: stream {} {err}", stream.name()));
+            assert_eq!(
+                encoded,
+                frame.get_data_u64(),
+                "{context}: stream {} does not serialize to the sent frame",
+                stream.name()
+            );
+            for (attribute, object_entry) in decoded.attributes().iter().zip(stream.mapping()) {
+                let Some(object_entry) = object_entry else {
+                    continue;
+                };
+                let Some(expected) = values.get(&object_entry.id()) else {
+                    continue;
+                };
+                assert_conforms(
+                    expected,
+                    attribute.value(),
+                    object_entry.ty(),
+                    &format!("{context} stream {}.{}", stream.name(), attribute.name()),
+                );
+            }
+            stream_frames.push((message.bus().id(), frame.clone()));
+        }
+
+        // transaction of all global entries
+        let mut session = Session::new();
+        let mut staged = HashMap::new();
+        let stage_req_bus = network.set_stage_req_message().bus().id();
+        for (object_entry, value) in node
+            .object_entries()
+            .iter()
+            .filter(|object_entry| matches!(object_entry.access(), ObjectEntryAccess::Global))
+            .filter_map(|object_entry| Some((object_entry, random_value(rng, object_entry.ty())?)))
+            .take(MAX_STAGED_ENTRIES)
+        {
+            let (bits, last_fill) = value.get_as_bin::<u32>(object_entry.ty());
+            for frame in stage_request_frames(
+                network,
+                client_id,
+                node.id(),
+                object_entry.id(),
+                &bits,
+                last_fill,
+            ) {
+                session.receive(stage_req_bus, &frame);
+            }
+            staged.insert(object_entry.id(), value);
+        }
+        session.receive(
+            network.set_commit_req_message().bus().id(),
+            &commit_request_frame(
+                network,
+                client_id,
+                node.id(),
+                CommitAction::Commit,
+                staged.len() as u8,
+            ),
+        );
+        let frames = check_object_entries(network, node, binary, session, &staged, context);
+        let set_commit_resp = network.set_commit_resp_message();
+        let commit_resp = frames
+            .iter()
+            .find(|frame| frame_matches(frame, set_commit_resp))
+            .unwrap_or_else(|| panic!("{context} {}: no commit response", node.name()));
+        let commit_resp =
+            FrameDeserializer::new(set_commit_resp).deserialize(commit_resp.get_data_u64());
+        let erno = commit_resp.attribute("header").and_then(|h| h.attribute("erno"));
+        assert!(
+            matches!(erno, Some(Value::EnumValue(erno)) if erno == "Success"),
+            "{context} {}: transaction failed {erno:?}",
+            node.name()
+        );
+
+        // commands
+        for command in node.commands() {
+            let message = command.tx_message();
+            let encoding = message.encoding().expect("commands are encoded");
+            let mut data = 0u64;
+            let mut offset = 0;
+            let Some(arguments) = encoding
+                .attributes()
+                .iter()
+                .map(|attribute| random_value(rng, attribute.ty()))
+                .collect::<Option<Vec<_>>>()
+            else {
+                continue;
+            };
+            for (attribute, value) in encoding.attributes().iter().zip(&arguments) {
+                let (bits, _) = value.get_as_bin::<u64>(attribute.ty());
+                if let Some(bits) = bits.first() {
+                    data |= bits << offset;
+                }
+                offset += attribute.ty().size();
+            }
+            let mut session = Session::new();
+            session.receive(
+                message.bus().id(),
+                &CanFrame::new(
+                    message.id().as_u32(),
+                    message.id().ide(),
+                    false,
+                    message.dlc(),
+                    data,
+                ),
+            );
+            let (_, calls) = session.run(binary);
+            let call = calls
+                .iter()
+                .find(|call| call.split_whitespace().next() == Some(command.name()))
+                .unwrap_or_else(|| panic!("{context}: command {} was not called", command.name()));
+            for ((attribute, expected), token) in encoding
+                .attributes()
+                .iter()
+                .zip(&arguments)
+                .zip(call.split_whitespace().skip(1))
+            {
+                let arg_context = format!("{context} command {}({})", command.name(), attribute.name());
+                let actual = match attribute.ty() as &Type {
+                    Type::Primitive(SignalType::UnsignedInt { size: _ }) => {
+                        Value::UnsignedValue(token.parse().unwrap())
+                    }
+                    Type::Primitive(SignalType::SignedInt { size: _ }) => {
+                        Value::SignedValue(token.parse().unwrap())
+                    }
+                    Type::Primitive(SignalType::Decimal { .. }) => {
+                        Value::RealValue(token.parse().unwrap())
+                    }
+                    Type::Enum {
+                        name: _,
+                        description: _,
+                        size: _,
+                        entries,
+                        visibility: _,
+                    } => {
+                        let raw: u64 = token.parse().unwrap();
+                        Value::EnumValue(
+                            entries
+                                .iter()
+                                .find(|(_, value)| *value == raw)
+                                .map(|(name, _)| name.clone())
+                                .unwrap_or("UNDEFINED".to_owned()),
+                        )
+                    }
+                    _ => continue,
+                };
+                assert_conforms(expected, &actual, attribute.ty(), &arg_context);
+            }
+        }
+    }
+
+    // forward the streams to the receivers and read back the mapped object entries.
+    for node in network.nodes() {
+        if node.rx_streams().is_empty() {
+            continue;
+        }
+        let mut session = Session::new();
+        let mut expected = HashMap::new();
+        for rx_stream in node.rx_streams() {
+            let message = rx_stream.message();
+            let Some((bus_id, frame)) = stream_frames
+                .iter()
+                .rev()
+                .find(|(_, frame)| frame_matches(frame, message))
+            else {
+                continue;
+            };
+            session.receive(*bus_id, frame);
+            let decoded = FrameDeserializer::new(message).deserialize(frame.get_data_u64());
+            for (attribute, object_entry) in decoded.attributes().iter().zip(rx_stream.mapping()) {
+                if let Some(object_entry) = object_entry {
+                    expected.insert(object_entry.id(), attribute.value().clone());
+                }
+            }
+        }
+        check_object_entries(
+            network,
+            node,
+            &harnesses[node.name()],
+            session,
+            &expected,
+            &format!("{context} rx_streams"),
+        );
+    }
+}
+
+#[test]
+fn generated_code_conforms_to_cnl() {
+    for config_path in example_configs() {
+        let network = parse_yaml_config_from_file(config_path.to_str().unwrap()).unwrap();
+        let mut harnesses = HashMap::new();
+        for node in network.nodes() {
+            let Some(binary) = build_harness(&config_path, node.name()) else {
+                return;
+            };
+            harnesses.insert(node.name().to_owned(), binary);
+        }
+        let mut rng = StdRng::seed_from_u64(SEED);
+        for round in 0..ROUNDS {
+            let context = format!("{:?} round {round}", config_path.file_name().unwrap());
+            check_round(&network, &harnesses, &mut rng, &context);
+        }
+    }
+}
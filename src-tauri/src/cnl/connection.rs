@@ -4,6 +4,8 @@ use color_print::cprintln;
 use serde::Serialize;
 use tauri::Manager;
 
+use crate::journal::{self, JournalEvent};
+
 #[derive(Clone, Debug, PartialEq)]
 pub enum ConnectionStatus {
     NetworkConnected,
@@ -57,6 +59,13 @@ impl ConnectionObject {
             return;
         }
         cprintln!("<blue>{:?} -> {:?}</blue>", *lck, connection_status);
+        journal::record(
+            &self.app_handle,
+            JournalEvent::ConnectionStatus {
+                from: format!("{:?}", *lck),
+                to: format!("{connection_status:?}"),
+            },
+        );
 
         *lck = connection_status.clone();
 
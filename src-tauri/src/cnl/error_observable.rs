@@ -1,25 +1,44 @@
-use std::sync::{
-    atomic::{AtomicUsize, Ordering},
-    Arc, Mutex,
+use std::{
+    collections::{BTreeMap, VecDeque},
+    path::Path,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc, Mutex,
+    },
 };
 
+use canzero_appdata::{AlarmLevel, AlarmRule, ErrorEntry};
 use canzero_config::config::Type;
 use color_print::cprintln;
 use serde::Serialize;
 use tauri::Manager;
 use tokio::time::{self, Duration, Instant};
 
-use super::network::{
-    object_entry_object::{database::value::ObjectEntryValue, vlistener::ObjectEntryListener},
-    NetworkObject,
+use super::{
+    alarm,
+    network::{
+        object_entry_object::{
+            database::value::ObjectEntryValue, vlistener::ObjectEntryListener, ObjectEntryObject,
+        },
+        NetworkObject,
+    },
 };
 
 #[derive(Serialize, Clone, Debug)]
-struct Friend {
+pub struct Friend {
     node_name : String,
     object_entry_name : String,
 }
 
+impl Friend {
+    pub fn new(node_name: &str, object_entry_name: &str) -> Self {
+        Self {
+            node_name: node_name.to_owned(),
+            object_entry_name: object_entry_name.to_owned(),
+        }
+    }
+}
+
 #[derive(Serialize, Clone, Debug)]
 pub struct ErrorEvent {
     level: ErrorLevel,
@@ -28,35 +47,213 @@ pub struct ErrorEvent {
     description: Option<String>,
     friend: Option<Friend>,
     timestamp : String,
+    acknowledged_by: Option<String>,
 }
 
 #[derive(PartialEq, Clone, Copy, PartialOrd, Serialize, Debug)]
-enum ErrorLevel {
+pub enum ErrorLevel {
     OK,
     INFO,
     WARNING,
     ERROR,
 }
 
-enum ErrorTaskMsg {
+impl From<AlarmLevel> for ErrorLevel {
+    fn from(level: AlarmLevel) -> Self {
+        match level {
+            AlarmLevel::Info => ErrorLevel::INFO,
+            AlarmLevel::Warning => ErrorLevel::WARNING,
+            AlarmLevel::Error => ErrorLevel::ERROR,
+        }
+    }
+}
+
+#[derive(Serialize, Clone, Copy, Debug)]
+#[serde(rename_all = "lowercase")]
+pub enum ErrorLogKind {
+    Transition,
+    Acknowledged,
+    Reset,
+}
+
+/// Entry of the error log, `from` and `to` are only set for transitions
+/// and `user` only for acknowledgements.
+#[derive(Serialize, Clone, Debug)]
+pub struct ErrorLogEntry {
+    time: String,
+    timestamp: String,
+    label: String,
+    kind: ErrorLogKind,
+    from: Option<ErrorLevel>,
+    to: Option<ErrorLevel>,
+    user: Option<String>,
+}
+
+/// The oldest entries are dropped once the log holds `MAX_ERROR_LOG_ENTRIES`.
+pub type ErrorLog = Arc<Mutex<VecDeque<ErrorLogEntry>>>;
+
+const MAX_ERROR_LOG_ENTRIES: usize = 10_000;
+
+fn push_error_log(log: &ErrorLog, entry: ErrorLogEntry) {
+    let mut log = log.lock().unwrap();
+    if log.len() >= MAX_ERROR_LOG_ENTRIES {
+        log.pop_front();
+    }
+    log.push_back(entry);
+}
+
+pub enum ErrorTaskMsg {
     NewValue,
     StopTask,
 }
 
+struct ErrorStatus {
+    level: ErrorLevel,
+    last_level_update_timestamp: Duration,
+    // highest level since the last acknowledgement, errors stay latched
+    // until they are acknowledged even if the level dropped in between.
+    latched_level: ErrorLevel,
+    last_latched_update_timestamp: Duration,
+    acknowledged_by: Option<String>,
+}
+
 pub struct ErrorState {
     label: String,
-    pub level: Mutex<ErrorLevel>,
-    last_level_update_timestamp : Mutex<Duration>,
-    max_level: Mutex<ErrorLevel>,
-    last_max_level_update_timestamp : Mutex<Duration>,
+    status: Mutex<ErrorStatus>,
     description: Option<String>,
     tx: tokio::sync::mpsc::Sender<ErrorTaskMsg>,
+    log: ErrorLog,
     friend: Option<Friend>,
 }
 
-impl ObjectEntryListener for ErrorState {
+impl ErrorState {
+    pub fn new(
+        label: String,
+        description: Option<String>,
+        friend: Option<Friend>,
+        tx: tokio::sync::mpsc::Sender<ErrorTaskMsg>,
+        log: &ErrorLog,
+    ) -> Self {
+        Self {
+            label,
+            status: Mutex::new(ErrorStatus {
+                level: ErrorLevel::OK,
+                last_level_update_timestamp: Duration::ZERO,
+                latched_level: ErrorLevel::OK,
+                last_latched_update_timestamp: Duration::ZERO,
+                acknowledged_by: None,
+            }),
+            description,
+            tx,
+            log: log.clone(),
+            friend,
+        }
+    }
+
+    pub fn update(&self, current_level: ErrorLevel, timestamp: Duration) {
+        let mut status = self
+            .status
+            .lock()
+            .expect("Failed to acquire error state lock");
+        let prev_level = status.level;
+        if prev_level != current_level {
+            status.level = current_level;
+            status.last_level_update_timestamp = timestamp;
+            // an acknowledged error latches again as soon as its level rises.
+            let recurred = status.acknowledged_by.is_some() && current_level > prev_level;
+            if current_level > status.latched_level || recurred {
+                status.latched_level = current_level;
+                status.last_latched_update_timestamp = timestamp;
+                status.acknowledged_by = None;
+            } else if current_level == ErrorLevel::OK && status.acknowledged_by.is_some() {
+                // acknowledged errors are resolved once they are cleared.
+                status.latched_level = ErrorLevel::OK;
+            }
+            drop(status);
+            self.push_log(
+                ErrorLogKind::Transition,
+                timestamp,
+                Some((prev_level, current_level)),
+                None,
+            );
+            self.notify();
+        }
+    }
+
+    /// Acknowledges the error, returns false if there was nothing to acknowledge.
+    fn acknowledge(&self, user: &str, timestamp: Duration) -> bool {
+        let mut status = self
+            .status
+            .lock()
+            .expect("Failed to acquire error state lock");
+        let unacknowledged = status.acknowledged_by.is_none()
+            && (status.level != ErrorLevel::OK || status.latched_level != ErrorLevel::OK);
+        if !unacknowledged {
+            return false;
+        }
+        status.acknowledged_by = Some(user.to_owned());
+        status.latched_level = status.level;
+        status.last_latched_update_timestamp = status.last_level_update_timestamp;
+        drop(status);
+        self.push_log(ErrorLogKind::Acknowledged, timestamp, None, Some(user));
+        self.notify();
+        true
+    }
+
+    fn reset(&self) {
+        let mut status = self
+            .status
+            .lock()
+            .expect("Failed to acquire error state lock");
+        status.level = ErrorLevel::OK;
+        status.latched_level = ErrorLevel::OK;
+        status.acknowledged_by = None;
+    }
+
+    fn push_log(
+        &self,
+        kind: ErrorLogKind,
+        timestamp: Duration,
+        transition: Option<(ErrorLevel, ErrorLevel)>,
+        user: Option<&str>,
+    ) {
+        push_error_log(
+            &self.log,
+            ErrorLogEntry {
+                time: chrono::Local::now().to_rfc3339(),
+                timestamp: format!("{:.3}s", timestamp.as_secs_f64()),
+                label: self.label.clone(),
+                kind,
+                from: transition.map(|(from, _)| from),
+                to: transition.map(|(_, to)| to),
+                user: user.map(str::to_owned),
+            },
+        );
+    }
+
+    fn notify(&self) {
+        let tx = self.tx.clone();
+        tokio::spawn(async move {
+            tx.send(ErrorTaskMsg::NewValue)
+                .await
+                .expect("Failed to notify error observable task")
+        });
+    }
+}
+
+/// Maps the variants of an error object entry to levels.
+struct ErrorEntryListener {
+    state: Arc<ErrorState>,
+    levels: BTreeMap<String, AlarmLevel>,
+}
+
+impl ObjectEntryListener for ErrorEntryListener {
     fn notify(&self, value: &ObjectEntryValue) {
         let current_level: ErrorLevel = match &value.value {
+            super::frame::Value::EnumValue(level_str) if !self.levels.is_empty() => self
+                .levels
+                .get(level_str)
+                .map_or(ErrorLevel::OK, |level| ErrorLevel::from(*level)),
             super::frame::Value::EnumValue(level_str) => {
                 if level_str == "OK" {
                     ErrorLevel::OK
@@ -76,29 +273,7 @@ impl ObjectEntryListener for ErrorState {
                 ErrorLevel::ERROR
             }
         };
-        let mut level_lck = self
-            .level
-            .lock()
-            .expect("Failed to acquire error state lock");
-        let prev_level = *level_lck;
-        if prev_level != current_level {
-            *level_lck = current_level;
-            *self.last_level_update_timestamp.lock().unwrap() = value.timestamp;
-            let mut max_level_lck = self
-                .max_level
-                .lock()
-                .expect("Failed to acquire max error level mutex");
-            if current_level >= *max_level_lck {
-                *max_level_lck = current_level;
-                *self.last_max_level_update_timestamp.lock().unwrap() = value.timestamp;
-            }
-            let tx = self.tx.clone();
-            tokio::spawn(async move {
-                tx.send(ErrorTaskMsg::NewValue)
-                    .await
-                    .expect("Failed to notify error observable task")
-            });
-        }
+        self.state.update(current_level, value.timestamp);
     }
 }
 
@@ -107,11 +282,65 @@ pub struct ErrorObservable {
     tx: tokio::sync::mpsc::Sender<ErrorTaskMsg>,
     listen_count: AtomicUsize,
     states: Arc<Vec<Arc<ErrorState>>>,
+    log: ErrorLog,
+    timebase: std::time::Instant,
     app_handle: tauri::AppHandle,
 }
 
 impl ErrorObservable {
-    pub async fn new(app_handle: &tauri::AppHandle, network_object: &NetworkObject) -> Self {
+    /// `error_entries` are the object entries reporting errors, without them all
+    /// entries with an enum type named `error_flag` or `error_level` are used.
+    pub async fn new(
+        app_handle: &tauri::AppHandle,
+        network_object: &NetworkObject,
+        timebase: std::time::Instant,
+        error_entries: Option<&[ErrorEntry]>,
+        alarms: &[AlarmRule],
+    ) -> Self {
+        let error_oes = match error_entries {
+            Some(error_entries) => Self::configured_error_entries(network_object, error_entries),
+            None => Self::error_entries_by_type_name(network_object),
+        };
+        let (tx, rx) = tokio::sync::mpsc::channel::<ErrorTaskMsg>(16);
+        let log = ErrorLog::default();
+        let mut states = vec![];
+        for (error_oe, levels) in error_oes {
+            let friend = error_oe.friend().map(|oeo| Friend {
+                node_name: oeo.node().name().to_owned(),
+                object_entry_name: oeo.name().to_owned(),
+            });
+
+            let state = Arc::new(ErrorState::new(
+                format!("{}::{}", error_oe.node_ref().name(), error_oe.name()),
+                error_oe.information().description().map(str::to_owned),
+                friend,
+                tx.clone(),
+                &log,
+            ));
+            error_oe
+                .vlisten(Arc::new(ErrorEntryListener {
+                    state: state.clone(),
+                    levels,
+                }))
+                .await;
+            states.push(state);
+        }
+        states.extend(alarm::register_alarms(alarms, network_object, &tx, &log).await);
+
+        Self {
+            rx: Arc::new(tokio::sync::Mutex::new(rx)),
+            tx,
+            listen_count: AtomicUsize::new(0),
+            states: Arc::new(states),
+            log,
+            timebase,
+            app_handle: app_handle.clone(),
+        }
+    }
+
+    fn error_entries_by_type_name(
+        network_object: &NetworkObject,
+    ) -> Vec<(Arc<ObjectEntryObject>, BTreeMap<String, AlarmLevel>)> {
         let mut error_oes = vec![];
         for node in network_object.nodes() {
             for oe in node.object_entries() {
@@ -124,49 +353,120 @@ impl ErrorObservable {
                         visibility: _,
                     } => {
                         if name == "error_flag" || name == "error_level" {
-                            error_oes.push(oe.clone());
+                            error_oes.push((oe.clone(), BTreeMap::new()));
                         }
                     }
                     _ => continue,
                 }
             }
         }
-        let (tx, rx) = tokio::sync::mpsc::channel::<ErrorTaskMsg>(16);
-        let mut states = vec![];
-        for error_oe in error_oes {
-            let friend = error_oe.friend().map(|oeo| Friend {
-                node_name: oeo.node().name().to_owned(),
-                object_entry_name: oeo.name().to_owned(),
-            });
-
-            let state = Arc::new(ErrorState {
-                label: format!("{}::{}", error_oe.node_ref().name(), error_oe.name()),
-                level: Mutex::new(ErrorLevel::OK),
-                last_level_update_timestamp : Mutex::new(Duration::ZERO),
-                max_level: Mutex::new(ErrorLevel::OK),
-                last_max_level_update_timestamp : Mutex::new(Duration::ZERO),
-                description: error_oe.information().description().map(str::to_owned),
-                tx: tx.clone(),
-                friend,
-            });
-            error_oe.vlisten(state.clone()).await;
-            states.push(state);
-        }
+        error_oes
+    }
 
-        Self {
-            rx: Arc::new(tokio::sync::Mutex::new(rx)),
-            tx,
-            listen_count: AtomicUsize::new(0),
-            states: Arc::new(states),
-            app_handle: app_handle.clone(),
+    fn configured_error_entries(
+        network_object: &NetworkObject,
+        error_entries: &[ErrorEntry],
+    ) -> Vec<(Arc<ObjectEntryObject>, BTreeMap<String, AlarmLevel>)> {
+        let mut error_oes = vec![];
+        for error_entry in error_entries {
+            let Some(oe) = network_object
+                .nodes()
+                .iter()
+                .find(|node| node.name() == error_entry.node)
+                .and_then(|node| {
+                    node.object_entries()
+                        .iter()
+                        .find(|oe| oe.name() == error_entry.object_entry)
+                })
+            else {
+                cprintln!(
+                    "<yellow>Ignoring error entry : unknown object entry {}::{}</yellow>",
+                    error_entry.node,
+                    error_entry.object_entry
+                );
+                continue;
+            };
+            let Type::Enum { entries, .. } = oe.ty() as &Type else {
+                cprintln!(
+                    "<yellow>Ignoring error entry {}::{} : not an enum</yellow>",
+                    error_entry.node,
+                    error_entry.object_entry
+                );
+                continue;
+            };
+            if let Some(variant) = error_entry
+                .levels
+                .keys()
+                .find(|variant| !entries.iter().any(|(name, _)| name == *variant))
+            {
+                cprintln!(
+                    "<yellow>Error entry {}::{} has no variant {variant}</yellow>",
+                    error_entry.node,
+                    error_entry.object_entry
+                );
+            }
+            error_oes.push((oe.clone(), error_entry.levels.clone()));
         }
+        error_oes
     }
 
+    /// Clears all errors without acknowledging them.
     pub async fn reset(&self) {
         for s in self.states.iter() {
-            *s.level.lock().unwrap() = ErrorLevel::OK;
-            *s.max_level.lock().unwrap() = ErrorLevel::OK;
+            s.reset();
+        }
+        push_error_log(
+            &self.log,
+            ErrorLogEntry {
+                time: chrono::Local::now().to_rfc3339(),
+                timestamp: format!("{:.3}s", self.timebase.elapsed().as_secs_f64()),
+                label: "*".to_owned(),
+                kind: ErrorLogKind::Reset,
+                from: None,
+                to: None,
+                user: None,
+            },
+        );
+        let _ = self.tx.send(ErrorTaskMsg::NewValue).await;
+    }
+
+    /// Acknowledges the error with the label or all errors if `label` is None,
+    /// returns the number of acknowledged errors.
+    pub fn acknowledge(&self, label: Option<&str>, user: &str) -> Result<usize, String> {
+        let user = user.trim();
+        if user.is_empty() {
+            return Err("Acknowledging errors requires a user name".to_owned());
+        }
+        if let Some(label) = label {
+            if !self.states.iter().any(|state| state.label == label) {
+                return Err(format!("Unknown error {label}"));
+            }
+        }
+        let timestamp = self.timebase.elapsed();
+        Ok(self
+            .states
+            .iter()
+            .filter(|state| match label {
+                Some(label) => state.label == label,
+                None => true,
+            })
+            .filter(|state| state.acknowledge(user, timestamp))
+            .count())
+    }
+
+    /// The latest transitions, acknowledgements and resets since the CNL was created.
+    pub fn log(&self) -> Vec<ErrorLogEntry> {
+        self.log.lock().unwrap().iter().cloned().collect()
+    }
+
+    pub fn export_log(&self, path: &Path) -> Result<usize, String> {
+        let log = self.log();
+        let mut writer = csv::Writer::from_path(path).map_err(|err| err.to_string())?;
+        for entry in &log {
+            writer.serialize(entry).map_err(|err| err.to_string())?;
         }
+        writer.flush().map_err(|err| err.to_string())?;
+        Ok(log.len())
     }
 
     pub async fn current(&self) -> Vec<ErrorEvent> {
@@ -244,32 +544,31 @@ impl ErrorObservable {
         // filter states
         let mut events: Vec<ErrorEvent> = vec![];
         for state in states.iter() {
-            let level = *state
-                .level
-                .lock()
-                .expect("Failed to acquire error state lock (error_level)");
-            let max_level = *state
-                .max_level
+            let status = state
+                .status
                 .lock()
-                .expect("Failed to acquire error state lock (max_level)");
-            if level != ErrorLevel::OK {
+                .expect("Failed to acquire error state lock");
+            if status.level != ErrorLevel::OK {
                 events.push(ErrorEvent {
-                    level,
-                    timestamp : format!("{}s", state.last_level_update_timestamp.lock().unwrap().as_secs()),
+                    level: status.level,
+                    timestamp : format!("{}s", status.last_level_update_timestamp.as_secs()),
                     deprecated: false,
                     label: state.label.clone(),
                     friend : state.friend.clone(),
                     description: state.description.clone(),
+                    acknowledged_by: status.acknowledged_by.clone(),
                 });
             }
-            if level < max_level {
+            // cleared (or lowered) but not acknowledged yet.
+            if status.level < status.latched_level && status.acknowledged_by.is_none() {
                 events.push(ErrorEvent {
-                    level: max_level,
-                    timestamp : format!("{}s", state.last_max_level_update_timestamp.lock().unwrap().as_secs()),
+                    level: status.latched_level,
+                    timestamp : format!("{}s", status.last_latched_update_timestamp.as_secs()),
                     deprecated: true,
                     label: state.label.clone(),
                     friend : state.friend.clone(),
                     description: state.description.clone(),
+                    acknowledged_by: None,
                 });
             }
         }
@@ -302,3 +601,45 @@ impl ErrorObservable {
             .expect("Failed to emit event 'canzero_errors'");
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn latched(state: &ErrorState) -> (ErrorLevel, Option<String>) {
+        let status = state.status.lock().unwrap();
+        (status.latched_level, status.acknowledged_by.clone())
+    }
+
+    #[tokio::test]
+    async fn acknowledged_error_latches_again_when_it_recurs() {
+        let (tx, _rx) = tokio::sync::mpsc::channel(16);
+        let log = ErrorLog::default();
+        let state = ErrorState::new("node::error".to_owned(), None, None, tx, &log);
+
+        state.update(ErrorLevel::ERROR, Duration::from_millis(1));
+        assert!(state.acknowledge("operator", Duration::from_millis(2)));
+        assert_eq!(latched(&state), (ErrorLevel::ERROR, Some("operator".to_owned())));
+
+        state.update(ErrorLevel::WARNING, Duration::from_millis(3));
+        assert_eq!(latched(&state).1, Some("operator".to_owned()));
+
+        state.update(ErrorLevel::ERROR, Duration::from_millis(4));
+        assert_eq!(latched(&state), (ErrorLevel::ERROR, None));
+        assert!(state.acknowledge("operator", Duration::from_millis(5)));
+    }
+
+    #[tokio::test]
+    async fn error_log_is_bounded() {
+        let (tx, _rx) = tokio::sync::mpsc::channel(16);
+        let log = ErrorLog::default();
+        let state = ErrorState::new("node::error".to_owned(), None, None, tx, &log);
+        for i in 0..MAX_ERROR_LOG_ENTRIES + 10 {
+            let timestamp = Duration::from_millis(i as u64);
+            state.push_log(ErrorLogKind::Transition, timestamp, None, None);
+        }
+        let log = log.lock().unwrap();
+        assert_eq!(log.len(), MAX_ERROR_LOG_ENTRIES);
+        assert_eq!(log.front().unwrap().timestamp, "0.010s");
+    }
+}
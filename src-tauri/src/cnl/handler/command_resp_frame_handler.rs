@@ -0,0 +1,41 @@
+use std::sync::Arc;
+
+use canzero_common::TCanFrame;
+use canzero_config::config::MessageRef;
+
+use crate::cnl::{
+    deserialize::FrameDeserializer,
+    errors::Result,
+    frame::{TFrame, Value},
+    network::command_object::{CommandErno, CommandObject},
+};
+
+pub struct CommandRespFrameHandler {
+    frame_deserializer: FrameDeserializer,
+    command_object: Arc<CommandObject>,
+}
+
+impl CommandRespFrameHandler {
+    pub fn create(command_resp_msg: &MessageRef, command_object: &Arc<CommandObject>) -> Self {
+        Self {
+            frame_deserializer: FrameDeserializer::new(command_resp_msg),
+            command_object: command_object.clone(),
+        }
+    }
+    pub async fn handle(&self, can_frame: &TCanFrame) -> Result<TFrame> {
+        let frame = self
+            .frame_deserializer
+            .deserialize(can_frame.get_data_u64());
+        let Some(Value::EnumValue(erno)) = frame.attribute("erno") else {
+            panic!("DETECTED INVALID CONFIG: invalid format of command_resp : erno missing");
+        };
+        let erno = match erno.as_str() {
+            "Success" => CommandErno::Success,
+            _ => CommandErno::Error,
+        };
+        // other nodes may invoke the command as well, their responses are only traced.
+        self.command_object.push_response(erno);
+
+        Ok(can_frame.new_value(frame))
+    }
+}
@@ -1,5 +1,8 @@
+use self::command_resp_frame_handler::CommandRespFrameHandler;
+use self::external_frame_handler::ExternalFrameHandler;
 use self::get_req_frame_handler::GetReqFrameHandler;
 use self::heartbeat_frame_handler::HeartbeatFrameHandler;
+use self::set_commit_resp_frame_handler::SetCommitRespFrameHandler;
 use self::set_req_frame_handler::SetReqFrameHandler;
 use self::{
     get_resp_frame_handler::GetRespFrameHandler, set_resp_frame_handler::SetRespFrameHandler,
@@ -17,6 +20,9 @@ pub mod stream_frame_handler;
 pub mod get_req_frame_handler;
 pub mod set_req_frame_handler;
 pub mod heartbeat_frame_handler;
+pub mod external_frame_handler;
+pub mod set_commit_resp_frame_handler;
+pub mod command_resp_frame_handler;
 
 pub enum MessageHandler {
     GetRespFrameHandler(GetRespFrameHandler),
@@ -25,6 +31,9 @@ pub enum MessageHandler {
     SetReqFrameHandler(SetReqFrameHandler),
     StreamFrameHandler(StreamFrameHandler),
     HeartbeatFrameHandler(HeartbeatFrameHandler),
+    ExternalFrameHandler(ExternalFrameHandler),
+    SetCommitRespFrameHandler(SetCommitRespFrameHandler),
+    CommandRespFrameHandler(CommandRespFrameHandler),
 }
 
 impl MessageHandler {
@@ -36,6 +45,9 @@ impl MessageHandler {
             MessageHandler::GetReqFrameHandler(handler) => handler.handle(frame).await,
             MessageHandler::SetReqFrameHandler(handler) => handler.handle(frame).await,
             MessageHandler::HeartbeatFrameHandler(handler) => handler.handle(frame).await,
+            MessageHandler::ExternalFrameHandler(handler) => handler.handle(frame).await,
+            MessageHandler::SetCommitRespFrameHandler(handler) => handler.handle(frame).await,
+            MessageHandler::CommandRespFrameHandler(handler) => handler.handle(frame).await,
         }
     }
 }
@@ -0,0 +1,101 @@
+use std::sync::Arc;
+
+use canzero_common::TCanFrame;
+use canzero_config::config::MessageRef;
+
+use crate::cnl::{
+    deserialize::FrameDeserializer,
+    errors::{Error, Result},
+    frame::{Frame, TFrame, Value},
+    network::{
+        node_object::{
+            transaction::{CommitErno, CommitResponse},
+            NodeObject,
+        },
+        NetworkObject,
+    },
+};
+
+struct SetCommitResponseFrame {
+    server_id: u8,
+    client_id: u8,
+    response: CommitResponse,
+}
+
+impl SetCommitResponseFrame {
+    fn create(frame: &Frame) -> Self {
+        let Some(header) = frame.attribute("header") else {
+            panic!("DETECTED INVALID CONFIG: invalid format of set_commit_resp : header missing");
+        };
+        let Some(Value::UnsignedValue(server_id)) = header.attribute("server_id") else {
+            panic!("DETECTED INVALID CONFIG: invalid format of set_commit_resp : header.server_id missing");
+        };
+        let Some(Value::UnsignedValue(client_id)) = header.attribute("client_id") else {
+            panic!("DETECTED INVALID CONFIG: invalid format of set_commit_resp : header.client_id missing");
+        };
+        let Some(Value::EnumValue(erno)) = header.attribute("erno") else {
+            panic!(
+                "DETECTED INVALID CONFIG: invalid format of set_commit_resp : header.erno missing"
+            );
+        };
+        let Some(Value::UnsignedValue(entries)) = header.attribute("entries") else {
+            panic!("DETECTED INVALID CONFIG: invalid format of set_commit_resp : header.entries missing");
+        };
+        let Some(Value::UnsignedValue(failed)) = frame.attribute("failed") else {
+            panic!("DETECTED INVALID CONFIG: invalid format of set_commit_resp : failed missing");
+        };
+        let erno = match erno.as_str() {
+            "Success" => CommitErno::Success,
+            "Aborted" => CommitErno::Aborted,
+            _ => CommitErno::Error,
+        };
+        Self {
+            server_id: *server_id as u8,
+            client_id: *client_id as u8,
+            response: CommitResponse {
+                erno,
+                entries: *entries as u8,
+                failed: *failed as u32,
+            },
+        }
+    }
+}
+
+pub struct SetCommitRespFrameHandler {
+    frame_deserializer: FrameDeserializer,
+    node_objects: Vec<Arc<NodeObject>>,
+    node_id: u8,
+}
+
+impl SetCommitRespFrameHandler {
+    pub fn create(
+        network_object: &Arc<NetworkObject>,
+        set_commit_resp_msg: &MessageRef,
+        node_id: u8,
+    ) -> Self {
+        Self {
+            frame_deserializer: FrameDeserializer::new(set_commit_resp_msg),
+            node_objects: network_object.nodes().clone(),
+            node_id,
+        }
+    }
+    pub async fn handle(&self, can_frame: &TCanFrame) -> Result<TFrame> {
+        let frame = self
+            .frame_deserializer
+            .deserialize(can_frame.get_data_u64());
+        let commit_resp = SetCommitResponseFrame::create(&frame);
+
+        if commit_resp.client_id == self.node_id {
+            let Some(node_object) = self
+                .node_objects
+                .iter()
+                .find(|node| node.id() == commit_resp.server_id)
+            else {
+                return Err(Error::InvalidSetCommitResponseServer);
+            };
+            node_object.push_commit_response(commit_resp.response);
+        }
+
+        Ok(can_frame.new_value(frame))
+    }
+}
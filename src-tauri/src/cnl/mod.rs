@@ -1,28 +1,41 @@
+mod alarm;
+pub mod capture;
 pub mod connection;
 mod deserialize;
+pub mod serialize;
 pub mod errors;
 pub mod frame;
 mod gamepad;
 mod handler;
 pub mod network;
+pub mod parameters;
 mod rx;
+pub mod script;
 pub mod trace;
+pub mod transmitter;
 mod tx;
 pub mod watchdog;
 pub mod error_observable;
 
 pub mod can_adapter;
 
+#[cfg(test)]
+mod conformance_test;
+
 use std::{
     sync::Arc,
     time::{Duration, Instant},
 };
 
 use self::{
-    can_adapter::CanAdapter, connection::{ConnectionObject, ConnectionStatus}, error_observable::ErrorObservable, gamepad::Gamepad, network::{node_object::NodeObject, NetworkObject}, rx::RxCom, trace::TraceObject, tx::TxCom, watchdog::{Watchdog, WatchdogOverlord, WdgTag}
+    can_adapter::{replay::Replay, CanAdapter}, capture::CaptureObject, connection::{ConnectionObject, ConnectionStatus}, error_observable::ErrorObservable, gamepad::Gamepad, network::{
+        node_object::NodeObject,
+        object_entry_object::database::{HistorySession, DEFAULT_HISTORY_WINDOW},
+        NetworkObject,
+    }, rx::RxCom, script::ScriptRunner, trace::TraceObject, transmitter::Transmitter, tx::TxCom, watchdog::{Watchdog, WatchdogOverlord, WdgTag}
 };
 
-use canzero_appdata::{AppData, WdgLevel};
+use canzero_appdata::{AppData, RequestSettings, WdgLevel};
 use canzero_config::config;
 
 // Can Network Layer (CNL)
@@ -44,6 +57,11 @@ pub struct CNL {
     external_watchdog: Option<Watchdog>,
 
     error_observable : ErrorObservable,
+
+    capture: Arc<CaptureObject>,
+    replay: Option<Arc<Replay>>,
+    transmitter: Transmitter,
+    script_runner: ScriptRunner,
 }
 
 impl CNL {
@@ -64,7 +82,27 @@ impl CNL {
 
         let node_id = node_id.unwrap_or(network_config.nodes().len() as u8);
 
-        let trace = Arc::new(TraceObject::create(app_handle));
+        let trace = Arc::new(TraceObject::create(app_handle, network_config));
+
+        let (deadlock_lvl, frontend_lvl, history_window, alarms, error_entries, request_settings) =
+            match AppData::read() {
+                Ok(app_data) => (
+                    app_data.get_deadlock_wdg_lvl(),
+                    app_data.get_frontend_wdg_lvl(),
+                    app_data.get_history_window(),
+                    app_data.get_alarms().clone(),
+                    app_data.get_error_entries().cloned(),
+                    app_data.get_requests().clone(),
+                ),
+                Err(_) => (
+                    WdgLevel::Active,
+                    WdgLevel::Active,
+                    None,
+                    vec![],
+                    None,
+                    RequestSettings::default(),
+                ),
+            };
 
         let tx = Arc::new(TxCom::create(
             &network_config,
@@ -73,36 +111,44 @@ impl CNL {
             timebase,
             &connection_object,
             node_id,
+            &request_settings,
         ));
-
-        let (deadlock_lvl, frontend_lvl) = match AppData::read() {
-            Ok(app_data) => (
-                app_data.get_deadlock_wdg_lvl(),
-                app_data.get_frontend_wdg_lvl(),
-            ),
-            Err(_) => (WdgLevel::Active, WdgLevel::Active),
-        };
+        let replay = can_adapters.iter().find_map(|adapter| adapter.replay().cloned());
         let watchdog_overlord = WatchdogOverlord::new(
             network_config,
             &connection_object,
             &tx,
             deadlock_lvl,
             frontend_lvl,
+            replay.as_ref().map(|replay| replay.suspended()),
             &app_handle,
         );
         let gamepad = Gamepad::create(&tx, network_config);
 
+        let history_session =
+            HistorySession::create(history_window.unwrap_or(DEFAULT_HISTORY_WINDOW));
+
         let network = Arc::new(NetworkObject::create(
             network_config,
             app_handle,
             tx.clone(),
+            &request_settings,
             timebase,
             &watchdog_overlord,
+            &history_session,
         ));
 
+        let capture = Arc::new(CaptureObject::create(
+            network_config,
+            &network,
+            app_handle,
+            timebase,
+        ).await);
+
         let rx = RxCom::create(
             network_config,
             &trace,
+            &capture,
             &network,
             app_handle,
             &can_adapters,
@@ -136,6 +182,11 @@ impl CNL {
         }
 
 
+        // a replay starts as soon as the control panel is ready.
+        if let Some(replay) = &replay {
+            replay.play();
+        }
+
         let connection_object_sync = connection_object.clone();
         match sync_complete{
             Some(sync_complete_signal) => {
@@ -147,15 +198,29 @@ impl CNL {
             None => connection_object_sync.set_status(ConnectionStatus::SyncDone),
         }
 
+        let transmitter = Transmitter::create(network_config, &tx);
+        let script_runner = ScriptRunner::create(network_config, &network, &tx, app_handle);
+
         Self {
             rx,
             tx,
             trace,
-            error_observable : ErrorObservable::new(&app_handle, &network).await,
+            error_observable : ErrorObservable::new(
+                &app_handle,
+                &network,
+                timebase,
+                error_entries.as_deref(),
+                &alarms,
+            )
+            .await,
             network,
             connection_object,
+            capture,
             _watchdog_overlord: watchdog_overlord,
             external_watchdog,
+            replay,
+            transmitter,
+            script_runner,
         }
     }
 
@@ -188,4 +253,21 @@ impl CNL {
     pub fn error_observable(&self) -> &ErrorObservable {
         &self.error_observable
     }
+
+    /// The replay, if the CNL was created from a recording.
+    pub fn replay(&self) -> Option<&Arc<Replay>> {
+        self.replay.as_ref()
+    }
+
+    pub fn capture(&self) -> &Arc<CaptureObject> {
+        &self.capture
+    }
+
+    pub fn transmitter(&self) -> &Transmitter {
+        &self.transmitter
+    }
+
+    pub fn script_runner(&self) -> &ScriptRunner {
+        &self.script_runner
+    }
 }
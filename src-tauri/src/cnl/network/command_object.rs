@@ -1,13 +1,33 @@
+use std::{collections::VecDeque, sync::Mutex, time::Duration};
+
+use canzero_appdata::RequestSettings;
 use canzero_config::config;
+use tokio::sync::oneshot;
+
+/// The discriminants of the `command_resp_erno` enum.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum CommandErno {
+    Success,
+    Error,
+}
 
 pub struct CommandObject {
     command_ref: config::CommandRef,
+    timeout: Duration,
+    // responses don't identify the invocation, the node answers them in order.
+    open: Mutex<VecDeque<oneshot::Sender<CommandErno>>>,
 }
 
 impl CommandObject {
-    pub fn create(command_config: &config::CommandRef, _app_handle: &tauri::AppHandle) -> Self {
+    pub fn create(
+        command_config: &config::CommandRef,
+        _app_handle: &tauri::AppHandle,
+        request_settings: &RequestSettings,
+    ) -> Self {
         Self {
             command_ref: command_config.clone(),
+            timeout: Duration::from_millis(request_settings.timeout_ms),
+            open: Mutex::new(VecDeque::new()),
         }
     }
     pub fn name(&self) -> &str {
@@ -16,10 +36,44 @@ impl CommandObject {
     pub fn description(&self) -> Option<&String> {
         self.command_ref.description()
     }
+    pub fn tx_message(&self) -> &config::Message {
+        self.command_ref.tx_message()
+    }
 
-    #[allow(unused)] //FIXME
-    pub fn build_invoke(&self) -> InvokeCommandBuilder {
-        InvokeCommandBuilder::new(self)
+    /// Waits for the response of an invocation, `send` transmits the command request.
+    pub async fn invoke(
+        &self,
+        send: impl std::future::Future<Output = Result<(), String>>,
+    ) -> Result<(), String> {
+        let (done, response) = oneshot::channel();
+        self.open.lock().unwrap().push_back(done);
+        let result = match send.await {
+            Ok(()) => tokio::time::timeout(self.timeout, response).await,
+            Err(err) => {
+                drop(response);
+                self.open.lock().unwrap().retain(|done| !done.is_closed());
+                return Err(err);
+            }
+        };
+        match result {
+            Ok(Ok(CommandErno::Success)) => Ok(()),
+            Ok(Ok(CommandErno::Error)) => Err(format!("Command {} failed", self.name())),
+            _ => {
+                self.open.lock().unwrap().retain(|done| !done.is_closed());
+                Err(format!("Command {} timed out", self.name()))
+            }
+        }
+    }
+
+    /// Completes the oldest pending invocation, returns false if nobody waits for the response.
+    pub fn push_response(&self, erno: CommandErno) -> bool {
+        let mut open = self.open.lock().unwrap();
+        while let Some(done) = open.pop_front() {
+            if done.send(erno).is_ok() {
+                return true;
+            }
+        }
+        false
     }
 
     pub async fn deadlock_watchdog(&self) {
@@ -27,20 +81,52 @@ impl CommandObject {
     }
 }
 
-pub struct InvokeCommandBuilder {
-}
+#[cfg(test)]
+mod tests {
+    use canzero_yaml::parse_yaml_config_from_file;
+
+    use super::*;
 
-impl InvokeCommandBuilder {
-    pub fn new(_command_object: &CommandObject) -> Self {
-        InvokeCommandBuilder {
+    fn command_object(timeout_ms: u64) -> CommandObject {
+        let network = parse_yaml_config_from_file("lib/codegen/examples/conformance.yaml").unwrap();
+        let command = network.nodes()[0].commands()[0].clone();
+        CommandObject {
+            command_ref: command,
+            timeout: Duration::from_millis(timeout_ms),
+            open: Mutex::new(VecDeque::new()),
         }
     }
-}
 
+    #[tokio::test]
+    async fn invoke_waits_for_the_response() {
+        let command = command_object(1000);
+        let (result, ()) = tokio::join!(command.invoke(async { Ok(()) }), async {
+            tokio::task::yield_now().await;
+            assert!(command.push_response(CommandErno::Success));
+        });
+        assert_eq!(result, Ok(()));
+        assert!(!command.push_response(CommandErno::Success));
+    }
+
+    #[tokio::test]
+    async fn error_and_timeout_fail_the_invocation() {
+        let command = command_object(20);
+        let (result, ()) = tokio::join!(command.invoke(async { Ok(()) }), async {
+            tokio::task::yield_now().await;
+            command.push_response(CommandErno::Error);
+        });
+        assert!(result.is_err());
+
+        assert!(command.invoke(async { Ok(()) }).await.is_err());
+        // the timed out invocation doesn't take the next response.
+        assert!(!command.push_response(CommandErno::Success));
+    }
 
-#[allow(unused)] //FIXME
-enum CommandEvent {
-    Resp,
-    Req(u32),
-    Timeout(u32),
+    #[tokio::test]
+    async fn failed_send_is_not_pending() {
+        let command = command_object(1000);
+        let result = command.invoke(async { Err("bus down".to_owned()) }).await;
+        assert_eq!(result, Err("bus down".to_owned()));
+        assert!(!command.push_response(CommandErno::Success));
+    }
 }
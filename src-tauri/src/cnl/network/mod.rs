@@ -1,6 +1,7 @@
 use std::{sync::Arc, time::Instant};
 
-use self::node_object::NodeObject;
+use self::{node_object::NodeObject, object_entry_object::database::HistorySession};
+use canzero_appdata::RequestSettings;
 use canzero_config::config;
 
 use super::{tx::TxCom, watchdog::WatchdogOverlord};
@@ -8,6 +9,7 @@ use super::{tx::TxCom, watchdog::WatchdogOverlord};
 pub mod command_object;
 pub mod node_object;
 pub mod object_entry_object;
+pub mod request_scheduler;
 
 pub struct NetworkObject {
     nodes: Vec<Arc<NodeObject>>,
@@ -18,8 +20,10 @@ impl NetworkObject {
         network_config: &config::NetworkRef,
         app_handle: &tauri::AppHandle,
         tx_com: Arc<TxCom>,
+        request_settings: &RequestSettings,
         timebase: Instant,
         watchdog_overloard: &WatchdogOverlord,
+        history_session: &HistorySession,
     ) -> Self {
         Self {
             nodes: network_config
@@ -31,8 +35,10 @@ impl NetworkObject {
                         node_config,
                         app_handle,
                         tx_com.clone(),
+                        request_settings,
                         timebase,
                         watchdog_overloard,
+                        history_session,
                     ))
                 })
                 .collect(),
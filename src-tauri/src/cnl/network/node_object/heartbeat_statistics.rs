@@ -0,0 +1,221 @@
+use std::time::Instant;
+
+use serde::Serialize;
+
+use crate::cnl::watchdog::WdgStatus;
+
+/// Intervals longer than this multiple of the mean interval are counted as missed beats,
+/// they are not part of the interval statistics.
+const MISSED_BEAT_FACTOR: f64 = 1.5;
+/// Number of intervals required to estimate the mean before beats are counted as missed.
+const MIN_INTERVALS: u64 = 8;
+
+/// Statistics of the heartbeats of a node on one bus.
+pub struct HeartbeatStatistics {
+    bus_id: u32,
+    beats: u64,
+    missed: u64,
+    // running mean and sum of squared deviations of the intervals in ms (Welford).
+    intervals: u64,
+    mean: f64,
+    m2: f64,
+    last_seen: Option<(Instant, chrono::DateTime<chrono::Local>)>,
+    alive_since: Option<Instant>,
+}
+
+#[derive(Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct HeartbeatStatus {
+    pub bus: String,
+    pub status: WdgStatus,
+    pub statistics: HeartbeatStatisticsSnapshot,
+}
+
+#[derive(Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct HeartbeatStatisticsSnapshot {
+    pub beats: u64,
+    pub missed_beats: u64,
+    pub interval_mean_ms: Option<f64>,
+    /// Standard deviation of the intervals.
+    pub interval_jitter_ms: Option<f64>,
+    pub last_seen: Option<String>,
+    /// Time since the heartbeats resumed after the node was timed out or inactive,
+    /// which usually is the time since the last reset of the node.
+    pub uptime_ms: Option<u64>,
+}
+
+impl HeartbeatStatistics {
+    pub fn new(bus_id: u32) -> Self {
+        Self {
+            bus_id,
+            beats: 0,
+            missed: 0,
+            intervals: 0,
+            mean: 0.0,
+            m2: 0.0,
+            last_seen: None,
+            alive_since: None,
+        }
+    }
+
+    pub fn bus_id(&self) -> u32 {
+        self.bus_id
+    }
+
+    /// Records a heartbeat, `status` is the status of the watchdog before the heartbeat.
+    pub fn record(&mut self, status: WdgStatus, unregister: bool) {
+        self.record_at(status, unregister, Instant::now());
+    }
+
+    fn record_at(&mut self, status: WdgStatus, unregister: bool, now: Instant) {
+        self.beats += 1;
+        let gap = self
+            .last_seen
+            .map(|(last, _)| now.duration_since(last).as_secs_f64() * 1000.0);
+        self.last_seen = Some((now, chrono::Local::now()));
+        if unregister {
+            self.alive_since = None;
+            return;
+        }
+        match (status, gap) {
+            (WdgStatus::Good, Some(interval)) => {
+                if self.missed_beats(interval) == 0 {
+                    self.intervals += 1;
+                    let delta = interval - self.mean;
+                    self.mean += delta / self.intervals as f64;
+                    self.m2 += delta * (interval - self.mean);
+                } else {
+                    self.missed += self.missed_beats(interval);
+                }
+            }
+            (WdgStatus::TimedOut, Some(interval)) => {
+                self.missed += self.missed_beats(interval);
+                self.alive_since = Some(now);
+            }
+            _ => self.alive_since = Some(now),
+        }
+    }
+
+    fn missed_beats(&self, interval: f64) -> u64 {
+        if self.intervals < MIN_INTERVALS || interval <= self.mean * MISSED_BEAT_FACTOR {
+            return 0;
+        }
+        ((interval / self.mean).round() as u64)
+            .saturating_sub(1)
+            .max(1)
+    }
+
+    pub fn snapshot(&self, status: WdgStatus) -> HeartbeatStatisticsSnapshot {
+        self.snapshot_at(status, Instant::now())
+    }
+
+    fn snapshot_at(&self, status: WdgStatus, now: Instant) -> HeartbeatStatisticsSnapshot {
+        HeartbeatStatisticsSnapshot {
+            beats: self.beats,
+            missed_beats: self.missed,
+            interval_mean_ms: (self.intervals != 0).then_some(self.mean),
+            interval_jitter_ms: (self.intervals > 1)
+                .then(|| (self.m2 / (self.intervals - 1) as f64).sqrt()),
+            last_seen: self.last_seen.map(|(_, time)| time.to_rfc3339()),
+            uptime_ms: match status {
+                WdgStatus::Good => self
+                    .alive_since
+                    .map(|since| now.duration_since(since).as_millis() as u64),
+                _ => None,
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::*;
+
+    /// Records heartbeats after the `intervals` in ms, starting with a first heartbeat at `t0`.
+    fn beat(statistics: &mut HeartbeatStatistics, t0: Instant, intervals: &[u64]) -> Instant {
+        let mut now = t0;
+        for interval in intervals {
+            now += Duration::from_millis(*interval);
+            statistics.record_at(WdgStatus::Good, false, now);
+        }
+        now
+    }
+
+    fn started() -> (HeartbeatStatistics, Instant) {
+        let mut statistics = HeartbeatStatistics::new(0);
+        let t0 = Instant::now();
+        statistics.record_at(WdgStatus::InActive, false, t0);
+        (statistics, t0)
+    }
+
+    #[test]
+    fn mean_and_jitter_of_the_intervals() {
+        let (mut statistics, t0) = started();
+        beat(&mut statistics, t0, &[90, 110, 90, 110]);
+        let snapshot = statistics.snapshot_at(WdgStatus::Good, t0);
+        assert_eq!(snapshot.beats, 5);
+        assert_eq!(snapshot.missed_beats, 0);
+        assert!((snapshot.interval_mean_ms.unwrap() - 100.0).abs() < 1e-9);
+        // sample standard deviation of ±10ms.
+        let jitter = (400.0f64 / 3.0).sqrt();
+        assert!((snapshot.interval_jitter_ms.unwrap() - jitter).abs() < 1e-9);
+    }
+
+    #[test]
+    fn no_statistics_without_intervals() {
+        let (statistics, t0) = started();
+        let snapshot = statistics.snapshot_at(WdgStatus::Good, t0);
+        assert_eq!(snapshot.beats, 1);
+        assert!(snapshot.interval_mean_ms.is_none());
+        assert!(snapshot.interval_jitter_ms.is_none());
+        assert!(snapshot.last_seen.is_some());
+    }
+
+    #[test]
+    fn long_intervals_count_as_missed_beats() {
+        let (mut statistics, t0) = started();
+        let now = beat(&mut statistics, t0, &[100; MIN_INTERVALS as usize]);
+        // the beats after 200ms and 300ms were missed.
+        beat(&mut statistics, now, &[300, 100, 140]);
+        let snapshot = statistics.snapshot_at(WdgStatus::Good, now);
+        assert_eq!(snapshot.missed_beats, 2);
+        assert_eq!(statistics.intervals, MIN_INTERVALS + 2);
+        assert!(snapshot.interval_mean_ms.unwrap() < 110.0);
+    }
+
+    #[test]
+    fn long_intervals_are_not_missed_before_the_mean_is_known() {
+        let (mut statistics, t0) = started();
+        beat(&mut statistics, t0, &[100, 100, 500]);
+        assert_eq!(statistics.snapshot_at(WdgStatus::Good, t0).missed_beats, 0);
+    }
+
+    #[test]
+    fn uptime_restarts_after_a_timeout() {
+        let (mut statistics, t0) = started();
+        let now = beat(&mut statistics, t0, &[100; MIN_INTERVALS as usize]);
+        let snapshot = statistics.snapshot_at(WdgStatus::Good, now);
+        assert_eq!(snapshot.uptime_ms, Some(800));
+
+        let resumed = now + Duration::from_millis(1000);
+        statistics.record_at(WdgStatus::TimedOut, false, resumed);
+        let snapshot = statistics.snapshot_at(WdgStatus::Good, resumed + Duration::from_millis(50));
+        assert_eq!(snapshot.uptime_ms, Some(50));
+        assert_eq!(snapshot.missed_beats, 9);
+        assert!(statistics
+            .snapshot_at(WdgStatus::TimedOut, resumed)
+            .uptime_ms
+            .is_none());
+    }
+
+    #[test]
+    fn unregister_clears_the_uptime() {
+        let (mut statistics, t0) = started();
+        let now = t0 + Duration::from_millis(100);
+        statistics.record_at(WdgStatus::Good, true, now);
+        assert!(statistics.snapshot_at(WdgStatus::Good, now).uptime_ms.is_none());
+    }
+}
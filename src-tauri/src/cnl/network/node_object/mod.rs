@@ -1,24 +1,53 @@
 use std::{
-    sync::Arc,
-    time::{Duration, Instant},
+    sync::{Arc, Mutex},
+    time::Instant,
 };
 
-use canzero_config::config::{self, bus::BusRef};
+use canzero_appdata::RequestSettings;
+use canzero_config::config::{
+    self, bus::BusRef, message::MessageUsage, make_config_ref, MessageRef, ObjectEntry, ObjectEntryAccess,
+    Type, Visibility,
+};
+use serde::Serialize;
 
 use crate::{
     cnl::{
+        frame::Value,
         tx::TxCom,
-        watchdog::{Watchdog, WatchdogOverlord, WdgStatus, WdgTag},
+        watchdog::{Watchdog, WatchdogOverlord, WdgTag},
     },
-    notification::notify_error,
+    journal::{self, JournalEvent},
+    notification::{notify_error, notify_info},
 };
 
-use self::{heartbeat_observable::HeartbeatObservable, latest::NodeLatestObservable};
+use self::{
+    heartbeat_observable::HeartbeatObservable,
+    heartbeat_statistics::{HeartbeatStatistics, HeartbeatStatus},
+    latest::NodeLatestObservable,
+    transaction::{
+        CommitErno, CommitResponse, EntryStatus, StagedValue, TransactionEntry,
+        TransactionResult, Transactions, MAX_STAGED_ENTRIES,
+    },
+};
 
-use super::{command_object::CommandObject, object_entry_object::ObjectEntryObject};
+use super::{
+    command_object::CommandObject,
+    object_entry_object::{database::HistorySession, ObjectEntryObject},
+    request_scheduler::RequestScheduler,
+};
 
 pub mod heartbeat_observable;
+pub mod heartbeat_statistics;
 pub mod latest;
+pub mod transaction;
+
+#[derive(Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct NodeDiagnostics {
+    pub node: String,
+    pub heartbeat_timeout_ms: u64,
+    pub heartbeats: Vec<HeartbeatStatus>,
+}
 
 pub struct NodeObject {
     node_ref: config::NodeRef,
@@ -27,7 +56,60 @@ pub struct NodeObject {
     latest_observable: NodeLatestObservable,
     heartbeat_wdgs: Vec<Watchdog>,
     heartbeat_observables: Vec<HeartbeatObservable>,
+    heartbeat_statistics: Vec<Mutex<HeartbeatStatistics>>,
+    scheduler: Arc<RequestScheduler>,
+    transactions: Transactions,
     app_handle: tauri::AppHandle,
+    external: bool,
+}
+
+/// Nodes which only transmit external (DBC) messages are not canzero nodes.
+/// They have no object dictionary, no heartbeat and don't answer requests.
+///
+/// The builder gives every node the responses of the configuration protocol and
+/// a heartbeat, only the remaining messages tell them apart.
+pub fn is_external_node(node_config: &config::NodeRef) -> bool {
+    let mut messages = node_config
+        .tx_messages()
+        .iter()
+        .filter(|msg| !msg.usage().is_configuration())
+        .filter(|msg| !matches!(msg.usage(), MessageUsage::Heartbeat))
+        .peekable();
+    messages.peek().is_some()
+        && messages.all(|msg| matches!(msg.usage(), MessageUsage::External { interval: _ }))
+}
+
+/// Virtual object entries of an external node, one read-only entry for
+/// every signal of its external messages.
+pub fn external_object_entries(node_config: &config::NodeRef) -> Vec<config::ObjectEntryRef> {
+    let mut object_entries = vec![];
+    for message in node_config
+        .tx_messages()
+        .iter()
+        .filter(|msg| matches!(msg.usage(), MessageUsage::External { interval: _ }))
+    {
+        for signal in message.signals() {
+            let object_entry = make_config_ref(ObjectEntry::new(
+                external_object_entry_name(signal),
+                signal.description().map(str::to_owned),
+                None,
+                None,
+                object_entries.len() as u32,
+                make_config_ref(Type::Primitive(signal.ty().clone())),
+                ObjectEntryAccess::Const,
+                Visibility::Global,
+            ));
+            object_entry.__set_node(node_config.clone());
+            object_entries.push(object_entry);
+        }
+    }
+    object_entries
+}
+
+/// Name of the virtual object entry, which holds a signal of an external message.
+/// The config prefixes signal names with their message, which makes them unique.
+pub fn external_object_entry_name(signal: &config::SignalRef) -> String {
+    signal.name().to_owned()
 }
 
 impl NodeObject {
@@ -36,9 +118,23 @@ impl NodeObject {
         node_config: &config::NodeRef,
         app_handle: &tauri::AppHandle,
         tx_com: Arc<TxCom>,
+        request_settings: &RequestSettings,
         timebase: Instant,
         watchdog_overloard: &WatchdogOverlord,
+        history_session: &HistorySession,
     ) -> Self {
+        let scheduler = Arc::new(RequestScheduler::new(&tx_com, request_settings, app_handle));
+        let transactions = Transactions::new(&tx_com, node_config.id(), request_settings);
+        if is_external_node(node_config) {
+            return Self::create_external(
+                node_config,
+                app_handle,
+                scheduler,
+                transactions,
+                timebase,
+                history_session,
+            );
+        }
         let object_entries = node_config
             .object_entries()
             .iter()
@@ -48,22 +144,26 @@ impl NodeObject {
                     node_config,
                     object_entry,
                     app_handle,
-                    tx_com.clone(),
+                    &scheduler,
+                    request_settings.verifies(node_config.name(), object_entry.name()),
                     timebase,
+                    history_session,
                 ))
             })
             .collect();
         let node_name = node_config.name();
         let mut heartbeat_wdgs = vec![];
         let mut heartbeat_observables = vec![];
+        let mut heartbeat_statistics = vec![];
         for bus in node_config.buses() {
             let wdg = watchdog_overloard.register(
                 WdgTag::Heartbeat {
                     node_id: node_config.id(),
                     bus_id: bus.id(),
                 },
-                Duration::from_millis(200),
+                node_config.heartbeat_timeout(),
             );
+            tx_com.register_heartbeat(node_config.id(), bus.id(), wdg.status_rx().clone());
             let heartbeat_observable = HeartbeatObservable::new(
                 &format!("{node_name}_{}_heartbeat", bus.name()),
                 app_handle,
@@ -72,6 +172,7 @@ impl NodeObject {
             );
             heartbeat_wdgs.push(wdg);
             heartbeat_observables.push(heartbeat_observable);
+            heartbeat_statistics.push(Mutex::new(HeartbeatStatistics::new(bus.id())));
         }
         Self {
             latest_observable: NodeLatestObservable::new(
@@ -83,12 +184,59 @@ impl NodeObject {
             commands: node_config
                 .commands()
                 .iter()
-                .map(|command| Arc::new(CommandObject::create(command, app_handle)))
+                .map(|command| {
+                    Arc::new(CommandObject::create(command, app_handle, request_settings))
+                })
                 .collect(),
             node_ref: node_config.clone(),
             heartbeat_wdgs,
             heartbeat_observables,
+            heartbeat_statistics,
+            scheduler,
+            transactions,
+            app_handle: app_handle.clone(),
+            external: false,
+        }
+    }
+
+    /// Pseudo-node of a transmitter of external messages, every signal of
+    /// its messages is represented as a read-only virtual object entry.
+    fn create_external(
+        node_config: &config::NodeRef,
+        app_handle: &tauri::AppHandle,
+        scheduler: Arc<RequestScheduler>,
+        transactions: Transactions,
+        timebase: Instant,
+        history_session: &HistorySession,
+    ) -> Self {
+        let object_entries: Vec<_> = external_object_entries(node_config)
+            .iter()
+            .map(|object_entry| {
+                Arc::new(ObjectEntryObject::create_external(
+                    object_entry,
+                    app_handle,
+                    &scheduler,
+                    timebase,
+                    history_session,
+                ))
+            })
+            .collect();
+        Self {
+            latest_observable: NodeLatestObservable::new(
+                &object_entries,
+                &format!("{}_latest", node_config.name()),
+                app_handle,
+            ),
+            object_entries,
+            commands: vec![],
+            node_ref: node_config.clone(),
+            heartbeat_wdgs: vec![],
+            heartbeat_observables: vec![],
+            heartbeat_statistics: vec![],
+            scheduler,
+            transactions,
             app_handle: app_handle.clone(),
+            external: true,
         }
     }
     pub fn id(&self) -> u8 {
@@ -109,6 +257,12 @@ impl NodeObject {
     pub fn buses(&self) -> &Vec<BusRef> {
         self.node_ref.buses()
     }
+    pub fn is_external(&self) -> bool {
+        self.external
+    }
+    pub fn tx_messages(&self) -> &Vec<MessageRef> {
+        self.node_ref.tx_messages()
+    }
     pub async fn listen(&self) -> String {
         self.latest_observable.listen().await
     }
@@ -120,12 +274,109 @@ impl NodeObject {
         for oe in &self.object_entries {
             oe.deadlock_watchdog().await;
         }
+        self.scheduler.deadlock_watchdog();
         // for c in &self.commands {
         //     c.deadlock_watchdog().await;
         // }
         // let _ = self.latest_observable.deadlock_watchdog().await;
     }
 
+    /// Stages all values and commits them with a single request, the node
+    /// applies either all or none of them.
+    pub async fn set_atomic(&self, values: Vec<(String, Value)>) -> Result<TransactionResult, String> {
+        if self.external {
+            return Err(format!("{} is an external node", self.name()));
+        }
+        if values.is_empty() || values.len() > MAX_STAGED_ENTRIES {
+            return Err(format!(
+                "A transaction sets 1 to {MAX_STAGED_ENTRIES} object entries, got {}",
+                values.len()
+            ));
+        }
+        let mut object_entries = vec![];
+        for (name, _) in &values {
+            let Some(object_entry) = self.object_entries.iter().find(|oe| oe.name() == name) else {
+                return Err(format!("{}::{name} does not exist", self.name()));
+            };
+            if !matches!(object_entry.access(), ObjectEntryAccess::Global) {
+                return Err(format!("{}::{name} can't be set", self.name()));
+            }
+            if object_entries.iter().any(|oe: &Arc<ObjectEntryObject>| oe.id() == object_entry.id()) {
+                return Err(format!("{}::{name} is set twice", self.name()));
+            }
+            object_entries.push(object_entry.clone());
+        }
+
+        let mut staged = vec![];
+        let mut entries = vec![];
+        for (object_entry, (_, value)) in object_entries.iter().zip(values) {
+            let (bits, last_fill) = value.get_as_bin(object_entry.ty());
+            staged.push(StagedValue {
+                object_entry_id: object_entry.id(),
+                bits,
+                last_fill,
+            });
+            entries.push(TransactionEntry {
+                object_entry: object_entry.name().to_owned(),
+                old: object_entry.latest_value().await,
+                new: value,
+                status: EntryStatus::NotApplied,
+            });
+        }
+
+        let response = self.transactions.commit(staged).await;
+        if let Some(response) = &response {
+            for (i, entry) in entries.iter_mut().enumerate() {
+                entry.status = response.status(i, object_entries.len());
+            }
+            if response.erno == CommitErno::Success {
+                for (object_entry, entry) in object_entries.iter().zip(&entries) {
+                    object_entry
+                        .push_value(entry.new.clone(), &object_entry.now())
+                        .await;
+                }
+            }
+        }
+        let result = TransactionResult {
+            node: self.name().to_owned(),
+            outcome: response.map(|response| response.erno),
+            entries,
+        };
+        journal::record(&self.app_handle, JournalEvent::Transaction(result.clone()));
+        match result.outcome {
+            Some(CommitErno::Success) => notify_info(
+                &self.app_handle,
+                "Transaction Committed",
+                &format!("{} object entries of {} were set", result.entries.len(), self.name()),
+                chrono::Local::now(),
+            ),
+            Some(_) => notify_error(
+                &self.app_handle,
+                "Transaction Failed",
+                &format!("{} rejected the transaction, no object entry was set", self.name()),
+                chrono::Local::now(),
+            ),
+            None => notify_error(
+                &self.app_handle,
+                "Transaction Timeout",
+                &format!("{} did not answer the commit request", self.name()),
+                chrono::Local::now(),
+            ),
+        }
+        Ok(result)
+    }
+
+    pub fn push_commit_response(&self, response: CommitResponse) {
+        if !self.transactions.push_response(response) {
+            notify_info(
+                &self.app_handle,
+                "Ignoring Commit Response",
+                &format!("Commit response of {} came in after timeout", self.name()),
+                chrono::Local::now(),
+            );
+        }
+    }
+
     pub async fn reset_heartbeat_wdg(&self, bus_id: u32, unregister: bool, ticks_next: u8) {
         if let Some(wdg) = self.heartbeat_wdgs.iter().find(|wdg| {
             wdg.tag()
@@ -134,6 +385,13 @@ impl NodeObject {
                     bus_id,
                 }
         }) {
+            if let Some(statistics) = self
+                .heartbeat_statistics
+                .iter()
+                .find(|statistics| statistics.lock().unwrap().bus_id() == bus_id)
+            {
+                statistics.lock().unwrap().record(wdg.status(), unregister);
+            }
             wdg.reset(unregister, Some(std::cmp::min(ticks_next + 20, 127))).await;
                 // the +10 helps with tcp buffering for control panel, while nodes themselves
                 // can still react quickly.
@@ -147,7 +405,7 @@ impl NodeObject {
         );
     }
 
-    pub fn get_heartbeat_status(&self, bus_id: u32) -> Result<WdgStatus, ()> {
+    pub fn get_heartbeat_status(&self, bus_id: u32) -> Result<HeartbeatStatus, ()> {
         let wdg = self.heartbeat_wdgs.iter().find(|wdg| match wdg.tag() {
             WdgTag::FrontendWdg => return false,
             WdgTag::DeadlockWdg => return false,
@@ -156,9 +414,31 @@ impl NodeObject {
                 bus_id: id1,
             } => return *id1 == bus_id,
         });
-        match wdg {
-            Some(wdg) => Ok(wdg.status()),
-            None => Err(()),
+        let statistics = self
+            .heartbeat_statistics
+            .iter()
+            .map(|statistics| statistics.lock().unwrap())
+            .find(|statistics| statistics.bus_id() == bus_id);
+        let bus = self.buses().iter().find(|bus| bus.id() == bus_id);
+        match (wdg, statistics, bus) {
+            (Some(wdg), Some(statistics), Some(bus)) => Ok(HeartbeatStatus {
+                bus: bus.name().to_owned(),
+                status: wdg.status(),
+                statistics: statistics.snapshot(wdg.status()),
+            }),
+            _ => Err(()),
+        }
+    }
+
+    pub fn diagnostics(&self) -> NodeDiagnostics {
+        NodeDiagnostics {
+            node: self.name().to_owned(),
+            heartbeat_timeout_ms: self.node_ref.heartbeat_timeout().as_millis() as u64,
+            heartbeats: self
+                .buses()
+                .iter()
+                .filter_map(|bus| self.get_heartbeat_status(bus.id()).ok())
+                .collect(),
         }
     }
 
@@ -187,3 +467,127 @@ impl NodeObject {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use canzero_yaml::parse_yaml_config_from_file;
+
+    use crate::cnl::network::object_entry_object::set_rejection;
+
+    use super::*;
+
+    const BMS_DBC: &str = r#"VERSION ""
+
+NS_ :
+
+BS_:
+
+BU_: BMS
+
+BO_ 768 bms_status: 3 BMS
+ SG_ voltage : 0|16@1+ (0.01,0) [0|655.35] "V" Vector__XXX
+ SG_ soc : 16|8@1+ (1,0) [0|100] "%" Vector__XXX
+"#;
+
+    fn network(name: &str) -> config::NetworkRef {
+        let dir = std::env::temp_dir().join(format!("canzero-external-node-{name}"));
+        std::fs::create_dir_all(&dir).unwrap();
+        let dbc_path = dir.join("bms.dbc");
+        std::fs::write(&dbc_path, BMS_DBC).unwrap();
+        let config_path = dir.join("network.yaml");
+        std::fs::write(
+            &config_path,
+            format!(
+                "buses:
+  can0:
+    baudrate: 1000000
+    database: {}
+nodes:
+  primary:
+    object_dictionary:
+      flag:
+        type: u1
+",
+                dbc_path.display()
+            ),
+        )
+        .unwrap();
+        parse_yaml_config_from_file(config_path.to_str().unwrap()).unwrap()
+    }
+
+    fn node(network: &config::NetworkRef, name: &str) -> config::NodeRef {
+        network
+            .nodes()
+            .iter()
+            .find(|node| node.name() == name)
+            .cloned()
+            .unwrap()
+    }
+
+    #[test]
+    fn dbc_transmitters_are_external_nodes() {
+        let network = network("dbc");
+        assert!(is_external_node(&node(&network, "BMS")));
+        assert!(!is_external_node(&node(&network, "primary")));
+        assert!(network
+            .nodes()
+            .iter()
+            .all(|node| node.name() != "Vector__XXX"));
+    }
+
+    #[test]
+    fn signals_become_virtual_object_entries() {
+        let network = network("signals");
+        let object_entries = external_object_entries(&node(&network, "BMS"));
+        let names: Vec<&str> = object_entries.iter().map(|oe| oe.name()).collect();
+        assert_eq!(names, vec!["bms_status_voltage", "bms_status_soc"]);
+        for (id, object_entry) in object_entries.iter().enumerate() {
+            assert_eq!(object_entry.id(), id as u32);
+            assert_eq!(object_entry.node().name(), "BMS");
+            assert!(matches!(object_entry.access(), ObjectEntryAccess::Const));
+        }
+        assert!(matches!(
+            **object_entries[0].ty(),
+            Type::Primitive(config::SignalType::Decimal { size: 16, .. })
+        ));
+    }
+
+    #[test]
+    fn external_messages_resolve_to_virtual_object_entries() {
+        let network = network("external");
+        let message = network
+            .messages()
+            .iter()
+            .find(|message| message.name() == "bms_status")
+            .unwrap();
+        // the rx handler looks up the transmitter and the entry of each signal by name.
+        let transmitter = network
+            .nodes()
+            .iter()
+            .find(|node| {
+                is_external_node(node)
+                    && node
+                        .tx_messages()
+                        .iter()
+                        .any(|m| m.name() == message.name())
+            })
+            .unwrap();
+        assert_eq!(transmitter.name(), "BMS");
+        let object_entries = external_object_entries(transmitter);
+        for signal in message.signals() {
+            let name = external_object_entry_name(signal);
+            assert!(object_entries.iter().any(|oe| oe.name() == name), "{name}");
+        }
+    }
+
+    #[test]
+    fn virtual_object_entries_reject_set_requests() {
+        let network = network("virtual");
+        for object_entry in external_object_entries(&node(&network, "BMS")) {
+            assert!(set_rejection(&object_entry).is_some());
+        }
+        for object_entry in node(&network, "primary").object_entries() {
+            assert_eq!(set_rejection(object_entry), None);
+        }
+    }
+}
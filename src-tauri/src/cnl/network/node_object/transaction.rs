@@ -0,0 +1,152 @@
+use std::{
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+use canzero_appdata::RequestSettings;
+use serde::Serialize;
+use tokio::sync::oneshot;
+
+use crate::cnl::{
+    frame::Value,
+    tx::{CommitAction, TxCom},
+};
+
+pub use canzero_config::builder::network_builder::MAX_STAGED_ENTRIES;
+
+/// The discriminants of the `set_commit_erno` enum.
+#[derive(Serialize, Clone, Copy, PartialEq, Debug)]
+#[serde(rename_all = "lowercase")]
+pub enum CommitErno {
+    Success,
+    Error,
+    Aborted,
+}
+
+pub struct CommitResponse {
+    pub erno: CommitErno,
+    /// Number of entries the node staged.
+    pub entries: u8,
+    /// Bit i is set if the i-th staged entry was rejected.
+    pub failed: u32,
+}
+
+impl CommitResponse {
+    /// Status of the i-th of `staged` entries.
+    pub fn status(&self, i: usize, staged: usize) -> EntryStatus {
+        if self.erno == CommitErno::Success {
+            EntryStatus::Committed
+        } else if self.entries as usize == staged && self.failed & (1 << i) != 0 {
+            EntryStatus::Rejected
+        } else {
+            EntryStatus::NotApplied
+        }
+    }
+}
+
+#[derive(Serialize, Clone, Copy, PartialEq, Debug)]
+#[serde(rename_all = "camelCase")]
+pub enum EntryStatus {
+    Committed,
+    /// The node rejected the staged value.
+    Rejected,
+    /// The transaction failed because of another entry.
+    NotApplied,
+}
+
+#[derive(Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct TransactionEntry {
+    pub object_entry: String,
+    pub old: Option<Value>,
+    pub new: Value,
+    pub status: EntryStatus,
+}
+
+#[derive(Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct TransactionResult {
+    pub node: String,
+    /// None if the node didn't answer the commit request.
+    pub outcome: Option<CommitErno>,
+    pub entries: Vec<TransactionEntry>,
+}
+
+/// Value of an object entry encoded by `Value::get_as_bin`.
+pub struct StagedValue {
+    pub object_entry_id: u32,
+    pub bits: Vec<u32>,
+    pub last_fill: u8,
+}
+
+/// Stages values on a node and commits them with a single request.
+pub struct Transactions {
+    tx_com: Arc<TxCom>,
+    server_id: u8,
+    timeout: Duration,
+    // a node stages the entries of one client at a time, our transactions
+    // to the node are serialized.
+    lock: tokio::sync::Mutex<()>,
+    open: Mutex<Option<oneshot::Sender<CommitResponse>>>,
+}
+
+impl Transactions {
+    pub fn new(tx_com: &Arc<TxCom>, server_id: u8, request_settings: &RequestSettings) -> Self {
+        Self {
+            tx_com: tx_com.clone(),
+            server_id,
+            timeout: Duration::from_millis(request_settings.timeout_ms),
+            lock: tokio::sync::Mutex::new(()),
+            open: Mutex::new(None),
+        }
+    }
+
+    /// Stages all values and commits them, returns None if the commit request timed out.
+    pub async fn commit(&self, staged: Vec<StagedValue>) -> Option<CommitResponse> {
+        assert!(staged.len() <= MAX_STAGED_ENTRIES);
+        let _transaction = self.lock.lock().await;
+        let (done, response) = oneshot::channel();
+        *self.open.lock().unwrap() = Some(done);
+
+        let entries = staged.len() as u8;
+        for value in staged {
+            self.tx_com
+                .send_stage_request(
+                    self.server_id,
+                    value.object_entry_id,
+                    value.bits,
+                    value.last_fill,
+                )
+                .await;
+        }
+        self.tx_com
+            .send_commit_request(self.server_id, CommitAction::Commit, entries)
+            .await;
+
+        match tokio::time::timeout(self.timeout, response).await {
+            Ok(Ok(response)) => Some(response),
+            _ => {
+                self.open.lock().unwrap().take();
+                // discards the staged entries in case the commit request got lost.
+                self.tx_com
+                    .send_commit_request(self.server_id, CommitAction::Abort, 0)
+                    .await;
+                None
+            }
+        }
+    }
+
+    pub fn push_response(&self, response: CommitResponse) -> bool {
+        // only aborts after a timeout are answered with Aborted, nobody waits for them.
+        if response.erno == CommitErno::Aborted {
+            return true;
+        }
+        match self.open.lock().unwrap().take() {
+            Some(done) => {
+                let _ = done.send(response);
+                true
+            }
+            None => false,
+        }
+    }
+}
@@ -1,24 +1,320 @@
-use self::value::ObjectEntryValue;
+use std::{collections::VecDeque, ops::Range, path::PathBuf, time::Duration};
 
+use canzero_appdata::AppData;
+use canzero_config::config::TypeRef;
+use color_print::cprintln;
+
+use self::{segment::HistorySegment, value::ObjectEntryValue};
+
+pub mod segment;
 pub mod value;
 
+pub const DEFAULT_HISTORY_WINDOW: usize = 10_000;
+const MAX_HISTORY_SESSIONS: usize = 8;
+
+/// History settings shared by all object entries of a network.
+/// Every session spills into its own directory
+/// `~/.canzero/history/<start time>/`, which contains one segment per object entry.
+pub struct HistorySession {
+    dir: Option<PathBuf>,
+    window: usize,
+}
+
+impl HistorySession {
+    pub fn create(window: usize) -> Self {
+        let history_dir = AppData::dir().join("history");
+        let dir = history_dir.join(chrono::Local::now().format("%Y-%m-%d_%H-%M-%S").to_string());
+        match std::fs::create_dir_all(&dir) {
+            Ok(_) => {
+                Self::remove_old_sessions(&history_dir);
+                Self {
+                    dir: Some(dir),
+                    window: window.max(1),
+                }
+            }
+            Err(err) => {
+                cprintln!("<yellow>Failed to create history directory {dir:?} ({err}), keeping the complete history in memory</yellow>");
+                Self { dir: None, window: window.max(1) }
+            }
+        }
+    }
+
+    /// Keeps the history of the last few sessions, mostly to inspect what
+    /// happend before a crash.
+    fn remove_old_sessions(history_dir: &PathBuf) {
+        let Ok(entries) = std::fs::read_dir(history_dir) else {
+            return;
+        };
+        let mut sessions: Vec<PathBuf> = entries
+            .flatten()
+            .map(|entry| entry.path())
+            .filter(|path| path.is_dir())
+            .collect();
+        // the directory names are timestamps, therefor sorting by name is sorting by age.
+        sessions.sort();
+        let count = sessions.len().saturating_sub(MAX_HISTORY_SESSIONS);
+        for old in &sessions[..count] {
+            let _ = std::fs::remove_dir_all(old);
+        }
+    }
+
+    pub fn window(&self) -> usize {
+        self.window
+    }
+
+    pub fn dir(&self) -> Option<&PathBuf> {
+        self.dir.as_ref()
+    }
+}
+
+/// History of a object entry.
+///
+/// The newest values are kept in memory, older values are spilled to a segment on disk.
+/// Values are addressed by their index since the start of the session, independent of where
+/// they are stored.
 pub struct ObjectEntryDatabase {
-    history: Vec<ObjectEntryValue>,
+    window: VecDeque<ObjectEntryValue>,
+    // index of the first value in the window.
+    window_offset: usize,
+    window_size: usize,
+    segment: Option<HistorySegment>,
 }
 
 impl ObjectEntryDatabase {
-    pub fn new() -> Self {
-        Self { history: vec![] }
+    pub fn new(session: &HistorySession, name: &str, ty: &TypeRef) -> Self {
+        let segment = session.dir().and_then(|dir| {
+            let path = dir.join(format!("{name}.seg"));
+            match HistorySegment::create(&path, ty) {
+                Ok(segment) => Some(segment),
+                Err(err) => {
+                    cprintln!("<yellow>Failed to create history segment {path:?} ({err})</yellow>");
+                    None
+                }
+            }
+        });
+        Self {
+            window: VecDeque::new(),
+            window_offset: 0,
+            window_size: session.window(),
+            segment,
+        }
+    }
+
+    /// Number of values since the start of the session.
+    pub fn len(&self) -> usize {
+        self.window_offset + self.window.len()
     }
 
-    pub fn history(&self) -> &Vec<ObjectEntryValue> {
-        &self.history
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
     }
 
     pub fn latest_value(&self) -> Option<&ObjectEntryValue> {
-        self.history.last()
+        self.window.back()
     }
+
     pub fn push_value(&mut self, value: ObjectEntryValue) {
-        self.history.push(value);
+        self.window.push_back(value);
+        // spill in batches to avoid a write for every value.
+        let batch = (self.window_size / 4).max(1);
+        if self.window.len() < self.window_size + batch {
+            return;
+        }
+        let Some(segment) = &mut self.segment else {
+            return;
+        };
+        if let Err(err) = segment.append(self.window.range(..batch)) {
+            cprintln!(
+                "<red>Failed to write history segment {:?} ({err}), keeping the complete history in memory</red>",
+                segment.path()
+            );
+            self.segment = None;
+            return;
+        }
+        self.window.drain(..batch);
+        self.window_offset += batch;
+    }
+
+    /// Values with the indices in `range`, read from disk if required.
+    pub fn values(&self, range: Range<usize>) -> Vec<ObjectEntryValue> {
+        let end = range.end.min(self.len());
+        let start = range.start.min(end);
+        let mut values = Vec::with_capacity(end - start);
+        if start < self.window_offset {
+            if let Some(segment) = &self.segment {
+                match segment.read(start, end.min(self.window_offset)) {
+                    Ok(spilled) => values.extend(spilled),
+                    Err(err) => cprintln!(
+                        "<red>Failed to read history segment {:?} ({err})</red>",
+                        segment.path()
+                    ),
+                }
+            }
+        }
+        let window_start = start.max(self.window_offset) - self.window_offset;
+        let window_end = end.max(self.window_offset) - self.window_offset;
+        values.extend(self.window.range(window_start..window_end).cloned());
+        values
+    }
+
+    /// All values since the start of the session.
+    pub fn history(&self) -> Vec<ObjectEntryValue> {
+        self.values(0..self.len())
+    }
+
+    fn timestamp(&self, index: usize) -> Option<Duration> {
+        if index >= self.window_offset {
+            self.window
+                .get(index - self.window_offset)
+                .map(|value| value.timestamp)
+        } else {
+            self.segment
+                .as_ref()
+                .and_then(|segment| segment.timestamp(index).ok())
+        }
+    }
+
+    /// Index of the first value at or after `from`, whose timestamp is not before `time`.
+    /// Timestamps are assumed to be increasing, which allows a binary search
+    /// without reading the spilled values.
+    pub fn index_of_time(&self, from: usize, time: Duration) -> usize {
+        let (mut low, mut high) = (from.min(self.len()), self.len());
+        while low < high {
+            let mid = low + (high - low) / 2;
+            match self.timestamp(mid) {
+                Some(timestamp) if timestamp < time => low = mid + 1,
+                _ => high = mid,
+            }
+        }
+        low
+    }
+
+    /// Values with a timestamp in `start..end`.
+    pub fn values_between(&self, start: Duration, end: Duration) -> Vec<ObjectEntryValue> {
+        let first = self.index_of_time(0, start);
+        let last = self.index_of_time(first, end);
+        self.values(first..last)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use canzero_config::config::{make_config_ref, SignalType, Type, Visibility};
+
+    use super::*;
+    use crate::cnl::frame::{Attribute, Value};
+
+    fn session(name: &str, window: usize) -> HistorySession {
+        let dir = std::env::temp_dir().join(format!("canzero-history-{name}"));
+        std::fs::create_dir_all(&dir).unwrap();
+        HistorySession {
+            dir: Some(dir),
+            window,
+        }
+    }
+
+    /// A 48 bit struct, which takes two words in a segment.
+    fn ty() -> TypeRef {
+        make_config_ref(Type::Struct {
+            name: "sample".to_owned(),
+            description: None,
+            attribs: vec![
+                (
+                    "counter".to_owned(),
+                    make_config_ref(Type::Primitive(SignalType::UnsignedInt { size: 40 })),
+                ),
+                (
+                    "offset".to_owned(),
+                    make_config_ref(Type::Primitive(SignalType::SignedInt { size: 8 })),
+                ),
+            ],
+            visibility: Visibility::Global,
+        })
+    }
+
+    fn value(i: usize) -> ObjectEntryValue {
+        ObjectEntryValue::new(
+            Value::StructValue(vec![
+                Attribute::new("counter", Value::UnsignedValue(i as u64 * 0x1_0000_0001)),
+                Attribute::new("offset", Value::SignedValue(-(i as i64))),
+            ]),
+            Duration::from_millis(10 * i as u64),
+            Duration::from_millis(10),
+        )
+    }
+
+    fn database(name: &str, window: usize, count: usize) -> ObjectEntryDatabase {
+        let mut database = ObjectEntryDatabase::new(&session(name, window), "sample", &ty());
+        for i in 0..count {
+            database.push_value(value(i));
+        }
+        database
+    }
+
+    fn assert_values(values: &[ObjectEntryValue], indices: Range<usize>) {
+        assert_eq!(values.len(), indices.len());
+        for (value, i) in values.iter().zip(indices) {
+            let expected = self::value(i);
+            assert_eq!(format!("{:?}", value.value), format!("{:?}", expected.value));
+            assert_eq!(value.timestamp, expected.timestamp);
+            assert_eq!(value.delta_time, expected.delta_time);
+        }
+    }
+
+    #[test]
+    fn spills_in_batches() {
+        // window of 8 values, which spills 2 at a time.
+        let mut database = database("batches", 8, 9);
+        assert_eq!(database.window_offset, 0);
+
+        database.push_value(value(9));
+        assert_eq!(database.window_offset, 2);
+        assert_eq!(database.window.len(), 8);
+        assert_eq!(database.segment.as_ref().unwrap().len(), 2);
+
+        for i in 10..20 {
+            database.push_value(value(i));
+        }
+        assert_eq!(database.len(), 20);
+        assert_eq!(database.window_offset, 12);
+        assert_eq!(database.latest_value().unwrap().timestamp, value(19).timestamp);
+    }
+
+    #[test]
+    fn values_are_reloaded_across_segment_and_window() {
+        let database = database("reload", 8, 25);
+        assert!(database.window_offset > 10);
+        assert_values(&database.history(), 0..25);
+        // starts in the segment and ends in the window.
+        assert_values(&database.values(3..20), 3..20);
+        assert_values(&database.values(1..4), 1..4);
+        assert_values(&database.values(20..100), 20..25);
+        assert!(database.values(30..40).is_empty());
+    }
+
+    #[test]
+    fn values_between_search_the_spilled_timestamps() {
+        let database = database("between", 4, 30);
+        assert_values(
+            &database.values_between(Duration::from_millis(25), Duration::from_millis(105)),
+            3..11,
+        );
+        assert_eq!(database.index_of_time(0, Duration::from_millis(0)), 0);
+        assert_eq!(database.index_of_time(12, Duration::from_millis(50)), 12);
+        assert_eq!(database.index_of_time(0, Duration::from_secs(10)), 30);
+    }
+
+    #[test]
+    fn keeps_the_history_in_memory_without_a_directory() {
+        let session = HistorySession {
+            dir: None,
+            window: 4,
+        };
+        let mut database = ObjectEntryDatabase::new(&session, "sample", &ty());
+        for i in 0..20 {
+            database.push_value(value(i));
+        }
+        assert_eq!(database.window_offset, 0);
+        assert_values(&database.history(), 0..20);
     }
 }
@@ -0,0 +1,129 @@
+use std::{
+    fs::File,
+    io::{Read, Seek, SeekFrom, Write},
+    path::{Path, PathBuf},
+    sync::Mutex,
+    time::Duration,
+};
+
+use bitvec::slice::BitSlice;
+use canzero_config::config::TypeRef;
+
+use crate::cnl::deserialize::type_deserializer::TypeDeserializer;
+
+use super::value::ObjectEntryValue;
+
+/// Append only file of history samples, which no longer fit into memory.
+///
+/// Every sample is stored as a fixed size record
+/// `timestamp_ns : u64, delta_time_ns : u64, value : [u32; words]` (little endian),
+/// where the value is encoded like the payload of a set request.
+/// This makes the n-th sample addressable without an index.
+pub struct HistorySegment {
+    file: Mutex<File>,
+    path: PathBuf,
+    ty: TypeRef,
+    type_deserializer: TypeDeserializer,
+    value_words: usize,
+    len: usize,
+}
+
+impl HistorySegment {
+    pub fn create(path: &Path, ty: &TypeRef) -> std::io::Result<Self> {
+        let file = File::options()
+            .read(true)
+            .write(true)
+            .create(true)
+            .truncate(true)
+            .open(path)?;
+        Ok(Self {
+            file: Mutex::new(file),
+            path: path.to_owned(),
+            ty: ty.clone(),
+            type_deserializer: TypeDeserializer::new(ty),
+            value_words: (ty.size() as usize).div_ceil(32),
+            len: 0,
+        })
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Number of samples in the segment.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    fn record_size(&self) -> usize {
+        16 + self.value_words * 4
+    }
+
+    pub fn append<'a>(
+        &mut self,
+        values: impl Iterator<Item = &'a ObjectEntryValue>,
+    ) -> std::io::Result<()> {
+        let mut buffer = vec![];
+        let mut count = 0;
+        for value in values {
+            buffer.extend_from_slice(&(value.timestamp.as_nanos() as u64).to_le_bytes());
+            buffer.extend_from_slice(&(value.delta_time.as_nanos() as u64).to_le_bytes());
+            let (mut words, _) = value.value.get_as_bin::<u32>(&self.ty);
+            words.resize(self.value_words, 0);
+            for word in words {
+                buffer.extend_from_slice(&word.to_le_bytes());
+            }
+            count += 1;
+        }
+        let mut file = self.file.lock().unwrap();
+        file.seek(SeekFrom::Start((self.len * self.record_size()) as u64))?;
+        file.write_all(&buffer)?;
+        self.len += count;
+        Ok(())
+    }
+
+    /// Reads the samples with the indices `start..end`.
+    pub fn read(&self, start: usize, end: usize) -> std::io::Result<Vec<ObjectEntryValue>> {
+        let end = end.min(self.len);
+        if start >= end {
+            return Ok(vec![]);
+        }
+        let record_size = self.record_size();
+        let mut buffer = vec![0u8; (end - start) * record_size];
+        let mut file = self.file.lock().unwrap();
+        file.seek(SeekFrom::Start((start * record_size) as u64))?;
+        file.read_exact(&mut buffer)?;
+        drop(file);
+
+        let mut words = vec![0u32; self.value_words];
+        Ok(buffer
+            .chunks_exact(record_size)
+            .map(|record| {
+                let timestamp = u64::from_le_bytes(record[0..8].try_into().unwrap());
+                let delta_time = u64::from_le_bytes(record[8..16].try_into().unwrap());
+                for (i, word) in words.iter_mut().enumerate() {
+                    let offset = 16 + i * 4;
+                    *word = u32::from_le_bytes(record[offset..offset + 4].try_into().unwrap());
+                }
+                ObjectEntryValue::new(
+                    self.type_deserializer
+                        .deserialize(BitSlice::from_slice(&words)),
+                    Duration::from_nanos(timestamp),
+                    Duration::from_nanos(delta_time),
+                )
+            })
+            .collect())
+    }
+
+    pub fn timestamp(&self, index: usize) -> std::io::Result<Duration> {
+        let mut bytes = [0u8; 8];
+        let mut file = self.file.lock().unwrap();
+        file.seek(SeekFrom::Start((index * self.record_size()) as u64))?;
+        file.read_exact(&mut bytes)?;
+        Ok(Duration::from_nanos(u64::from_le_bytes(bytes)))
+    }
+}
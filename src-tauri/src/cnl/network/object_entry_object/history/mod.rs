@@ -65,7 +65,7 @@ impl ObjectEntryHistroyObservable {
             self.app_handle.clone(),
             self.rx.clone(),
             store.clone(),
-            store.lock().await.history().len(),
+            store.lock().await.len(),
             self.start_index,
             self.start_time,
         ));
@@ -101,23 +101,17 @@ impl ObjectEntryHistroyObservable {
                     match opt {
                         Some(ObserverCommand::Poison) => {
                             let store_lock = store.lock().await;
-                            if store_lock.history().len() < latest_index {
-                                let mut deprecated_count = 0;
-
+                            if store_lock.len() < latest_index {
                                 let now = std::time::Instant::now().duration_since(start_time);
                                 let breakpoint = now.saturating_sub(frame_size);
-                                for event in store_lock.history()[start_index..].iter() {
-                                    if event.timestamp >= breakpoint {
-                                        break;
-                                    }
-                                    deprecated_count += 1;
-                                }
+                                let deprecated_count =
+                                    store_lock.index_of_time(start_index, breakpoint) - start_index;
 
-                                let payload = &store_lock.history()[latest_index..];
+                                let payload = store_lock.values(latest_index..store_lock.len());
                                 app_handle
                                     .emit_all(
                                         &event_name,
-                                        ObjectEntryHistoryEvent::new(payload, deprecated_count),
+                                        ObjectEntryHistoryEvent::new(&payload, deprecated_count),
                                     )
                                     .unwrap();
                             }
@@ -130,28 +124,21 @@ impl ObjectEntryHistroyObservable {
                                 let store_lock = store.lock().await;
                                 // should never be false because the store should be updated before
                                 // the notify (otherwise notify should not be called)
-                                if store_lock.history().len() > latest_index {
+                                if store_lock.len() > latest_index {
                                     // go up from start_index and count how many messages are
                                     // deprecated afterwards update start_index
-
-                                    let mut deprecated_count = 0;
-
                                     let now = std::time::Instant::now().duration_since(start_time);
                                     let breakpoint = now.saturating_sub(frame_size);
-                                    for event in store_lock.history()[start_index..].iter() {
-                                        if event.timestamp >= breakpoint {
-                                            break;
-                                        }
-                                        deprecated_count += 1;
-                                    }
+                                    let deprecated_count =
+                                        store_lock.index_of_time(start_index, breakpoint) - start_index;
                                     start_index += deprecated_count;
 
-                                    let payload = &store_lock.history()[latest_index..];
-                                    latest_index = store_lock.history().len();
+                                    let payload = store_lock.values(latest_index..store_lock.len());
+                                    latest_index = store_lock.len();
                                     app_handle
                                         .emit_all(
                                             &event_name,
-                                            ObjectEntryHistoryEvent::new(payload, deprecated_count),
+                                            ObjectEntryHistoryEvent::new(&payload, deprecated_count),
                                         )
                                         .unwrap();
                                     next_batch_time = tokio::time::Instant::now() + min_interval;
@@ -168,25 +155,19 @@ impl ObjectEntryHistroyObservable {
                 }
                 Err(_elapsed) => {
                     let store_lock = store.lock().await;
-                    if store_lock.history().len() < latest_index {
-                        let mut deprecated_count = 0;
-
+                    if store_lock.len() < latest_index {
                         let now = std::time::Instant::now().duration_since(start_time);
                         let breakpoint = now.saturating_sub(frame_size);
-                        for event in store_lock.history()[start_index..].iter() {
-                            if event.timestamp >= breakpoint {
-                                break;
-                            }
-                            deprecated_count += 1;
-                        }
+                        let deprecated_count =
+                            store_lock.index_of_time(start_index, breakpoint) - start_index;
                         start_index += deprecated_count;
 
-                        let payload = &store_lock.history()[latest_index..];
-                        latest_index = store_lock.history().len();
+                        let payload = store_lock.values(latest_index..store_lock.len());
+                        latest_index = store_lock.len();
                         app_handle
                             .emit_all(
                                 &event_name,
-                                ObjectEntryHistoryEvent::new(payload, deprecated_count),
+                                ObjectEntryHistoryEvent::new(&payload, deprecated_count),
                             )
                             .unwrap();
                         next_batch_time = tokio::time::Instant::now() + min_interval;
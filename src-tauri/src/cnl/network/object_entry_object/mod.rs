@@ -1,18 +1,28 @@
 use std::{
     cmp::Ordering,
     ops::Deref,
-    sync::{atomic::AtomicU64, Arc, OnceLock},
+    sync::{
+        atomic::{self, AtomicBool, AtomicU64},
+        Arc, OnceLock,
+    },
     time::{Duration, Instant, SystemTime, UNIX_EPOCH},
 };
 
-use canzero_config::config::{self, NodeRef, ObjectEntryRef};
+use canzero_config::config::{self, NodeRef, ObjectEntryAccess, ObjectEntryRef};
 use chrono;
-use tokio::sync::{Mutex, OnceCell};
+use tokio::sync::{oneshot, Mutex, OnceCell};
 
 use crate::{
     cnl::{
-        self, frame::Value, network::object_entry_object::database::ObjectEntryDatabase, tx::TxCom,
+        self,
+        frame::Value,
+        network::{
+            node_object::is_external_node,
+            object_entry_object::database::{HistorySession, ObjectEntryDatabase},
+            request_scheduler::{RequestKind, RequestResult, RequestScheduler, RequestTarget},
+        },
     },
+    journal::{self, JournalEvent},
     notification::{notify_error, notify_info, notify_warning},
 };
 
@@ -21,6 +31,7 @@ use self::{
     history::ObjectEntryHistroyObservable,
     info::{ty::ObjectEntryType, ObjectEntryInformation},
     latest::{event::OwnedObjectEntryEvent, ObjectEntryLatestObservable},
+    verify::SetVerification,
     vlistener::ObjectEntryListener,
 };
 
@@ -28,6 +39,7 @@ pub mod database;
 pub mod history;
 pub mod info;
 pub mod latest;
+pub mod verify;
 pub mod vlistener;
 
 pub struct ObjectEntryObject {
@@ -40,13 +52,25 @@ pub struct ObjectEntryObject {
     history_observables_id_acc: AtomicU64,
     history_event_name_prefix: String,
     app_handle: tauri::AppHandle,
-    tx_com: Arc<TxCom>,
-    open_set_request: Arc<Mutex<(u64, Option<Value>)>>,
-    open_get_request: Arc<Mutex<u64>>,
-    set_request_timeout: Duration,
-    get_request_timeout: Duration,
+    scheduler: Arc<RequestScheduler>,
+    request_target: Arc<RequestTarget>,
+    req_num_acc: AtomicU64,
+    verify_sets: AtomicBool,
     vlisteners: Mutex<Vec<Option<Arc<dyn ObjectEntryListener + Send + Sync>>>>,
     plottable: bool,
+    external: bool,
+}
+
+/// Reason why a set request for the object entry is rejected, the virtual object
+/// entries of external messages have no node which could answer it.
+pub fn set_rejection(object_entry: &config::ObjectEntryRef) -> Option<String> {
+    is_external_node(object_entry.node()).then(|| {
+        format!(
+            "{}::{} is decoded from an external message and can't be set",
+            object_entry.node().name(),
+            object_entry.name()
+        )
+    })
 }
 
 impl ObjectEntryObject {
@@ -55,8 +79,61 @@ impl ObjectEntryObject {
         node_config: &config::NodeRef,
         object_entry_config: &config::ObjectEntryRef,
         app_handle: &tauri::AppHandle,
-        tx_com: Arc<TxCom>,
+        scheduler: &Arc<RequestScheduler>,
+        verify_sets: bool,
         timebase: Instant,
+        history_session: &HistorySession,
+    ) -> Self {
+        let plottable = node_config.tx_streams().iter().any(|stream| {
+            stream
+                .mapping()
+                .iter()
+                .flatten()
+                .any(|o| o.id() == object_entry_config.id())
+        });
+        Self::new(
+            object_entry_config,
+            app_handle,
+            scheduler,
+            verify_sets,
+            timebase,
+            history_session,
+            plottable,
+            false,
+        )
+    }
+
+    /// Virtual object entry, which holds a signal of an external (DBC) message.
+    /// Its values are only ever received, there is no node that answers
+    /// get or set requests for it.
+    pub fn create_external(
+        object_entry_config: &config::ObjectEntryRef,
+        app_handle: &tauri::AppHandle,
+        scheduler: &Arc<RequestScheduler>,
+        timebase: Instant,
+        history_session: &HistorySession,
+    ) -> Self {
+        Self::new(
+            object_entry_config,
+            app_handle,
+            scheduler,
+            false,
+            timebase,
+            history_session,
+            true,
+            true,
+        )
+    }
+
+    fn new(
+        object_entry_config: &config::ObjectEntryRef,
+        app_handle: &tauri::AppHandle,
+        scheduler: &Arc<RequestScheduler>,
+        verify_sets: bool,
+        timebase: Instant,
+        history_session: &HistorySession,
+        plottable: bool,
+        external: bool,
     ) -> Self {
         let latest_event_name = format!(
             "{}_{}_latest",
@@ -70,17 +147,17 @@ impl ObjectEntryObject {
         );
         let get_req_num_frames = object_entry_config.ty().size().div_ceil(32) as u64;
 
-        let plottable = node_config.tx_streams().iter().any(|stream| {
-            stream
-                .mapping()
-                .iter()
-                .flatten()
-                .any(|o| o.id() == object_entry_config.id())
-        });
-
         Self {
             object_entry_ref: object_entry_config.clone(),
-            store: Arc::new(Mutex::new(ObjectEntryDatabase::new())),
+            store: Arc::new(Mutex::new(ObjectEntryDatabase::new(
+                history_session,
+                &format!(
+                    "{}_{}",
+                    object_entry_config.node().name(),
+                    object_entry_config.name()
+                ),
+                object_entry_config.ty(),
+            ))),
             timebase,
             latest_observable: ObjectEntryLatestObservable::new(
                 &latest_event_name,
@@ -92,12 +169,18 @@ impl ObjectEntryObject {
             history_observables_id_acc: AtomicU64::new(0),
             history_event_name_prefix: history_event_name,
             app_handle: app_handle.clone(),
-            tx_com,
-            open_set_request: Arc::new(Mutex::new((0, None))),
-            open_get_request: Arc::new(Mutex::new(0)),
-            set_request_timeout: Duration::from_millis(1000 + get_req_num_frames * 200),
-            get_request_timeout: Duration::from_millis(1000 + get_req_num_frames * 200),
+            scheduler: scheduler.clone(),
+            request_target: Arc::new(RequestTarget::new(
+                object_entry_config.node().name(),
+                object_entry_config.name(),
+                object_entry_config.node().id() as u8,
+                object_entry_config.id(),
+                Duration::from_millis(get_req_num_frames * 200),
+            )),
+            req_num_acc: AtomicU64::new(0),
+            verify_sets: AtomicBool::new(verify_sets),
             plottable,
+            external,
             vlisteners: Mutex::new(vec![]),
         }
     }
@@ -123,6 +206,23 @@ impl ObjectEntryObject {
         self.object_entry_ref.node()
     }
 
+    pub fn is_external(&self) -> bool {
+        self.external
+    }
+
+    pub fn access(&self) -> &ObjectEntryAccess {
+        self.object_entry_ref.access()
+    }
+
+    /// If set requests are read back to check that the node holds the requested value.
+    pub fn verify_sets(&self) -> bool {
+        self.verify_sets.load(atomic::Ordering::Relaxed)
+    }
+
+    pub fn set_verify_sets(&self, verify_sets: bool) {
+        self.verify_sets.store(verify_sets, atomic::Ordering::Relaxed);
+    }
+
     pub fn friend(&self) -> Option<ObjectEntryRef> {
         match self.object_entry_ref.friend() {
             Some(friend_name) => self
@@ -135,145 +235,100 @@ impl ObjectEntryObject {
         }
     }
 
-    pub async fn request_current_value(&self) {
-        let mut get_req_num = match self.open_get_request.try_lock() {
-            Ok(n) => {
-                if *n % 2 == 0 {
-                    n
-                } else {
-                    notify_warning(
-                        &self.app_handle,
-                        "Ignoring Get Request",
-                        &format!(
-                            "Older get reasync quest for {}::{} still in progress",
-                            self.object_entry_ref.node().name(),
-                            self.name()
-                        ),
-                        chrono::Local::now(),
-                    );
-                    return;
-                }
-            }
-            Err(_) => {
-                notify_warning(
-                    &self.app_handle,
-                    "Ignoring Get Request",
-                    &format!(
-                        "Older get request for {}::{} still in progress",
-                        self.object_entry_ref.node().name(),
-                        self.name()
-                    ),
-                    chrono::Local::now(),
-                );
-                return;
-            }
-        };
-        *get_req_num += 1;
-        let my_req_num = *get_req_num;
-        drop(get_req_num);
-        self.tx_com
-            .send_get_req(self.node_id(), self.id() as u16)
-            .await;
-        tokio::spawn({
-            let timeout = self.get_request_timeout.clone();
-            let oe_name = self.name().to_owned();
-            let node_name = self.object_entry_ref.node().name().to_owned();
-            let get_requests = self.open_get_request.clone();
-            let app_handle = self.app_handle.clone();
-
-            async move {
-                tokio::time::sleep(timeout).await;
-                let mut new_req_num = get_requests.lock().await;
-                if *new_req_num == my_req_num {
-                    *new_req_num += 1;
-                    drop(new_req_num);
-                    notify_error(
-                        &app_handle,
-                        "Get Request Timeout",
-                        &format!("Get request for {node_name}::{oe_name} timed out",),
-                        chrono::Local::now(),
-                    );
-                }
-            }
-        });
+    /// Queues a get request, returns the request number or None if the request was rejected.
+    pub async fn request_current_value(&self) -> Option<u64> {
+        // the result is reported by the notifications.
+        self.submit_get().map(|(req_num, _)| req_num)
     }
 
-    pub async fn set_request(&self, value: Value) {
+    fn submit_get(&self) -> Option<(u64, oneshot::Receiver<RequestResult>)> {
+        if self.external {
+            notify_warning(
+                &self.app_handle,
+                "Ignoring Get Request",
+                &format!(
+                    "{}::{} is decoded from an external message and can't be requested",
+                    self.object_entry_ref.node().name(),
+                    self.name()
+                ),
+                chrono::Local::now(),
+            );
+            return None;
+        }
+        let req_num = self.next_req_num();
+        journal::record(
+            &self.app_handle,
+            JournalEvent::GetRequest {
+                node: self.object_entry_ref.node().name().to_owned(),
+                object_entry: self.name().to_owned(),
+                request: req_num,
+            },
+        );
+        let result = self
+            .scheduler
+            .submit(&self.request_target, req_num, RequestKind::Get);
+        Some((req_num, result))
+    }
+
+    /// Queues a set request, returns the request number or None if the request was rejected.
+    pub async fn set_request(&self, value: Value) -> Option<u64> {
+        self.submit_set(value).await.map(|(req_num, _)| req_num)
+    }
+
+    async fn submit_set(&self, value: Value) -> Option<(u64, oneshot::Receiver<RequestResult>)> {
+        if let Some(rejection) = set_rejection(&self.object_entry_ref) {
+            notify_error(
+                &self.app_handle,
+                "Rejected Set Request",
+                &rejection,
+                chrono::Local::now(),
+            );
+            return None;
+        }
         let start = SystemTime::now();
         let since_the_epoch = start
             .duration_since(UNIX_EPOCH)
             .expect("Time went backwards");
         println!("Sending set request: {:?}", since_the_epoch);
-        let mut set_req_data = match self.open_set_request.try_lock() {
-            Ok(set_req_data) => {
-                if set_req_data.0 % 2 == 0 {
-                    set_req_data
-                } else {
-                    drop(set_req_data);
-                    notify_warning(
-                        &self.app_handle,
-                        "Ignoring Set Request",
-                        &format!(
-                            "Older set request for {}::{} still in progress",
-                            self.object_entry_ref.node().name(),
-                            self.name()
-                        ),
-                        chrono::Local::now(),
-                    );
-                    return;
-                }
-            }
-            Err(_) => {
-                notify_warning(
-                    &self.app_handle,
-                    "Ignoring Set Request",
-                    &format!(
-                        "Older set request for {}::{} still in progress",
-                        self.object_entry_ref.node().name(),
-                        self.name()
-                    ),
-                    chrono::Local::now(),
-                );
-                return;
-            }
-        };
-        set_req_data.0 += 1;
-        let my_req_num = set_req_data.0;
-        let (bit_value, last_fill) = value.get_as_bin(self.ty());
-        set_req_data.1 = Some(value);
-        drop(set_req_data);
-
-        let server_id = self.node_id();
-        let oe_id = self.id();
-
-        let tx = self.tx().clone();
-        tokio::spawn(async move {
-            tx.send_set_request(server_id, oe_id, bit_value, last_fill)
-                .await;
-        });
+        let req_num = self.next_req_num();
+        let (bits, last_fill) = value.get_as_bin(self.ty());
+        let old = self.latest_value().await;
 
-        tokio::task::spawn({
-            let timeout = self.set_request_timeout.clone();
-            let oe_name = self.name().to_owned();
-            let node_name = self.object_entry_ref.node().name().to_owned();
-            let app_handle = self.app_handle.clone();
-            let set_req_data = self.open_set_request.clone();
-
-            async move {
-                tokio::time::sleep(timeout).await;
-                let mut curr_req = set_req_data.lock().await;
-                if curr_req.0 == my_req_num {
-                    *curr_req = (curr_req.0 + 1, None);
-                    drop(curr_req);
-                    notify_error(
-                        &app_handle,
-                        "Set Request Timeout",
-                        &format!("Set request for {node_name}::{oe_name} timed out",),
-                        chrono::Local::now(),
-                    );
-                }
-            }
+        journal::record(
+            &self.app_handle,
+            JournalEvent::SetRequest {
+                node: self.object_entry_ref.node().name().to_owned(),
+                object_entry: self.name().to_owned(),
+                request: req_num,
+                old: old.clone(),
+                new: value.clone(),
+            },
+        );
+        let verify = self.verify_sets().then(|| SetVerification {
+            app_handle: self.app_handle.clone(),
+            node: self.object_entry_ref.node().name().to_owned(),
+            object_entry: self.name().to_owned(),
+            ty: self.ty().clone(),
+            request: req_num,
+            requested: value.clone(),
+            old,
         });
+
+        let result = self.scheduler.submit(
+            &self.request_target,
+            req_num,
+            RequestKind::Set {
+                value,
+                bits,
+                last_fill,
+                verify,
+            },
+        );
+        Some((req_num, result))
+    }
+
+    fn next_req_num(&self) -> u64 {
+        self.req_num_acc.fetch_add(1, atomic::Ordering::Relaxed) + 1
     }
 
     pub async fn push_value(&self, value: Value, timestamp: &Duration) {
@@ -299,9 +354,7 @@ impl ObjectEntryObject {
     }
 
     pub async fn push_get_response(&self, value: Value, timestamp: &Duration) {
-        let mut get_req_num = self.open_get_request.lock().await;
-        if *get_req_num % 2 == 0 {
-            drop(get_req_num);
+        let Some(open_request) = self.request_target.take_open(false) else {
             notify_warning(
                 &self.app_handle,
                 "Ignoring Get Response",
@@ -313,12 +366,20 @@ impl ObjectEntryObject {
                 chrono::Local::now(),
             );
             return;
-        }
-        *get_req_num += 1;
-        // The value has to be stored before notify because the observables
-        // use the values in the store directly to reduce clones
-        drop(get_req_num);
-        self.push_value(value, timestamp).await;
+        };
+        // the value is stored before the request completes, requests queued
+        // behind this one already see it.
+        self.push_value(value.clone(), timestamp).await;
+        journal::record(
+            &self.app_handle,
+            JournalEvent::GetResponse {
+                node: self.object_entry_ref.node().name().to_owned(),
+                object_entry: self.name().to_owned(),
+                request: open_request.req_num,
+                value: Some(value.clone()),
+            },
+        );
+        open_request.complete(Ok(value));
         notify_info(
             &self.app_handle,
             "Get Request Successfull",
@@ -346,9 +407,7 @@ impl ObjectEntryObject {
     }
 
     pub async fn push_set_response(&self, result: cnl::errors::Result<()>) {
-        let mut set_req_data = self.open_set_request.lock().await;
-        if set_req_data.0 % 2 == 0 {
-            drop(set_req_data);
+        let Some(open_request) = self.request_target.take_open(true) else {
             notify_info(
                 &self.app_handle,
                 "Ignoring Set Response",
@@ -361,14 +420,23 @@ impl ObjectEntryObject {
             );
             return;
         };
-        set_req_data.0 += 1;
+        journal::record(
+            &self.app_handle,
+            JournalEvent::SetResponse {
+                node: self.object_entry_ref.node().name().to_owned(),
+                object_entry: self.name().to_owned(),
+                request: open_request.req_num,
+                error: result.as_ref().err().map(|err| err.description().to_owned()),
+            },
+        );
         match result {
             Ok(_) => {
-                if let Some(value) = set_req_data.1.as_ref() {
-                    self.push_value(value.clone(), &self.now()).await;
-                    *set_req_data = (set_req_data.0, None);
-                };
-                drop(set_req_data);
+                let value = open_request
+                    .set_value
+                    .clone()
+                    .expect("set requests always have a value");
+                self.push_value(value.clone(), &self.now()).await;
+                open_request.complete(Ok(value));
                 notify_info(
                     &self.app_handle,
                     "Set Request Successfull",
@@ -381,8 +449,7 @@ impl ObjectEntryObject {
                 );
             }
             Err(err) => {
-                *set_req_data = (set_req_data.0, None);
-                drop(set_req_data);
+                open_request.complete(Err(err.description().to_owned()));
                 notify_error(
                     &self.app_handle,
                     err.reason(),
@@ -393,12 +460,31 @@ impl ObjectEntryObject {
         }
     }
 
-    pub fn ty(&self) -> &config::TypeRef {
-        &self.object_entry_ref.ty()
+    /// Requests the current value and waits for the response.
+    pub async fn get(&self) -> Result<Value, String> {
+        let Some((_, result)) = self.submit_get() else {
+            return Err(format!("Get request for {} was rejected", self.name()));
+        };
+        result
+            .await
+            .unwrap_or_else(|_| Err("closed".to_owned()))
+            .map_err(|err| format!("Get request for {} failed : {err}", self.name()))
+    }
+
+    /// Sets the value and waits until the node acknowledged it.
+    pub async fn set(&self, value: Value) -> Result<(), String> {
+        let Some((_, result)) = self.submit_set(value).await else {
+            return Err(format!("Set request for {} was rejected", self.name()));
+        };
+        result
+            .await
+            .unwrap_or_else(|_| Err("closed".to_owned()))
+            .map(|_| ())
+            .map_err(|err| format!("Set request for {} failed : {err}", self.name()))
     }
 
-    fn tx(&self) -> Arc<TxCom> {
-        self.tx_com.clone()
+    pub fn ty(&self) -> &config::TypeRef {
+        &self.object_entry_ref.ty()
     }
 
     pub fn listen_to_latest(&self) {
@@ -416,26 +502,27 @@ impl ObjectEntryObject {
             .map(OwnedObjectEntryEvent::new)
     }
 
+    pub async fn latest_value(&self) -> Option<Value> {
+        self.store
+            .lock()
+            .await
+            .latest_value()
+            .map(|latest| latest.value.clone())
+    }
+
     pub async fn listen_to_history(
         &self,
         frame_size: Duration,
         min_interval: Duration,
     ) -> (String, Vec<OwnedObjectEntryEvent>) {
         let store_lock = self.store.lock().await;
-        let history = store_lock.history();
         let now = std::time::Instant::now().duration_since(self.timebase);
         let breakpoint = now.saturating_sub(frame_size);
-        let mut breakpoint_index = None;
-        for (i, event) in history.iter().enumerate().rev() {
-            if event.timestamp <= breakpoint {
-                breakpoint_index = Some(i);
-                break;
-            }
-        }
-        let start_index = breakpoint_index.unwrap_or(0);
-        let history_of = history[start_index..]
-            .iter()
-            .map(ObjectEntryValue::clone)
+        // include the last value before the breakpoint.
+        let start_index = store_lock.index_of_time(0, breakpoint).saturating_sub(1);
+        let history_of = store_lock
+            .values(start_index..store_lock.len())
+            .into_iter()
             .map(OwnedObjectEntryEvent::new)
             .collect();
         drop(store_lock);
@@ -466,10 +553,9 @@ impl ObjectEntryObject {
 
     pub async fn complete_history(&self) -> Vec<OwnedObjectEntryEvent> {
         let store_lock = self.store.lock().await;
-        let history = store_lock.history();
-        let mut history_data = history
-            .iter()
-            .map(ObjectEntryValue::clone)
+        let mut history_data = store_lock
+            .history()
+            .into_iter()
             .map(OwnedObjectEntryEvent::new)
             .collect::<Vec<OwnedObjectEntryEvent>>();
         history_data.sort_unstable_by(|val1, val2| val1.timestamp().cmp(&val2.timestamp()));
@@ -536,7 +622,6 @@ impl ObjectEntryObject {
     pub async fn deadlock_watchdog(&self) {
         drop(self.store.lock().await);
         drop(self.history_observables.lock().await);
-        drop(self.open_set_request.lock().await);
-        drop(self.open_get_request.lock().await);
+        self.request_target.deadlock_watchdog();
     }
 }
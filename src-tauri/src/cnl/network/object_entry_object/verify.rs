@@ -0,0 +1,251 @@
+use canzero_config::config::{SignalType, Type, TypeRef};
+use serde::Serialize;
+
+use crate::{
+    cnl::{frame::Value, network::request_scheduler::RequestResult, serialize::value_to_json},
+    journal::{self, JournalEvent},
+    notification::{notify_error, notify_info, notify_warning},
+};
+
+/// Outcome of reading back the value of an acknowledged set request.
+#[derive(Serialize, Clone, Copy, PartialEq, Debug)]
+#[serde(rename_all = "lowercase")]
+pub enum VerifyOutcome {
+    /// The node holds the requested value.
+    Verified,
+    /// The node holds a value between the previous and the requested one,
+    /// e.g. because the firmware limits the value.
+    Clamped,
+    Mismatch,
+    /// The value could not be read back.
+    Unverified,
+}
+
+/// Compares two values of the type, decimals are equal if they encode to the same value.
+pub fn values_match(ty: &Type, a: &Value, b: &Value) -> bool {
+    match (ty, a, b) {
+        (
+            Type::Primitive(SignalType::Decimal { scale, .. }),
+            Value::RealValue(a),
+            Value::RealValue(b),
+        ) => (a - b).abs() <= scale / 2.0,
+        (Type::Struct { attribs, .. }, Value::StructValue(_), Value::StructValue(_)) => attribs
+            .iter()
+            .all(|(name, ty)| match (a.attribute(name), b.attribute(name)) {
+                (Some(a), Some(b)) => values_match(ty, a, b),
+                _ => false,
+            }),
+        (_, Value::UnsignedValue(a), Value::UnsignedValue(b)) => a == b,
+        (_, Value::SignedValue(a), Value::SignedValue(b)) => a == b,
+        (_, Value::EnumValue(a), Value::EnumValue(b)) => a == b,
+        _ => false,
+    }
+}
+
+pub fn verify(ty: &Type, requested: &Value, old: Option<&Value>, live: &Value) -> VerifyOutcome {
+    if values_match(ty, requested, live) {
+        return VerifyOutcome::Verified;
+    }
+    // the firmware rejected the value, instead of limiting it.
+    if old.is_some_and(|old| values_match(ty, old, live)) {
+        return VerifyOutcome::Mismatch;
+    }
+    match (old.and_then(as_f64), as_f64(requested), as_f64(live)) {
+        (Some(old), Some(requested), Some(live))
+            if live >= old.min(requested) && live <= old.max(requested) =>
+        {
+            VerifyOutcome::Clamped
+        }
+        _ => VerifyOutcome::Mismatch,
+    }
+}
+
+fn as_f64(value: &Value) -> Option<f64> {
+    match value {
+        Value::UnsignedValue(value) => Some(*value as f64),
+        Value::SignedValue(value) => Some(*value as f64),
+        Value::RealValue(value) => Some(*value),
+        _ => None,
+    }
+}
+
+/// Set request, whose value is read back after the node acknowledged it.
+pub struct SetVerification {
+    pub app_handle: tauri::AppHandle,
+    pub node: String,
+    pub object_entry: String,
+    pub ty: TypeRef,
+    pub request: u64,
+    pub requested: Value,
+    pub old: Option<Value>,
+}
+
+impl SetVerification {
+    /// Reports the outcome of the read back value as notification and in the journal.
+    pub fn check(self, result: &RequestResult) {
+        let (outcome, live) = match result {
+            Ok(live) => (
+                verify(&self.ty, &self.requested, self.old.as_ref(), live),
+                Some(live.clone()),
+            ),
+            Err(_) => (VerifyOutcome::Unverified, None),
+        };
+        let name = format!("{}::{}", self.node, self.object_entry);
+        let requested = value_to_json(&self.requested);
+        let now = chrono::Local::now();
+        match (&outcome, &live) {
+            (VerifyOutcome::Verified, _) => notify_info(
+                &self.app_handle,
+                "Set Request Verified",
+                &format!("{name} holds the requested value {requested}"),
+                now,
+            ),
+            (VerifyOutcome::Clamped, Some(live)) => notify_warning(
+                &self.app_handle,
+                "Set Request Clamped",
+                &format!(
+                    "{name} was set to {} instead of {requested}",
+                    value_to_json(live)
+                ),
+                now,
+            ),
+            (VerifyOutcome::Mismatch, Some(live)) => notify_error(
+                &self.app_handle,
+                "Set Request Mismatch",
+                &format!(
+                    "{name} holds {} after it was set to {requested}",
+                    value_to_json(live)
+                ),
+                now,
+            ),
+            _ => notify_warning(
+                &self.app_handle,
+                "Set Request Unverified",
+                &format!("{name} could not be read back after it was set to {requested}"),
+                now,
+            ),
+        }
+        journal::record(
+            &self.app_handle,
+            JournalEvent::SetVerification {
+                node: self.node,
+                object_entry: self.object_entry,
+                request: self.request,
+                requested: self.requested,
+                live,
+                outcome,
+            },
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use canzero_config::config::{make_config_ref, Visibility};
+
+    use super::*;
+    use crate::cnl::frame::Attribute;
+
+    fn decimal() -> Type {
+        Type::Primitive(SignalType::Decimal {
+            size: 8,
+            offset: 0.0,
+            scale: 0.1,
+        })
+    }
+
+    fn unsigned() -> Type {
+        Type::Primitive(SignalType::UnsignedInt { size: 8 })
+    }
+
+    fn point() -> Type {
+        Type::Struct {
+            name: "point".to_owned(),
+            description: None,
+            attribs: vec![
+                ("x".to_owned(), make_config_ref(decimal())),
+                ("mode".to_owned(), make_config_ref(unsigned())),
+            ],
+            visibility: Visibility::Global,
+        }
+    }
+
+    fn point_value(x: f64, mode: u64) -> Value {
+        Value::StructValue(vec![
+            Attribute::new("x", Value::RealValue(x)),
+            Attribute::new("mode", Value::UnsignedValue(mode)),
+        ])
+    }
+
+    #[test]
+    fn decimals_match_within_half_a_step() {
+        let ty = decimal();
+        assert!(values_match(&ty, &Value::RealValue(1.0), &Value::RealValue(1.04)));
+        assert!(!values_match(&ty, &Value::RealValue(1.0), &Value::RealValue(1.1)));
+        assert!(!values_match(&ty, &Value::RealValue(1.0), &Value::UnsignedValue(1)));
+    }
+
+    #[test]
+    fn structs_match_attribute_wise() {
+        let ty = point();
+        assert!(values_match(&ty, &point_value(1.0, 2), &point_value(1.01, 2)));
+        assert!(!values_match(&ty, &point_value(1.0, 2), &point_value(1.0, 3)));
+        let partial = Value::StructValue(vec![Attribute::new("x", Value::RealValue(1.0))]);
+        assert!(!values_match(&ty, &point_value(1.0, 2), &partial));
+    }
+
+    #[test]
+    fn enums_match_by_name() {
+        let ty = unsigned();
+        let idle = Value::EnumValue("idle".to_owned());
+        assert!(values_match(&ty, &idle, &idle.clone()));
+        assert!(!values_match(&ty, &idle, &Value::EnumValue("brake".to_owned())));
+    }
+
+    #[test]
+    fn verified_if_the_requested_value_is_held() {
+        let ty = decimal();
+        let outcome = verify(
+            &ty,
+            &Value::RealValue(2.0),
+            Some(&Value::RealValue(1.0)),
+            &Value::RealValue(2.02),
+        );
+        assert_eq!(outcome, VerifyOutcome::Verified);
+    }
+
+    #[test]
+    fn clamped_if_the_value_is_between_old_and_requested() {
+        let ty = unsigned();
+        let old = Value::UnsignedValue(10);
+        for (requested, live) in [(100, 50), (0, 5)] {
+            let requested = Value::UnsignedValue(requested);
+            let live = Value::UnsignedValue(live);
+            assert_eq!(verify(&ty, &requested, Some(&old), &live), VerifyOutcome::Clamped);
+        }
+    }
+
+    #[test]
+    fn mismatch_if_the_old_value_is_kept() {
+        let ty = decimal();
+        let outcome = verify(
+            &ty,
+            &Value::RealValue(2.0),
+            Some(&Value::RealValue(1.0)),
+            &Value::RealValue(1.0),
+        );
+        assert_eq!(outcome, VerifyOutcome::Mismatch);
+    }
+
+    #[test]
+    fn mismatch_if_the_value_is_outside_of_the_range() {
+        let ty = unsigned();
+        let requested = Value::UnsignedValue(100);
+        let old = Value::UnsignedValue(10);
+        let live = Value::UnsignedValue(200);
+        assert_eq!(verify(&ty, &requested, Some(&old), &live), VerifyOutcome::Mismatch);
+        // without the old value a clamp can not be told apart.
+        let live = Value::UnsignedValue(50);
+        assert_eq!(verify(&ty, &requested, None, &live), VerifyOutcome::Mismatch);
+    }
+}
@@ -0,0 +1,434 @@
+use std::{
+    collections::{HashSet, VecDeque},
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc, Mutex,
+    },
+    time::Duration,
+};
+
+use canzero_appdata::RequestSettings;
+use serde::Serialize;
+use tauri::Manager;
+use tokio::sync::oneshot;
+
+use crate::{
+    cnl::{frame::Value, network::object_entry_object::verify::SetVerification, tx::TxCom},
+    journal::{self, JournalEvent},
+    notification::notify_error,
+};
+
+const REQUEST_PROGRESS_EVENT_NAME: &str = "request-progress";
+
+pub type RequestResult = Result<Value, String>;
+
+pub enum RequestKind {
+    Get,
+    Set {
+        value: Value,
+        bits: Vec<u32>,
+        last_fill: u8,
+        /// Reads the value back after the node acknowledged it.
+        verify: Option<SetVerification>,
+    },
+}
+
+/// Request, which was sent and waits for its response.
+pub struct OpenRequest {
+    pub req_num: u64,
+    /// The value of a set request, None for get requests.
+    pub set_value: Option<Value>,
+    done: oneshot::Sender<RequestResult>,
+}
+
+impl OpenRequest {
+    pub fn complete(self, result: RequestResult) {
+        let _ = self.done.send(result);
+    }
+}
+
+/// The object entry a request is sent to, shared by all requests of the object entry.
+pub struct RequestTarget {
+    pub node_name: String,
+    pub object_entry_name: String,
+    pub server_id: u8,
+    pub object_entry_id: u32,
+    /// Additional time for responses, which are fragmented.
+    pub response_time: Duration,
+    open: Mutex<Option<OpenRequest>>,
+}
+
+impl RequestTarget {
+    pub fn new(
+        node_name: &str,
+        object_entry_name: &str,
+        server_id: u8,
+        object_entry_id: u32,
+        response_time: Duration,
+    ) -> Self {
+        Self {
+            node_name: node_name.to_owned(),
+            object_entry_name: object_entry_name.to_owned(),
+            server_id,
+            object_entry_id,
+            response_time,
+            open: Mutex::new(None),
+        }
+    }
+
+    /// Takes the open get (`set == false`) or set request, responses without an open
+    /// request of their kind came in after the timeout.
+    pub fn take_open(&self, set: bool) -> Option<OpenRequest> {
+        let mut open = self.open.lock().unwrap();
+        match open.as_ref() {
+            Some(request) if request.set_value.is_some() == set => open.take(),
+            _ => None,
+        }
+    }
+
+    fn open(&self, open_request: OpenRequest) {
+        *self.open.lock().unwrap() = Some(open_request);
+    }
+
+    fn close(&self, req_num: u64) {
+        let mut open = self.open.lock().unwrap();
+        if open
+            .as_ref()
+            .is_some_and(|request| request.req_num == req_num)
+        {
+            *open = None;
+        }
+    }
+
+    pub fn deadlock_watchdog(&self) {
+        drop(self.open.lock().unwrap());
+    }
+}
+
+struct Request {
+    target: Arc<RequestTarget>,
+    req_num: u64,
+    kind: RequestKind,
+    result: oneshot::Sender<RequestResult>,
+}
+
+struct SchedulerState {
+    queue: VecDeque<Request>,
+    // object entries with a request in flight.
+    busy: HashSet<u32>,
+}
+
+impl SchedulerState {
+    /// Takes the oldest request, whose object entry has no request in flight,
+    /// while less than `max_inflight` requests are in flight.
+    fn next(&mut self, max_inflight: usize) -> Option<Request> {
+        if self.busy.len() >= max_inflight.max(1) {
+            return None;
+        }
+        let index = self
+            .queue
+            .iter()
+            .position(|request| !self.busy.contains(&request.target.object_entry_id))?;
+        let request = self.queue.remove(index)?;
+        self.busy.insert(request.target.object_entry_id);
+        Some(request)
+    }
+}
+
+struct SchedulerInner {
+    tx_com: Arc<TxCom>,
+    settings: RequestSettings,
+    app_handle: tauri::AppHandle,
+    state: Mutex<SchedulerState>,
+}
+
+/// Queues the get and set requests of a node and sends up to `max_inflight` of them
+/// without waiting for the responses. Requests of the same object entry are sent one
+/// after another, because the protocol can't tell their responses apart.
+pub struct RequestScheduler {
+    inner: Arc<SchedulerInner>,
+}
+
+impl RequestScheduler {
+    pub fn new(
+        tx_com: &Arc<TxCom>,
+        settings: &RequestSettings,
+        app_handle: &tauri::AppHandle,
+    ) -> Self {
+        Self {
+            inner: Arc::new(SchedulerInner {
+                tx_com: tx_com.clone(),
+                settings: settings.clone(),
+                app_handle: app_handle.clone(),
+                state: Mutex::new(SchedulerState {
+                    queue: VecDeque::new(),
+                    busy: HashSet::new(),
+                }),
+            }),
+        }
+    }
+
+    pub fn submit(
+        &self,
+        target: &Arc<RequestTarget>,
+        req_num: u64,
+        kind: RequestKind,
+    ) -> oneshot::Receiver<RequestResult> {
+        let (result, receiver) = oneshot::channel();
+        self.inner.state.lock().unwrap().queue.push_back(Request {
+            target: target.clone(),
+            req_num,
+            kind,
+            result,
+        });
+        SchedulerInner::dispatch(&self.inner);
+        receiver
+    }
+
+    /// Number of requests, which are queued or in flight.
+    pub fn pending(&self) -> usize {
+        let state = self.inner.state.lock().unwrap();
+        state.queue.len() + state.busy.len()
+    }
+
+    pub fn deadlock_watchdog(&self) {
+        drop(self.inner.state.lock().unwrap());
+    }
+}
+
+impl SchedulerInner {
+    fn dispatch(inner: &Arc<SchedulerInner>) {
+        let mut state = inner.state.lock().unwrap();
+        while let Some(request) = state.next(inner.settings.max_inflight) {
+            let inner = inner.clone();
+            tokio::spawn(async move {
+                let object_entry_id = request.target.object_entry_id;
+                inner.execute(request).await;
+                inner.state.lock().unwrap().busy.remove(&object_entry_id);
+                SchedulerInner::dispatch(&inner);
+            });
+        }
+    }
+
+    async fn execute(&self, request: Request) {
+        let Request {
+            target,
+            req_num,
+            kind,
+            result,
+        } = request;
+        let response = self.transmit(&target, req_num, &kind).await;
+        if let (
+            Ok(_),
+            RequestKind::Set {
+                verify: Some(verification),
+                ..
+            },
+        ) = (&response, kind)
+        {
+            // the object entry is still busy, no other request changes it before the read back.
+            let live = self.transmit(&target, req_num, &RequestKind::Get).await;
+            verification.check(&live);
+        }
+        let _ = result.send(response);
+    }
+
+    /// Sends the request and waits for the response, the request is repeated
+    /// up to `retries` times if it times out.
+    async fn transmit(
+        &self,
+        target: &RequestTarget,
+        req_num: u64,
+        kind: &RequestKind,
+    ) -> RequestResult {
+        let timeout = Duration::from_millis(self.settings.timeout_ms) + target.response_time;
+        let attempts = self.settings.retries + 1;
+        for _ in 0..attempts {
+            let (done, response) = oneshot::channel();
+            target.open(OpenRequest {
+                req_num,
+                set_value: match kind {
+                    RequestKind::Get => None,
+                    RequestKind::Set { value, .. } => Some(value.clone()),
+                },
+                done,
+            });
+            match kind {
+                RequestKind::Get => {
+                    self.tx_com
+                        .send_get_req(target.server_id, target.object_entry_id as u16)
+                        .await
+                }
+                RequestKind::Set {
+                    bits, last_fill, ..
+                } => {
+                    self.tx_com
+                        .send_set_request(
+                            target.server_id,
+                            target.object_entry_id,
+                            bits.clone(),
+                            *last_fill,
+                        )
+                        .await
+                }
+            }
+            if let Ok(Ok(result)) = tokio::time::timeout(timeout, response).await {
+                return result;
+            }
+            target.close(req_num);
+        }
+
+        let (title, kind) = match kind {
+            RequestKind::Get => {
+                journal::record(
+                    &self.app_handle,
+                    JournalEvent::GetResponse {
+                        node: target.node_name.clone(),
+                        object_entry: target.object_entry_name.clone(),
+                        request: req_num,
+                        value: None,
+                    },
+                );
+                ("Get Request Timeout", "Get")
+            }
+            RequestKind::Set { .. } => {
+                journal::record(
+                    &self.app_handle,
+                    JournalEvent::SetResponse {
+                        node: target.node_name.clone(),
+                        object_entry: target.object_entry_name.clone(),
+                        request: req_num,
+                        error: Some("timed out".to_owned()),
+                    },
+                );
+                ("Set Request Timeout", "Set")
+            }
+        };
+        notify_error(
+            &self.app_handle,
+            title,
+            &format!(
+                "{kind} request for {}::{} timed out after {attempts} attempts",
+                target.node_name, target.object_entry_name
+            ),
+            chrono::Local::now(),
+        );
+        Err("timed out".to_owned())
+    }
+}
+
+#[derive(Serialize, Clone)]
+struct RequestProgressEvent<'a> {
+    operation: &'a str,
+    total: usize,
+    completed: usize,
+    failed: usize,
+}
+
+/// Progress of an operation, which consists of many requests, e.g. a parameter
+/// snapshot. Every completed request emits a `request-progress` event.
+pub struct BulkProgress {
+    app_handle: tauri::AppHandle,
+    operation: String,
+    total: usize,
+    completed: AtomicUsize,
+    failed: AtomicUsize,
+}
+
+impl BulkProgress {
+    pub fn new(app_handle: &tauri::AppHandle, operation: &str, total: usize) -> Self {
+        let progress = Self {
+            app_handle: app_handle.clone(),
+            operation: operation.to_owned(),
+            total,
+            completed: AtomicUsize::new(0),
+            failed: AtomicUsize::new(0),
+        };
+        progress.emit();
+        progress
+    }
+
+    pub fn complete<T>(&self, result: &Result<T, String>) {
+        if result.is_err() {
+            self.failed.fetch_add(1, Ordering::Relaxed);
+        }
+        self.completed.fetch_add(1, Ordering::Relaxed);
+        self.emit();
+    }
+
+    fn emit(&self) {
+        let _ = self.app_handle.emit_all(
+            REQUEST_PROGRESS_EVENT_NAME,
+            RequestProgressEvent {
+                operation: &self.operation,
+                total: self.total,
+                completed: self.completed.load(Ordering::Relaxed),
+                failed: self.failed.load(Ordering::Relaxed),
+            },
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn state(requests: &[(u32, u64)]) -> SchedulerState {
+        SchedulerState {
+            queue: requests
+                .iter()
+                .map(|(object_entry_id, req_num)| {
+                    let (result, _) = oneshot::channel();
+                    Request {
+                        target: Arc::new(RequestTarget::new(
+                            "node",
+                            "object_entry",
+                            0,
+                            *object_entry_id,
+                            Duration::ZERO,
+                        )),
+                        req_num: *req_num,
+                        kind: RequestKind::Get,
+                        result,
+                    }
+                })
+                .collect(),
+            busy: HashSet::new(),
+        }
+    }
+
+    fn next(state: &mut SchedulerState, max_inflight: usize) -> Option<u64> {
+        state.next(max_inflight).map(|request| request.req_num)
+    }
+
+    #[test]
+    fn sends_up_to_max_inflight_requests() {
+        let mut state = state(&[(1, 0), (2, 1), (3, 2)]);
+        assert_eq!(next(&mut state, 2), Some(0));
+        assert_eq!(next(&mut state, 2), Some(1));
+        assert_eq!(next(&mut state, 2), None);
+
+        state.busy.remove(&1);
+        assert_eq!(next(&mut state, 2), Some(2));
+        assert!(state.queue.is_empty());
+    }
+
+    #[test]
+    fn requests_of_a_object_entry_are_sent_one_after_another() {
+        let mut state = state(&[(1, 0), (1, 1), (2, 2)]);
+        assert_eq!(next(&mut state, 3), Some(0));
+        // skips the second request of the busy object entry.
+        assert_eq!(next(&mut state, 3), Some(2));
+        assert_eq!(next(&mut state, 3), None);
+
+        state.busy.remove(&1);
+        assert_eq!(next(&mut state, 3), Some(1));
+    }
+
+    #[test]
+    fn at_least_one_request_is_in_flight() {
+        let mut state = state(&[(1, 0), (2, 1)]);
+        assert_eq!(next(&mut state, 0), Some(0));
+        assert_eq!(next(&mut state, 0), None);
+    }
+}
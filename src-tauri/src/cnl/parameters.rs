@@ -0,0 +1,474 @@
+use std::{collections::BTreeMap, path::Path, sync::Arc};
+
+use canzero_config::config::{ObjectEntryAccess, TypeRef};
+use serde::{Deserialize, Serialize};
+use tokio::task::JoinSet;
+
+use super::{
+    frame::Value,
+    network::{
+        node_object::{
+            transaction::{EntryStatus, MAX_STAGED_ENTRIES},
+            NodeObject,
+        },
+        object_entry_object::{verify::values_match, ObjectEntryObject},
+        request_scheduler::BulkProgress,
+    },
+    serialize::{value_from_typed_json, value_to_json},
+};
+
+/// Values of `Global` object entries keyed by node and object entry, e.g.
+/// ```yaml
+/// name: pid gains
+/// created: 2024-05-12T14:03:11+02:00
+/// parameters:
+///   motor_driver:
+///     kp: 1.25
+///     control_mode: TORQUE
+/// ```
+#[derive(Serialize, Deserialize, Clone)]
+pub struct ParameterFile {
+    pub name: String,
+    pub created: String,
+    pub parameters: BTreeMap<String, BTreeMap<String, serde_json::Value>>,
+}
+
+#[derive(Serialize, Clone, Copy, PartialEq, Debug)]
+#[serde(rename_all = "lowercase")]
+pub enum ParameterStatus {
+    Equal,
+    Different,
+    /// The live value could not be read.
+    Unknown,
+    /// The entry of the file does not exist, is not writable or has an invalid value.
+    Invalid,
+}
+
+#[derive(Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct ParameterDiff {
+    node: String,
+    object_entry: String,
+    file: Option<Value>,
+    live: Option<Value>,
+    status: ParameterStatus,
+    error: Option<String>,
+}
+
+impl ParameterFile {
+    pub fn load(path: &Path) -> Result<Self, String> {
+        let content = std::fs::read_to_string(path)
+            .map_err(|err| format!("Failed to read {path:?} : {err}"))?;
+        if is_yaml(path) {
+            serde_yaml::from_str(&content).map_err(|err| format!("Invalid parameter file : {err}"))
+        } else {
+            serde_json::from_str(&content).map_err(|err| format!("Invalid parameter file : {err}"))
+        }
+    }
+
+    /// Writes the file as yaml if the path ends with `.yaml` or `.yml` and as json otherwise.
+    pub fn save(&self, path: &Path) -> Result<(), String> {
+        let content = if is_yaml(path) {
+            serde_yaml::to_string(self).map_err(|err| err.to_string())?
+        } else {
+            serde_json::to_string_pretty(self).map_err(|err| err.to_string())?
+        };
+        std::fs::write(path, content).map_err(|err| format!("Failed to write {path:?} : {err}"))
+    }
+
+    /// Number of object entries in the file.
+    pub fn count(&self) -> usize {
+        self.parameters.values().map(BTreeMap::len).sum()
+    }
+}
+
+fn is_yaml(path: &Path) -> bool {
+    matches!(
+        path.extension().and_then(|ext| ext.to_str()),
+        Some("yaml" | "yml")
+    )
+}
+
+/// Reads all `Global` object entries of the node, or of all nodes if `node` is None.
+/// Fails if any of the get requests fails, to never store an incomplete parameter set.
+pub async fn snapshot(
+    app_handle: &tauri::AppHandle,
+    nodes: &[Arc<NodeObject>],
+    node: Option<&str>,
+    name: &str,
+) -> Result<ParameterFile, String> {
+    if let Some(node) = node {
+        if !nodes.iter().any(|n| n.name() == node) {
+            return Err(format!("Unknown node {node}"));
+        }
+    }
+    let object_entries: Vec<Arc<ObjectEntryObject>> = nodes
+        .iter()
+        .filter(|n| match node {
+            Some(node) => n.name() == node,
+            None => true,
+        })
+        .flat_map(|n| n.object_entries().iter())
+        .filter(|oe| is_parameter(oe))
+        .cloned()
+        .collect();
+
+    let mut parameters: BTreeMap<String, BTreeMap<String, serde_json::Value>> = BTreeMap::new();
+    let mut errors = vec![];
+    for (oe, result) in get_all(app_handle, "snapshot", object_entries).await {
+        match result {
+            Ok(value) => {
+                parameters
+                    .entry(oe.node_ref().name().to_owned())
+                    .or_default()
+                    .insert(oe.name().to_owned(), value_to_json(&value));
+            }
+            Err(err) => errors.push(err),
+        }
+    }
+    if !errors.is_empty() {
+        return Err(errors.join("\n"));
+    }
+    Ok(ParameterFile {
+        name: name.to_owned(),
+        created: chrono::Local::now().to_rfc3339(),
+        parameters,
+    })
+}
+
+/// Compares the file against the live values, which are requested from the nodes.
+pub async fn diff(
+    app_handle: &tauri::AppHandle,
+    nodes: &[Arc<NodeObject>],
+    file: &ParameterFile,
+) -> Vec<ParameterDiff> {
+    let (mut diffs, valid) = resolve(nodes, file);
+    let object_entries = valid.iter().map(|(oe, _)| oe.clone()).collect();
+    let live: Vec<_> = get_all(app_handle, "diff", object_entries).await;
+    for ((oe, value), (_, result)) in valid.into_iter().zip(live) {
+        diffs.push(compare(&oe, value, result));
+    }
+    diffs
+}
+
+/// Writes all values of the file and reads them back to check that the writes took effect.
+/// Nothing is written if any entry of the file is invalid.
+pub async fn restore(
+    app_handle: &tauri::AppHandle,
+    nodes: &[Arc<NodeObject>],
+    file: &ParameterFile,
+    atomic: bool,
+) -> Result<Vec<ParameterDiff>, String> {
+    let (invalid, valid) = resolve(nodes, file);
+    if !invalid.is_empty() {
+        return Err(invalid
+            .into_iter()
+            .map(|diff| {
+                format!(
+                    "{}::{} : {}",
+                    diff.node,
+                    diff.object_entry,
+                    diff.error.unwrap_or_default()
+                )
+            })
+            .collect::<Vec<_>>()
+            .join("\n"));
+    }
+
+    let progress = Arc::new(BulkProgress::new(app_handle, "restore", valid.len()));
+    let mut set_errors = if atomic {
+        set_transactions(nodes, &valid, &progress).await?
+    } else {
+        set_each(&valid, &progress).await?
+    };
+
+    let object_entries = valid.iter().map(|(oe, _)| oe.clone()).collect();
+    let read_back: Vec<_> = get_all(app_handle, "verify", object_entries).await;
+    Ok(valid
+        .into_iter()
+        .zip(read_back)
+        .map(|((oe, value), (_, result))| {
+            let key = (oe.node_ref().name().to_owned(), oe.name().to_owned());
+            match set_errors.remove(&key) {
+                Some(err) => ParameterDiff {
+                    node: key.0,
+                    object_entry: key.1,
+                    file: Some(value),
+                    live: result.ok(),
+                    status: ParameterStatus::Different,
+                    error: Some(err),
+                },
+                None => compare(&oe, value, result),
+            }
+        })
+        .collect())
+}
+
+/// Failed set requests by node and object entry.
+type SetErrors = BTreeMap<(String, String), String>;
+
+async fn set_each(
+    valid: &[(Arc<ObjectEntryObject>, Value)],
+    progress: &Arc<BulkProgress>,
+) -> Result<SetErrors, String> {
+    let mut requests = JoinSet::new();
+    for (index, (oe, value)) in valid.iter().enumerate() {
+        let oe = oe.clone();
+        let value = value.clone();
+        let progress = progress.clone();
+        requests.spawn(async move {
+            let result = oe.set(value).await;
+            progress.complete(&result);
+            (index, result)
+        });
+    }
+    let mut set_errors = BTreeMap::new();
+    while let Some(result) = requests.join_next().await {
+        let (index, result) = result.map_err(|err| err.to_string())?;
+        if let Err(err) = result {
+            let (oe, _) = &valid[index];
+            set_errors.insert((oe.node_ref().name().to_owned(), oe.name().to_owned()), err);
+        }
+    }
+    Ok(set_errors)
+}
+
+/// Sets the entries of each node in a single transaction.
+async fn set_transactions(
+    nodes: &[Arc<NodeObject>],
+    valid: &[(Arc<ObjectEntryObject>, Value)],
+    progress: &Arc<BulkProgress>,
+) -> Result<SetErrors, String> {
+    let mut per_node = vec![];
+    for node in nodes {
+        let values: Vec<(String, Value)> = valid
+            .iter()
+            .filter(|(oe, _)| oe.node_id() == node.id())
+            .map(|(oe, value)| (oe.name().to_owned(), value.clone()))
+            .collect();
+        if values.len() > MAX_STAGED_ENTRIES {
+            return Err(format!(
+                "{} has more than {MAX_STAGED_ENTRIES} parameters, which can't be set in one transaction",
+                node.name()
+            ));
+        }
+        if !values.is_empty() {
+            per_node.push((node.clone(), values));
+        }
+    }
+
+    let mut transactions = JoinSet::new();
+    for (node, values) in per_node {
+        transactions.spawn(async move {
+            let names: Vec<String> = values.iter().map(|(name, _)| name.clone()).collect();
+            let result = node.set_atomic(values).await;
+            (node.name().to_owned(), names, result)
+        });
+    }
+    let mut set_errors = BTreeMap::new();
+    while let Some(result) = transactions.join_next().await {
+        let (node, names, result) = result.map_err(|err| err.to_string())?;
+        let results: Vec<Result<(), String>> = match result {
+            Ok(transaction) => transaction
+                .entries
+                .iter()
+                .map(|entry| match (transaction.outcome, entry.status) {
+                    (_, EntryStatus::Committed) => Ok(()),
+                    (None, _) => Err("Commit request timed out".to_owned()),
+                    (_, EntryStatus::Rejected) => Err("Rejected by the node".to_owned()),
+                    (_, EntryStatus::NotApplied) => {
+                        Err("Transaction failed, the value was not applied".to_owned())
+                    }
+                })
+                .collect(),
+            Err(err) => names.iter().map(|_| Err(err.clone())).collect(),
+        };
+        for (name, result) in names.into_iter().zip(results) {
+            progress.complete(&result);
+            if let Err(err) = result {
+                set_errors.insert((node.clone(), name), err);
+            }
+        }
+    }
+    Ok(set_errors)
+}
+
+fn is_parameter(oe: &ObjectEntryObject) -> bool {
+    matches!(oe.access(), ObjectEntryAccess::Global) && !oe.is_external()
+}
+
+/// Looks up the object entries of the file, entries which can't be written are
+/// returned as invalid diffs.
+#[allow(clippy::type_complexity)]
+fn resolve(
+    nodes: &[Arc<NodeObject>],
+    file: &ParameterFile,
+) -> (Vec<ParameterDiff>, Vec<(Arc<ObjectEntryObject>, Value)>) {
+    let mut invalid = vec![];
+    let mut valid = vec![];
+    for (node_name, entries) in &file.parameters {
+        let node = nodes.iter().find(|node| node.name() == node_name);
+        for (oe_name, json) in entries {
+            let oe =
+                node.and_then(|node| node.object_entries().iter().find(|oe| oe.name() == oe_name));
+            let result = match oe {
+                None => Err(format!("Unknown object entry {node_name}::{oe_name}")),
+                Some(oe) => parameter_value(oe_name, oe.ty(), is_parameter(oe), json)
+                    .map(|value| (oe.clone(), value)),
+            };
+            match result {
+                Ok(entry) => valid.push(entry),
+                Err(err) => invalid.push(ParameterDiff {
+                    node: node_name.clone(),
+                    object_entry: oe_name.clone(),
+                    file: None,
+                    live: None,
+                    status: ParameterStatus::Invalid,
+                    error: Some(err),
+                }),
+            }
+        }
+    }
+    (invalid, valid)
+}
+
+fn parameter_value(
+    oe_name: &str,
+    ty: &TypeRef,
+    writable: bool,
+    json: &serde_json::Value,
+) -> Result<Value, String> {
+    if !writable {
+        return Err(format!("{oe_name} is not writable"));
+    }
+    value_from_typed_json(ty, json)
+}
+
+fn compare(oe: &ObjectEntryObject, value: Value, live: Result<Value, String>) -> ParameterDiff {
+    let (status, live, error) = match live {
+        Ok(live) if values_match(oe.ty(), &value, &live) => {
+            (ParameterStatus::Equal, Some(live), None)
+        }
+        Ok(live) => (ParameterStatus::Different, Some(live), None),
+        Err(err) => (ParameterStatus::Unknown, None, Some(err)),
+    };
+    ParameterDiff {
+        node: oe.node_ref().name().to_owned(),
+        object_entry: oe.name().to_owned(),
+        file: Some(value),
+        live,
+        status,
+        error,
+    }
+}
+
+/// Requests the current value of every object entry, the request schedulers of the
+/// nodes limit how many of them are in flight. The results are in the order of `object_entries`.
+async fn get_all(
+    app_handle: &tauri::AppHandle,
+    operation: &str,
+    object_entries: Vec<Arc<ObjectEntryObject>>,
+) -> Vec<(Arc<ObjectEntryObject>, Result<Value, String>)> {
+    let progress = Arc::new(BulkProgress::new(
+        app_handle,
+        operation,
+        object_entries.len(),
+    ));
+    let mut requests = JoinSet::new();
+    for (index, oe) in object_entries.iter().enumerate() {
+        let oe = oe.clone();
+        let progress = progress.clone();
+        requests.spawn(async move {
+            let result = oe.get().await;
+            progress.complete(&result);
+            (index, result)
+        });
+    }
+    let mut results: Vec<Option<Result<Value, String>>> = vec![None; object_entries.len()];
+    while let Some(result) = requests.join_next().await {
+        if let Ok((index, result)) = result {
+            results[index] = Some(result);
+        }
+    }
+    object_entries
+        .into_iter()
+        .zip(results)
+        .map(|(oe, result)| {
+            let result =
+                result.unwrap_or_else(|| Err(format!("Get request for {} panicked", oe.name())));
+            (oe, result)
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use canzero_config::config::{make_config_ref, SignalType, Type, Visibility};
+
+    use super::*;
+
+    fn gains() -> TypeRef {
+        let decimal = make_config_ref(Type::Primitive(SignalType::Decimal {
+            size: 16,
+            offset: 0.0,
+            scale: 0.001,
+        }));
+        make_config_ref(Type::Struct {
+            name: "gains".to_owned(),
+            description: None,
+            attribs: vec![
+                ("kp".to_owned(), decimal.clone()),
+                ("ki".to_owned(), decimal),
+            ],
+            visibility: Visibility::Global,
+        })
+    }
+
+    #[test]
+    fn parameters_must_be_writable() {
+        let json = serde_json::json!({ "kp": 1.25, "ki": 0.5 });
+        assert!(parameter_value("gains", &gains(), true, &json).is_ok());
+        assert_eq!(
+            parameter_value("gains", &gains(), false, &json).unwrap_err(),
+            "gains is not writable"
+        );
+    }
+
+    #[test]
+    fn parameters_must_match_the_type() {
+        let missing = serde_json::json!({ "kp": 1.25 });
+        assert_eq!(
+            parameter_value("gains", &gains(), true, &missing).unwrap_err(),
+            "Attribute ki does not exist"
+        );
+        let out_of_range = serde_json::json!({ "kp": 1.25, "ki": 100.0 });
+        assert!(parameter_value("gains", &gains(), true, &out_of_range).is_err());
+    }
+
+    #[test]
+    fn files_round_trip_as_yaml_and_json() {
+        let file = ParameterFile {
+            name: "pid gains".to_owned(),
+            created: "2024-05-12T14:03:11+02:00".to_owned(),
+            parameters: BTreeMap::from([(
+                "motor_driver".to_owned(),
+                BTreeMap::from([
+                    ("kp".to_owned(), serde_json::json!(1.25)),
+                    ("control_mode".to_owned(), serde_json::json!("TORQUE")),
+                ]),
+            )]),
+        };
+        let dir = std::env::temp_dir().join("canzero-parameters");
+        std::fs::create_dir_all(&dir).unwrap();
+        for name in ["gains.yaml", "gains.json"] {
+            let path = dir.join(name);
+            file.save(&path).unwrap();
+            let loaded = ParameterFile::load(&path).unwrap();
+            assert_eq!(loaded.name, file.name);
+            assert_eq!(loaded.parameters, file.parameters);
+            assert_eq!(loaded.count(), 2);
+        }
+        let yaml = std::fs::read_to_string(dir.join("gains.yaml")).unwrap();
+        assert!(yaml.contains("control_mode: TORQUE"));
+    }
+}
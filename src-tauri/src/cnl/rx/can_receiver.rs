@@ -5,6 +5,7 @@ use canzero_config::config::MessageRef;
 use crate::{
     cnl::{
         can_adapter::CanAdapter,
+        capture::CaptureObject,
         connection::{ConnectionObject, ConnectionStatus},
         network::NetworkObject,
         rx::handler_lookup::HandlerLookup,
@@ -24,6 +25,7 @@ impl CanReceiver {
         can_adapter: &Arc<CanAdapter>,
         messages: &Vec<MessageRef>,
         trace: &Arc<TraceObject>,
+        capture: &Arc<CaptureObject>,
         network_object: &Arc<NetworkObject>,
         app_handle: &tauri::AppHandle,
         connection_object: Arc<ConnectionObject>,
@@ -33,6 +35,7 @@ impl CanReceiver {
             can_adapter,
             messages,
             trace,
+            capture,
             network_object,
             app_handle,
             node_id,
@@ -43,16 +46,25 @@ impl CanReceiver {
             bus_name: String,
             bus_id: u32,
         ) -> Result<()> {
+            if let Ok(frame) = &frame {
+                receiver_data.trace.record(frame, bus_id).await;
+            }
             match frame {
                 Ok(frame) => match receiver_data.lookup.get_handler(frame.key()) {
                     Some(handler) => {
-                        let frame = handler.handle(&frame).await?;
+                        let decoded = handler.handle(&frame).await;
+                        receiver_data.capture.push_frame(
+                            &frame,
+                            decoded.as_ref().ok().map(|decoded| &decoded.value),
+                            bus_id,
+                        );
                         receiver_data
                             .trace
-                            .push_normal_frame(frame, &bus_name, bus_id)
+                            .push_normal_frame(decoded?, &bus_name, bus_id)
                             .await;
                     }
                     None => {
+                        receiver_data.capture.push_frame(&frame, None, bus_id);
                         receiver_data
                             .trace
                             .push_undefined_frame(frame, &bus_name, bus_id)
@@ -60,6 +72,7 @@ impl CanReceiver {
                     }
                 },
                 Err(error) => {
+                    receiver_data.capture.push_error(&error, bus_id);
                     receiver_data
                         .trace
                         .push_error_frame(error, &bus_name, bus_id)
@@ -124,6 +137,7 @@ impl CanReceiver {
 struct CanReceiverData {
     can_adapter: Arc<CanAdapter>,
     trace: Arc<TraceObject>,
+    capture: Arc<CaptureObject>,
     app_handle: tauri::AppHandle,
     lookup: HandlerLookup,
 }
@@ -133,23 +147,37 @@ impl CanReceiverData {
         can_adapter: &Arc<CanAdapter>,
         messages: &Vec<MessageRef>,
         trace: &Arc<TraceObject>,
+        capture: &Arc<CaptureObject>,
         network_object: &Arc<NetworkObject>,
         app_handle: &tauri::AppHandle,
         node_id: u8,
     ) -> Self {
+        let (lookup, errors) = HandlerLookup::create(
+            &messages
+                .iter()
+                // responses to requests, which were sent on a fallback bus, arrive on that bus.
+                .filter(|msg| {
+                    msg.bus().id() == can_adapter.bus().id() || msg.usage().is_configuration()
+                })
+                .cloned()
+                .collect(),
+            network_object,
+            node_id,
+        );
+        for err in errors {
+            notify_error(
+                app_handle,
+                "Unresolved Message Handler",
+                &err,
+                chrono::Local::now(),
+            );
+        }
         Self {
             can_adapter: can_adapter.clone(),
             trace: trace.clone(),
+            capture: capture.clone(),
             app_handle: app_handle.clone(),
-            lookup: HandlerLookup::create(
-                &messages
-                    .iter()
-                    .filter(|msg| msg.bus().id() == can_adapter.bus().id())
-                    .cloned()
-                    .collect(),
-                network_object,
-                node_id,
-            ),
+            lookup,
         }
     }
 }
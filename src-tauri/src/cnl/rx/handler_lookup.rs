@@ -4,22 +4,28 @@ use canzero_config::config::{message, MessageId, MessageRef};
 
 use crate::cnl::{
     handler::{
-        get_req_frame_handler::GetReqFrameHandler, get_resp_frame_handler::GetRespFrameHandler,
-        heartbeat_frame_handler::HeartbeatFrameHandler, set_req_frame_handler::SetReqFrameHandler,
-        set_resp_frame_handler::SetRespFrameHandler, stream_frame_handler::StreamFrameHandler,
-        MessageHandler,
+        command_resp_frame_handler::CommandRespFrameHandler,
+        external_frame_handler::ExternalFrameHandler, get_req_frame_handler::GetReqFrameHandler,
+        get_resp_frame_handler::GetRespFrameHandler,
+        heartbeat_frame_handler::HeartbeatFrameHandler,
+        set_commit_resp_frame_handler::SetCommitRespFrameHandler,
+        set_req_frame_handler::SetReqFrameHandler, set_resp_frame_handler::SetRespFrameHandler,
+        stream_frame_handler::StreamFrameHandler, MessageHandler,
     },
-    network::NetworkObject,
+    network::{node_object::external_object_entry_name, NetworkObject},
 };
 
 pub struct HandlerLookup(HashMap<u32, MessageHandler>);
 
 impl HandlerLookup {
+    /// Messages without a handler are only shown in the trace, the errors of
+    /// handlers, which couldn't be resolved, are returned.
     pub fn create(
         messages: &Vec<MessageRef>,
         network_object: &Arc<NetworkObject>,
         node_id: u8,
-    ) -> Self {
+    ) -> (Self, Vec<String>) {
+        let mut errors = vec![];
         let map = messages
             .iter()
             .map(|msg| {
@@ -53,7 +59,19 @@ impl HandlerLookup {
                         )),
                     )),
                     message::MessageUsage::CommandReq(_) => None, //TODO
-                    message::MessageUsage::CommandResp(_) => None, //TODO
+                    message::MessageUsage::CommandResp(command) => {
+                        let command_object = network_object
+                            .nodes()
+                            .iter()
+                            .flat_map(|no| no.commands())
+                            .find(|co| co.tx_message().name() == command.tx_message().name())?;
+                        Some((
+                            key,
+                            MessageHandler::CommandRespFrameHandler(
+                                CommandRespFrameHandler::create(msg, command_object),
+                            ),
+                        ))
+                    }
                     message::MessageUsage::GetResp => Some((
                         key,
                         MessageHandler::GetRespFrameHandler(GetRespFrameHandler::create(
@@ -78,6 +96,19 @@ impl HandlerLookup {
                         key,
                         MessageHandler::SetReqFrameHandler(SetReqFrameHandler::create(msg)),
                     )),
+                    // stage and commit requests are only decoded for the trace, like set requests.
+                    message::MessageUsage::SetStageReq | message::MessageUsage::SetCommitReq => {
+                        Some((
+                            key,
+                            MessageHandler::SetReqFrameHandler(SetReqFrameHandler::create(msg)),
+                        ))
+                    }
+                    message::MessageUsage::SetCommitResp => Some((
+                        key,
+                        MessageHandler::SetCommitRespFrameHandler(
+                            SetCommitRespFrameHandler::create(network_object, msg, node_id),
+                        ),
+                    )),
                     message::MessageUsage::Heartbeat => Some((
                         key,
                         MessageHandler::HeartbeatFrameHandler(HeartbeatFrameHandler::create(
@@ -85,16 +116,60 @@ impl HandlerLookup {
                             network_object.nodes(),
                         )),
                     )),
-                    message::MessageUsage::External { interval: _ } => None,
+                    message::MessageUsage::External { interval: _ } => {
+                        match external_handler(msg, network_object) {
+                            Ok(handler) => handler.map(|handler| {
+                                (key, MessageHandler::ExternalFrameHandler(handler))
+                            }),
+                            Err(err) => {
+                                errors.push(err);
+                                None
+                            }
+                        }
+                    }
                 }
             })
             .flatten()
             .collect();
 
-        Self(map)
+        (Self(map), errors)
     }
 
     pub fn get_handler(&self, key: u32) -> Option<&MessageHandler> {
         self.0.get(&key)
     }
 }
+
+/// Handler of an external message, None if the message has no known transmitter.
+fn external_handler(
+    msg: &MessageRef,
+    network_object: &Arc<NetworkObject>,
+) -> Result<Option<ExternalFrameHandler>, String> {
+    let Some(node_object) = network_object
+        .nodes()
+        .iter()
+        .find(|no| no.is_external() && no.tx_messages().iter().any(|m| m.name() == msg.name()))
+    else {
+        return Ok(None);
+    };
+    let object_entries = msg
+        .signals()
+        .iter()
+        .map(|signal| {
+            let name = external_object_entry_name(signal);
+            node_object
+                .object_entries()
+                .iter()
+                .find(|oeo| oeo.name() == name)
+                .cloned()
+                .ok_or_else(|| {
+                    format!(
+                        "{} has no virtual object entry {name} for the message {}",
+                        node_object.name(),
+                        msg.name()
+                    )
+                })
+        })
+        .collect::<Result<Vec<_>, String>>()?;
+    Ok(Some(ExternalFrameHandler::create(msg, &object_entries)))
+}
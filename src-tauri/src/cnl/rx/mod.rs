@@ -4,7 +4,7 @@ use canzero_config::config::NetworkRef;
 
 use self::can_receiver::CanReceiver;
 
-use super::{can_adapter::CanAdapter, connection::ConnectionObject, network::NetworkObject, trace::TraceObject};
+use super::{can_adapter::CanAdapter, capture::CaptureObject, connection::ConnectionObject, network::NetworkObject, trace::TraceObject};
 
 mod can_receiver;
 mod handler_lookup;
@@ -23,6 +23,7 @@ impl RxCom {
     pub fn create(
         network_config: &NetworkRef,
         trace: &Arc<TraceObject>,
+        capture: &Arc<CaptureObject>,
         network_object: &Arc<NetworkObject>,
         app_handle: &tauri::AppHandle,
         can_adapters: &Vec<Arc<CanAdapter>>,
@@ -37,6 +38,7 @@ impl RxCom {
                         can_adapter,
                         network_config.messages(),
                         trace,
+                        capture,
                         network_object,
                         app_handle,
                         connection_object.clone(),
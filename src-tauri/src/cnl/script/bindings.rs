@@ -0,0 +1,247 @@
+use std::{
+    sync::{atomic::Ordering, Arc},
+    time::{Duration, Instant},
+};
+
+use rhai::{Array, Dynamic, Engine, EvalAltResult, FnPtr, Map, NativeCallContext};
+
+use crate::{
+    cnl::{
+        network::object_entry_object::ObjectEntryObject, serialize::FrameSerializer,
+        transmitter::raw_frame,
+    },
+    journal::{self, JournalEvent},
+};
+
+use super::{
+    value::{attributes_from_map, from_dynamic, to_dynamic},
+    ScriptContext, ScriptOutputLevel,
+};
+
+type ScriptResult<T> = Result<T, Box<EvalAltResult>>;
+
+// granularity in which blocking functions check if the script was stopped.
+const POLL_INTERVAL: Duration = Duration::from_millis(10);
+
+/// Registers the CNL api of scripts:
+/// - `get("node.entry")` requests the current value and `read("node.entry")` returns the
+///   latest received value (or `()`).
+/// - `set("node.entry", value)` waits until the node acknowledged the value.
+/// - `wait_until(|| condition, timeout_ms)` returns false if the condition didn't hold in time.
+/// - `sleep(ms)`, `log(message)`, `assert(condition, message)`.
+/// - `command("node.command", #{ argument: value })` invokes a command and waits for its
+///   response.
+/// - `send("bus", id, [bytes])` sends a raw frame, `send_message("message", #{ ... })`
+///   composes a configured message.
+pub fn register(engine: &mut Engine, context: &Arc<ScriptContext>) {
+    let ctx = context.clone();
+    engine.register_fn("get", move |path: &str| -> ScriptResult<Dynamic> {
+        let object_entry = ctx.object_entry(path)?;
+        let value = ctx.block_on(object_entry.get())?;
+        Ok(to_dynamic(&value))
+    });
+
+    let ctx = context.clone();
+    engine.register_fn("read", move |path: &str| -> ScriptResult<Dynamic> {
+        let object_entry = ctx.object_entry(path)?;
+        Ok(ctx
+            .runtime
+            .block_on(object_entry.latest_value())
+            .map_or(Dynamic::UNIT, |value| to_dynamic(&value)))
+    });
+
+    let ctx = context.clone();
+    engine.register_fn("set", move |path: &str, value: Dynamic| -> ScriptResult<()> {
+        let object_entry = ctx.object_entry(path)?;
+        let value = from_dynamic(object_entry.ty(), &value, path)?;
+        ctx.block_on(object_entry.set(value))?;
+        Ok(())
+    });
+
+    let ctx = context.clone();
+    engine.register_fn("sleep", move |millis: i64| -> ScriptResult<()> {
+        ctx.sleep(Duration::from_millis(millis.max(0) as u64))
+    });
+
+    let ctx = context.clone();
+    engine.register_fn(
+        "wait_until",
+        move |call: NativeCallContext, condition: FnPtr, timeout: i64| -> ScriptResult<bool> {
+            let deadline = Instant::now() + Duration::from_millis(timeout.max(0) as u64);
+            loop {
+                if condition.call_within_context::<bool>(&call, ())? {
+                    return Ok(true);
+                }
+                if Instant::now() >= deadline {
+                    return Ok(false);
+                }
+                ctx.sleep(POLL_INTERVAL)?;
+            }
+        },
+    );
+
+    let ctx = context.clone();
+    engine.register_fn("log", move |message: Dynamic| {
+        ctx.output(ScriptOutputLevel::Info, &message.to_string())
+    });
+
+    engine.register_fn("assert", |condition: bool| -> ScriptResult<()> {
+        if condition {
+            Ok(())
+        } else {
+            Err("Assertion failed".into())
+        }
+    });
+    engine.register_fn("assert", |condition: bool, message: &str| -> ScriptResult<()> {
+        if condition {
+            Ok(())
+        } else {
+            Err(format!("Assertion failed : {message}").into())
+        }
+    });
+
+    let ctx = context.clone();
+    engine.register_fn("command", move |path: &str, arguments: Map| -> ScriptResult<()> {
+        ctx.command(path, &arguments)
+    });
+    let ctx = context.clone();
+    engine.register_fn("command", move |path: &str| -> ScriptResult<()> {
+        ctx.command(path, &Map::new())
+    });
+
+    let ctx = context.clone();
+    engine.register_fn(
+        "send",
+        move |bus: &str, id: i64, data: Array| -> ScriptResult<()> {
+            let bus_id = ctx.bus_id(bus)?;
+            let bytes = data
+                .iter()
+                .map(|byte| match byte.as_int() {
+                    Ok(byte) if (0..=0xFF).contains(&byte) => Ok(byte as u8),
+                    _ => Err(format!("Invalid data byte {byte}")),
+                })
+                .collect::<Result<Vec<u8>, String>>()?;
+            let id = u32::try_from(id).map_err(|_| format!("Invalid CAN id {id}"))?;
+            let frame = raw_frame(id, id > 0x7FF, None, &bytes)?;
+            ctx.block_on(ctx.tx.send_raw(bus_id, frame))?;
+            Ok(())
+        },
+    );
+
+    let ctx = context.clone();
+    engine.register_fn(
+        "send_message",
+        move |message_name: &str, values: Map| -> ScriptResult<()> {
+            let Some(message) = ctx
+                .network_config
+                .messages()
+                .iter()
+                .find(|message| message.name() == message_name)
+            else {
+                return Err(format!("Unknown message {message_name}").into());
+            };
+            let frame = FrameSerializer::new(message)?.can_frame(&attributes_from_map(&values)?)?;
+            ctx.block_on(ctx.tx.send_message(message, frame))?;
+            Ok(())
+        },
+    );
+}
+
+impl ScriptContext {
+    fn block_on<T>(
+        &self,
+        future: impl std::future::Future<Output = Result<T, String>>,
+    ) -> ScriptResult<T> {
+        self.runtime.block_on(future).map_err(Into::into)
+    }
+
+    /// Sleeps in small steps, so a stopped script doesn't linger in a long sleep.
+    fn sleep(&self, duration: Duration) -> ScriptResult<()> {
+        let deadline = Instant::now() + duration;
+        loop {
+            if self.stop.load(Ordering::Relaxed) {
+                return Err(EvalAltResult::ErrorTerminated(
+                    Dynamic::UNIT,
+                    rhai::Position::NONE,
+                )
+                .into());
+            }
+            let now = Instant::now();
+            if now >= deadline {
+                return Ok(());
+            }
+            std::thread::sleep((deadline - now).min(POLL_INTERVAL));
+        }
+    }
+
+    fn object_entry(&self, path: &str) -> ScriptResult<Arc<ObjectEntryObject>> {
+        let Some((node_name, object_entry_name)) = path.split_once('.') else {
+            return Err(format!("Expected <node>.<object_entry>, got {path}").into());
+        };
+        let Some(node) = self
+            .network
+            .nodes()
+            .iter()
+            .find(|node| node.name() == node_name)
+        else {
+            return Err(format!("Unknown node {node_name}").into());
+        };
+        node.object_entries()
+            .iter()
+            .find(|object_entry| object_entry.name() == object_entry_name)
+            .cloned()
+            .ok_or_else(|| format!("Unknown object entry {path}").into())
+    }
+
+    fn bus_id(&self, bus_name: &str) -> ScriptResult<u32> {
+        self.network_config
+            .buses()
+            .iter()
+            .find(|bus| bus.name() == bus_name)
+            .map(|bus| bus.id())
+            .ok_or_else(|| format!("Unknown bus {bus_name}").into())
+    }
+
+    fn command(&self, path: &str, arguments: &Map) -> ScriptResult<()> {
+        let Some((node_name, command_name)) = path.split_once('.') else {
+            return Err(format!("Expected <node>.<command>, got {path}").into());
+        };
+        let Some(command) = self
+            .network
+            .nodes()
+            .iter()
+            .find(|node| node.name() == node_name)
+            .and_then(|node| {
+                node.commands()
+                    .iter()
+                    .find(|command| command.name() == command_name)
+            })
+        else {
+            return Err(format!("Unknown command {path}").into());
+        };
+        // the command only knows the message itself, the serializer needs the shared config.
+        let Some(message) = self
+            .network_config
+            .messages()
+            .iter()
+            .find(|message| message.name() == command.tx_message().name())
+        else {
+            return Err(format!("Command {path} has no request message").into());
+        };
+        let frame = FrameSerializer::new(message)?.can_frame(&attributes_from_map(arguments)?)?;
+        let result = self
+            .runtime
+            .block_on(command.invoke(self.tx.send_message(message, frame)));
+        journal::record(
+            &self.app_handle,
+            JournalEvent::Command {
+                node: node_name.to_owned(),
+                command: command_name.to_owned(),
+                error: result.as_ref().err().cloned(),
+            },
+        );
+        result?;
+        self.output(ScriptOutputLevel::Debug, &format!("invoked {path}"));
+        Ok(())
+    }
+}
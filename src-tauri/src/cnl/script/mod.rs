@@ -0,0 +1,271 @@
+use std::{
+    collections::BTreeMap,
+    sync::{
+        atomic::{AtomicBool, AtomicU32, Ordering},
+        Arc,
+    },
+    time::Instant,
+};
+
+use canzero_config::config;
+use rhai::Engine;
+use serde::Serialize;
+use tauri::Manager;
+
+use super::{network::NetworkObject, tx::TxCom};
+
+mod bindings;
+mod value;
+
+const SCRIPT_OUTPUT_EVENT: &str = "script-output";
+const SCRIPT_STATUS_EVENT: &str = "script-status";
+// finished runs are only kept for the run list of the frontend.
+const MAX_FINISHED_RUNS: usize = 32;
+
+#[derive(Serialize, Clone, Copy)]
+#[serde(rename_all = "lowercase")]
+pub enum ScriptStatus {
+    Running,
+    Passed,
+    Failed,
+    Stopped,
+}
+
+#[derive(Serialize, Clone, Copy)]
+#[serde(rename_all = "lowercase")]
+pub enum ScriptOutputLevel {
+    Debug,
+    Info,
+    Error,
+}
+
+#[derive(Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct ScriptOutput {
+    run_id: u32,
+    level: ScriptOutputLevel,
+    message: String,
+    // milliseconds since the start of the run.
+    time: u64,
+}
+
+#[derive(Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct ScriptRunInfo {
+    run_id: u32,
+    name: String,
+    status: ScriptStatus,
+}
+
+/// Everything a running script can reach, scripts run on a blocking thread and
+/// call into the async CNL through the runtime handle.
+pub struct ScriptContext {
+    run_id: u32,
+    start: Instant,
+    network_config: config::NetworkRef,
+    network: Arc<NetworkObject>,
+    tx: Arc<TxCom>,
+    app_handle: tauri::AppHandle,
+    runtime: tokio::runtime::Handle,
+    stop: Arc<AtomicBool>,
+}
+
+impl ScriptContext {
+    fn output(&self, level: ScriptOutputLevel, message: &str) {
+        let _ = self.app_handle.emit_all(
+            SCRIPT_OUTPUT_EVENT,
+            ScriptOutput {
+                run_id: self.run_id,
+                level,
+                message: message.to_owned(),
+                time: self.start.elapsed().as_millis() as u64,
+            },
+        );
+    }
+}
+
+struct ScriptRun {
+    name: String,
+    status: ScriptStatus,
+    stop: Arc<AtomicBool>,
+}
+
+/// Runs test sequences written in rhai against the CNL.
+///
+/// Every run gets its own engine on a blocking thread, its output and status changes
+/// are emitted as events, so the frontend can follow several runs at once.
+pub struct ScriptRunner {
+    network_config: config::NetworkRef,
+    network: Arc<NetworkObject>,
+    tx: Arc<TxCom>,
+    app_handle: tauri::AppHandle,
+    runs: Arc<std::sync::Mutex<BTreeMap<u32, ScriptRun>>>,
+    next_run_id: AtomicU32,
+}
+
+impl ScriptRunner {
+    pub fn create(
+        network_config: &config::NetworkRef,
+        network: &Arc<NetworkObject>,
+        tx: &Arc<TxCom>,
+        app_handle: &tauri::AppHandle,
+    ) -> Self {
+        Self {
+            network_config: network_config.clone(),
+            network: network.clone(),
+            tx: tx.clone(),
+            app_handle: app_handle.clone(),
+            runs: Arc::new(std::sync::Mutex::new(BTreeMap::new())),
+            next_run_id: AtomicU32::new(0),
+        }
+    }
+
+    /// Compiles the script and starts it, syntax errors are returned directly.
+    pub fn run(&self, name: &str, source: &str) -> Result<ScriptRunInfo, String> {
+        let ast = Engine::new().compile(source).map_err(|err| err.to_string())?;
+        let run_id = self.next_run_id.fetch_add(1, Ordering::SeqCst);
+        let stop = Arc::new(AtomicBool::new(false));
+        self.runs.lock().unwrap().insert(
+            run_id,
+            ScriptRun {
+                name: name.to_owned(),
+                status: ScriptStatus::Running,
+                stop: stop.clone(),
+            },
+        );
+        let context = Arc::new(ScriptContext {
+            run_id,
+            start: Instant::now(),
+            network_config: self.network_config.clone(),
+            network: self.network.clone(),
+            tx: self.tx.clone(),
+            app_handle: self.app_handle.clone(),
+            runtime: tokio::runtime::Handle::current(),
+            stop,
+        });
+        let runs = self.runs.clone();
+        let run_name = name.to_owned();
+        tokio::task::spawn_blocking(move || {
+            let mut engine = Engine::new();
+            let stop = context.stop.clone();
+            engine.on_progress(move |_| {
+                if stop.load(Ordering::Relaxed) {
+                    Some(rhai::Dynamic::UNIT)
+                } else {
+                    None
+                }
+            });
+            let print_context = context.clone();
+            engine.on_print(move |message| {
+                print_context.output(ScriptOutputLevel::Info, message)
+            });
+            let debug_context = context.clone();
+            engine.on_debug(move |message, _, _| {
+                debug_context.output(ScriptOutputLevel::Debug, message)
+            });
+            bindings::register(&mut engine, &context);
+
+            let status = match engine.run_ast(&ast) {
+                Ok(()) => ScriptStatus::Passed,
+                // stopped scripts end with the error of whichever call noticed it first.
+                Err(_) if context.stop.load(Ordering::Relaxed) => ScriptStatus::Stopped,
+                Err(err) => {
+                    context.output(ScriptOutputLevel::Error, &err.to_string());
+                    ScriptStatus::Failed
+                }
+            };
+            {
+                let mut runs = runs.lock().unwrap();
+                if let Some(run) = runs.get_mut(&context.run_id) {
+                    run.status = status;
+                }
+                evict_finished(&mut runs);
+            }
+            let _ = context.app_handle.emit_all(
+                SCRIPT_STATUS_EVENT,
+                ScriptRunInfo {
+                    run_id: context.run_id,
+                    name: run_name,
+                    status,
+                },
+            );
+        });
+        Ok(ScriptRunInfo {
+            run_id,
+            name: name.to_owned(),
+            status: ScriptStatus::Running,
+        })
+    }
+
+    pub fn stop(&self, run_id: u32) -> Result<(), String> {
+        match self.runs.lock().unwrap().get(&run_id) {
+            Some(run) => {
+                run.stop.store(true, Ordering::Relaxed);
+                Ok(())
+            }
+            None => Err(format!("No script run with id {run_id}")),
+        }
+    }
+
+    pub fn runs(&self) -> Vec<ScriptRunInfo> {
+        self.runs
+            .lock()
+            .unwrap()
+            .iter()
+            .map(|(run_id, run)| ScriptRunInfo {
+                run_id: *run_id,
+                name: run.name.clone(),
+                status: run.status,
+            })
+            .collect()
+    }
+}
+
+/// Removes the oldest finished runs, running scripts are never evicted.
+fn evict_finished(runs: &mut BTreeMap<u32, ScriptRun>) {
+    let finished: Vec<u32> = runs
+        .iter()
+        .filter(|(_, run)| !matches!(run.status, ScriptStatus::Running))
+        .map(|(run_id, _)| *run_id)
+        .collect();
+    let evicted = finished.len().saturating_sub(MAX_FINISHED_RUNS);
+    for run_id in &finished[..evicted] {
+        runs.remove(run_id);
+    }
+}
+
+impl Drop for ScriptRunner {
+    fn drop(&mut self) {
+        for run in self.runs.lock().unwrap().values() {
+            run.stop.store(true, Ordering::Relaxed);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn run(status: ScriptStatus) -> ScriptRun {
+        ScriptRun {
+            name: String::new(),
+            status,
+            stop: Arc::new(AtomicBool::new(false)),
+        }
+    }
+
+    #[test]
+    fn evicts_oldest_finished_runs() {
+        let mut runs = BTreeMap::new();
+        runs.insert(0, run(ScriptStatus::Running));
+        for run_id in 1..=MAX_FINISHED_RUNS as u32 + 2 {
+            runs.insert(run_id, run(ScriptStatus::Passed));
+        }
+        evict_finished(&mut runs);
+        assert_eq!(runs.len(), MAX_FINISHED_RUNS + 1);
+        assert!(runs.contains_key(&0));
+        assert!(!runs.contains_key(&1));
+        assert!(!runs.contains_key(&2));
+        assert!(runs.contains_key(&3));
+    }
+}
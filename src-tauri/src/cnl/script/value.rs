@@ -0,0 +1,122 @@
+use canzero_config::config::{SignalType, Type};
+use rhai::{Dynamic, Map};
+
+use crate::cnl::frame::{Attribute, Value};
+
+pub fn to_dynamic(value: &Value) -> Dynamic {
+    match value {
+        Value::UnsignedValue(value) => Dynamic::from_int(*value as i64),
+        Value::SignedValue(value) => Dynamic::from_int(*value),
+        Value::RealValue(value) => Dynamic::from_float(*value),
+        Value::StructValue(attributes) => Dynamic::from_map(
+            attributes
+                .iter()
+                .map(|attribute| (attribute.name().into(), to_dynamic(attribute.value())))
+                .collect(),
+        ),
+        Value::EnumValue(variant) => Dynamic::from(variant.clone()),
+    }
+}
+
+/// Converts a script value into a value of the object entry type `ty`, values outside
+/// of the range of the type are rejected.
+pub fn from_dynamic(ty: &Type, value: &Dynamic, path: &str) -> Result<Value, String> {
+    match ty {
+        Type::Primitive(SignalType::UnsignedInt { size }) => {
+            let max = u64::MAX >> (64 - *size as u32);
+            match value.as_int() {
+                Ok(int) if int >= 0 && int as u64 <= max => Ok(Value::UnsignedValue(int as u64)),
+                Ok(int) => Err(format!("{path} = {int} is out of range [0, {max}]")),
+                Err(_) => Err(format!("{path} expects an integer, got {}", value.type_name())),
+            }
+        }
+        Type::Primitive(SignalType::SignedInt { size }) => {
+            let min = i64::MIN >> (64 - *size as u32);
+            let max = !min;
+            match value.as_int() {
+                Ok(int) if (min..=max).contains(&int) => Ok(Value::SignedValue(int)),
+                Ok(int) => Err(format!("{path} = {int} is out of range [{min}, {max}]")),
+                Err(_) => Err(format!("{path} expects an integer, got {}", value.type_name())),
+            }
+        }
+        Type::Primitive(SignalType::Decimal {
+            size,
+            offset,
+            scale,
+        }) => {
+            let max = (u64::MAX >> (64 - *size as u32)) as f64 * scale + offset;
+            let real = value
+                .as_float()
+                .or_else(|_| value.as_int().map(|int| int as f64))
+                .map_err(|_| format!("{path} expects a number, got {}", value.type_name()))?;
+            if real < *offset || real > max {
+                return Err(format!("{path} = {real} is out of range [{offset}, {max}]"));
+            }
+            Ok(Value::RealValue(real))
+        }
+        Type::Enum {
+            name: _,
+            description: _,
+            size: _,
+            entries,
+            visibility: _,
+        } => {
+            let variant = value
+                .clone()
+                .into_string()
+                .map_err(|_| format!("{path} expects a variant name, got {}", value.type_name()))?;
+            if !entries.iter().any(|(name, _)| name == &variant) {
+                return Err(format!("{variant} is not a variant of {path}"));
+            }
+            Ok(Value::EnumValue(variant))
+        }
+        Type::Struct {
+            name: _,
+            description: _,
+            attribs,
+            visibility: _,
+        } => {
+            let Some(map) = value.read_lock::<Map>() else {
+                return Err(format!("{path} expects a map, got {}", value.type_name()));
+            };
+            attribs
+                .iter()
+                .map(|(name, attrib_ty)| {
+                    let Some(attrib_value) = map.get(name.as_str()) else {
+                        return Err(format!("Missing attribute {path}.{name}"));
+                    };
+                    Ok(Attribute::new(
+                        name,
+                        from_dynamic(attrib_ty, attrib_value, &format!("{path}.{name}"))?,
+                    ))
+                })
+                .collect::<Result<Vec<_>, String>>()
+                .map(Value::StructValue)
+        }
+        Type::Array { len: _, ty: _ } => Err(format!("{path} : arrays are not supported")),
+    }
+}
+
+/// Converts the arguments of a message, the `FrameSerializer` checks them against the encoding.
+pub fn attributes_from_map(map: &Map) -> Result<Vec<Attribute>, String> {
+    map.iter()
+        .map(|(name, value)| Ok(Attribute::new(name, attribute_value(name, value)?)))
+        .collect()
+}
+
+fn attribute_value(name: &str, value: &Dynamic) -> Result<Value, String> {
+    if let Ok(int) = value.as_int() {
+        Ok(match u64::try_from(int) {
+            Ok(unsigned) => Value::UnsignedValue(unsigned),
+            Err(_) => Value::SignedValue(int),
+        })
+    } else if let Ok(real) = value.as_float() {
+        Ok(Value::RealValue(real))
+    } else if let Some(map) = value.read_lock::<Map>() {
+        Ok(Value::StructValue(attributes_from_map(&map)?))
+    } else if let Ok(variant) = value.clone().into_string() {
+        Ok(Value::EnumValue(variant))
+    } else {
+        Err(format!("Invalid value for {name} : {}", value.type_name()))
+    }
+}
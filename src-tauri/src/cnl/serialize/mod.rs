@@ -0,0 +1,286 @@
+use canzero_config::config::{SignalType, Type, TypeRef};
+
+use super::frame::{Attribute, Value};
+
+pub use canzero_serialize::FrameSerializer;
+
+/// Parses the attributes of a message from a json object, e.g.
+/// `{"header" : {"od_index" : 3, ...}, "state" : "Idle", "position" : 1.25}`.
+///
+/// Numbers are kept as they are and checked against the signal type by the serializer,
+/// objects are structs and strings are enum variants. Integers may also be given as strings,
+/// which is how `Value` serializes them to keep 64 bit values intact in the frontend.
+pub fn attributes_from_json(json: &serde_json::Value) -> Result<Vec<Attribute>, String> {
+    let Some(map) = json.as_object() else {
+        return Err("Expected an object of attributes".to_owned());
+    };
+    map.iter()
+        .map(|(name, value)| Ok(Attribute::new(name, value_from_json(name, value)?)))
+        .collect()
+}
+
+fn value_from_json(name: &str, json: &serde_json::Value) -> Result<Value, String> {
+    match json {
+        serde_json::Value::Number(number) => {
+            if let Some(value) = number.as_u64() {
+                Ok(Value::UnsignedValue(value))
+            } else if let Some(value) = number.as_i64() {
+                Ok(Value::SignedValue(value))
+            } else {
+                Ok(Value::RealValue(number.as_f64().unwrap_or(f64::NAN)))
+            }
+        }
+        serde_json::Value::String(string) => {
+            if let Ok(value) = string.parse::<u64>() {
+                Ok(Value::UnsignedValue(value))
+            } else if let Ok(value) = string.parse::<i64>() {
+                Ok(Value::SignedValue(value))
+            } else {
+                Ok(Value::EnumValue(string.clone()))
+            }
+        }
+        serde_json::Value::Object(_) => Ok(Value::StructValue(attributes_from_json(json)?)),
+        _ => Err(format!("Invalid value for {name} : {json}")),
+    }
+}
+
+/// Parses the value of an object entry from json, the value has to match the type exactly,
+/// e.g. a struct needs all of its attributes and integers must be in the range of their size.
+pub fn value_from_typed_json(ty: &TypeRef, json: &serde_json::Value) -> Result<Value, String> {
+    match ty.as_ref() {
+        Type::Primitive(SignalType::SignedInt { size }) => {
+            let Some(val) = json.as_i64() else {
+                return Err("Expected primitive value".to_owned());
+            };
+            let max_uvalue = u64::MAX >> (64 - *size as u32);
+            let max_ivalue = (max_uvalue >> 1) as i64;
+            let min_ivalue = (u64::MAX << (*size as u32 - 1)) as i64;
+            if val <= max_ivalue && val >= min_ivalue {
+                Ok(Value::SignedValue(val))
+            } else {
+                Err("Expected Signed Value".to_owned())
+            }
+        }
+        Type::Primitive(SignalType::UnsignedInt { size }) => {
+            let Some(val) = json.as_u64() else {
+                return Err("Expected primitive value".to_owned());
+            };
+            let max_uvalue = u64::MAX >> (64 - *size as u32);
+            if val <= max_uvalue {
+                Ok(Value::UnsignedValue(val))
+            } else {
+                Err("Expected unsigned value".to_owned())
+            }
+        }
+        Type::Primitive(SignalType::Decimal {
+            size,
+            offset,
+            scale,
+        }) => {
+            let Some(val) = json.as_f64() else {
+                return Err("Expected float value".to_owned());
+            };
+            let max_uvalue = u64::MAX >> (64 - *size as u32);
+            let max = (max_uvalue as f64) * scale + offset;
+            if val <= max && val >= *offset {
+                Ok(Value::RealValue(val))
+            } else {
+                Err("Expected float value".to_owned())
+            }
+        }
+        Type::Struct {
+            name: _,
+            description: _,
+            attribs,
+            visibility: _,
+        } => {
+            let Some(map) = json.as_object() else {
+                return Err("Expected Object".to_owned());
+            };
+            let mut attributes: Vec<Attribute> = vec![];
+            for (name, attr_type) in attribs {
+                let Some(val) = map.get(name) else {
+                    return Err(format!("Attribute {name} does not exist"));
+                };
+                attributes.push(Attribute::new(name, value_from_typed_json(attr_type, val)?));
+            }
+            Ok(Value::StructValue(attributes))
+        }
+        Type::Enum {
+            name: _,
+            description: _,
+            size: _,
+            entries,
+            visibility: _,
+        } => {
+            let Some(variant_str) = json.as_str() else {
+                return Err("Expected Enum value".to_owned());
+            };
+            if entries.iter().any(|e| e.0 == variant_str) {
+                Ok(Value::EnumValue(variant_str.to_string()))
+            } else {
+                Err("Invalid variant".to_owned())
+            }
+        }
+        Type::Array { len: _, ty: _ } => Err("Arrays are not supported".to_owned()),
+    }
+}
+
+/// Inverse of `value_from_typed_json`, unlike the serialization of `Value` integers
+/// are written as numbers.
+pub fn value_to_json(value: &Value) -> serde_json::Value {
+    match value {
+        Value::UnsignedValue(value) => serde_json::Value::from(*value),
+        Value::SignedValue(value) => serde_json::Value::from(*value),
+        Value::RealValue(value) => serde_json::Value::from(*value),
+        Value::StructValue(attributes) => serde_json::Value::Object(
+            attributes
+                .iter()
+                .map(|attribute| {
+                    (
+                        attribute.name().to_owned(),
+                        value_to_json(attribute.value()),
+                    )
+                })
+                .collect(),
+        ),
+        Value::EnumValue(variant) => serde_json::Value::from(variant.clone()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use canzero_config::config::{make_config_ref, Visibility};
+    use serde_json::json;
+
+    use super::*;
+
+    fn primitive(signal_type: SignalType) -> TypeRef {
+        make_config_ref(Type::Primitive(signal_type))
+    }
+
+    fn drive_mode() -> TypeRef {
+        make_config_ref(Type::Enum {
+            name: "drive_mode".to_owned(),
+            description: None,
+            size: 2,
+            entries: vec![("IDLE".to_owned(), 0), ("CRUISE".to_owned(), 1)],
+            visibility: Visibility::Global,
+        })
+    }
+
+    fn parses(ty: &TypeRef, json: serde_json::Value) -> bool {
+        value_from_typed_json(ty, &json).is_ok()
+    }
+
+    #[test]
+    fn signed_range() {
+        let i8 = primitive(SignalType::SignedInt { size: 8 });
+        assert!(parses(&i8, json!(127)));
+        assert!(parses(&i8, json!(-128)));
+        assert!(!parses(&i8, json!(128)));
+        assert!(!parses(&i8, json!(-129)));
+        assert!(!parses(&i8, json!(1.5)));
+
+        let i64 = primitive(SignalType::SignedInt { size: 64 });
+        assert!(parses(&i64, json!(i64::MAX)));
+        assert!(parses(&i64, json!(i64::MIN)));
+        assert!(!parses(&i64, json!(u64::MAX)));
+
+        let i1 = primitive(SignalType::SignedInt { size: 1 });
+        assert!(parses(&i1, json!(-1)));
+        assert!(!parses(&i1, json!(1)));
+    }
+
+    #[test]
+    fn unsigned_range() {
+        let u8 = primitive(SignalType::UnsignedInt { size: 8 });
+        assert!(parses(&u8, json!(0)));
+        assert!(parses(&u8, json!(255)));
+        assert!(!parses(&u8, json!(256)));
+        assert!(!parses(&u8, json!(-1)));
+
+        let u64 = primitive(SignalType::UnsignedInt { size: 64 });
+        assert!(parses(&u64, json!(u64::MAX)));
+    }
+
+    #[test]
+    fn decimal_range() {
+        // -1.0 up to -1.0 + 255 * 0.01
+        let decimal = primitive(SignalType::Decimal {
+            size: 8,
+            offset: -1.0,
+            scale: 0.01,
+        });
+        assert!(parses(&decimal, json!(-1.0)));
+        assert!(parses(&decimal, json!(1.54)));
+        assert!(parses(&decimal, json!(0)));
+        assert!(!parses(&decimal, json!(1.56)));
+        assert!(!parses(&decimal, json!(-1.01)));
+        assert!(!parses(&decimal, json!("0.5")));
+    }
+
+    #[test]
+    fn structs_need_all_attributes() {
+        let ty = make_config_ref(Type::Struct {
+            name: "state".to_owned(),
+            description: None,
+            attribs: vec![
+                ("mode".to_owned(), drive_mode()),
+                (
+                    "speed".to_owned(),
+                    primitive(SignalType::UnsignedInt { size: 8 }),
+                ),
+            ],
+            visibility: Visibility::Global,
+        });
+        let value = value_from_typed_json(&ty, &json!({ "speed": 10, "mode": "CRUISE" })).unwrap();
+        assert_eq!(
+            value_to_json(&value),
+            json!({ "mode": "CRUISE", "speed": 10 })
+        );
+        assert!(!parses(&ty, json!({ "mode": "CRUISE" })));
+        assert!(!parses(&ty, json!({ "mode": "CRUISE", "speed": 300 })));
+        assert!(!parses(&ty, json!(["CRUISE", 10])));
+    }
+
+    #[test]
+    fn enums_need_a_variant() {
+        assert!(parses(&drive_mode(), json!("IDLE")));
+        assert!(!parses(&drive_mode(), json!("BRAKE")));
+        assert!(!parses(&drive_mode(), json!(0)));
+    }
+
+    #[test]
+    fn arrays_are_not_supported() {
+        let ty = make_config_ref(Type::Array {
+            len: 2,
+            ty: primitive(SignalType::UnsignedInt { size: 8 }),
+        });
+        assert!(!parses(&ty, json!([1, 2])));
+    }
+
+    #[test]
+    fn untyped_attributes() {
+        let attributes = attributes_from_json(
+            &json!({ "a": 1, "b": -1, "c": 0.5, "d": "18446744073709551615", "e": "IDLE" }),
+        )
+        .unwrap();
+        let values: Vec<String> = attributes
+            .iter()
+            .map(|attribute| format!("{}={:?}", attribute.name(), attribute.value()))
+            .collect();
+        assert_eq!(
+            values,
+            vec![
+                "a=UnsignedValue(1)",
+                "b=SignedValue(-1)",
+                "c=RealValue(0.5)",
+                "d=UnsignedValue(18446744073709551615)",
+                "e=EnumValue(\"IDLE\")",
+            ]
+        );
+        assert!(attributes_from_json(&json!([1])).is_err());
+        assert!(attributes_from_json(&json!({ "a": null })).is_err());
+    }
+}
@@ -0,0 +1,334 @@
+use std::{
+    collections::{HashMap, VecDeque},
+    sync::Arc,
+    time::Duration,
+};
+
+use serde::Serialize;
+
+use super::{
+    database::{matches_filter, sort_indices, SortCriteria, SortOrder},
+    event::TraceEvent,
+};
+
+pub const DEFAULT_CHRONOLOGICAL_CAPACITY: usize = 100_000;
+pub const DEFAULT_PAGE_SIZE: usize = 200;
+
+/// A page of the chronological trace, as it is shown by the frontend.
+///
+/// Positions count the events, which matched the filter since it was set, therefor
+/// a position keeps referring to the same event, while older events are dropped.
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TracePage {
+    events: Vec<TraceEvent>,
+    // position of the first event of the page.
+    offset: u64,
+    // position of the oldest event, which is still in the ring buffer.
+    first: u64,
+    // position after the newest visible event.
+    total: u64,
+    // number of frames, which were overwritten, because the ring buffer was full.
+    dropped: u64,
+    paused: bool,
+}
+
+/// The events of a page, taken while the trace database is locked.
+/// Sorting them happens after the lock is released, because rx waits for it.
+pub struct PageSnapshot {
+    events: Vec<Arc<TraceEvent>>,
+    // index of the page within the events, after they are sorted.
+    skip: usize,
+    size: usize,
+    offset: u64,
+    first: u64,
+    total: u64,
+    dropped: u64,
+    paused: bool,
+}
+
+impl PageSnapshot {
+    pub fn into_page(self, criteria: &SortCriteria, order: &SortOrder) -> TracePage {
+        let mut indices: Vec<usize> = (0..self.events.len()).collect();
+        sort_indices(&mut indices, &self.events, criteria, order);
+        TracePage {
+            events: indices
+                .iter()
+                .skip(self.skip)
+                .take(self.size)
+                .map(|i| self.events[*i].as_ref().clone())
+                .collect(),
+            offset: self.offset,
+            first: self.first,
+            total: self.total,
+            dropped: self.dropped,
+            paused: self.paused,
+        }
+    }
+}
+
+/// Trace, which keeps every frame in order of arrival (in contrast to the
+/// fixed view of the `TraceDatabase`, which only keeps the latest frame per id).
+///
+/// Frames are stored in a ring buffer, if it is full the oldest frames are dropped.
+/// While paused the view is frozen, but incoming frames are still recorded
+/// and become visible on resume.
+pub struct ChronologicalTrace {
+    events: VecDeque<Arc<TraceEvent>>,
+    id_strings: VecDeque<String>,
+    capacity: usize,
+    // sequence number of the first event in the ring buffer.
+    first_seq: u64,
+    // sequence number up to which events are visible while paused.
+    paused_at: Option<u64>,
+    filter_string: Option<String>,
+    // sequence numbers of the events, which match the filter, in order of arrival.
+    filtered: VecDeque<u64>,
+    // number of events, which matched the filter and were dropped from the ring buffer.
+    filtered_dropped: u64,
+    // timestamp of the previous frame with the same key, used for the delta time.
+    last_timestamps: HashMap<u64, Duration>,
+    // None follows the newest events.
+    page_offset: Option<u64>,
+    page_size: usize,
+}
+
+impl ChronologicalTrace {
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            events: VecDeque::new(),
+            id_strings: VecDeque::new(),
+            capacity: capacity.max(1),
+            first_seq: 0,
+            paused_at: None,
+            filter_string: None,
+            filtered: VecDeque::new(),
+            filtered_dropped: 0,
+            last_timestamps: HashMap::new(),
+            page_offset: None,
+            page_size: DEFAULT_PAGE_SIZE,
+        }
+    }
+
+    pub fn push(&mut self, event: TraceEvent, id_string: String) {
+        let seq = self.first_seq + self.events.len() as u64;
+        if self.events.len() == self.capacity {
+            self.events.pop_front();
+            self.id_strings.pop_front();
+            if self.filtered.front() == Some(&self.first_seq) {
+                self.filtered.pop_front();
+                self.filtered_dropped += 1;
+            }
+            self.first_seq += 1;
+        }
+        let matches = match &self.filter_string {
+            Some(filter_string) => matches_filter(&event, &id_string, filter_string),
+            None => true,
+        };
+        if matches {
+            self.filtered.push_back(seq);
+        }
+        self.events.push_back(Arc::new(event));
+        self.id_strings.push_back(id_string);
+    }
+
+    /// Timestamp of the previous frame with the same key, updated to `timestamp`.
+    pub fn replace_last_timestamp(&mut self, key: u64, timestamp: Duration) -> Option<Duration> {
+        self.last_timestamps.insert(key, timestamp)
+    }
+
+    /// Rebuilds the filtered events, positions restart at the oldest matching event.
+    pub fn set_filter(&mut self, filter_string: Option<String>) {
+        if self.filter_string == filter_string {
+            return;
+        }
+        self.filtered = (0..self.events.len())
+            .filter(|i| match &filter_string {
+                Some(filter_string) => {
+                    matches_filter(&self.events[*i], &self.id_strings[*i], filter_string)
+                }
+                None => true,
+            })
+            .map(|i| self.first_seq + i as u64)
+            .collect();
+        self.filtered_dropped = 0;
+        self.filter_string = filter_string;
+    }
+
+    pub fn pause(&mut self) {
+        if self.paused_at.is_none() {
+            self.paused_at = Some(self.first_seq + self.events.len() as u64);
+        }
+    }
+
+    pub fn resume(&mut self) {
+        self.paused_at = None;
+    }
+
+    pub fn set_page(&mut self, offset: Option<u64>, size: usize) {
+        self.page_offset = offset;
+        self.page_size = size;
+    }
+
+    /// Number of filtered events, which are part of the (possibly frozen) view.
+    fn visible_len(&self) -> usize {
+        match self.paused_at {
+            Some(paused_at) => self.filtered.partition_point(|seq| *seq < paused_at),
+            None => self.filtered.len(),
+        }
+    }
+
+    fn event(&self, seq: u64) -> &Arc<TraceEvent> {
+        &self.events[(seq - self.first_seq) as usize]
+    }
+
+    /// The `size` events starting at position `offset` of the filtered view.
+    /// Without a offset the page ends at the newest event.
+    ///
+    /// Only the page itself is copied if the events are not sorted, otherwise all
+    /// visible events, because the page depends on the order of all of them.
+    pub fn page(&self, offset: Option<u64>, size: usize, criteria: &SortCriteria) -> PageSnapshot {
+        let visible = self.visible_len();
+        let first = self.filtered_dropped;
+        let total = first + visible as u64;
+        let offset = offset
+            .unwrap_or(total.saturating_sub(size as u64))
+            .clamp(first, total);
+        let start = (offset - first) as usize;
+        let (range, skip) = match criteria {
+            SortCriteria::None => (start..(start + size).min(visible), 0),
+            _ => (0..visible, start),
+        };
+        PageSnapshot {
+            events: self
+                .filtered
+                .range(range)
+                .map(|seq| self.event(*seq).clone())
+                .collect(),
+            skip,
+            size,
+            offset,
+            first,
+            total,
+            dropped: self.first_seq,
+            paused: self.paused_at.is_some(),
+        }
+    }
+
+    /// The page, which was last selected by the frontend.
+    pub fn current_page(&self, criteria: &SortCriteria) -> PageSnapshot {
+        self.page(self.page_offset, self.page_size, criteria)
+    }
+
+    /// Filtered events with a timestamp in `start..end` in order of arrival.
+    pub fn between(&self, start: Duration, end: Duration) -> Vec<TraceEvent> {
+        self.filtered
+            .range(0..self.visible_len())
+            .map(|seq| self.event(*seq))
+            .filter(|event| (start..end).contains(event.timestamp()))
+            .map(|event| event.as_ref().clone())
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use canzero_common::CanFrame;
+
+    use super::*;
+    use crate::cnl::trace::{database::id_string, event::Direction, frame::TraceFrame};
+
+    fn push(trace: &mut ChronologicalTrace, id: u32, timestamp_ms: u64) {
+        let frame = TraceFrame::Undefined(CanFrame::new(id, false, false, 0, 0));
+        let id_string = id_string(&frame);
+        let event = TraceEvent::new_relative(
+            "can0",
+            0,
+            frame,
+            None,
+            Duration::from_millis(timestamp_ms),
+            Direction::Rx,
+        );
+        trace.push(event, id_string);
+    }
+
+    fn timestamps(page: &TracePage) -> Vec<u128> {
+        page.events
+            .iter()
+            .map(|event| event.timestamp().as_millis())
+            .collect()
+    }
+
+    #[test]
+    fn follows_newest_events() {
+        let mut trace = ChronologicalTrace::new(10);
+        for i in 0..5 {
+            push(&mut trace, 1, i);
+        }
+        let page = trace.page(None, 2, &SortCriteria::None).into_page(&SortCriteria::None, &SortOrder::Desc);
+        assert_eq!(timestamps(&page), vec![3, 4]);
+        assert_eq!((page.offset, page.first, page.total), (3, 0, 5));
+    }
+
+    #[test]
+    fn paused_view_is_anchored_while_the_ring_wraps() {
+        let mut trace = ChronologicalTrace::new(4);
+        for i in 0..4 {
+            push(&mut trace, 1, i);
+        }
+        trace.pause();
+        let before = trace.page(Some(2), 2, &SortCriteria::None).into_page(&SortCriteria::None, &SortOrder::Desc);
+        // overwrites the events 0 and 1.
+        push(&mut trace, 1, 4);
+        push(&mut trace, 1, 5);
+        let after = trace.page(Some(2), 2, &SortCriteria::None).into_page(&SortCriteria::None, &SortOrder::Desc);
+        assert_eq!(timestamps(&before), vec![2, 3]);
+        assert_eq!(timestamps(&after), vec![2, 3]);
+        assert_eq!((after.first, after.total, after.dropped), (2, 4, 2));
+
+        trace.resume();
+        let resumed = trace.page(None, 2, &SortCriteria::None).into_page(&SortCriteria::None, &SortOrder::Desc);
+        assert_eq!(timestamps(&resumed), vec![4, 5]);
+        assert_eq!(resumed.total, 6);
+    }
+
+    #[test]
+    fn dropped_offsets_are_clamped_to_the_oldest_event() {
+        let mut trace = ChronologicalTrace::new(3);
+        for i in 0..6 {
+            push(&mut trace, 1, i);
+        }
+        let page = trace.page(Some(0), 1, &SortCriteria::None).into_page(&SortCriteria::None, &SortOrder::Desc);
+        assert_eq!(timestamps(&page), vec![3]);
+        assert_eq!(page.offset, 3);
+    }
+
+    #[test]
+    fn filter_is_kept_up_to_date() {
+        let mut trace = ChronologicalTrace::new(4);
+        trace.set_filter(Some("0x2".to_owned()));
+        for i in 0..6 {
+            push(&mut trace, if i % 2 == 0 { 2 } else { 1 }, i);
+        }
+        let page = trace.page(None, 10, &SortCriteria::None).into_page(&SortCriteria::None, &SortOrder::Desc);
+        assert_eq!(timestamps(&page), vec![2, 4]);
+        assert_eq!((page.first, page.total), (1, 3));
+
+        trace.set_filter(None);
+        let page = trace.page(None, 10, &SortCriteria::None).into_page(&SortCriteria::None, &SortOrder::Desc);
+        assert_eq!(timestamps(&page), vec![2, 3, 4, 5]);
+        assert_eq!(trace.between(Duration::from_millis(3), Duration::from_millis(5)).len(), 2);
+    }
+
+    #[test]
+    fn sorted_pages_cover_all_visible_events() {
+        let mut trace = ChronologicalTrace::new(10);
+        for (i, id) in [3, 1, 2].into_iter().enumerate() {
+            push(&mut trace, id, i as u64);
+        }
+        let criteria = SortCriteria::ById;
+        let page = trace.page(Some(1), 2, &criteria).into_page(&criteria, &SortOrder::Desc);
+        let ids: Vec<u32> = page.events.iter().map(|event| event.id()).collect();
+        assert_eq!(ids, vec![2, 3]);
+    }
+}
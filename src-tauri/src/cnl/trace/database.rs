@@ -1,14 +1,15 @@
-use std::{cmp::Reverse, collections::HashMap, time::Duration};
+use std::{borrow::Borrow, cmp::Reverse, collections::HashMap, ops::Index, time::Duration};
 
 use canzero_config::config;
 use tokio::sync::Mutex;
 
 use super::{
-    event::TraceEvent,
+    chronological::{ChronologicalTrace, TracePage, DEFAULT_CHRONOLOGICAL_CAPACITY},
+    event::{Direction, TraceEvent},
     frame::{TraceFrame, TraceFrameKey},
 };
 
-#[derive(Debug)]
+#[derive(Debug, Clone, Copy)]
 pub enum SortCriteria {
     None,
     ByAbsoluteTime,
@@ -19,20 +20,22 @@ pub enum SortCriteria {
     ByDlc,
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone, Copy)]
 pub enum SortOrder {
     Asc,
     Desc,
 }
 
 pub struct TraceDatabaseData {
-    lookup: HashMap<TraceFrameKey, usize>,
+    // frames sent by the control panel get their own row.
+    lookup: HashMap<(TraceFrameKey, Direction), usize>,
     trace: Vec<TraceEvent>,
     trace_id_strings: Vec<String>,
     sort_criteria: SortCriteria,
     sort_order: SortOrder,
     filter_string: Option<String>,
     sorted_filter: Vec<usize>,
+    chronological: ChronologicalTrace,
 }
 
 impl TraceDatabaseData {
@@ -47,9 +50,7 @@ impl TraceDatabaseData {
         match &self.filter_string {
             Some(filter_string) => {
                 for i in 0..self.trace.len() {
-                    if self.trace[i].name().to_lowercase().contains(&filter_string.to_lowercase())
-                        | self.trace_id_strings[i].starts_with(filter_string)
-                    {
+                    if matches_filter(&self.trace[i], &self.trace_id_strings[i], filter_string) {
                         self.sorted_filter.push(i);
                     }
                 }
@@ -65,75 +66,69 @@ impl TraceDatabaseData {
     }
 
     pub fn update_sorting(&mut self) {
-        match &self.sort_criteria {
-            SortCriteria::None => (),
-            SortCriteria::ByAbsoluteTime => self.sort_by_absolute_time(),
-            SortCriteria::ByDeltaTime => self.sort_by_delta_time(),
-            SortCriteria::ById => self.sort_by_id(),
-            SortCriteria::ByName => self.sort_by_name(),
-            SortCriteria::ByDlc => self.sort_by_dlc(),
-            SortCriteria::ByBus => self.sort_by_bus(),
-        }
+        sort_indices(
+            &mut self.sorted_filter,
+            &self.trace,
+            &self.sort_criteria,
+            &self.sort_order,
+        );
     }
+}
 
+/// Filter of the trace view: a case insensitive match of the name or a prefix of the id string.
+pub(super) fn matches_filter(event: &TraceEvent, id_string: &str, filter_string: &str) -> bool {
+    event.name().to_lowercase().contains(&filter_string.to_lowercase())
+        | id_string.starts_with(filter_string)
+}
 
-    pub fn sort_by_absolute_time(&mut self) {
-        match self.sort_order {
-            SortOrder::Asc => self
-                .sorted_filter
-                .sort_by_key(|i| Reverse(self.trace[*i].timestamp().as_millis())),
-            SortOrder::Desc => self
-                .sorted_filter
-                .sort_by_key(|i| self.trace[*i].timestamp().as_millis()),
+/// Sorts the indices of events in `trace`. Sorting is stable, therefor events
+/// with equal keys keep the order of the trace.
+pub(super) fn sort_indices<T>(
+    indices: &mut [usize],
+    trace: &T,
+    criteria: &SortCriteria,
+    order: &SortOrder,
+) where
+    T: Index<usize> + ?Sized,
+    T::Output: Borrow<TraceEvent>,
+{
+    let trace = |i: usize| -> &TraceEvent { trace[i].borrow() };
+    fn sort_by_key<K: Ord>(indices: &mut [usize], order: &SortOrder, key: impl Fn(usize) -> K) {
+        match order {
+            SortOrder::Asc => indices.sort_by_key(|i| Reverse(key(*i))),
+            SortOrder::Desc => indices.sort_by_key(|i| key(*i)),
         }
     }
-
-    pub fn sort_by_delta_time(&mut self) {
-        match self.sort_order {
-            SortOrder::Asc => self
-                .sorted_filter
-                .sort_by_key(|i| Reverse(self.trace[*i].delta_time().as_millis())),
-            SortOrder::Desc => self
-                .sorted_filter
-                .sort_by_key(|i| self.trace[*i].delta_time().as_millis()),
+    match criteria {
+        SortCriteria::None => (),
+        SortCriteria::ByAbsoluteTime => {
+            sort_by_key(indices, order, |i| trace(i).timestamp().as_millis())
         }
-    }
-
-    pub fn sort_by_id(&mut self) {
-        match self.sort_order {
-            SortOrder::Asc => self
-                .sorted_filter
-                .sort_by_key(|i| Reverse(self.trace[*i].id())),
-            SortOrder::Desc => self.sorted_filter.sort_by_key(|i| self.trace[*i].id()),
-        }
-    }
-
-    pub fn sort_by_name(&mut self) {
-        match self.sort_order {
-            SortOrder::Asc => self
-                .sorted_filter
-                .sort_by_key(|i| Reverse(self.trace[*i].name())),
-            SortOrder::Desc => self.sorted_filter.sort_by_key(|i| self.trace[*i].name()),
+        SortCriteria::ByDeltaTime => {
+            sort_by_key(indices, order, |i| trace(i).delta_time().as_millis())
         }
+        SortCriteria::ById => sort_by_key(indices, order, |i| trace(i).id()),
+        SortCriteria::ByName => sort_by_key(indices, order, |i| trace(i).name()),
+        SortCriteria::ByDlc => sort_by_key(indices, order, |i| trace(i).dlc()),
+        SortCriteria::ByBus => sort_by_key(indices, order, |i| trace(i).bus()),
     }
+}
 
-    pub fn sort_by_bus(&mut self) {
-        match self.sort_order {
-            SortOrder::Asc => self
-                .sorted_filter
-                .sort_by_key(|i| Reverse(self.trace[*i].bus())),
-            SortOrder::Desc => self
-                .sorted_filter
-                .sort_by_key(|i| self.trace[*i].bus()),
-        }
-    }
-    pub fn sort_by_dlc(&mut self) {
-        match self.sort_order {
-            SortOrder::Asc => self
-                .sorted_filter
-                .sort_by_key(|i| Reverse(self.trace[*i].dlc())),
-            SortOrder::Desc => self.sorted_filter.sort_by_key(|i| self.trace[*i].dlc()),
+/// The string which is matched against the filter string of the trace.
+pub(super) fn id_string(frame: &TraceFrame) -> String {
+    match frame {
+        TraceFrame::Undefined(can_frame) => {
+            if can_frame.get_ide_flag() {
+                format!("0x{:X}x", can_frame.get_id())
+            } else {
+                format!("0x{:X}", can_frame.get_id())
+            }
         }
+        TraceFrame::Error(err) => err.name().to_owned(),
+        TraceFrame::Frame(frame) => match frame.id() {
+            config::MessageId::StandardId(id) => format!("0x{id:X}"),
+            config::MessageId::ExtendedId(id) => format!("0x{id:X}x"),
+        },
     }
 }
 
@@ -152,13 +147,28 @@ impl TraceDatabase {
                 sort_order: SortOrder::Desc,
                 sort_criteria: SortCriteria::None,
                 sorted_filter: vec![],
+                chronological: ChronologicalTrace::new(DEFAULT_CHRONOLOGICAL_CAPACITY),
             }),
         }
     }
 
-    pub async fn update(&self, frame: TraceFrame, arrive: Duration, bus_name : &str, bus_id : u32) {
+    pub async fn update(
+        &self,
+        frame: TraceFrame,
+        arrive: Duration,
+        bus_name: &str,
+        bus_id: u32,
+        direction: Direction,
+    ) {
         let mut unlocked_data = self.data.lock().await;
-        let trace_lookup_entry = unlocked_data.lookup.get(&frame.key()).cloned();
+        let prev = unlocked_data
+            .chronological
+            .replace_last_timestamp(TraceEvent::key_of(bus_id, &frame, direction), arrive);
+        unlocked_data.chronological.push(
+            TraceEvent::new_relative(bus_name, bus_id, frame.clone(), prev, arrive, direction),
+            id_string(&frame),
+        );
+        let trace_lookup_entry = unlocked_data.lookup.get(&(frame.key(), direction)).cloned();
         match trace_lookup_entry {
             Some(trace_lookup_index) => {
                 // NOTE overwrite previous trace event! }
@@ -172,31 +182,20 @@ impl TraceDatabase {
                             .clone(),
                     ),
                     arrive,
+                    direction,
                 );
             }
             None => {
                 let frame_index = unlocked_data.trace.len();
-                unlocked_data.lookup.insert(frame.key(), frame_index);
-                unlocked_data.trace_id_strings.push(match &frame {
-                    TraceFrame::Undefined(can_frame) => {
-                        if can_frame.get_ide_flag() {
-                            format!("0x{:X}x", can_frame.get_id())
-                        } else {
-                            format!("0x{:X}", can_frame.get_id())
-                        }
-                    }
-                    TraceFrame::Error(err) => err.name().to_owned(),
-                    TraceFrame::Frame(frame) => match frame.id() {
-                        config::MessageId::StandardId(id) => format!("0x{id:X}"),
-                        config::MessageId::ExtendedId(id) => format!("0x{id:X}x"),
-                    },
-                });
+                unlocked_data.lookup.insert((frame.key(), direction), frame_index);
+                unlocked_data.trace_id_strings.push(id_string(&frame));
                 unlocked_data.trace.push(TraceEvent::new_relative(
                     bus_name,
                     bus_id,
                     frame,
                     None,
                     arrive,
+                    direction,
                 ));
             }
         }
@@ -210,6 +209,7 @@ impl TraceDatabase {
 
     pub async fn filter_by(&self, filter_string : Option<String>) {
         let mut unlocked_data = self.data.lock().await;
+        unlocked_data.chronological.set_filter(filter_string.clone());
         unlocked_data.filter_string = filter_string;
     }
 
@@ -223,6 +223,49 @@ impl TraceDatabase {
             .collect()
     }
 
+    /// Freezes the chronological view, frames are still recorded.
+    pub async fn pause(&self) {
+        self.data.lock().await.chronological.pause();
+    }
+
+    pub async fn resume(&self) {
+        self.data.lock().await.chronological.resume();
+    }
+
+    pub async fn set_chronological_page(&self, offset: Option<u64>, size: usize) {
+        self.data.lock().await.chronological.set_page(offset, size);
+    }
+
+    pub async fn get_chronological_page(&self, offset: Option<u64>, size: usize) -> TracePage {
+        let (snapshot, criteria, order) = {
+            let unlocked_data = self.data.lock().await;
+            let criteria = unlocked_data.sort_criteria;
+            (
+                unlocked_data.chronological.page(offset, size, &criteria),
+                criteria,
+                unlocked_data.sort_order,
+            )
+        };
+        snapshot.into_page(&criteria, &order)
+    }
+
+    pub async fn get_current_chronological_page(&self) -> TracePage {
+        let (snapshot, criteria, order) = {
+            let unlocked_data = self.data.lock().await;
+            let criteria = unlocked_data.sort_criteria;
+            (
+                unlocked_data.chronological.current_page(&criteria),
+                criteria,
+                unlocked_data.sort_order,
+            )
+        };
+        snapshot.into_page(&criteria, &order)
+    }
+
+    pub async fn get_chronological_between(&self, start: Duration, end: Duration) -> Vec<TraceEvent> {
+        self.data.lock().await.chronological.between(start, end)
+    }
+
     pub async fn deadlock_watchdog(&self) {
         let _ = self.data.lock().await;
     }
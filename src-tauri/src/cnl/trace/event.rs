@@ -7,9 +7,17 @@ use serde::{ser::SerializeMap, Serialize};
 
 use super::frame::TraceFrame;
 
+/// Received frames and frames sent by the control panel itself.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Direction {
+    Rx,
+    Tx,
+}
+
 #[derive(Clone)]
 pub struct TraceEvent {
     frame: TraceFrame,
+    direction: Direction,
     bus: String,
     key: u64,
     delta_time: Duration, // duration since last frame
@@ -24,6 +32,7 @@ impl TraceEvent {
         frame: TraceFrame,
         prev: Option<Duration>,
         timestamp: Duration,
+        direction: Direction,
     ) -> Self {
         let delta_time = match prev {
             Some(prev) => timestamp.saturating_sub(prev),
@@ -32,12 +41,18 @@ impl TraceEvent {
         Self {
             delta_time,
             bus: bus_name.to_owned(),
-            key: (bus_id as u64) << 32 | (frame.key_u32() as u64),
+            key: Self::key_of(bus_id, &frame, direction),
             frame,
+            direction,
             timestamp,
             arrive: Instant::now(),
         }
     }
+    /// Key of the frame, which is unique per id, bus and direction.
+    pub fn key_of(bus_id: u32, frame: &TraceFrame, direction: Direction) -> u64 {
+        let tx = (direction == Direction::Tx) as u64;
+        tx << 63 | (bus_id as u64) << 32 | (frame.key_u32() as u64)
+    }
     pub fn bus(&self) -> &str {
         &self.bus
     }
@@ -47,6 +62,9 @@ impl TraceEvent {
     pub fn timestamp(&self) -> &Duration {
         &self.timestamp
     }
+    pub fn direction(&self) -> Direction {
+        self.direction
+    }
 }
 
 impl Serialize for TraceEvent {
@@ -62,6 +80,13 @@ impl Serialize for TraceEvent {
             &Instant::now().duration_since(self.arrive).as_millis(),
         )?;
         map.serialize_entry("bus", &self.bus)?;
+        map.serialize_entry(
+            "direction",
+            match self.direction {
+                Direction::Rx => "rx",
+                Direction::Tx => "tx",
+            },
+        )?;
         map.serialize_entry("key", &self.key)?;
         map.serialize_entry(
             "absoluteTime",
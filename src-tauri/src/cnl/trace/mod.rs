@@ -1,33 +1,110 @@
-use std::sync::Arc;
+use std::{fs::File, io::BufWriter, path::Path, sync::Arc, time::Duration};
 
 use self::{
+    chronological::TracePage,
     database::{SortCriteria, SortOrder, TraceDatabase},
+    event::{Direction, TraceEvent},
     frame::{CanErrorType, TraceFrame},
-    observable::TraceObservable,
+    observable::{TraceObservable, TraceView},
 };
 
-use super::frame::TFrame;
+use super::frame::{Frame, TFrame};
 
-use canzero_common::{TCanFrame, TCanError};
+use canzero_canlog::{ChannelMap, LogFormat, LogWriter};
+use canzero_common::{NetworkFrame, TCanError, TCanFrame, Timestamped};
+use canzero_config::config;
+use tokio::sync::Mutex;
 
+use crate::notification::notify_error;
+
+pub mod chronological;
 pub mod database;
 pub mod event;
 mod frame;
 mod observable;
 
 const TRACE_EVENT_NAME: &'static str = "trace";
+const CHRONOLOGICAL_TRACE_EVENT_NAME: &'static str = "trace-chronological";
 
 pub struct TraceObject {
     database: Arc<TraceDatabase>,
     observable: TraceObservable,
+    chronological_observable: TraceObservable,
+    recorder: Mutex<Option<LogWriter<BufWriter<File>>>>,
+    channels: ChannelMap,
+    app_handle: tauri::AppHandle,
 }
 
 impl TraceObject {
-    pub fn create(app_handle: &tauri::AppHandle) -> Self {
+    pub fn create(app_handle: &tauri::AppHandle, network_config: &config::NetworkRef) -> Self {
         let database = Arc::new(TraceDatabase::new());
         Self {
-            observable: TraceObservable::new(app_handle, TRACE_EVENT_NAME, &database),
+            observable: TraceObservable::new(
+                app_handle,
+                TRACE_EVENT_NAME,
+                &database,
+                TraceView::Fixed,
+            ),
+            chronological_observable: TraceObservable::new(
+                app_handle,
+                CHRONOLOGICAL_TRACE_EVENT_NAME,
+                &database,
+                TraceView::Chronological,
+            ),
             database,
+            recorder: Mutex::new(None),
+            channels: ChannelMap::from_buses(network_config.buses()),
+            app_handle: app_handle.clone(),
+        }
+    }
+
+    /// Writes the raw frame to the log file, if a recording is running.
+    pub async fn record(&self, frame: &TCanFrame, bus_id: u32) {
+        let mut recorder = self.recorder.lock().await;
+        let Some(writer) = recorder.as_mut() else {
+            return;
+        };
+        let network_frame = Timestamped::new(
+            frame.timestamp,
+            NetworkFrame {
+                bus_id,
+                can_frame: frame.value.clone(),
+            },
+        );
+        if let Err(err) = writer.write(&network_frame) {
+            *recorder = None;
+            notify_error(
+                &self.app_handle,
+                "Recording stopped",
+                &format!("Failed to write the trace recording: {err}"),
+                chrono::Local::now(),
+            );
+        }
+    }
+
+    /// Starts recording all received frames, a previous recording is completed first.
+    pub async fn start_recording(
+        &self,
+        path: &Path,
+        format: LogFormat,
+    ) -> canzero_canlog::Result<()> {
+        let writer = LogWriter::create(path, format, self.channels.clone())?;
+        let previous = self.recorder.lock().await.replace(writer);
+        if let Some(previous) = previous {
+            previous.finish()?;
+        }
+        Ok(())
+    }
+
+    /// Completes the recording and returns the number of recorded frames.
+    pub async fn stop_recording(&self) -> canzero_canlog::Result<Option<usize>> {
+        match self.recorder.lock().await.take() {
+            Some(writer) => {
+                let count = writer.count();
+                writer.finish()?;
+                Ok(Some(count))
+            }
+            None => Ok(None),
         }
     }
 
@@ -39,7 +116,7 @@ impl TraceObject {
     ) {
         let (arrive, can_frame) = undefined_frame.destruct();
         self.database
-            .update(TraceFrame::Undefined(can_frame), arrive, bus_name, bus_id)
+            .update(TraceFrame::Undefined(can_frame), arrive, bus_name, bus_id, Direction::Rx)
             .await;
     }
 
@@ -62,7 +139,13 @@ impl TraceObject {
 
         for err in errors {
             self.database
-                .update(TraceFrame::Error(err), arrive_instant, bus_name, bus_id)
+                .update(
+                    TraceFrame::Error(err),
+                    arrive_instant,
+                    bus_name,
+                    bus_id,
+                    Direction::Rx,
+                )
                 .await;
         }
     }
@@ -71,7 +154,27 @@ impl TraceObject {
         let (arrive_instant, frame) = frame.destruct();
         let trace_frame = TraceFrame::Frame(frame);
         self.database
-            .update(trace_frame, arrive_instant, bus_name, bus_id)
+            .update(trace_frame, arrive_instant, bus_name, bus_id, Direction::Rx)
+            .await;
+    }
+
+    /// Frames sent by the control panel, `frame` is the decoded frame if the
+    /// id belongs to a message of the network.
+    pub async fn push_tx_frame(
+        &self,
+        can_frame: TCanFrame,
+        frame: Option<Frame>,
+        bus_name: &str,
+        bus_id: u32,
+    ) {
+        self.record(&can_frame, bus_id).await;
+        let (arrive, can_frame) = can_frame.destruct();
+        let trace_frame = match frame {
+            Some(frame) => TraceFrame::Frame(frame),
+            None => TraceFrame::Undefined(can_frame),
+        };
+        self.database
+            .update(trace_frame, arrive, bus_name, bus_id, Direction::Tx)
             .await;
     }
 
@@ -91,6 +194,35 @@ impl TraceObject {
         self.observable.unlisten().await
     }
 
+    pub async fn listen_chronological(&self) -> &'static str {
+        self.chronological_observable.listen().await
+    }
+
+    pub async fn unlisten_chronological(&self) {
+        self.chronological_observable.unlisten().await
+    }
+
+    pub async fn pause(&self) {
+        self.database.pause().await;
+    }
+
+    pub async fn resume(&self) {
+        self.database.resume().await;
+    }
+
+    /// Selects the page of the chronological trace, which is emitted to listeners.
+    pub async fn set_chronological_page(&self, offset: Option<u64>, size: usize) {
+        self.database.set_chronological_page(offset, size).await;
+    }
+
+    pub async fn chronological_page(&self, offset: Option<u64>, size: usize) -> TracePage {
+        self.database.get_chronological_page(offset, size).await
+    }
+
+    pub async fn chronological_between(&self, start: Duration, end: Duration) -> Vec<TraceEvent> {
+        self.database.get_chronological_between(start, end).await
+    }
+
     pub async fn deadlock_watchdog(&self) {
     }
 }
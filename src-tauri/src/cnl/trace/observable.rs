@@ -8,16 +8,25 @@ use tokio::time::interval;
 
 use super::database::TraceDatabase;
 
+/// Which view of the trace database is emitted.
+#[derive(Clone, Copy)]
+pub enum TraceView {
+    Fixed,
+    Chronological,
+}
+
 pub struct TraceObservable {
     database: Arc<TraceDatabase>,
+    view: TraceView,
     event_name: &'static str,
     listen_count: Arc<AtomicUsize>,
     app_handle : tauri::AppHandle,
 }
 
 impl TraceObservable {
-    pub fn new(app_handle : &tauri::AppHandle,event_name: &'static str, database: &Arc<TraceDatabase>) -> Self {
+    pub fn new(app_handle : &tauri::AppHandle,event_name: &'static str, database: &Arc<TraceDatabase>, view: TraceView) -> Self {
         Self {
+            view,
             app_handle : app_handle.clone(),
             event_name,
             database: database.clone(),
@@ -31,6 +40,7 @@ impl TraceObservable {
             // start listening task
             tokio::spawn(notify_task(
                 self.database.clone(),
+                self.view,
                 self.event_name,
                 self.listen_count.clone(),
                 self.app_handle.clone(),
@@ -49,6 +59,7 @@ impl TraceObservable {
 
 async fn notify_task(
     database: Arc<TraceDatabase>,
+    view: TraceView,
     event_name: &'static str,
     listen_count: Arc<AtomicUsize>,
     app_handle : tauri::AppHandle,
@@ -60,8 +71,16 @@ async fn notify_task(
         if listen_count.load(Ordering::SeqCst) == 0 {
             break;
         }
-        let sorted_and_filtered_frames = database.get_sorted_and_filtered_frames().await;
-        app_handle.emit_all(event_name, sorted_and_filtered_frames).expect("Failed to emit trace event");
+        match view {
+            TraceView::Fixed => {
+                let sorted_and_filtered_frames = database.get_sorted_and_filtered_frames().await;
+                app_handle.emit_all(event_name, sorted_and_filtered_frames).expect("Failed to emit trace event");
+            }
+            TraceView::Chronological => {
+                let page = database.get_current_chronological_page().await;
+                app_handle.emit_all(event_name, page).expect("Failed to emit trace event");
+            }
+        }
     }
 
 }
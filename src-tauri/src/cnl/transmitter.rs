@@ -0,0 +1,300 @@
+use std::{
+    collections::BTreeMap,
+    sync::{
+        atomic::{AtomicU32, AtomicUsize, Ordering},
+        Arc,
+    },
+    time::Duration,
+};
+
+use canzero_common::CanFrame;
+use canzero_config::config;
+use serde::Serialize;
+use tokio::{
+    sync::{watch, Mutex},
+    task::JoinHandle,
+};
+
+use super::{frame::Attribute, serialize::FrameSerializer, tx::TxCom};
+
+const MIN_PERIOD: Duration = Duration::from_millis(1);
+
+#[derive(Clone)]
+pub struct TxJobParams {
+    pub bus_id: u32,
+    pub frame: CanFrame,
+    pub period: Duration,
+}
+
+#[derive(Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct TxJobInfo {
+    job_id: u32,
+    bus: String,
+    id: u32,
+    ide: bool,
+    dlc: u8,
+    data: Vec<u8>,
+    period_ms: u64,
+    sent: usize,
+}
+
+#[derive(Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct ComposedFrame {
+    bus: String,
+    id: u32,
+    ide: bool,
+    dlc: u8,
+    data: Vec<u8>,
+}
+
+struct TxJob {
+    params: watch::Sender<TxJobParams>,
+    sent: Arc<AtomicUsize>,
+    task: JoinHandle<()>,
+}
+
+/// Sends raw frames on behalf of the user, once or cyclically.
+///
+/// Cyclic jobs run until they are stopped or the CNL is dropped, their parameters
+/// can be changed while they are running.
+pub struct Transmitter {
+    network_config: config::NetworkRef,
+    tx: Arc<TxCom>,
+    jobs: Mutex<BTreeMap<u32, TxJob>>,
+    next_job_id: AtomicU32,
+}
+
+impl Transmitter {
+    pub fn create(network_config: &config::NetworkRef, tx: &Arc<TxCom>) -> Self {
+        Self {
+            network_config: network_config.clone(),
+            tx: tx.clone(),
+            jobs: Mutex::new(BTreeMap::new()),
+            next_job_id: AtomicU32::new(0),
+        }
+    }
+
+    pub fn bus_id(&self, bus_name: &str) -> Result<u32, String> {
+        self.network_config
+            .buses()
+            .iter()
+            .find(|bus| bus.name() == bus_name)
+            .map(|bus| bus.id())
+            .ok_or_else(|| format!("Unknown bus {bus_name}"))
+    }
+
+    fn message(&self, message_name: &str) -> Result<&config::MessageRef, String> {
+        self.network_config
+            .messages()
+            .iter()
+            .find(|message| message.name() == message_name)
+            .ok_or_else(|| format!("Unknown message {message_name}"))
+    }
+
+    /// Composes a configured message from the values of its attributes, returns the
+    /// frame together with the id of the bus the message is assigned to.
+    pub fn compose(
+        &self,
+        message_name: &str,
+        attributes: &[Attribute],
+    ) -> Result<(u32, CanFrame), String> {
+        let message = self.message(message_name)?;
+        let frame = FrameSerializer::new(message)?.can_frame(attributes)?;
+        Ok((message.bus().id(), frame))
+    }
+
+    /// Composes and sends a configured message, command requests fall back to another bus
+    /// if the node timed out on the bus of the message.
+    pub async fn send_message(
+        &self,
+        message_name: &str,
+        attributes: &[Attribute],
+    ) -> Result<(), String> {
+        let message = self.message(message_name)?;
+        let frame = FrameSerializer::new(message)?.can_frame(attributes)?;
+        self.tx.send_message(message, frame).await
+    }
+
+    pub fn composed_frame(&self, bus_id: u32, frame: &CanFrame) -> ComposedFrame {
+        ComposedFrame {
+            bus: self.bus_name(bus_id),
+            id: frame.get_id(),
+            ide: frame.get_ide_flag(),
+            dlc: frame.get_dlc(),
+            data: frame.get_data_8u8()[..frame.get_dlc().min(8) as usize].to_vec(),
+        }
+    }
+
+    pub async fn send(&self, bus_id: u32, frame: CanFrame) -> Result<(), String> {
+        self.tx.send_raw(bus_id, frame).await
+    }
+
+    pub async fn start_job(&self, params: TxJobParams) -> Result<TxJobInfo, String> {
+        // the job task ignores failed sends, so it would never send a frame.
+        if self.tx.is_replay() {
+            return Err("Cyclic frames can not be sent during a replay".to_owned());
+        }
+        let params = validate_period(params)?;
+        let job_id = self.next_job_id.fetch_add(1, Ordering::SeqCst);
+        let (params_tx, params_rx) = watch::channel(params);
+        let sent = Arc::new(AtomicUsize::new(0));
+        let task = tokio::spawn(job_task(self.tx.clone(), params_rx, sent.clone()));
+        let job = TxJob {
+            params: params_tx,
+            sent,
+            task,
+        };
+        let info = self.info(job_id, &job);
+        self.jobs.lock().await.insert(job_id, job);
+        Ok(info)
+    }
+
+    pub async fn update_job(&self, job_id: u32, params: TxJobParams) -> Result<TxJobInfo, String> {
+        let params = validate_period(params)?;
+        let jobs = self.jobs.lock().await;
+        let Some(job) = jobs.get(&job_id) else {
+            return Err(format!("No cyclic frame with id {job_id}"));
+        };
+        // the job task holds the receiver, until it is aborted.
+        let _ = job.params.send(params);
+        Ok(self.info(job_id, job))
+    }
+
+    pub async fn stop_job(&self, job_id: u32) -> Result<(), String> {
+        match self.jobs.lock().await.remove(&job_id) {
+            Some(job) => {
+                job.task.abort();
+                Ok(())
+            }
+            None => Err(format!("No cyclic frame with id {job_id}")),
+        }
+    }
+
+    pub async fn jobs(&self) -> Vec<TxJobInfo> {
+        self.jobs
+            .lock()
+            .await
+            .iter()
+            .map(|(job_id, job)| self.info(*job_id, job))
+            .collect()
+    }
+
+    fn bus_name(&self, bus_id: u32) -> String {
+        self.network_config
+            .buses()
+            .iter()
+            .find(|bus| bus.id() == bus_id)
+            .map_or(format!("can{bus_id}"), |bus| bus.name().to_owned())
+    }
+
+    fn info(&self, job_id: u32, job: &TxJob) -> TxJobInfo {
+        let params = job.params.borrow();
+        let frame = &params.frame;
+        TxJobInfo {
+            job_id,
+            bus: self.bus_name(params.bus_id),
+            id: frame.get_id(),
+            ide: frame.get_ide_flag(),
+            dlc: frame.get_dlc(),
+            data: frame.get_data_8u8()[..frame.get_dlc().min(8) as usize].to_vec(),
+            period_ms: params.period.as_millis() as u64,
+            sent: job.sent.load(Ordering::Relaxed),
+        }
+    }
+}
+
+impl Drop for Transmitter {
+    fn drop(&mut self) {
+        for job in self.jobs.get_mut().values() {
+            job.task.abort();
+        }
+    }
+}
+
+/// Builds a data frame, the dlc defaults to the number of data bytes.
+pub fn raw_frame(id: u32, ide: bool, dlc: Option<u8>, data: &[u8]) -> Result<CanFrame, String> {
+    let max_id = if ide { 0x1FFFFFFF } else { 0x7FF };
+    if id > max_id {
+        return Err(format!("Invalid CAN id 0x{id:X}"));
+    }
+    if data.len() > 8 {
+        return Err(format!("A CAN frame carries at most 8 bytes, got {}", data.len()));
+    }
+    let dlc = dlc.unwrap_or(data.len() as u8);
+    if dlc > 8 {
+        return Err(format!("Invalid dlc {dlc}"));
+    }
+    // bytes beyond the dlc are not transmitted.
+    let mut value = 0u64;
+    for (i, byte) in data.iter().take(dlc as usize).enumerate() {
+        value |= (*byte as u64) << (i * 8);
+    }
+    Ok(CanFrame::new(id, ide, false, dlc, value))
+}
+
+fn validate_period(params: TxJobParams) -> Result<TxJobParams, String> {
+    if params.period < MIN_PERIOD {
+        return Err(format!(
+            "The period of a cyclic frame has to be at least {}ms",
+            MIN_PERIOD.as_millis()
+        ));
+    }
+    Ok(params)
+}
+
+async fn job_task(
+    tx: Arc<TxCom>,
+    mut params: watch::Receiver<TxJobParams>,
+    sent: Arc<AtomicUsize>,
+) {
+    loop {
+        let TxJobParams {
+            bus_id,
+            frame,
+            period,
+        } = params.borrow_and_update().clone();
+        let mut interval = tokio::time::interval(period);
+        interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+        loop {
+            tokio::select! {
+                _ = interval.tick() => {
+                    if tx.send_raw(bus_id, frame.clone()).await.is_ok() {
+                        sent.fetch_add(1, Ordering::Relaxed);
+                    }
+                }
+                changed = params.changed() => match changed {
+                    Ok(()) => break,
+                    Err(_) => return,
+                },
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn raw_frame_masks_bytes_beyond_the_dlc() {
+        let frame = raw_frame(0x123, false, Some(2), &[0x11, 0x22, 0x33, 0x44]).unwrap();
+        assert_eq!(frame.get_dlc(), 2);
+        assert_eq!(frame.get_data_u64(), 0x2211);
+    }
+
+    #[test]
+    fn raw_frame_defaults_the_dlc_to_the_data_length() {
+        let frame = raw_frame(0x1FFFFFFF, true, None, &[0x11, 0x22, 0x33]).unwrap();
+        assert_eq!(frame.get_dlc(), 3);
+        assert_eq!(frame.get_data_u64(), 0x332211);
+    }
+
+    #[test]
+    fn raw_frame_rejects_invalid_frames() {
+        assert!(raw_frame(0x800, false, None, &[]).is_err());
+        assert!(raw_frame(0x123, false, Some(9), &[]).is_err());
+        assert!(raw_frame(0x123, false, None, &[0; 9]).is_err());
+    }
+}
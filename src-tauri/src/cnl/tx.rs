@@ -1,32 +1,49 @@
 use std::{
-    sync::Arc,
+    sync::{Arc, Mutex},
     time::{Duration, Instant},
 };
 
-use canzero_config::config::{self, MessageRef};
+use canzero_appdata::RequestSettings;
+use canzero_config::config::{self, message::MessageUsage, MessageRef};
 use color_print::cprintln;
-use tokio::time;
+use tokio::{sync::watch, time};
 
 use crate::cnl::connection::ConnectionStatus;
 
 use super::{
-    connection::ConnectionObject, deserialize::FrameDeserializer, frame::Frame, trace::TraceObject,
+    connection::ConnectionObject,
+    deserialize::FrameDeserializer,
+    frame::{Attribute, Frame, Value},
+    serialize::FrameSerializer,
+    trace::TraceObject,
+    watchdog::WdgStatus,
     CanAdapter,
 };
 
-use canzero_common::CanFrame;
+use canzero_common::{CanFrame, TCanFrame};
 
 pub struct TxCom {
     network_ref: config::NetworkRef,
     set_req_can_adapter: Arc<CanAdapter>,
+    set_stage_req_can_adapter: Arc<CanAdapter>,
+    set_commit_req_can_adapter: Arc<CanAdapter>,
     get_req_frame_deserializer: FrameDeserializer,
     get_req_can_adapter: Arc<CanAdapter>,
     can_adapters: Vec<Arc<CanAdapter>>,
     my_node_id: u8,
-    frag_time_ms: u64,
+    fragment_gap: Duration,
+    max_fragment_gap: Duration,
     timebase: Instant,
     trace: Arc<TraceObject>,
     connection_object: Arc<ConnectionObject>,
+    heartbeats: Mutex<Vec<NodeHeartbeat>>,
+}
+
+/// Heartbeat watchdog status of a node on one bus.
+struct NodeHeartbeat {
+    node_id: u8,
+    bus_id: u32,
+    status_rx: watch::Receiver<WdgStatus>,
 }
 
 impl TxCom {
@@ -37,6 +54,7 @@ impl TxCom {
         basetime: Instant,
         connection_object: &Arc<ConnectionObject>,
         node_id: u8,
+        request_settings: &RequestSettings,
     ) -> TxCom {
         let set_req_can_adapter = can_adapters
             .iter()
@@ -44,6 +62,18 @@ impl TxCom {
             .expect("DETECTED INVALID CONFIG: no set_req message in the config")
             .clone();
 
+        let set_stage_req_can_adapter = can_adapters
+            .iter()
+            .find(|adapter| adapter.bus().id() == network_ref.set_stage_req_message().bus().id())
+            .expect("DETECTED INVALID CONFIG: no set_stage_req message in the config")
+            .clone();
+
+        let set_commit_req_can_adapter = can_adapters
+            .iter()
+            .find(|adapter| adapter.bus().id() == network_ref.set_commit_req_message().bus().id())
+            .expect("DETECTED INVALID CONFIG: no set_commit_req message in the config")
+            .clone();
+
         let get_req_can_adapter = can_adapters
             .iter()
             .find(|adapter| adapter.bus().id() == network_ref.get_req_message().bus().id())
@@ -54,84 +84,182 @@ impl TxCom {
             network_ref: network_ref.clone(),
             my_node_id: node_id,
             set_req_can_adapter,
+            set_stage_req_can_adapter,
+            set_commit_req_can_adapter,
             get_req_frame_deserializer: FrameDeserializer::new(network_ref.get_req_message()),
             get_req_can_adapter,
             can_adapters : can_adapters.to_owned(),
-            frag_time_ms: 200,
+            fragment_gap: Duration::from_millis(request_settings.fragment_gap_ms),
+            max_fragment_gap: Duration::from_millis(request_settings.max_fragment_gap_ms),
             timebase: basetime,
             trace: trace.clone(),
             connection_object: connection_object.clone(),
+            heartbeats: Mutex::new(vec![]),
         }
     }
 
-    pub async fn send_set_request(&self, server_id: u8, oe_id: u32, val: Vec<u32>, last_fill: u8) {
-        let (set_request_id, ide) = match self.network_ref.set_req_message().id() {
-            config::MessageId::StandardId(id) => (*id, false),
-            config::MessageId::ExtendedId(id) => (*id, true),
+    pub fn register_heartbeat(
+        &self,
+        node_id: u8,
+        bus_id: u32,
+        status_rx: watch::Receiver<WdgStatus>,
+    ) {
+        self.heartbeats.lock().unwrap().push(NodeHeartbeat {
+            node_id,
+            bus_id,
+            status_rx,
+        });
+    }
+
+    /// Nodes accept requests on every bus they are attached to. If the heartbeat of the
+    /// server timed out on the bus of the request message, the request is sent on another
+    /// bus on which the server is alive.
+    fn request_adapter(&self, server_id: u8, adapter: &Arc<CanAdapter>) -> Arc<CanAdapter> {
+        let heartbeats = self.heartbeats.lock().unwrap();
+        let status = |bus_id: u32| {
+            heartbeats
+                .iter()
+                .find(|hb| hb.node_id == server_id && hb.bus_id == bus_id)
+                .map(|hb| *hb.status_rx.borrow())
         };
-        let mut frame_data: Vec<CanFrame> = vec![];
-        let frames_to_send = val.len();
-
-        for i in 0..frames_to_send {
-            // SOF
-            let mut data_curr = if i == 0 { 1u64 } else { 0u64 };
-            // EOF
-            data_curr |= if i == frames_to_send - 1 {
-                1u64 << 1
-            } else {
-                0u64
-            };
-            // toggle
-            data_curr |= ((i % 2) as u64) << 2;
-            // oe-id
-            data_curr |= (oe_id as u64) << 3;
-            // client-id
-            data_curr |= (self.my_node_id as u64) << 16;
-            // server-id
-            data_curr |= (server_id as u64) << 24;
-            // data
-            data_curr |= (val[i] as u64) << 32;
-
-            let dlc = if i == (frames_to_send - 1) {
-                4 + last_fill
-            } else {
-                8
-            };
-            frame_data.push(CanFrame::new(set_request_id, ide, false, dlc, data_curr));
+        if !matches!(status(adapter.bus().id()), Some(WdgStatus::TimedOut)) {
+            return adapter.clone();
         }
+        self.can_adapters
+            .iter()
+            .find(|fallback| matches!(status(fallback.bus().id()), Some(WdgStatus::Good)))
+            .unwrap_or(adapter)
+            .clone()
+    }
+
+    pub async fn send_set_request(&self, server_id: u8, oe_id: u32, val: Vec<u32>, last_fill: u8) {
+        let frame_data = set_request_frames(
+            &self.network_ref,
+            self.my_node_id,
+            server_id,
+            oe_id,
+            &val,
+            last_fill,
+        );
+
+        fragmented_can_send(
+            frame_data,
+            self.request_adapter(server_id, &self.set_req_can_adapter),
+            self.fragment_gap,
+            self.max_fragment_gap,
+            self.connection_object.clone(),
+        )
+        .await;
+    }
+
+    /// Stages a value for the next commit request, the fragments are sent like a set request.
+    pub async fn send_stage_request(&self, server_id: u8, oe_id: u32, val: Vec<u32>, last_fill: u8) {
+        let frame_data = stage_request_frames(
+            &self.network_ref,
+            self.my_node_id,
+            server_id,
+            oe_id,
+            &val,
+            last_fill,
+        );
 
         fragmented_can_send(
             frame_data,
-            self.set_req_can_adapter.clone(),
-            self.frag_time_ms,
+            self.request_adapter(server_id, &self.set_stage_req_can_adapter),
+            self.fragment_gap,
+            self.max_fragment_gap,
             self.connection_object.clone(),
         )
         .await;
     }
 
+    pub async fn send_commit_request(&self, server_id: u8, action: CommitAction, entries: u8) {
+        let frame = commit_request_frame(
+            &self.network_ref,
+            self.my_node_id,
+            server_id,
+            action,
+            entries,
+        );
+        let adapter = self.request_adapter(server_id, &self.set_commit_req_can_adapter);
+        if let Err(err) = adapter.send(frame, true).await {
+            send_failed(&self.connection_object, "commit req", err);
+        }
+    }
+
     pub async fn send_native(&self, msg : &MessageRef, data : u64) {
         let adapter = self.can_adapters.iter().find(|adap| adap.bus().id() == msg.bus().id()).unwrap();
         let _ = adapter.send(CanFrame::new(msg.id().as_u32(), msg.id().ide(), false, msg.dlc(), data), true).await;
     }
 
+    /// There is no network to send frames to during a replay.
+    pub fn is_replay(&self) -> bool {
+        self.can_adapters
+            .iter()
+            .any(|adapter| adapter.replay().is_some())
+    }
+
+    /// Sends a raw frame without loopback, it is pushed to the trace as a tx event instead.
+    pub async fn send_raw(&self, bus_id: u32, frame: CanFrame) -> Result<(), String> {
+        let Some(adapter) = self
+            .can_adapters
+            .iter()
+            .find(|adapter| adapter.bus().id() == bus_id)
+        else {
+            return Err(format!("Not connected to a bus with id {bus_id}"));
+        };
+        adapter
+            .send(frame.clone(), false)
+            .await
+            .map_err(|err| format!("Failed to send frame : {err}"))?;
+        let decoded = self
+            .network_ref
+            .messages()
+            .iter()
+            .find(|msg| {
+                msg.bus().id() == bus_id
+                    && msg.id().as_u32() == frame.get_id()
+                    && msg.id().ide() == frame.get_ide_flag()
+            })
+            .map(|msg| FrameDeserializer::new(msg).deserialize(frame.get_data_u64()));
+        self.trace
+            .push_tx_frame(
+                TCanFrame::now(self.timebase, frame),
+                decoded,
+                adapter.bus().name(),
+                bus_id,
+            )
+            .await;
+        Ok(())
+    }
+
+    /// Sends a composed frame of a configured message. Command requests are routed like
+    /// the other requests to the node, which executes the command.
+    pub async fn send_message(&self, message: &MessageRef, frame: CanFrame) -> Result<(), String> {
+        let mut bus_id = message.bus().id();
+        if let MessageUsage::CommandReq(command) = message.usage() {
+            let server = self.network_ref.nodes().iter().find(|node| {
+                node.commands()
+                    .iter()
+                    .any(|cmd| cmd.tx_message().name() == command.tx_message().name())
+            });
+            let adapter = self
+                .can_adapters
+                .iter()
+                .find(|adapter| adapter.bus().id() == bus_id);
+            if let (Some(server), Some(adapter)) = (server, adapter) {
+                bus_id = self.request_adapter(server.id(), adapter).bus().id();
+            }
+        }
+        self.send_raw(bus_id, frame).await
+    }
+
     pub async fn send_get_req(&self, server_id: u8, object_entry_id: u16) {
-        let mut data: u64 = 0;
-        data |= object_entry_id as u64;
-        data |= (self.network_ref.nodes().len() as u64) << 13;
-        data |= (server_id as u64) << 21;
-
-        let get_req_frame = CanFrame::new(
-            self.network_ref.get_req_message().id().as_u32(),
-            self.network_ref.get_req_message().id().ide(),
-            false,
-            self.network_ref.get_req_message().dlc(),
-            data,
-        );
+        let get_req_frame = get_request_frame(&self.network_ref, server_id, object_entry_id);
 
-        if let Err(err) = self.get_req_can_adapter.send(get_req_frame, true).await {
-            println!("<red>Failed to send get req </red>: {err:?}");
-            self.connection_object
-                .set_status(ConnectionStatus::NetworkDisconnected);
+        let adapter = self.request_adapter(server_id, &self.get_req_can_adapter);
+        if let Err(err) = adapter.send(get_req_frame, true).await {
+            send_failed(&self.connection_object, "get req", err);
         }
     }
 
@@ -146,6 +274,10 @@ impl TxCom {
         }
 
         for can_adapter in self.can_adapters.iter() {
+            // there are no nodes, which expect our heartbeat, during a replay.
+            if can_adapter.replay().is_some() {
+                continue;
+            }
             let msg = self
                 .network_ref
                 .heartbeat_messages()
@@ -168,21 +300,194 @@ impl TxCom {
     }
 }
 
+/// Action of a commit request, the variants of the `set_commit_action` enum.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum CommitAction {
+    Commit,
+    Abort,
+}
+
+impl CommitAction {
+    fn variant(&self) -> &'static str {
+        match self {
+            CommitAction::Commit => "Commit",
+            CommitAction::Abort => "Abort",
+        }
+    }
+}
+
+/// Splits a value encoded by `Value::get_as_bin` into the fragments of a set request.
+pub fn set_request_frames(
+    network_ref: &config::NetworkRef,
+    client_id: u8,
+    server_id: u8,
+    oe_id: u32,
+    val: &[u32],
+    last_fill: u8,
+) -> Vec<CanFrame> {
+    request_fragments(
+        network_ref.set_req_message(),
+        client_id,
+        server_id,
+        oe_id,
+        val,
+        last_fill,
+    )
+}
+
+pub fn stage_request_frames(
+    network_ref: &config::NetworkRef,
+    client_id: u8,
+    server_id: u8,
+    oe_id: u32,
+    val: &[u32],
+    last_fill: u8,
+) -> Vec<CanFrame> {
+    request_fragments(
+        network_ref.set_stage_req_message(),
+        client_id,
+        server_id,
+        oe_id,
+        val,
+        last_fill,
+    )
+}
+
+pub fn commit_request_frame(
+    network_ref: &config::NetworkRef,
+    client_id: u8,
+    server_id: u8,
+    action: CommitAction,
+    entries: u8,
+) -> CanFrame {
+    let header = Value::StructValue(vec![
+        Attribute::new("client_id", Value::UnsignedValue(client_id as u64)),
+        Attribute::new("server_id", Value::UnsignedValue(server_id as u64)),
+        Attribute::new("action", Value::EnumValue(action.variant().to_owned())),
+        Attribute::new("entries", Value::UnsignedValue(entries as u64)),
+    ]);
+    FrameSerializer::new(network_ref.set_commit_req_message())
+        .and_then(|serializer| serializer.can_frame(&[Attribute::new("header", header)]))
+        .expect("DETECTED INVALID CONFIG: invalid format of set_commit_req")
+}
+
+/// Set and stage requests share the set_req_header.
+fn request_fragments(
+    message: &MessageRef,
+    client_id: u8,
+    server_id: u8,
+    oe_id: u32,
+    val: &[u32],
+    last_fill: u8,
+) -> Vec<CanFrame> {
+    let (set_request_id, ide) = match message.id() {
+        config::MessageId::StandardId(id) => (*id, false),
+        config::MessageId::ExtendedId(id) => (*id, true),
+    };
+    let mut frame_data: Vec<CanFrame> = vec![];
+    let frames_to_send = val.len();
+
+    for i in 0..frames_to_send {
+        // SOF
+        let mut data_curr = if i == 0 { 1u64 } else { 0u64 };
+        // EOF
+        data_curr |= if i == frames_to_send - 1 {
+            1u64 << 1
+        } else {
+            0u64
+        };
+        // toggle
+        data_curr |= ((i % 2) as u64) << 2;
+        // oe-id
+        data_curr |= (oe_id as u64) << 3;
+        // client-id
+        data_curr |= (client_id as u64) << 16;
+        // server-id
+        data_curr |= (server_id as u64) << 24;
+        // data
+        data_curr |= (val[i] as u64) << 32;
+
+        let dlc = if i == (frames_to_send - 1) {
+            4 + last_fill
+        } else {
+            8
+        };
+        frame_data.push(CanFrame::new(set_request_id, ide, false, dlc, data_curr));
+    }
+    frame_data
+}
+
+pub fn get_request_frame(
+    network_ref: &config::NetworkRef,
+    server_id: u8,
+    object_entry_id: u16,
+) -> CanFrame {
+    let mut data: u64 = 0;
+    data |= object_entry_id as u64;
+    data |= (network_ref.nodes().len() as u64) << 13;
+    data |= (server_id as u64) << 21;
+
+    CanFrame::new(
+        network_ref.get_req_message().id().as_u32(),
+        network_ref.get_req_message().id().ide(),
+        false,
+        network_ref.get_req_message().dlc(),
+        data,
+    )
+}
+
+/// Sends the fragments of a request, the gap between two fragments grows with
+/// the load of the bus to leave room for the other traffic.
 async fn fragmented_can_send(
     frames: Vec<CanFrame>,
     can_adapter: Arc<CanAdapter>,
-    frag_time_ms: u64,
+    fragment_gap: Duration,
+    max_fragment_gap: Duration,
     connection_object : Arc<ConnectionObject>,
 ) {
-    let mut interval = time::interval(Duration::from_millis(frag_time_ms));
-
-    for frame in frames {
-        // first tick completes instantaniously
-        interval.tick().await;
+    for (i, frame) in frames.into_iter().enumerate() {
+        if i != 0 {
+            time::sleep(load_fragment_gap(
+                can_adapter.bus_load(),
+                fragment_gap,
+                max_fragment_gap,
+            ))
+            .await;
+        }
 
         if let Err(err) = can_adapter.send(frame, true).await {
-            cprintln!("<red>Failed to send set req </red>: {err:?}");
-            connection_object.set_status(ConnectionStatus::NetworkDisconnected);
+            send_failed(&connection_object, "set req", err);
         }
     }
 }
+
+fn load_fragment_gap(load: f64, fragment_gap: Duration, max_fragment_gap: Duration) -> Duration {
+    fragment_gap
+        .div_f64(1.0 - load.clamp(0.0, 0.95))
+        .min(max_fragment_gap)
+}
+
+/// A replay can not send frames, which doesn't mean that the network is disconnected.
+/// The request is not answered and fails with a timeout.
+fn send_failed(connection_object: &ConnectionObject, request: &str, err: std::io::Error) {
+    cprintln!("<red>Failed to send {request} </red>: {err:?}");
+    if err.kind() != std::io::ErrorKind::Unsupported {
+        connection_object.set_status(ConnectionStatus::NetworkDisconnected);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn fragment_gap_grows_with_the_bus_load() {
+        let gap = Duration::from_millis(1);
+        let max = Duration::from_millis(10);
+        assert_eq!(load_fragment_gap(0.0, gap, max), gap);
+        assert_eq!(load_fragment_gap(0.5, gap, max), gap * 2);
+        assert_eq!(load_fragment_gap(0.95, gap, max), max);
+        let max = Duration::from_secs(1);
+        assert_eq!(load_fragment_gap(1.0, gap, max), load_fragment_gap(0.95, gap, max));
+    }
+}
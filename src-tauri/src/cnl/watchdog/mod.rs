@@ -15,7 +15,11 @@ use tokio::{
     time::Instant,
 };
 
-use crate::{cnl::connection::ConnectionStatus, notification::notify_warning};
+use crate::{
+    cnl::connection::ConnectionStatus,
+    journal::{self, JournalEvent},
+    notification::notify_warning,
+};
 
 use super::{connection::ConnectionObject, tx::TxCom};
 
@@ -63,10 +67,15 @@ impl Watchdog {
     fn create(tag: WdgTag, timeout: Duration, overlord: Arc<WatchdogOverlordInner>) -> Self {
         let (reset_tx, reset_rx) = mpsc::channel(16);
         let (status_tx, status_rx) = watch::channel(WdgStatus::InActive);
+        let suspend_rx = match tag {
+            WdgTag::Heartbeat { .. } => overlord.heartbeat_suspend.clone(),
+            _ => None,
+        };
 
         let abort_handle = tokio::spawn(Self::watchdog_task(
             reset_rx,
             status_tx,
+            suspend_rx,
             timeout,
             overlord.clone(),
             tag,
@@ -93,6 +102,7 @@ impl Watchdog {
     async fn watchdog_task(
         mut reset_rx: mpsc::Receiver<WatchdogSignal>,
         status_tx: watch::Sender<WdgStatus>,
+        mut suspend_rx: Option<watch::Receiver<bool>>,
         timeout: Duration,
         overlord: Arc<WatchdogOverlordInner>,
         wdg_tag: WdgTag,
@@ -100,8 +110,20 @@ impl Watchdog {
         let sleep = tokio::time::sleep(timeout);
         tokio::pin!(sleep);
         let mut active = false;
+        let mut suspended = suspend_rx.as_mut().is_some_and(|rx| *rx.borrow_and_update());
         loop {
+            let suspend_changed = async {
+                match suspend_rx.as_mut() {
+                    Some(rx) => rx.changed().await.is_ok(),
+                    None => std::future::pending().await,
+                }
+            };
             tokio::select! {
+                true = suspend_changed => {
+                    suspended = *suspend_rx.as_mut().unwrap().borrow_and_update();
+                    // the time while suspended doesn't count towards the timeout.
+                    sleep.as_mut().reset(Instant::now() + timeout);
+                },
                 Some(WatchdogSignal{unregister, ticks_next}) = reset_rx.recv() => {
                     active = !unregister;
                     if active {
@@ -109,12 +131,13 @@ impl Watchdog {
                     } else {
                         let _ = status_tx.send(WdgStatus::InActive);
                     }
+                    // the announced ticks can only extend the timeout the watchdog was registered with.
                     match ticks_next {
-                        Some(ticks) => sleep.as_mut().reset(Instant::now() + Duration::from_millis(50 * ticks as u64)),
+                        Some(ticks) => sleep.as_mut().reset(Instant::now() + Duration::from_millis(50 * ticks as u64).max(timeout)),
                         None => sleep.as_mut().reset(Instant::now() + timeout),
                     };
                 },
-                () = sleep.as_mut(), if active => {
+                () = sleep.as_mut(), if active && !suspended => {
                     overlord.notify_timeout(WatchdogTimeout {
                         tag : wdg_tag,
                         error : WatchdogError::Timeout,
@@ -163,6 +186,8 @@ enum OverlordTimeoutSignal {
 
 struct WatchdogOverlordInner {
     network_config: NetworkRef,
+    // heartbeat watchdogs don't time out while this is true.
+    heartbeat_suspend: Option<watch::Receiver<bool>>,
     watchdogs: Mutex<Vec<Watchdog>>,
     connection_object: Arc<ConnectionObject>,
     status_tx: watch::Sender<OverlordTimeoutSignal>,
@@ -174,6 +199,12 @@ struct WatchdogOverlordInner {
 impl WatchdogOverlordInner {
     fn notify_timeout(&self, timeout: WatchdogTimeout) {
         cprintln!("<red> Watchdog {:?} timeout</red>", timeout.tag);
+        journal::record(
+            &self.app_handle,
+            JournalEvent::WatchdogTimeout {
+                tag: format!("{:?}", timeout.tag),
+            },
+        );
         match timeout.tag {
             WdgTag::FrontendWdg => {
                 if self.frontend_lvl == WdgLevel::Ignore {
@@ -232,6 +263,7 @@ impl WatchdogOverlord {
         tx_com: &Arc<TxCom>,
         deadlock_lvl: WdgLevel,
         frontend_lvl: WdgLevel,
+        heartbeat_suspend: Option<watch::Receiver<bool>>,
         app_handle: &tauri::AppHandle,
     ) -> Self {
         let (over_status_tx, over_status_rx) = watch::channel(OverlordTimeoutSignal::Good);
@@ -244,6 +276,7 @@ impl WatchdogOverlord {
 
         Self(Arc::new(WatchdogOverlordInner {
             network_config: network_config.clone(),
+            heartbeat_suspend,
             watchdogs: Mutex::new(vec![]),
             connection_object: connection_object.clone(),
             status_tx: over_status_tx,
@@ -0,0 +1,37 @@
+use crate::{
+    cnl::capture::{trigger::TriggerDefinition, TriggerInfo},
+    state::cnl_state::CNLState,
+};
+
+/// Arms a capture trigger, every status change of the trigger is emitted as a
+/// `capture-trigger` event.
+#[tauri::command]
+pub async fn arm_capture_trigger(
+    state: tauri::State<'_, CNLState>,
+    trigger: TriggerDefinition,
+) -> Result<TriggerInfo, String> {
+    #[cfg(feature = "logging-invoke")]
+    println!("invoke: arm_capture_trigger({:?})", trigger.name);
+    let capture = state.lock().await.capture().clone();
+    capture.arm(trigger).await
+}
+
+#[tauri::command]
+pub async fn disarm_capture_trigger(
+    state: tauri::State<'_, CNLState>,
+    trigger_id: u32,
+) -> Result<(), String> {
+    #[cfg(feature = "logging-invoke")]
+    println!("invoke: disarm_capture_trigger({trigger_id})");
+    let capture = state.lock().await.capture().clone();
+    capture.disarm(trigger_id).await
+}
+
+#[tauri::command]
+pub async fn list_capture_triggers(
+    state: tauri::State<'_, CNLState>,
+) -> Result<Vec<TriggerInfo>, ()> {
+    #[cfg(feature = "logging-invoke")]
+    println!("invoke: list_capture_triggers()");
+    Ok(state.lock().await.capture().triggers())
+}
@@ -1,4 +1,10 @@
-use crate::{cnl::error_observable::ErrorEvent, state::cnl_state::CNLState};
+use std::path::PathBuf;
+
+use crate::{
+    cnl::error_observable::{ErrorEvent, ErrorLogEntry},
+    journal::{self, JournalEvent},
+    state::cnl_state::CNLState,
+};
 
 
 #[tauri::command]
@@ -11,9 +17,16 @@ pub async fn listen_to_errors(state: tauri::State<'_, CNLState>) -> Result<Vec<E
 }
 
 #[tauri::command]
-pub async fn reset_errors(state : tauri::State<'_, CNLState>) -> Result<(),()>{
+pub async fn reset_errors(state : tauri::State<'_, CNLState>, app_handle: tauri::AppHandle) -> Result<(),()>{
     let cnl = state.lock().await;
     cnl.error_observable().reset().await;
+    journal::record(
+        &app_handle,
+        JournalEvent::Action {
+            action: "reset errors".to_owned(),
+            detail: String::new(),
+        },
+    );
     Ok(())
 }
 
@@ -23,3 +36,39 @@ pub async fn unlisten_from_errors(state : tauri::State<'_, CNLState>) -> Result<
     cnl.error_observable().unlisten().await;
     Ok(())
 }
+
+/// Acknowledges the error with the label or all errors if no label is given.
+#[tauri::command]
+pub async fn acknowledge_errors(
+    state: tauri::State<'_, CNLState>,
+    app_handle: tauri::AppHandle,
+    label: Option<String>,
+    user: String,
+) -> Result<usize, String> {
+    let cnl = state.lock().await;
+    let count = cnl.error_observable().acknowledge(label.as_deref(), &user)?;
+    journal::record(
+        &app_handle,
+        JournalEvent::Action {
+            action: "acknowledge errors".to_owned(),
+            detail: format!("{} by {user} ({count} errors)", label.as_deref().unwrap_or("all")),
+        },
+    );
+    Ok(count)
+}
+
+#[tauri::command]
+pub async fn get_error_log(state: tauri::State<'_, CNLState>) -> Result<Vec<ErrorLogEntry>, ()> {
+    let cnl = state.lock().await;
+    Ok(cnl.error_observable().log())
+}
+
+/// Writes the error log of the run as csv, returns the number of entries.
+#[tauri::command]
+pub async fn export_error_log(
+    state: tauri::State<'_, CNLState>,
+    path: String,
+) -> Result<usize, String> {
+    let cnl = state.lock().await;
+    cnl.error_observable().export_log(&PathBuf::from(path))
+}
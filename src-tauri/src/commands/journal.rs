@@ -0,0 +1,32 @@
+use crate::journal::Journal;
+
+/// Names of the recorded journals, the newest first.
+#[tauri::command]
+pub fn list_journal_sessions() -> Result<Vec<String>, String> {
+    #[cfg(feature = "logging-invoke")]
+    println!("invoke: list_journal_sessions()");
+    Journal::sessions()
+}
+
+/// Entries of a journal, which contain the search string. Without a session
+/// the journal of the running session is read.
+#[tauri::command]
+pub fn get_journal_entries(
+    journal: tauri::State<'_, Journal>,
+    session: Option<String>,
+    search: Option<String>,
+    limit: Option<usize>,
+) -> Result<Vec<serde_json::Value>, String> {
+    #[cfg(feature = "logging-invoke")]
+    println!("invoke: get_journal_entries({session:?}, {search:?}, {limit:?})");
+    let path = match session {
+        Some(session) => {
+            if session.contains(['/', '\\']) || session.contains("..") {
+                return Err(format!("Invalid journal session {session:?}"));
+            }
+            Journal::dir().join(format!("{session}.jsonl"))
+        }
+        None => journal.path().to_path_buf(),
+    };
+    Journal::read(&path, search.as_deref(), limit)
+}
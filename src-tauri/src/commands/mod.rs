@@ -8,3 +8,9 @@ pub mod startup;
 pub mod settings;
 pub mod search_string_storage;
 pub mod errors;
+pub mod replay;
+pub mod transmit;
+pub mod script;
+pub mod capture;
+pub mod journal;
+pub mod parameters;
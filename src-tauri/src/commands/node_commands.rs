@@ -1,4 +1,4 @@
-use crate::cnl::watchdog::WdgStatus;
+use crate::cnl::network::node_object::{heartbeat_statistics::HeartbeatStatus, NodeDiagnostics};
 use crate::state::cnl_state::CNLState;
 
 #[tauri::command]
@@ -39,7 +39,7 @@ pub async fn get_heartbeat_status(
     node_name: String,
     bus_name: String,
     state: tauri::State<'_, CNLState>,
-) -> Result<WdgStatus, ()> {
+) -> Result<HeartbeatStatus, ()> {
     #[cfg(feature = "logging-invoke")]
     println!("invoke: get_heartbeat_status({node_name:?})");
     let cnl = state.lock().await;
@@ -56,6 +56,22 @@ pub async fn get_heartbeat_status(
     node.get_heartbeat_status(bus.id())
 }
 
+#[tauri::command]
+pub async fn get_node_diagnostics(
+    node_name: String,
+    state: tauri::State<'_, CNLState>,
+) -> Result<NodeDiagnostics, ()> {
+    #[cfg(feature = "logging-invoke")]
+    println!("invoke: get_node_diagnostics({node_name:?})");
+    let cnl = state.lock().await;
+    let node = cnl.nodes().iter().find(|no| no.name() == &node_name);
+    let node = match node {
+        Some(node) => node,
+        None => return Err(()),
+    };
+    Ok(node.diagnostics())
+}
+
 #[tauri::command]
 pub async fn listen_to_heartbeat(
     node_name: String,
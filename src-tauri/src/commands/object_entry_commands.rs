@@ -1,17 +1,16 @@
 use std::time::Duration;
 
+use canzero_appdata::AppData;
 use serde::Serialize;
 use tauri::utils::config::parse::parse_value;
 use tauri::Manager;
 
-use crate::cnl::frame::{Attribute, Value};
+use crate::cnl::network::node_object::transaction::TransactionResult;
+use crate::cnl::serialize::value_from_typed_json;
 use crate::cnl::network::object_entry_object::info::ObjectEntryInformation;
 use crate::cnl::network::object_entry_object::latest::event::OwnedObjectEntryEvent;
 use crate::state::cnl_state::CNLState;
 
-use canzero_config::config;
-use canzero_config::config::{SignalType, Type};
-
 use serde_json;
 
 use super::network_information::NodeInformation;
@@ -42,111 +41,51 @@ pub async fn set_object_entry_value(
         Err(_) => return Err("Failed to parse JSON.".to_owned()),
     };
 
-    fn parse_value(oe_type: &config::TypeRef, json_value: &serde_json::Value) -> Result<Value, String> {
-        match oe_type.as_ref() {
-            Type::Primitive(SignalType::SignedInt { size }) => {
-                if let Some(val) = json_value.as_i64() {
-                    let max_uvalue = u64::MAX >> (64 - *size as u32);
-                    let max_ivalue: i64 = (max_uvalue >> 1) as i64;
-                    let min_ivalue: i64 = unsafe {
-                        std::mem::transmute(u64::MAX << (*size as u32 - 1))
-                    };
-                    if val <= max_ivalue && val >= min_ivalue {
-                        Ok(Value::SignedValue(val))
-                    } else {
-                        return Err("Expected Signed Value".to_owned());
-                    }
-                } else {
-                    return Err("Expected primitive value".to_owned());
-                }
-            }
-            Type::Primitive(SignalType::UnsignedInt { size }) => {
-                if let Some(val) = json_value.as_u64() {
-                    let max_uvalue = u64::MAX >> (64 - *size as u32);
-                    if val <= max_uvalue {
-                        Ok(Value::UnsignedValue(val))
-                    } else {
-                        return Err("Expected unsigned value".to_owned());
-                    }
-                } else {
-                    return Err("Expected primitive value".to_owned());
-                }
-            }
-            Type::Primitive(SignalType::Decimal {
-                size,
-                offset,
-                scale,
-            }) => {
-                if let Some(val) = json_value.as_f64() {
-                    let max_uvalue = u64::MAX >> (64 - *size as u32);
-                    let min = *offset;
-                    let max = (max_uvalue as f64) * scale + offset;
-                    if val <= max && val >= min {
-                        Ok(Value::RealValue(val))
-                    } else {
-                        return Err("Expected float value".to_owned());
-                    }
-                } else {
-                    return Err("Expected float value".to_owned());
-                }
-            }
-
-            Type::Struct {
-                name: _,
-                description: _,
-                attribs,
-                visibility: _,
-            } => {
-                if let Some(map) = json_value.as_object() {
-                    let mut attributes: Vec<Attribute> = vec![];
-
-                    for (name, attr_type) in attribs {
-                        if let Some(val) = map.get(name) {
-                            let type_val = parse_value(attr_type,val)?;
-                            attributes.push(Attribute::new(name, type_val));
-                            // if let Ok(type_val) = parse_value(attr_type, val) {
-                            // } else {
-                            //     return Err("Failed to parse attr");
-                            // }
-                        } else {
-                            return Err(format!("Attribute {name} does not exist"));
-                        }
-                    }
-                    Ok(Value::StructValue(attributes))
-                } else {
-                    return Err("Expected Object".to_owned());
-                }
-            }
-            Type::Enum {
-                name: _,
-                description: _,
-                size: _,
-                entries,
-                visibility: _,
-            } => {
-                if let Some(variant_str) = json_value.as_str() {
-                    if entries.iter().any(|e| e.0 == variant_str) {
-                        Ok(Value::EnumValue(variant_str.to_string()))
-                        // Ok(TypeValue::Enum(oe_type.clone(), variant_str.to_string()))
-                    } else {
-                        return Err("Invalid variant".to_owned());
-                    }
-                } else {
-                    return Err("Expected Enum value".to_owned());
-                }
-            }
-            Type::Array { len: _, ty: _ } => todo!(),
-        }
-    }
-
-
-    let value = parse_value(oe_type, &json_value)?;
+    let value = value_from_typed_json(oe_type, &json_value)?;
 
     object_entry_object.set_request(value).await;
 
     Ok(())
 }
 
+/// Sets several object entries of the node at once, `values` maps object entry names
+/// to JSON values. The node applies either all or none of them.
+#[tauri::command]
+pub async fn set_object_entry_values_atomic(
+    state: tauri::State<'_, CNLState>,
+    node_name: String,
+    values: Vec<(String, String)>,
+) -> Result<TransactionResult, String> {
+    #[cfg(feature = "logging-invoke")]
+    println!("invoke: set_object_entry_values_atomic({node_name:?}, {values:?})");
+    // the node is cloned to not block the CNL while waiting for the commit response.
+    let Some(node) = state
+        .lock()
+        .await
+        .nodes()
+        .iter()
+        .find(|no| no.name() == &node_name)
+        .cloned()
+    else {
+        return Err("Invalid node name".to_owned());
+    };
+    let mut typed_values = vec![];
+    for (object_entry_name, value_json) in values {
+        let Some(object_entry) = node
+            .object_entries()
+            .iter()
+            .find(|oe| oe.name() == &object_entry_name)
+        else {
+            return Err(format!("Invalid object entry name {object_entry_name}"));
+        };
+        let json_value = serde_json::from_str::<serde_json::Value>(&value_json)
+            .map_err(|_| "Failed to parse JSON.".to_owned())?;
+        let value = value_from_typed_json(object_entry.ty(), &json_value)?;
+        typed_values.push((object_entry_name, value));
+    }
+    node.set_atomic(typed_values).await
+}
+
 #[derive(Debug, Clone, Serialize)]
 pub struct ObjectEntryListenLatestResponse {
     event_name: String,
@@ -308,6 +247,61 @@ pub async fn request_object_entry_value(
     Ok(())
 }
 
+#[tauri::command]
+pub async fn get_object_entry_verify(
+    state: tauri::State<'_, CNLState>,
+    node_name: String,
+    object_entry_name: String,
+) -> Result<bool, String> {
+    #[cfg(feature = "logging-invoke")]
+    println!("invoke: get_object_entry_verify({node_name:?}, {object_entry_name:?})");
+    let cnl = state.lock().await;
+    let Some(node) = cnl.nodes().iter().find(|no| no.name() == &node_name) else {
+        return Err("Invalid node name".to_owned());
+    };
+    let Some(object_entry) = node
+        .object_entries()
+        .iter()
+        .find(|oe| oe.name() == &object_entry_name)
+    else {
+        return Err("Invalid object entry name".to_owned());
+    };
+    Ok(object_entry.verify_sets())
+}
+
+/// Enables or disables the read back of set requests, the setting is stored in the app data.
+#[tauri::command]
+pub async fn set_object_entry_verify(
+    state: tauri::State<'_, CNLState>,
+    node_name: String,
+    object_entry_name: String,
+    verify: bool,
+) -> Result<bool, String> {
+    #[cfg(feature = "logging-invoke")]
+    println!("invoke: set_object_entry_verify({node_name:?}, {object_entry_name:?}, {verify})");
+    let cnl = state.lock().await;
+    let Some(node) = cnl.nodes().iter().find(|no| no.name() == &node_name) else {
+        return Err("Invalid node name".to_owned());
+    };
+    let Some(object_entry) = node
+        .object_entries()
+        .iter()
+        .find(|oe| oe.name() == &object_entry_name)
+    else {
+        return Err("Invalid object entry name".to_owned());
+    };
+    if object_entry.is_external() {
+        return Err(format!("{object_entry_name} can't be set"));
+    }
+    object_entry.set_verify_sets(verify);
+    if let Ok(mut app_data) = AppData::read() {
+        let mut requests = app_data.get_requests().clone();
+        requests.set_verify(&node_name, &object_entry_name, verify);
+        app_data.set_requests(requests);
+    }
+    Ok(object_entry.verify_sets())
+}
+
 #[tauri::command]
 pub fn get_floating_window_info(window: tauri::Window) -> (String, String) {
     let split : Vec<&str> = window.label().split("-").collect();
@@ -0,0 +1,62 @@
+use std::path::PathBuf;
+
+use crate::{
+    cnl::parameters::{self, ParameterDiff, ParameterFile},
+    journal::{self, JournalEvent},
+    state::cnl_state::CNLState,
+};
+
+/// Reads the `Global` object entries of the node, or of all nodes, into a parameter file.
+/// Returns the number of stored entries.
+#[tauri::command]
+pub async fn snapshot_parameters(
+    state: tauri::State<'_, CNLState>,
+    app_handle: tauri::AppHandle,
+    node: Option<String>,
+    name: String,
+    path: String,
+) -> Result<usize, String> {
+    #[cfg(feature = "logging-invoke")]
+    println!("invoke: snapshot_parameters({node:?}, {name:?}, {path:?})");
+    // the nodes are cloned to not block the CNL while waiting for the responses.
+    let nodes = state.lock().await.nodes().clone();
+    let file = parameters::snapshot(&app_handle, &nodes, node.as_deref(), &name).await?;
+    file.save(&PathBuf::from(path))?;
+    Ok(file.count())
+}
+
+#[tauri::command]
+pub async fn diff_parameters(
+    state: tauri::State<'_, CNLState>,
+    app_handle: tauri::AppHandle,
+    path: String,
+) -> Result<Vec<ParameterDiff>, String> {
+    #[cfg(feature = "logging-invoke")]
+    println!("invoke: diff_parameters({path:?})");
+    let file = ParameterFile::load(&PathBuf::from(path))?;
+    let nodes = state.lock().await.nodes().clone();
+    Ok(parameters::diff(&app_handle, &nodes, &file).await)
+}
+
+/// Writes the parameter file back, returns the read back values of all entries.
+/// If `atomic` is set, the entries of each node are set in a single transaction.
+#[tauri::command]
+pub async fn restore_parameters(
+    state: tauri::State<'_, CNLState>,
+    app_handle: tauri::AppHandle,
+    path: String,
+    atomic: Option<bool>,
+) -> Result<Vec<ParameterDiff>, String> {
+    #[cfg(feature = "logging-invoke")]
+    println!("invoke: restore_parameters({path:?}, {atomic:?})");
+    let file = ParameterFile::load(&PathBuf::from(&path))?;
+    journal::record(
+        &app_handle,
+        JournalEvent::Action {
+            action: "restore parameters".to_owned(),
+            detail: format!("{} ({path})", file.name),
+        },
+    );
+    let nodes = state.lock().await.nodes().clone();
+    parameters::restore(&app_handle, &nodes, &file, atomic.unwrap_or(false)).await
+}
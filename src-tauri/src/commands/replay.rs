@@ -0,0 +1,58 @@
+use std::{sync::Arc, time::Duration};
+
+use crate::{
+    cnl::can_adapter::replay::{Replay, ReplayStatus},
+    state::cnl_state::CNLState,
+};
+
+async fn replay(state: &tauri::State<'_, CNLState>) -> Result<Arc<Replay>, String> {
+    state
+        .lock()
+        .await
+        .replay()
+        .cloned()
+        .ok_or_else(|| "No recording is replayed".to_owned())
+}
+
+#[tauri::command]
+pub async fn replay_play(state: tauri::State<'_, CNLState>) -> Result<(), String> {
+    #[cfg(feature = "logging-invoke")]
+    println!("invoke: replay_play()");
+    replay(&state).await?.play();
+    Ok(())
+}
+
+#[tauri::command]
+pub async fn replay_pause(state: tauri::State<'_, CNLState>) -> Result<(), String> {
+    #[cfg(feature = "logging-invoke")]
+    println!("invoke: replay_pause()");
+    replay(&state).await?.pause();
+    Ok(())
+}
+
+#[tauri::command]
+pub async fn replay_seek(
+    state: tauri::State<'_, CNLState>,
+    position_ms: u64,
+) -> Result<(), String> {
+    #[cfg(feature = "logging-invoke")]
+    println!("invoke: replay_seek({position_ms})");
+    replay(&state)
+        .await?
+        .seek(Duration::from_millis(position_ms));
+    Ok(())
+}
+
+#[tauri::command]
+pub async fn replay_set_speed(state: tauri::State<'_, CNLState>, speed: f64) -> Result<(), String> {
+    #[cfg(feature = "logging-invoke")]
+    println!("invoke: replay_set_speed({speed})");
+    replay(&state).await?.set_speed(speed)
+}
+
+#[tauri::command]
+pub async fn replay_status(state: tauri::State<'_, CNLState>) -> Result<ReplayStatus, String> {
+    #[cfg(feature = "logging-invoke")]
+    println!("invoke: replay_status()");
+    Ok(replay(&state).await?.status())
+}
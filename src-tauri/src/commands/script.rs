@@ -0,0 +1,43 @@
+use crate::{
+    cnl::script::ScriptRunInfo,
+    journal::{self, JournalEvent},
+    state::cnl_state::CNLState,
+};
+
+/// Starts a script, its output is emitted as `script-output` events and the end of the
+/// run as a `script-status` event.
+#[tauri::command]
+pub async fn run_script(
+    state: tauri::State<'_, CNLState>,
+    app_handle: tauri::AppHandle,
+    name: String,
+    source: String,
+) -> Result<ScriptRunInfo, String> {
+    #[cfg(feature = "logging-invoke")]
+    println!("invoke: run_script({name:?})");
+    let run = state.lock().await.script_runner().run(&name, &source)?;
+    journal::record(
+        &app_handle,
+        JournalEvent::Action {
+            action: "run script".to_owned(),
+            detail: format!("{name}\n{source}"),
+        },
+    );
+    Ok(run)
+}
+
+#[tauri::command]
+pub async fn stop_script(state: tauri::State<'_, CNLState>, run_id: u32) -> Result<(), String> {
+    #[cfg(feature = "logging-invoke")]
+    println!("invoke: stop_script({run_id})");
+    state.lock().await.script_runner().stop(run_id)
+}
+
+#[tauri::command]
+pub async fn list_script_runs(
+    state: tauri::State<'_, CNLState>,
+) -> Result<Vec<ScriptRunInfo>, ()> {
+    #[cfg(feature = "logging-invoke")]
+    println!("invoke: list_script_runs()");
+    Ok(state.lock().await.script_runner().runs())
+}
@@ -1,4 +1,4 @@
-use canzero_appdata::{AppData, AppDataConfig, WdgLevel};
+use canzero_appdata::{AlarmRule, AppData, AppDataConfig, ErrorEntry, RequestSettings, WdgLevel};
 use tauri::{api::dialog::FileDialogBuilder, Manager};
 
 #[tauri::command]
@@ -62,6 +62,48 @@ pub fn set_deadlock_lvl(lvl: WdgLevel) -> Result<WdgLevel, ()> {
     }
 }
 
+/// The alarms are evaluated by the CNL, changes apply the next time it is created.
+#[tauri::command]
+pub fn set_alarms(alarms: Vec<AlarmRule>) -> Result<Vec<AlarmRule>, ()> {
+    let app_data = AppData::read();
+    match app_data {
+        Ok(mut app_data) => {
+            app_data.set_alarms(alarms);
+            Ok(app_data.get_alarms().clone())
+        }
+        Err(_) => Err(()),
+    }
+}
+
+/// Without error entries all enum object entries of the types `error_flag` and
+/// `error_level` report errors.
+#[tauri::command]
+pub fn set_error_entries(
+    error_entries: Option<Vec<ErrorEntry>>,
+) -> Result<Option<Vec<ErrorEntry>>, ()> {
+    let app_data = AppData::read();
+    match app_data {
+        Ok(mut app_data) => {
+            app_data.set_error_entries(error_entries);
+            Ok(app_data.get_error_entries().cloned())
+        }
+        Err(_) => Err(()),
+    }
+}
+
+/// Settings of the request scheduler, which are applied after a restart.
+#[tauri::command]
+pub fn set_request_settings(requests: RequestSettings) -> Result<RequestSettings, ()> {
+    let app_data = AppData::read();
+    match app_data {
+        Ok(mut app_data) => {
+            app_data.set_requests(requests);
+            Ok(app_data.get_requests().clone())
+        }
+        Err(_) => Err(()),
+    }
+}
+
 #[tauri::command]
 pub fn select_network_configuration() {
     FileDialogBuilder::new().pick_file(|path| match path {
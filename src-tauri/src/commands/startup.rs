@@ -3,7 +3,7 @@ use std::time::Duration;
 use canzero_appdata::AppData;
 use canzero_udp::scanner::UdpNetworkScanner;
 use serde::Serialize;
-use tauri::Manager;
+use tauri::{api::dialog::FileDialogBuilder, Manager};
 
 use crate::state::startup::{NetworkConnectionCreateInfo, StartupState};
 
@@ -37,6 +37,7 @@ pub async fn download_network_configuration(
 pub enum ConnectionType {
     SocketCan = 0,
     Tcp = 1,
+    Virtual = 2,
 }
 
 impl Serialize for ConnectionType {
@@ -47,6 +48,7 @@ impl Serialize for ConnectionType {
         serializer.serialize_u8(match self {
             ConnectionType::SocketCan => 0,
             ConnectionType::Tcp => 1,
+            ConnectionType::Virtual => 2,
         })
     }
 }
@@ -82,10 +84,12 @@ pub async fn discover_servers(
 
     // CANzero discovery broadcast are always broadcast on port 9002!
 
-    let connections: Vec<NetworkConnectionCreateInfo> = connections
+    let mut connections: Vec<NetworkConnectionCreateInfo> = connections
         .into_iter()
         .map(NetworkConnectionCreateInfo::Tcp)
         .collect();
+    // the virtual network is always available.
+    connections.push(NetworkConnectionCreateInfo::Virtual);
 
     #[cfg(feature = "socket-can")]
     let Some(network_configuration): Option<canzero_config::config::NetworkRef> =
@@ -100,6 +104,18 @@ pub async fn discover_servers(
         connections
     };
 
+    let virtual_buses = state
+        .network_configuration()
+        .await
+        .map(|network_config| {
+            network_config
+                .buses()
+                .iter()
+                .map(|bus| bus.name().to_owned())
+                .collect::<Vec<String>>()
+        })
+        .unwrap_or_default();
+
     state.set_connections(connections.clone()).await;
     Ok(connections
         .iter()
@@ -123,6 +139,10 @@ pub async fn discover_servers(
                         .collect::<Vec<&str>>()
                 ),
             },
+            NetworkConnectionCreateInfo::Virtual => ConnectionDescription {
+                tag: ConnectionType::Virtual,
+                description: format!("Virtual CAN network at {virtual_buses:?}"),
+            },
         })
         .collect())
 }
@@ -149,6 +169,32 @@ pub async fn try_connect(
     state.try_connect(connection_index, &app_handle).await
 }
 
+/// Lets the user pick a recorded log, which is replayed instead of connecting to a server.
+/// Returns the description of the recording or None if the dialog was cancelled.
+#[tauri::command]
+pub async fn open_recording(
+    state: tauri::State<'_, StartupState>,
+    app_handle: tauri::AppHandle,
+) -> Result<Option<String>, String> {
+    let (tx, rx) = tokio::sync::oneshot::channel();
+    tokio::task::spawn_blocking(move || {
+        FileDialogBuilder::new()
+            .set_title("Open recording")
+            .add_filter("CAN log", &["log", "asc", "trc"])
+            .pick_file(move |path| {
+                let _ = tx.send(path);
+            });
+    });
+    let Some(path) = rx.await.ok().flatten() else {
+        return Ok(None);
+    };
+    state.open_recording(&path, &app_handle).await?;
+    Ok(Some(format!(
+        "Recording {}",
+        path.file_name().unwrap_or_default().to_string_lossy()
+    )))
+}
+
 #[tauri::command]
 pub async fn complete_setup(
     app_handle: tauri::AppHandle,
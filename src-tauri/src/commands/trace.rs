@@ -1,5 +1,13 @@
+use std::{path::PathBuf, time::Duration};
+
+use canzero_canlog::LogFormat;
+
 use crate::{
-    cnl::trace::database::{SortCriteria, SortOrder},
+    cnl::trace::{
+        chronological::TracePage,
+        database::{SortCriteria, SortOrder},
+        event::TraceEvent,
+    },
     state::cnl_state::CNLState,
 };
 
@@ -65,3 +73,136 @@ pub async fn filter_trace_by(
     state.lock().await.trace().filter_by(filter_string).await;
     Ok(())
 }
+
+#[tauri::command]
+pub async fn listen_to_chronological_trace(
+    state: tauri::State<'_, CNLState>,
+) -> Result<String, ()> {
+    #[cfg(feature = "logging-invoke")]
+    println!("invoke: listen_to_chronological_trace()");
+    Ok(state
+        .lock()
+        .await
+        .trace()
+        .listen_chronological()
+        .await
+        .to_owned())
+}
+
+#[tauri::command]
+pub async fn unlisten_from_chronological_trace(
+    state: tauri::State<'_, CNLState>,
+) -> Result<(), ()> {
+    #[cfg(feature = "logging-invoke")]
+    println!("invoke: unlisten_from_chronological_trace()");
+    state.lock().await.trace().unlisten_chronological().await;
+    Ok(())
+}
+
+#[tauri::command]
+pub async fn pause_trace(state: tauri::State<'_, CNLState>) -> Result<(), ()> {
+    #[cfg(feature = "logging-invoke")]
+    println!("invoke: pause_trace()");
+    state.lock().await.trace().pause().await;
+    Ok(())
+}
+
+#[tauri::command]
+pub async fn resume_trace(state: tauri::State<'_, CNLState>) -> Result<(), ()> {
+    #[cfg(feature = "logging-invoke")]
+    println!("invoke: resume_trace()");
+    state.lock().await.trace().resume().await;
+    Ok(())
+}
+
+#[tauri::command]
+pub async fn set_chronological_trace_page(
+    state: tauri::State<'_, CNLState>,
+    offset: Option<u64>,
+    size: usize,
+) -> Result<(), ()> {
+    #[cfg(feature = "logging-invoke")]
+    println!("invoke: set_chronological_trace_page({offset:?}, {size})");
+    state
+        .lock()
+        .await
+        .trace()
+        .set_chronological_page(offset, size)
+        .await;
+    Ok(())
+}
+
+#[tauri::command]
+pub async fn get_chronological_trace_page(
+    state: tauri::State<'_, CNLState>,
+    offset: Option<u64>,
+    size: usize,
+) -> Result<TracePage, ()> {
+    #[cfg(feature = "logging-invoke")]
+    println!("invoke: get_chronological_trace_page({offset:?}, {size})");
+    Ok(state
+        .lock()
+        .await
+        .trace()
+        .chronological_page(offset, size)
+        .await)
+}
+
+#[tauri::command]
+pub async fn get_chronological_trace_between(
+    state: tauri::State<'_, CNLState>,
+    start_ms: u64,
+    end_ms: u64,
+) -> Result<Vec<TraceEvent>, String> {
+    #[cfg(feature = "logging-invoke")]
+    println!("invoke: get_chronological_trace_between({start_ms}, {end_ms})");
+    if start_ms > end_ms {
+        return Err(format!("invalid time range {start_ms}ms..{end_ms}ms"));
+    }
+    Ok(state
+        .lock()
+        .await
+        .trace()
+        .chronological_between(
+            Duration::from_millis(start_ms),
+            Duration::from_millis(end_ms),
+        )
+        .await)
+}
+
+#[tauri::command]
+pub async fn start_trace_recording(
+    state: tauri::State<'_, CNLState>,
+    path: String,
+    format: Option<String>,
+) -> Result<(), String> {
+    #[cfg(feature = "logging-invoke")]
+    println!("invoke: start_trace_recording({path}, {format:?})");
+    let path = PathBuf::from(path);
+    let format = match format {
+        Some(format) => format.parse::<LogFormat>().map_err(|err| err.to_string())?,
+        None => LogFormat::from_path(&path).unwrap_or(LogFormat::Candump),
+    };
+    state
+        .lock()
+        .await
+        .trace()
+        .start_recording(&path, format)
+        .await
+        .map_err(|err| err.to_string())
+}
+
+#[tauri::command]
+pub async fn stop_trace_recording(
+    state: tauri::State<'_, CNLState>,
+) -> Result<Option<usize>, String> {
+    #[cfg(feature = "logging-invoke")]
+    println!("invoke: stop_trace_recording()");
+    state
+        .lock()
+        .await
+        .trace()
+        .stop_recording()
+        .await
+        .map_err(|err| err.to_string())
+}
@@ -0,0 +1,150 @@
+use std::time::Duration;
+
+use crate::{
+    cnl::{
+        serialize::attributes_from_json,
+        transmitter::{raw_frame, ComposedFrame, TxJobInfo, TxJobParams},
+    },
+    journal::{self, JournalEvent},
+    state::cnl_state::CNLState,
+};
+
+#[tauri::command]
+pub async fn send_frame(
+    state: tauri::State<'_, CNLState>,
+    app_handle: tauri::AppHandle,
+    bus: String,
+    id: u32,
+    ide: bool,
+    dlc: Option<u8>,
+    data: Vec<u8>,
+) -> Result<(), String> {
+    #[cfg(feature = "logging-invoke")]
+    println!("invoke: send_frame({bus:?}, {id}, {ide}, {dlc:?}, {data:?})");
+    let frame = raw_frame(id, ide, dlc, &data)?;
+    let cnl = state.lock().await;
+    let transmitter = cnl.transmitter();
+    transmitter.send(transmitter.bus_id(&bus)?, frame).await?;
+    journal::record(
+        &app_handle,
+        JournalEvent::Action {
+            action: "send frame".to_owned(),
+            detail: format!("{bus} {id:#x} {data:02X?}"),
+        },
+    );
+    Ok(())
+}
+
+/// Encodes a configured message from a json object of attribute values without sending it,
+/// the GUI uses this to preview the payload or to start a cyclic frame.
+#[tauri::command]
+pub async fn compose_message(
+    state: tauri::State<'_, CNLState>,
+    message_name: String,
+    values_json: String,
+) -> Result<ComposedFrame, String> {
+    #[cfg(feature = "logging-invoke")]
+    println!("invoke: compose_message({message_name:?}, {values_json})");
+    let values = serde_json::from_str(&values_json).map_err(|_| "Failed to parse JSON.")?;
+    let attributes = attributes_from_json(&values)?;
+    let cnl = state.lock().await;
+    let transmitter = cnl.transmitter();
+    let (bus_id, frame) = transmitter.compose(&message_name, &attributes)?;
+    Ok(transmitter.composed_frame(bus_id, &frame))
+}
+
+#[tauri::command]
+pub async fn send_message(
+    state: tauri::State<'_, CNLState>,
+    app_handle: tauri::AppHandle,
+    message_name: String,
+    values_json: String,
+) -> Result<(), String> {
+    #[cfg(feature = "logging-invoke")]
+    println!("invoke: send_message({message_name:?}, {values_json})");
+    let values = serde_json::from_str(&values_json).map_err(|_| "Failed to parse JSON.")?;
+    let attributes = attributes_from_json(&values)?;
+    let cnl = state.lock().await;
+    cnl.transmitter()
+        .send_message(&message_name, &attributes)
+        .await?;
+    journal::record(
+        &app_handle,
+        JournalEvent::Action {
+            action: "send message".to_owned(),
+            detail: format!("{message_name} {values_json}"),
+        },
+    );
+    Ok(())
+}
+
+#[tauri::command]
+pub async fn start_cyclic_frame(
+    state: tauri::State<'_, CNLState>,
+    bus: String,
+    id: u32,
+    ide: bool,
+    dlc: Option<u8>,
+    data: Vec<u8>,
+    period_ms: u64,
+) -> Result<TxJobInfo, String> {
+    #[cfg(feature = "logging-invoke")]
+    println!("invoke: start_cyclic_frame({bus:?}, {id}, {ide}, {dlc:?}, {data:?}, {period_ms})");
+    let cnl = state.lock().await;
+    let transmitter = cnl.transmitter();
+    transmitter
+        .start_job(TxJobParams {
+            bus_id: transmitter.bus_id(&bus)?,
+            frame: raw_frame(id, ide, dlc, &data)?,
+            period: Duration::from_millis(period_ms),
+        })
+        .await
+}
+
+#[tauri::command]
+pub async fn update_cyclic_frame(
+    state: tauri::State<'_, CNLState>,
+    job_id: u32,
+    bus: String,
+    id: u32,
+    ide: bool,
+    dlc: Option<u8>,
+    data: Vec<u8>,
+    period_ms: u64,
+) -> Result<TxJobInfo, String> {
+    #[cfg(feature = "logging-invoke")]
+    println!(
+        "invoke: update_cyclic_frame({job_id}, {bus:?}, {id}, {ide}, {dlc:?}, {data:?}, {period_ms})"
+    );
+    let cnl = state.lock().await;
+    let transmitter = cnl.transmitter();
+    transmitter
+        .update_job(
+            job_id,
+            TxJobParams {
+                bus_id: transmitter.bus_id(&bus)?,
+                frame: raw_frame(id, ide, dlc, &data)?,
+                period: Duration::from_millis(period_ms),
+            },
+        )
+        .await
+}
+
+#[tauri::command]
+pub async fn stop_cyclic_frame(
+    state: tauri::State<'_, CNLState>,
+    job_id: u32,
+) -> Result<(), String> {
+    #[cfg(feature = "logging-invoke")]
+    println!("invoke: stop_cyclic_frame({job_id})");
+    state.lock().await.transmitter().stop_job(job_id).await
+}
+
+#[tauri::command]
+pub async fn list_cyclic_frames(
+    state: tauri::State<'_, CNLState>,
+) -> Result<Vec<TxJobInfo>, ()> {
+    #[cfg(feature = "logging-invoke")]
+    println!("invoke: list_cyclic_frames()");
+    Ok(state.lock().await.transmitter().jobs().await)
+}
@@ -0,0 +1,302 @@
+use std::{
+    fs::{File, OpenOptions},
+    io::Write,
+    path::{Path, PathBuf},
+    sync::Mutex,
+};
+
+use canzero_appdata::AppData;
+use color_print::cprintln;
+use serde::Serialize;
+use tauri::Manager;
+
+use crate::{
+    cnl::{
+        frame::Value,
+        network::{
+            node_object::transaction::TransactionResult,
+            object_entry_object::verify::VerifyOutcome,
+        },
+    },
+    notification::NotificationLevel,
+};
+
+/// Events recorded in the session journal.
+#[derive(Serialize, Clone)]
+#[serde(tag = "kind", rename_all = "camelCase")]
+pub enum JournalEvent {
+    #[serde(rename_all = "camelCase")]
+    SetRequest {
+        node: String,
+        object_entry: String,
+        request: u64,
+        old: Option<Value>,
+        new: Value,
+    },
+    #[serde(rename_all = "camelCase")]
+    GetRequest {
+        node: String,
+        object_entry: String,
+        request: u64,
+    },
+    /// Answer to a get request, `value` is None if the request timed out.
+    #[serde(rename_all = "camelCase")]
+    GetResponse {
+        node: String,
+        object_entry: String,
+        request: u64,
+        value: Option<Value>,
+    },
+    /// Acknowledgement of a set request, `error` is None if the node accepted it.
+    #[serde(rename_all = "camelCase")]
+    SetResponse {
+        node: String,
+        object_entry: String,
+        request: u64,
+        error: Option<String>,
+    },
+    /// Read back of an acknowledged set request, `live` is None if it could not be read.
+    #[serde(rename_all = "camelCase")]
+    SetVerification {
+        node: String,
+        object_entry: String,
+        request: u64,
+        requested: Value,
+        live: Option<Value>,
+        outcome: VerifyOutcome,
+    },
+    /// Staged values of a node and the outcome of their commit.
+    Transaction(TransactionResult),
+    /// Invocation of a command, `error` is None if the node executed it.
+    Command {
+        node: String,
+        command: String,
+        error: Option<String>,
+    },
+    /// Any other action of the operator, e.g. acknowledging errors or sending frames.
+    Action {
+        action: String,
+        detail: String,
+    },
+    ConnectionStatus {
+        from: String,
+        to: String,
+    },
+    WatchdogTimeout {
+        tag: String,
+    },
+    /// Warnings and errors, info notifications repeat what the other events already record.
+    Notification {
+        level: NotificationLevel,
+        title: String,
+        message: String,
+    },
+}
+
+#[derive(Serialize)]
+struct JournalEntry<'a> {
+    seq: u64,
+    time: String,
+    #[serde(flatten)]
+    event: &'a JournalEvent,
+}
+
+/// Append-only journal of the session, written as JSON lines to
+/// `~/.canzero/journal/<start time>.jsonl`.
+pub struct Journal {
+    path: PathBuf,
+    // the file is created with the first entry.
+    file: Mutex<(u64, Option<File>)>,
+}
+
+impl Journal {
+    pub fn create() -> Self {
+        let file_name = chrono::Local::now()
+            .format("%Y-%m-%d_%H-%M-%S.jsonl")
+            .to_string();
+        Self {
+            path: Self::dir().join(file_name),
+            file: Mutex::new((0, None)),
+        }
+    }
+
+    pub fn dir() -> PathBuf {
+        AppData::dir().join("journal")
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    pub fn record(&self, event: &JournalEvent) {
+        let mut file = self.file.lock().unwrap();
+        if file.1.is_none() {
+            let dir = self.path.parent().unwrap_or(Path::new("."));
+            let opened = std::fs::create_dir_all(dir).and_then(|_| {
+                OpenOptions::new()
+                    .create(true)
+                    .append(true)
+                    .open(&self.path)
+            });
+            match opened {
+                Ok(opened) => file.1 = Some(opened),
+                Err(err) => {
+                    cprintln!(
+                        "<red>Failed to open session journal {:?} ({err})</red>",
+                        self.path
+                    );
+                    return;
+                }
+            }
+        }
+        file.0 += 1;
+        let entry = JournalEntry {
+            seq: file.0,
+            time: chrono::Local::now().to_rfc3339(),
+            event,
+        };
+        let mut line = serde_json::to_string(&entry).expect("journal entries are serializable");
+        line.push('\n');
+        if let Err(err) = file.1.as_mut().unwrap().write_all(line.as_bytes()) {
+            cprintln!("<red>Failed to write session journal ({err})</red>");
+        }
+    }
+
+    /// Names of all journals, the newest first.
+    pub fn sessions() -> Result<Vec<String>, String> {
+        let entries = match std::fs::read_dir(Self::dir()) {
+            Ok(entries) => entries,
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => return Ok(vec![]),
+            Err(err) => return Err(format!("Failed to read journal directory : {err}")),
+        };
+        let mut sessions: Vec<String> = entries
+            .flatten()
+            .filter_map(|entry| {
+                let name = entry.file_name().into_string().ok()?;
+                name.strip_suffix(".jsonl").map(str::to_owned)
+            })
+            .collect();
+        sessions.sort_unstable_by(|a, b| b.cmp(a));
+        Ok(sessions)
+    }
+
+    /// Reads the entries of a journal, which contain `search` (case insensitive).
+    /// Only the last `limit` matching entries are returned.
+    pub fn read(
+        path: &Path,
+        search: Option<&str>,
+        limit: Option<usize>,
+    ) -> Result<Vec<serde_json::Value>, String> {
+        let content = match std::fs::read_to_string(path) {
+            Ok(content) => content,
+            // nothing was recorded yet.
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => return Ok(vec![]),
+            Err(err) => return Err(format!("Failed to read journal {path:?} : {err}")),
+        };
+        let search = search
+            .map(str::trim)
+            .filter(|search| !search.is_empty())
+            .map(str::to_lowercase);
+        let mut entries: Vec<serde_json::Value> = content
+            .lines()
+            .filter(|line| match &search {
+                Some(search) => line.to_lowercase().contains(search),
+                None => true,
+            })
+            // a partially written last line is skipped.
+            .filter_map(|line| serde_json::from_str(line).ok())
+            .collect();
+        if let Some(limit) = limit {
+            entries.drain(..entries.len().saturating_sub(limit));
+        }
+        Ok(entries)
+    }
+}
+
+/// Records the event in the journal of the app, if it is managed already.
+pub fn record(app_handle: &tauri::AppHandle, event: JournalEvent) {
+    if let Some(journal) = app_handle.try_state::<Journal>() {
+        journal.record(&event);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn journal(name: &str) -> Journal {
+        let dir = std::env::temp_dir().join(format!("canzero-journal-{name}"));
+        let _ = std::fs::remove_dir_all(&dir);
+        Journal {
+            path: dir.join("session.jsonl"),
+            file: Mutex::new((0, None)),
+        }
+    }
+
+    fn seqs(entries: &[serde_json::Value]) -> Vec<u64> {
+        entries
+            .iter()
+            .map(|entry| entry["seq"].as_u64().unwrap())
+            .collect()
+    }
+
+    fn record_session(journal: &Journal) {
+        journal.record(&JournalEvent::GetRequest {
+            node: "primary".to_owned(),
+            object_entry: "position".to_owned(),
+            request: 1,
+        });
+        journal.record(&JournalEvent::Action {
+            action: "Acknowledge".to_owned(),
+            detail: "Errors of Secondary".to_owned(),
+        });
+        journal.record(&JournalEvent::Command {
+            node: "secondary".to_owned(),
+            command: "reset".to_owned(),
+            error: None,
+        });
+    }
+
+    #[test]
+    fn entries_are_numbered_in_order() {
+        let journal = journal("seq");
+        assert_eq!(Journal::read(journal.path(), None, None), Ok(vec![]));
+        record_session(&journal);
+
+        let entries = Journal::read(journal.path(), None, None).unwrap();
+        assert_eq!(seqs(&entries), vec![1, 2, 3]);
+        assert_eq!(entries[0]["kind"], "getRequest");
+        assert_eq!(entries[0]["objectEntry"], "position");
+        assert_eq!(entries[1]["kind"], "action");
+        assert_eq!(entries[2]["kind"], "command");
+    }
+
+    #[test]
+    fn search_and_limit_filter_the_entries() {
+        let journal = journal("search");
+        record_session(&journal);
+        let read = |search, limit| seqs(&Journal::read(journal.path(), search, limit).unwrap());
+
+        assert_eq!(read(Some("SECONDARY"), None), vec![2, 3]);
+        assert_eq!(read(Some("  "), None), vec![1, 2, 3]);
+        assert_eq!(read(Some("unknown"), None), Vec::<u64>::new());
+        // the newest entries are kept.
+        assert_eq!(read(None, Some(2)), vec![2, 3]);
+        assert_eq!(read(Some("secondary"), Some(1)), vec![3]);
+        assert_eq!(read(None, Some(10)), vec![1, 2, 3]);
+    }
+
+    #[test]
+    fn partially_written_last_line_is_skipped() {
+        let journal = journal("partial");
+        record_session(&journal);
+        let mut file = OpenOptions::new()
+            .append(true)
+            .open(journal.path())
+            .unwrap();
+        file.write_all(br#"{"seq":4,"time":"2024-"#).unwrap();
+
+        let entries = Journal::read(journal.path(), None, None).unwrap();
+        assert_eq!(seqs(&entries), vec![1, 2, 3]);
+    }
+}
@@ -5,11 +5,13 @@ use tauri::Manager;
 
 use crate::{
     commands::{connection_status, network_information, object_entry_commands, search_string_storage::SearchStringStorage},
+    journal::Journal,
     state::startup::StartupState,
 };
 
 mod cnl;
 mod commands;
+mod journal;
 mod notification;
 mod state;
 
@@ -21,6 +23,7 @@ async fn main() {
     // setup tauri
     tauri::Builder::default()
         .manage(SearchStringStorage { store: Default::default() })
+        .manage(Journal::create())
         .setup(|app| {
             let handle = app.handle();
             tokio::spawn(async move {
@@ -52,6 +55,15 @@ async fn main() {
             commands::trace::unlisten_from_trace,
             commands::trace::sort_trace_by,
             commands::trace::filter_trace_by,
+            commands::trace::listen_to_chronological_trace,
+            commands::trace::unlisten_from_chronological_trace,
+            commands::trace::pause_trace,
+            commands::trace::resume_trace,
+            commands::trace::set_chronological_trace_page,
+            commands::trace::get_chronological_trace_page,
+            commands::trace::get_chronological_trace_between,
+            commands::trace::start_trace_recording,
+            commands::trace::stop_trace_recording,
             network_information::network_information,
             network_information::node_information,
             network_information::object_entry_information,
@@ -62,6 +74,9 @@ async fn main() {
             object_entry_commands::unlisten_from_history_of_object_entry,
             object_entry_commands::request_object_entry_value,
             object_entry_commands::set_object_entry_value,
+            object_entry_commands::get_object_entry_verify,
+            object_entry_commands::set_object_entry_verify,
+            object_entry_commands::set_object_entry_values_atomic,
             object_entry_commands::get_floating_window_info,
             object_entry_commands::open_floating_object_entry_window,
             connection_status::get_connection_status,
@@ -74,6 +89,7 @@ async fn main() {
             commands::node_commands::listen_to_node_latest,
             commands::node_commands::unlisten_from_node_latest,
             commands::node_commands::get_heartbeat_status,
+            commands::node_commands::get_node_diagnostics,
             commands::node_commands::listen_to_heartbeat,
             commands::node_commands::unlisten_from_heartbeat,
             commands::export::export,
@@ -81,6 +97,7 @@ async fn main() {
             commands::startup::download_network_configuration,
             commands::startup::discover_servers,
             commands::startup::try_connect,
+            commands::startup::open_recording,
             commands::startup::complete_setup,
             commands::startup::close_startup,
             commands::settings::open_settings,
@@ -89,11 +106,40 @@ async fn main() {
             commands::settings::get_settings,
             commands::settings::set_frontend_lvl,
             commands::settings::set_deadlock_lvl,
+            commands::settings::set_alarms,
+            commands::settings::set_error_entries,
+            commands::settings::set_request_settings,
             commands::search_string_storage::get_stored_search_string,
             commands::search_string_storage::store_search_string,
             commands::errors::listen_to_errors,
             commands::errors::unlisten_from_errors,
             commands::errors::reset_errors,
+            commands::errors::acknowledge_errors,
+            commands::errors::get_error_log,
+            commands::errors::export_error_log,
+            commands::replay::replay_play,
+            commands::replay::replay_pause,
+            commands::replay::replay_seek,
+            commands::replay::replay_set_speed,
+            commands::replay::replay_status,
+            commands::transmit::send_frame,
+            commands::transmit::compose_message,
+            commands::transmit::send_message,
+            commands::transmit::start_cyclic_frame,
+            commands::transmit::update_cyclic_frame,
+            commands::transmit::stop_cyclic_frame,
+            commands::transmit::list_cyclic_frames,
+            commands::script::run_script,
+            commands::script::stop_script,
+            commands::script::list_script_runs,
+            commands::capture::arm_capture_trigger,
+            commands::capture::disarm_capture_trigger,
+            commands::capture::list_capture_triggers,
+            commands::journal::list_journal_sessions,
+            commands::journal::get_journal_entries,
+            commands::parameters::snapshot_parameters,
+            commands::parameters::diff_parameters,
+            commands::parameters::restore_parameters,
         ])
         .run(tauri::generate_context!())
         .expect("Error while running tauri application");
@@ -1,6 +1,8 @@
 use tauri::Manager;
 use chrono::{DateTime, Local};
 
+use crate::journal::{self, JournalEvent};
+
 // TODO remove allow dead_code before release!
 #[derive(Clone)]
 pub enum NotificationLevel {
@@ -49,28 +51,32 @@ impl Notification {
 
 
 pub fn notify_error(app_handle : &tauri::AppHandle, reason: &str, description: &str, time_stamp: DateTime<Local>) {
-    app_handle
-        .emit_all(
-            "notification",
-            Notification::new(NotificationLevel::Error, reason, description, time_stamp),
-        )
-        .expect("failed to emit notification");
+    emit_notification(app_handle, NotificationLevel::Error, reason, description, time_stamp);
 }
 
 pub fn notify_warning(app_handle : &tauri::AppHandle, reason: &str, description: &str, time_stamp: DateTime<Local>) {
-    app_handle
-        .emit_all(
-            "notification",
-            Notification::new(NotificationLevel::Warning, reason, description, time_stamp),
-        )
-        .expect("failed to emit notification");
+    emit_notification(app_handle, NotificationLevel::Warning, reason, description, time_stamp);
 }
 
 pub fn notify_info(app_handle : &tauri::AppHandle, reason: &str, description: &str, time_stamp: DateTime<Local>) {
+    emit_notification(app_handle, NotificationLevel::Info, reason, description, time_stamp);
+}
+
+fn emit_notification(app_handle : &tauri::AppHandle, level: NotificationLevel, reason: &str, description: &str, time_stamp: DateTime<Local>) {
+    if matches!(level, NotificationLevel::Warning | NotificationLevel::Error) {
+        journal::record(
+            app_handle,
+            JournalEvent::Notification {
+                level: level.clone(),
+                title: reason.to_owned(),
+                message: description.to_owned(),
+            },
+        );
+    }
     app_handle
         .emit_all(
             "notification",
-            Notification::new(NotificationLevel::Info, reason, description, time_stamp),
+            Notification::new(level, reason, description, time_stamp),
         )
         .expect("failed to emit notification");
 }
@@ -1,14 +1,17 @@
 use std::{
     future::IntoFuture,
+    path::Path,
     sync::{Arc, OnceLock},
     time::Instant,
 };
 
+use canzero_canlog::ChannelMap;
+
 use canzero_config::config::NetworkRef;
 use canzero_udp::frame::NetworkDescription;
 use tokio::sync::Mutex;
 
-use crate::cnl::can_adapter::CanAdapter;
+use crate::cnl::can_adapter::{virtual_can::VirtualCanNetwork, CanAdapter};
 
 use super::cnl_state::CNLState;
 
@@ -17,6 +20,7 @@ pub enum NetworkConnectionCreateInfo {
     Tcp(NetworkDescription),
     #[cfg(feature = "socket-can")]
     SocketCan,
+    Virtual,
 }
 
 pub struct StartupState {
@@ -83,6 +87,16 @@ impl StartupState {
                     Some(Mutex::new(sync_complete)),
                 );
             }
+            NetworkConnectionCreateInfo::Virtual => {
+                let network = VirtualCanNetwork::global();
+                let can_adapters = CanAdapter::create_virtual_adapters(&network_ref, network);
+                *self.established_connection.lock().await = (
+                    can_adapters.into_iter().map(Arc::new).collect(),
+                    Some(network.timebase()),
+                    None,
+                    None,
+                );
+            }
             #[cfg(feature = "socket-can")]
             NetworkConnectionCreateInfo::SocketCan => {
                 let can_adapter = CanAdapter::create_socketcan_adapters(&network_ref, app_handle)
@@ -97,6 +111,33 @@ impl StartupState {
         Ok(())
     }
 
+    /// Prepares a replay of a recorded log instead of a connection to a network.
+    pub async fn open_recording(
+        &self,
+        path: &Path,
+        app_handle: &tauri::AppHandle,
+    ) -> Result<(), String> {
+        let Some(network_ref) = self.network_config.lock().await.as_ref().cloned() else {
+            return Err("Failed to open recording. No network configuration avaiable".to_owned());
+        };
+        let channels = ChannelMap::from_buses(network_ref.buses());
+        let frames = canzero_canlog::read_log(path, None, &channels)
+            .map_err(|err| format!("Failed to read {path:?} : {err}"))?;
+        if frames.is_empty() {
+            return Err(format!("The recording {path:?} does not contain any frames"));
+        }
+        let timebase = Instant::now();
+        let can_adapters =
+            CanAdapter::create_replay_adapters(&network_ref, app_handle, frames, timebase);
+        *self.established_connection.lock().await = (
+            can_adapters.into_iter().map(Arc::new).collect(),
+            Some(timebase),
+            None,
+            None,
+        );
+        Ok(())
+    }
+
     pub async fn complete_setup(&self, app_handle: &tauri::AppHandle) -> Result<CNLState, String> {
         let Some(network_config) = self.network_config.lock().await.as_ref().cloned() else {
             return Err("Failed to complete setup. No network configuration avaiable".to_owned());
@@ -14,6 +14,7 @@ import {
     useTheme
 } from "@mui/material";
 import ShowPages from "./dashboard/ShowPages";
+import ReplayControls from "./replay/ReplayControls";
 import { invoke } from "@tauri-apps/api";
 import { listen } from "@tauri-apps/api/event";
 
@@ -87,6 +88,7 @@ function Content() {
           <ShowPages />
         </Box>
       </Box>
+      <ReplayControls />
       <Dialog open={backendError != undefined} >
         <DialogTitle>Network Connection closed</DialogTitle>
         <DialogContent>
@@ -1,8 +1,10 @@
-import { Alert, AlertTitle, AppBar, Box, Container, CssBaseline, IconButton, LinearProgress, List, ListItem, ListItemButton, ListItemIcon, ListItemText, Paper, Stack, Step, StepContent, StepLabel, Stepper, Toolbar, Typography, useTheme } from "@mui/material";
+import { Alert, AlertTitle, AppBar, Box, Button, Container, CssBaseline, IconButton, LinearProgress, List, ListItem, ListItemButton, ListItemIcon, ListItemText, Paper, Stack, Step, StepContent, StepLabel, Stepper, Toolbar, Typography, useTheme } from "@mui/material";
 import AccessibleIcon from '@mui/icons-material/Accessible';
 import LanIcon from '@mui/icons-material/Lan';
 import CellTowerIcon from '@mui/icons-material/CellTower';
+import MemoryIcon from '@mui/icons-material/Memory';
 import RefreshIcon from '@mui/icons-material/Refresh';
+import FolderOpenIcon from '@mui/icons-material/FolderOpen';
 import { invoke } from "@tauri-apps/api";
 import { useEffect, useState } from "react";
 
@@ -17,6 +19,7 @@ enum ConnectionState {
 enum ConnectionType {
   SocketCan = 0,
   Tcp = 1,
+  Virtual = 2,
 }
 
 interface ConnectionDescription {
@@ -90,6 +93,18 @@ function StartupContent() {
     // register to listener in the backend 
   }, [activeStep, connectionState]);
 
+  function openRecording() {
+    invoke<string | null>("open_recording", {}).then(description => {
+      if (description != null) {
+        setActiveConnection(description);
+        setActiveStep(2);
+      }
+    }).catch(err => {
+      setConnectionError(err);
+      setConnectionState(ConnectionState.Error);
+    });
+  }
+
   function reset() {
     setActiveStep(-1);
     setConnectionState(ConnectionState.Init);
@@ -212,7 +227,8 @@ function StartupContent() {
                                         setConnectionState(ConnectionState.Connecting);
                                       }}>
                                         <ListItemIcon>
-                                          {connection.tag == ConnectionType.SocketCan ? <LanIcon /> : <CellTowerIcon />}
+                                          {connection.tag == ConnectionType.SocketCan ? <LanIcon />
+                                            : connection.tag == ConnectionType.Virtual ? <MemoryIcon /> : <CellTowerIcon />}
                                         </ListItemIcon>
                                         <ListItemText>
                                           {connection.description}
@@ -236,6 +252,10 @@ function StartupContent() {
                         return <></>;
                     }
                   })()}
+                  {connectionState != ConnectionState.Connecting ?
+                    <Button size="small" startIcon={<FolderOpenIcon />} onClick={openRecording} sx={{ marginTop: 1 }}>
+                      Open Recording
+                    </Button> : <></>}
                 </StepContent>
               </Step>
               <Step key="complete-setup">
@@ -0,0 +1,176 @@
+import { Button, IconButton, MenuItem, Paper, Stack, Table, TableBody, TableCell, TableHead, TableRow, TextField, Typography } from "@mui/material";
+import DeleteIcon from '@mui/icons-material/Delete';
+import { invoke } from "@tauri-apps/api";
+import { useEffect, useState } from "react";
+import { AlarmCondition, AlarmLevel, AlarmRule } from "./types/AlarmRule.ts";
+
+type ConditionKind = AlarmCondition["kind"];
+
+const CONDITION_KINDS: { kind: ConditionKind, label: string }[] = [
+  { kind: "above", label: "Above" },
+  { kind: "below", label: "Below" },
+  { kind: "rateOfChange", label: "Rate of change" },
+  { kind: "stale", label: "Stale" },
+  { kind: "enumEquals", label: "Enum equals" },
+];
+
+function describe(condition: AlarmCondition): string {
+  switch (condition.kind) {
+    case "above":
+      return `> ${condition.threshold} (hysteresis ${condition.hysteresis})`;
+    case "below":
+      return `< ${condition.threshold} (hysteresis ${condition.hysteresis})`;
+    case "rateOfChange":
+      return `faster than ${condition.maxRate}/s`;
+    case "stale":
+      return `not received for ${condition.timeoutMs}ms`;
+    case "enumEquals":
+      return `== ${condition.variant}`;
+  }
+}
+
+function Alarms() {
+  const [rules, setRules] = useState<AlarmRule[]>([]);
+  const [changed, setChanged] = useState<boolean>(false);
+
+  const [name, setName] = useState<string>("");
+  const [objectEntry, setObjectEntry] = useState<string>("");
+  const [level, setLevel] = useState<AlarmLevel>("warning");
+  const [kind, setKind] = useState<ConditionKind>("above");
+  const [threshold, setThreshold] = useState<string>("0");
+  const [hysteresis, setHysteresis] = useState<string>("0");
+  const [maxRate, setMaxRate] = useState<string>("1");
+  const [timeoutMs, setTimeoutMs] = useState<string>("1000");
+  const [variant, setVariant] = useState<string>("");
+  const [description, setDescription] = useState<string>("");
+
+  useEffect(() => {
+    invoke<{ alarms: AlarmRule[] }>("get_settings")
+      .then(settings => setRules(settings.alarms))
+      .catch(console.error);
+  }, []);
+
+  function condition(): AlarmCondition {
+    switch (kind) {
+      case "above":
+      case "below":
+        return { kind, threshold: Number(threshold), hysteresis: Number(hysteresis) };
+      case "rateOfChange":
+        return { kind, maxRate: Number(maxRate) };
+      case "stale":
+        return { kind, timeoutMs: Number(timeoutMs) };
+      case "enumEquals":
+        return { kind, variant };
+    }
+  }
+
+  function add() {
+    const [node, entry] = objectEntry.split(".", 2);
+    const rule: AlarmRule = {
+      name: name.length == 0 ? objectEntry : name,
+      node,
+      objectEntry: entry ?? "",
+      level,
+      condition: condition(),
+      description: description.length == 0 ? undefined : description,
+    };
+    setRules(rules => [...rules, rule]);
+    setChanged(true);
+  }
+
+  function remove(index: number) {
+    setRules(rules => rules.filter((_, i) => i != index));
+    setChanged(true);
+  }
+
+  function save() {
+    invoke<AlarmRule[]>("set_alarms", { alarms: rules })
+      .then(alarms => {
+        setRules(alarms);
+        setChanged(false);
+      })
+      .catch(console.error);
+  }
+
+  const thresholdCondition = kind == "above" || kind == "below";
+  return (
+    <Stack spacing={2} sx={{ padding: 2 }}>
+      <Paper sx={{ padding: 2 }}>
+        <Stack spacing={2}>
+          <Stack direction="row" spacing={2}>
+            <TextField label="Name" size="small" value={name} onChange={event => setName(event.target.value)} />
+            <TextField label="Object entry" size="small" placeholder="node.entry" value={objectEntry}
+              onChange={event => setObjectEntry(event.target.value)} />
+            <TextField select label="Level" size="small" value={level} sx={{ minWidth: 120 }}
+              onChange={event => setLevel(event.target.value as AlarmLevel)}>
+              <MenuItem value="info">Info</MenuItem>
+              <MenuItem value="warning">Warning</MenuItem>
+              <MenuItem value="error">Error</MenuItem>
+            </TextField>
+          </Stack>
+          <Stack direction="row" spacing={2} alignItems="center">
+            <TextField select label="Condition" size="small" value={kind} sx={{ minWidth: 160 }}
+              onChange={event => setKind(event.target.value as ConditionKind)}>
+              {CONDITION_KINDS.map(({ kind, label }) => <MenuItem key={kind} value={kind}>{label}</MenuItem>)}
+            </TextField>
+            {thresholdCondition && <>
+              <TextField label="Threshold" size="small" type="number" value={threshold}
+                onChange={event => setThreshold(event.target.value)} />
+              <TextField label="Hysteresis" size="small" type="number" value={hysteresis}
+                onChange={event => setHysteresis(event.target.value)} />
+            </>}
+            {kind == "rateOfChange" &&
+              <TextField label="Max rate (1/s)" size="small" type="number" value={maxRate}
+                onChange={event => setMaxRate(event.target.value)} />}
+            {kind == "stale" &&
+              <TextField label="Timeout (ms)" size="small" type="number" value={timeoutMs}
+                onChange={event => setTimeoutMs(event.target.value)} />}
+            {kind == "enumEquals" &&
+              <TextField label="Variant" size="small" value={variant} onChange={event => setVariant(event.target.value)} />}
+            <TextField label="Description" size="small" sx={{ flexGrow: 1 }} value={description}
+              onChange={event => setDescription(event.target.value)} />
+            <Button variant="outlined" disabled={!objectEntry.includes(".")} onClick={add}>Add</Button>
+          </Stack>
+        </Stack>
+      </Paper>
+      <Paper>
+        <Table size="small">
+          <TableHead>
+            <TableRow>
+              <TableCell>Name</TableCell>
+              <TableCell>Object entry</TableCell>
+              <TableCell>Level</TableCell>
+              <TableCell>Condition</TableCell>
+              <TableCell>Description</TableCell>
+              <TableCell />
+            </TableRow>
+          </TableHead>
+          <TableBody>
+            {rules.map((rule, index) => (
+              <TableRow key={index}>
+                <TableCell>{rule.name}</TableCell>
+                <TableCell>{rule.node}::{rule.objectEntry}</TableCell>
+                <TableCell>{rule.level}</TableCell>
+                <TableCell>{describe(rule.condition)}</TableCell>
+                <TableCell>{rule.description ?? "-"}</TableCell>
+                <TableCell align="right">
+                  <IconButton size="small" onClick={() => remove(index)}>
+                    <DeleteIcon fontSize="small" />
+                  </IconButton>
+                </TableCell>
+              </TableRow>
+            ))}
+          </TableBody>
+        </Table>
+      </Paper>
+      <Stack direction="row" spacing={2} alignItems="center">
+        <Button variant="contained" disabled={!changed} onClick={save}>Save</Button>
+        <Typography variant="body2" color="text.secondary">
+          Alarms are applied the next time a network is connected.
+        </Typography>
+      </Stack>
+    </Stack>
+  );
+}
+
+export default Alarms;
@@ -0,0 +1,18 @@
+
+export type AlarmLevel = "info" | "warning" | "error";
+
+export type AlarmCondition =
+  | { kind: "above", threshold: number, hysteresis: number }
+  | { kind: "below", threshold: number, hysteresis: number }
+  | { kind: "rateOfChange", maxRate: number }
+  | { kind: "stale", timeoutMs: number }
+  | { kind: "enumEquals", variant: string };
+
+export interface AlarmRule {
+  name : string,
+  node : string,
+  objectEntry : string,
+  level : AlarmLevel,
+  condition : AlarmCondition,
+  description? : string,
+}
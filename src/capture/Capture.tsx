@@ -0,0 +1,160 @@
+import { Button, Checkbox, FormControlLabel, IconButton, MenuItem, Paper, Stack, Table, TableBody, TableCell, TableHead, TableRow, TextField, Typography } from "@mui/material";
+import DeleteIcon from '@mui/icons-material/Delete';
+import { invoke } from "@tauri-apps/api";
+import { listen } from "@tauri-apps/api/event";
+import { useEffect, useState } from "react";
+import { TriggerCondition, TriggerInfo } from "./types/CaptureTrigger.ts";
+
+type ConditionKind = TriggerCondition["kind"];
+
+const CONDITION_KINDS: { kind: ConditionKind, label: string }[] = [
+  { kind: "threshold", label: "Threshold" },
+  { kind: "enumValue", label: "Enum value" },
+  { kind: "frameId", label: "Frame id" },
+  { kind: "errorFrame", label: "Error frame" },
+];
+
+function Capture() {
+  const [triggers, setTriggers] = useState<TriggerInfo[]>([]);
+  const [error, setError] = useState<string>();
+
+  const [name, setName] = useState<string>("capture");
+  const [kind, setKind] = useState<ConditionKind>("threshold");
+  const [objectEntry, setObjectEntry] = useState<string>("");
+  const [threshold, setThreshold] = useState<string>("0");
+  const [edge, setEdge] = useState<"rising" | "falling" | "both">("rising");
+  const [variant, setVariant] = useState<string>("");
+  const [frameId, setFrameId] = useState<string>("0x0");
+  const [ide, setIde] = useState<boolean>(false);
+  const [bus, setBus] = useState<string>("");
+  const [preTriggerMs, setPreTriggerMs] = useState<string>("1000");
+  const [postTriggerMs, setPostTriggerMs] = useState<string>("1000");
+  const [directory, setDirectory] = useState<string>("");
+  const [rearm, setRearm] = useState<boolean>(false);
+
+  useEffect(() => {
+    invoke<TriggerInfo[]>("list_capture_triggers").then(setTriggers).catch(console.error);
+    let unlisten = listen<TriggerInfo>("capture-trigger", event => {
+      setTriggers(triggers => triggers.map(trigger =>
+        trigger.triggerId == event.payload.triggerId ? event.payload : trigger));
+    });
+    return () => {
+      unlisten.then(f => f()).catch(console.error);
+    };
+  }, []);
+
+  function condition(): TriggerCondition {
+    // object entries are selected as node.entry, like in scripts.
+    const [node, entry] = objectEntry.split(".", 2);
+    switch (kind) {
+      case "threshold":
+        return { kind, node, objectEntry: entry, threshold: Number(threshold), edge };
+      case "enumValue":
+        return { kind, node, objectEntry: entry, variant };
+      case "frameId":
+        return { kind, id: Number(frameId), ide, bus: bus.length == 0 ? undefined : bus };
+      case "errorFrame":
+        return { kind, bus: bus.length == 0 ? undefined : bus };
+    }
+  }
+
+  function arm() {
+    setError(undefined);
+    const trigger = {
+      name,
+      condition: condition(),
+      preTriggerMs: Number(preTriggerMs),
+      postTriggerMs: Number(postTriggerMs),
+      directory,
+      rearm,
+    };
+    invoke<TriggerInfo>("arm_capture_trigger", { trigger })
+      .then(info => setTriggers(triggers => [...triggers, info]))
+      .catch(setError);
+  }
+
+  function disarm(triggerId: number) {
+    invoke("disarm_capture_trigger", { triggerId })
+      .then(() => setTriggers(triggers => triggers.filter(trigger => trigger.triggerId != triggerId)))
+      .catch(console.error);
+  }
+
+  const valueCondition = kind == "threshold" || kind == "enumValue";
+  return (
+    <Stack spacing={2} sx={{ padding: 2 }}>
+      <Paper sx={{ padding: 2 }}>
+        <Stack spacing={2}>
+          <Stack direction="row" spacing={2}>
+            <TextField label="Name" size="small" value={name} onChange={event => setName(event.target.value)} />
+            <TextField select label="Condition" size="small" value={kind} sx={{ minWidth: 160 }}
+              onChange={event => setKind(event.target.value as ConditionKind)}>
+              {CONDITION_KINDS.map(({ kind, label }) => <MenuItem key={kind} value={kind}>{label}</MenuItem>)}
+            </TextField>
+            {valueCondition &&
+              <TextField label="Object entry" size="small" placeholder="node.entry" value={objectEntry}
+                onChange={event => setObjectEntry(event.target.value)} />}
+            {kind == "threshold" && <>
+              <TextField label="Threshold" size="small" type="number" value={threshold}
+                onChange={event => setThreshold(event.target.value)} />
+              <TextField select label="Edge" size="small" value={edge} sx={{ minWidth: 120 }}
+                onChange={event => setEdge(event.target.value as "rising" | "falling" | "both")}>
+                <MenuItem value="rising">Rising</MenuItem>
+                <MenuItem value="falling">Falling</MenuItem>
+                <MenuItem value="both">Both</MenuItem>
+              </TextField>
+            </>}
+            {kind == "enumValue" &&
+              <TextField label="Variant" size="small" value={variant} onChange={event => setVariant(event.target.value)} />}
+            {kind == "frameId" && <>
+              <TextField label="Id" size="small" value={frameId} onChange={event => setFrameId(event.target.value)} />
+              <FormControlLabel label="Extended" control={<Checkbox checked={ide} onChange={event => setIde(event.target.checked)} />} />
+            </>}
+            {!valueCondition &&
+              <TextField label="Bus" size="small" placeholder="any" value={bus} onChange={event => setBus(event.target.value)} />}
+          </Stack>
+          <Stack direction="row" spacing={2} alignItems="center">
+            <TextField label="Pre trigger (ms)" size="small" type="number" value={preTriggerMs}
+              onChange={event => setPreTriggerMs(event.target.value)} />
+            <TextField label="Post trigger (ms)" size="small" type="number" value={postTriggerMs}
+              onChange={event => setPostTriggerMs(event.target.value)} />
+            <TextField label="Directory" size="small" sx={{ flexGrow: 1 }} value={directory}
+              onChange={event => setDirectory(event.target.value)} />
+            <FormControlLabel label="Rearm" control={<Checkbox checked={rearm} onChange={event => setRearm(event.target.checked)} />} />
+            <Button variant="contained" onClick={arm}>Arm</Button>
+          </Stack>
+          {error && <Typography color="error">{error}</Typography>}
+        </Stack>
+      </Paper>
+      <Paper>
+        <Table size="small">
+          <TableHead>
+            <TableRow>
+              <TableCell>Name</TableCell>
+              <TableCell>Status</TableCell>
+              <TableCell>Captures</TableCell>
+              <TableCell>Last capture</TableCell>
+              <TableCell />
+            </TableRow>
+          </TableHead>
+          <TableBody>
+            {triggers.map(trigger => (
+              <TableRow key={trigger.triggerId}>
+                <TableCell>{trigger.name}</TableCell>
+                <TableCell>{trigger.status}</TableCell>
+                <TableCell>{trigger.captures}</TableCell>
+                <TableCell>{trigger.lastCapture ?? "-"}</TableCell>
+                <TableCell align="right">
+                  <IconButton size="small" onClick={() => disarm(trigger.triggerId)}>
+                    <DeleteIcon fontSize="small" />
+                  </IconButton>
+                </TableCell>
+              </TableRow>
+            ))}
+          </TableBody>
+        </Table>
+      </Paper>
+    </Stack>
+  );
+}
+
+export default Capture;
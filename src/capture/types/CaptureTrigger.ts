@@ -0,0 +1,23 @@
+
+export type TriggerCondition =
+  | { kind: "threshold", node: string, objectEntry: string, threshold: number, edge: "rising" | "falling" | "both" }
+  | { kind: "enumValue", node: string, objectEntry: string, variant: string }
+  | { kind: "frameId", id: number, ide: boolean, bus?: string }
+  | { kind: "errorFrame", bus?: string };
+
+export interface TriggerDefinition {
+  name : string,
+  condition : TriggerCondition,
+  preTriggerMs : number,
+  postTriggerMs : number,
+  directory : string,
+  rearm : boolean,
+}
+
+export interface TriggerInfo {
+  triggerId : number,
+  name : string,
+  status : "armed" | "capturing" | "fired",
+  captures : number,
+  lastCapture? : string,
+}
@@ -46,10 +46,10 @@ function HeartbeatNodeStatus({ nodeName, busNames }: Readonly<HeartbeatProps>) {
     async function asyncSetup() {
 
       busNames.map((busName, idx) => {
-        invoke<HeartbeatState>("get_heartbeat_status", { nodeName, busName }).then(s => {
+        invoke<{ status: HeartbeatState }>("get_heartbeat_status", { nodeName, busName }).then(s => {
           setHeartbeatStates(old => {
             let updt = old.slice();
-            updt[idx] = stateToString(s)!;
+            updt[idx] = stateToString(s.status)!;
             setHeartbeatNodeState(statesToNodeState(updt));
             return updt;
           })
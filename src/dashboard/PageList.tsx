@@ -1,6 +1,12 @@
 import ListItemIcon from '@mui/material/ListItemIcon';
 import ListItemText from '@mui/material/ListItemText';
 import TerminalIcon from '@mui/icons-material/Terminal';
+import CodeIcon from '@mui/icons-material/Code';
+import HistoryIcon from '@mui/icons-material/History';
+import TuneIcon from '@mui/icons-material/Tune';
+import SendIcon from '@mui/icons-material/Send';
+import ShutterSpeedIcon from '@mui/icons-material/ShutterSpeed';
+import NotificationsActiveIcon from '@mui/icons-material/NotificationsActive';
 import GamesIcon from '@mui/icons-material/Games';
 import ExpandMoreIcon from '@mui/icons-material/ExpandMore';
 import ChevronRightIcon from '@mui/icons-material/ChevronRight';
@@ -150,6 +156,12 @@ export const RouterList = (
     <React.Fragment>
         <ListItemButtonLink to="/" text="Overview" icon={<GamesIcon/>}/>
         <ListItemButtonLink to="/TracePanel" text="Trace" icon={<TerminalIcon/>}/>
+        <ListItemButtonLink to="/Transmit" text="Transmit" icon={<SendIcon/>}/>
+        <ListItemButtonLink to="/Scripting" text="Scripting" icon={<CodeIcon/>}/>
+        <ListItemButtonLink to="/Journal" text="Journal" icon={<HistoryIcon/>}/>
+        <ListItemButtonLink to="/Capture" text="Capture" icon={<ShutterSpeedIcon/>}/>
+        <ListItemButtonLink to="/Alarms" text="Alarms" icon={<NotificationsActiveIcon/>}/>
+        <ListItemButtonLink to="/Parameters" text="Parameters" icon={<TuneIcon/>}/>
         <ListItemButtonLink to="/LevitationControl" text="Levitation Control" icon={<FlightTakeoff/>}/>
         <ListItemButtonLink to="/MotorControl" text="Motor Control" icon={<Speed/>}/>
         <ListItemButtonLink to="/PowerControl" text="Power Control" icon={<BatteryChargingFullIcon/>}/>
@@ -11,7 +11,13 @@ import MotorControl from "../divisions/MotorControl.tsx";
 import PowerControl from "../divisions/PowerControl.tsx";
 import ObjectEntryPanel from "../object_entry/panel/ObjectEntryPanel.tsx";
 import Trace from '../trace/Trace.tsx';
+import Transmit from '../transmit/Transmit.tsx';
 import Logging from "../logging/Logging.tsx";
+import Scripting from "../scripting/Scripting.tsx";
+import Journal from "../journal/Journal.tsx";
+import Capture from "../capture/Capture.tsx";
+import Alarms from "../alarms/Alarms.tsx";
+import Parameters from "../parameters/Parameters.tsx";
 
 function Content() {
   const location = useLocation();
@@ -50,6 +56,12 @@ function ShowPages() {
     routes.push(<Route key="Overview" index element={<OverviewPanel nodes={nodes} />} />);
   }
   routes.push(<Route key="TracePanel" path="TracePanel" element={<Trace />} />);
+  routes.push(<Route key="Transmit" path="Transmit" element={<Transmit />} />);
+  routes.push(<Route key="Scripting" path="Scripting" element={<Scripting />} />);
+  routes.push(<Route key="Journal" path="Journal" element={<Journal />} />);
+  routes.push(<Route key="Capture" path="Capture" element={<Capture />} />);
+  routes.push(<Route key="Alarms" path="Alarms" element={<Alarms />} />);
+  routes.push(<Route key="Parameters" path="Parameters" element={<Parameters />} />);
   if (nodes) {
     routes.push(<Route key="Logging" path="Logging" element={<Logging nodes={nodes} />} />);
   }
@@ -0,0 +1,98 @@
+import { Box, Button, MenuItem, Paper, Select, Stack, TextField, useTheme } from "@mui/material";
+import { invoke } from "@tauri-apps/api";
+import { useEffect, useState } from "react";
+import { JournalEntry } from "./types/JournalEntry.ts";
+
+const CURRENT_SESSION = "current";
+const ENTRY_LIMIT = 1000;
+
+function describe(entry: JournalEntry): string {
+  switch (entry.kind) {
+    case "getRequest":
+      return `get ${entry.node}::${entry.objectEntry} (#${entry.request})`;
+    case "getResponse":
+      return `${entry.node}::${entry.objectEntry} (#${entry.request}) ${entry.value === null ? "timed out" : `= ${JSON.stringify(entry.value)}`}`;
+    case "setRequest":
+      return `set ${entry.node}::${entry.objectEntry} ${JSON.stringify(entry.old)} -> ${JSON.stringify(entry.new)} (#${entry.request})`;
+    case "setResponse":
+      return `${entry.node}::${entry.objectEntry} (#${entry.request}) ${entry.error ? `failed : ${entry.error}` : "acknowledged"}`;
+    case "setVerification":
+      return `${entry.node}::${entry.objectEntry} (#${entry.request}) ${entry.outcome}, requested ${JSON.stringify(entry.requested)} read ${JSON.stringify(entry.live)}`;
+    case "transaction": {
+      const entries = (entry.entries as { objectEntry: string, new: unknown, status: string }[])
+        .map(e => `${e.objectEntry} = ${JSON.stringify(e.new)} (${e.status})`);
+      return `transaction ${entry.node} ${entry.outcome ?? "timeout"} : ${entries.join(", ")}`;
+    }
+    case "command":
+      return `command ${entry.node}.${entry.command} ${entry.error ? `failed : ${entry.error}` : "executed"}`;
+    case "action":
+      return `${entry.action} ${entry.detail}`;
+    case "connectionStatus":
+      return `${entry.from} -> ${entry.to}`;
+    case "watchdogTimeout":
+      return `${entry.tag} timeout`;
+    case "notification":
+      return `[${entry.level}] ${entry.title} : ${entry.message}`;
+  }
+}
+
+function Journal() {
+  const theme = useTheme();
+  const [sessions, setSessions] = useState<string[]>([]);
+  const [session, setSession] = useState<string>(CURRENT_SESSION);
+  const [search, setSearch] = useState<string>("");
+  const [entries, setEntries] = useState<JournalEntry[]>([]);
+  const [error, setError] = useState<string>();
+
+  useEffect(() => {
+    invoke<string[]>("list_journal_sessions").then(setSessions).catch(console.error);
+  }, []);
+
+  function refresh() {
+    invoke<JournalEntry[]>("get_journal_entries", {
+      session: session == CURRENT_SESSION ? undefined : session,
+      search,
+      limit: ENTRY_LIMIT,
+    }).then(entries => {
+      setEntries(entries);
+      setError(undefined);
+    }).catch(setError);
+  }
+
+  useEffect(refresh, [session]);
+
+  return (
+    <Stack spacing={2} sx={{ padding: 2 }}>
+      <Stack direction="row" spacing={2} alignItems="center">
+        <Select size="small" value={session} onChange={event => setSession(event.target.value)}>
+          <MenuItem value={CURRENT_SESSION}>Current session</MenuItem>
+          {sessions.map(name => <MenuItem key={name} value={name}>{name}</MenuItem>)}
+        </Select>
+        <TextField
+          size="small"
+          placeholder="Search"
+          value={search}
+          onChange={event => setSearch(event.target.value)}
+          onKeyDown={event => { if (event.key == "Enter") refresh(); }}
+          error={error !== undefined}
+          helperText={error}
+        />
+        <Button variant="outlined" onClick={refresh}>Refresh</Button>
+      </Stack>
+      <Paper sx={{ padding: 1, minHeight: 200, maxHeight: 600, overflowY: "auto" }}>
+        {entries.map(entry => (
+          <Box key={entry.seq} sx={{
+            fontFamily: "monospace",
+            color: entry.kind == "watchdogTimeout" || entry.error ? theme.palette.error.main
+              : entry.kind == "action" || entry.kind == "setRequest" || entry.kind == "getRequest" || entry.kind == "command" ? theme.palette.text.primary
+              : theme.palette.text.secondary,
+          }}>
+            {new Date(entry.time).toLocaleTimeString()} {describe(entry)}
+          </Box>
+        ))}
+      </Paper>
+    </Stack>
+  );
+}
+
+export default Journal;
@@ -0,0 +1,7 @@
+
+export interface JournalEntry {
+  seq : number,
+  time : string,
+  kind : "getRequest" | "getResponse" | "setRequest" | "setResponse" | "setVerification" | "transaction" | "command" | "action" | "connectionStatus" | "watchdogTimeout" | "notification",
+  [field : string] : unknown,
+}
@@ -34,8 +34,8 @@ function HeartbeatStatus({ nodeName, busName }: Readonly<HeartbeatProps>) {
 
   useEffect(() => {
     async function asyncSetup() {
-      invoke<HeartbeatState>("get_heartbeat_status", { nodeName, busName }).then(s => {
-        setHeartbeatState(stateToString(s))
+      invoke<{ status: HeartbeatState }>("get_heartbeat_status", { nodeName, busName }).then(s => {
+        setHeartbeatState(stateToString(s.status))
       }).catch(console.error);
       let evt_name = await invoke<string>("listen_to_heartbeat", { nodeName, busName });
 
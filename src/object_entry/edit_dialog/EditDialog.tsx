@@ -1,4 +1,4 @@
-import { Box, Button, Modal, Paper, Stack, Typography } from "@mui/material";
+import { Box, Button, Checkbox, FormControlLabel, Modal, Paper, Stack, Typography } from "@mui/material";
 import { ObjectEntryInformation } from "../types/ObjectEntryInformation";
 import {ReactElement, useEffect, useState} from "react";
 import { EnumTypeInfo, IntTypeInfo, RealTypeInfo, StructTypeInfo, Type, UIntTypeInfo } from "../types/Type";
@@ -263,6 +263,20 @@ function EditDialog({ open, onClose, nodeName, objectEntryInfo }: Readonly<EditD
     setValue(undefined);
   }, [open]);
 
+  // NOTE: read back of set requests, stored in the app data by the backend.
+  const [verify, setVerify] = useState<boolean>(false);
+  useEffect(() => {
+    invoke<boolean>("get_object_entry_verify", { nodeName, objectEntryName: objectEntryInfo.name })
+      .then(setVerify)
+      .catch(console.error);
+  }, [nodeName, objectEntryInfo.name]);
+
+  function changeVerify(enabled: boolean) {
+    invoke<boolean>("set_object_entry_verify", { nodeName, objectEntryName: objectEntryInfo.name, verify: enabled })
+      .then(setVerify)
+      .catch(console.error);
+  }
+
   const enableUpload = value != undefined && isValidValue(objectEntryInfo.ty, value);
   return <Modal
     open={open}
@@ -279,6 +293,10 @@ function EditDialog({ open, onClose, nodeName, objectEntryInfo }: Readonly<EditD
             justifyContent: "flex-end",
           }}
         >
+          <FormControlLabel
+            control={<Checkbox size="small" checked={verify} onChange={event => changeVerify(event.target.checked)} />}
+            label="Verify"
+          />
           <Button
             variant="outlined"
             sx={{
@@ -11,4 +11,5 @@ export interface ErrorEvent{
     description?: string,
     friend?: Friend,
     timestamp : string,
+    acknowledged_by?: string,
 }
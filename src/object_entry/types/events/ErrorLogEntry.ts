@@ -0,0 +1,9 @@
+export interface ErrorLogEntry {
+    time: string,
+    timestamp: string,
+    label: string,
+    kind: "transition" | "acknowledged" | "reset",
+    from?: string,
+    to?: string,
+    user?: string,
+}
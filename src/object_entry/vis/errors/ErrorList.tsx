@@ -1,8 +1,10 @@
-import { Button, List, Paper, Stack, Typography, useTheme } from "@mui/material";
+import { Button, List, Paper, Stack, TextField, Typography, useTheme } from "@mui/material";
 import useCanzeroErrors from "../../../hooks/canzero_errors";
 import ErrorListItem from "./ErrorListItem";
+import ErrorLogDialog from "./ErrorLogDialog";
 import { ErrorEvent } from "../../types/events/ErrorEvent";
 import { invoke } from "@tauri-apps/api";
+import { useState } from "react";
 
 
 interface ErrorListProps {
@@ -14,8 +16,14 @@ function ErrorList({ width, height }: Readonly<ErrorListProps>) {
   const theme = useTheme();
 
   const errors = useCanzeroErrors();
+  const [user, setUser] = useState<string>("");
+  const [showLog, setShowLog] = useState(false);
   console.log(errors);
 
+  function acknowledge(label?: string) {
+    invoke("acknowledge_errors", { label, user }).catch(console.error);
+  }
+
   return (
     <Paper component="div" sx={{
       width,
@@ -25,14 +33,28 @@ function ErrorList({ width, height }: Readonly<ErrorListProps>) {
         <Typography textAlign="center" paddingTop="0.5em" paddingBottom="0.5em" variant="h6">
           Errors
         </Typography>
+        <TextField size="small" variant="standard" placeholder="Operator"
+          value={user} onChange={event => setUser(event.target.value)}
+          sx={{ width: "6em" }} />
+        <Button sx={{
+          height: "50%",
+        }} disabled={user.trim().length == 0} onClick={() => acknowledge()}>
+          Acknowledge
+        </Button>
         <Button sx={{
           height: "50%",
-        }} onClick={()=>{
+        }} onClick={() => {
           invoke("reset_errors").catch(console.error);
-        } }>
+        }}>
           Reset
         </Button>
+        <Button sx={{
+          height: "50%",
+        }} onClick={() => setShowLog(true)}>
+          Log
+        </Button>
       </Stack>
+      <ErrorLogDialog open={showLog} onClose={() => setShowLog(false)} />
       <Paper sx={{
         backgroundColor: theme.palette.background.paper2,
         marginLeft: "0.5em", marginRight: "0.5em",
@@ -44,7 +66,8 @@ function ErrorList({ width, height }: Readonly<ErrorListProps>) {
           padding: 0,
           margin: 0,
         }}>
-          { errors.map((error: ErrorEvent) => <ErrorListItem event={error} />) }
+          { errors.map((error: ErrorEvent) => <ErrorListItem event={error}
+            onAcknowledge={user.trim().length == 0 ? undefined : () => acknowledge(error.label)} />) }
         </List>
       </Paper>
     </Paper>
@@ -11,7 +11,8 @@ import { isInt, isReal, isUInt } from "../../types/Type.tsx";
 
 
 interface ErrorListItemProps {
-  event: ErrorEvent
+  event: ErrorEvent,
+  onAcknowledge?: () => void,
 }
 
 interface FriendItemProps {
@@ -38,7 +39,8 @@ function FriendItem({ friend }: Readonly<FriendItemProps>) {
 }
 
 function ErrorListItem({
-  event
+  event,
+  onAcknowledge,
 }: Readonly<ErrorListItemProps>) {
   const theme = useTheme();
 
@@ -72,7 +74,7 @@ function ErrorListItem({
 
   return (
     <Tooltip title={event.description} placement="top">
-      <ListItem sx={{
+      <ListItem onClick={event.acknowledged_by ? undefined : onAcknowledge} sx={{
         padding: 0,
         marginLeft: 0,
         marginRight: 0,
@@ -114,7 +116,7 @@ function ErrorListItem({
 
             }}
           >
-            {event.label}
+            {event.acknowledged_by ? `${event.label} (ack. ${event.acknowledged_by})` : event.label}
           </ListItemText>
         </Stack>
       </ListItem>
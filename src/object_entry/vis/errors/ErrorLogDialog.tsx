@@ -0,0 +1,76 @@
+import { Button, Dialog, DialogActions, DialogContent, DialogTitle, Stack, Table, TableBody, TableCell, TableHead, TableRow, TextField, Typography } from "@mui/material";
+import { invoke } from "@tauri-apps/api";
+import { useEffect, useState } from "react";
+import { ErrorLogEntry } from "../../types/events/ErrorLogEntry";
+
+interface ErrorLogDialogProps {
+  open: boolean,
+  onClose: () => void,
+}
+
+function describe(entry: ErrorLogEntry) {
+  switch (entry.kind) {
+    case "transition":
+      return `${entry.from} → ${entry.to}`;
+    case "acknowledged":
+      return `acknowledged by ${entry.user}`;
+    case "reset":
+      return "reset";
+  }
+}
+
+function ErrorLogDialog({ open, onClose }: Readonly<ErrorLogDialogProps>) {
+  const [log, setLog] = useState<ErrorLogEntry[]>([]);
+  const [path, setPath] = useState<string>("");
+  const [status, setStatus] = useState<string>();
+
+  useEffect(() => {
+    if (open) {
+      invoke<ErrorLogEntry[]>("get_error_log").then(setLog).catch(console.error);
+    }
+  }, [open]);
+
+  function exportLog() {
+    invoke<number>("export_error_log", { path })
+      .then(count => setStatus(`Exported ${count} entries`))
+      .catch(err => setStatus(`${err}`));
+  }
+
+  return (
+    <Dialog open={open} onClose={onClose} maxWidth="md" fullWidth>
+      <DialogTitle>Error Log</DialogTitle>
+      <DialogContent>
+        <Table size="small" stickyHeader>
+          <TableHead>
+            <TableRow>
+              <TableCell>Time</TableCell>
+              <TableCell>Timestamp</TableCell>
+              <TableCell>Error</TableCell>
+              <TableCell>Event</TableCell>
+            </TableRow>
+          </TableHead>
+          <TableBody>
+            {log.slice().reverse().map((entry, i) =>
+              <TableRow key={i}>
+                <TableCell>{new Date(entry.time).toLocaleTimeString()}</TableCell>
+                <TableCell>{entry.timestamp}</TableCell>
+                <TableCell>{entry.label}</TableCell>
+                <TableCell>{describe(entry)}</TableCell>
+              </TableRow>)}
+          </TableBody>
+        </Table>
+      </DialogContent>
+      <DialogActions>
+        <Stack direction="row" spacing={1} alignItems="center" width="100%">
+          <TextField size="small" placeholder="CSV file" value={path}
+            onChange={event => setPath(event.target.value)} sx={{ flexGrow: 1 }} />
+          <Button disabled={path.trim().length == 0} onClick={exportLog}>Export</Button>
+          <Typography variant="body2">{status}</Typography>
+          <Button onClick={onClose}>Close</Button>
+        </Stack>
+      </DialogActions>
+    </Dialog>
+  );
+}
+
+export default ErrorLogDialog;
@@ -0,0 +1,129 @@
+import { Box, Button, Checkbox, FormControlLabel, LinearProgress, MenuItem, Paper, Select, Stack, TextField, Typography, useTheme } from "@mui/material";
+import { invoke } from "@tauri-apps/api";
+import { listen } from "@tauri-apps/api/event";
+import { useEffect, useState } from "react";
+import { NetworkInformation } from "../nodes/types/NetworkInformation.ts";
+import { ParameterDiff } from "./types/ParameterDiff.ts";
+
+interface RequestProgress {
+  operation: string,
+  total: number,
+  completed: number,
+  failed: number,
+}
+
+const ALL_NODES = "all";
+
+function Parameters() {
+  const theme = useTheme();
+  const [nodes, setNodes] = useState<string[]>([]);
+  const [node, setNode] = useState<string>(ALL_NODES);
+  const [name, setName] = useState<string>("");
+  const [path, setPath] = useState<string>("");
+  const [diff, setDiff] = useState<ParameterDiff[]>();
+  const [diffPath, setDiffPath] = useState<string>();
+  const [message, setMessage] = useState<string>();
+  const [error, setError] = useState<string>();
+  const [busy, setBusy] = useState<boolean>(false);
+  const [atomic, setAtomic] = useState<boolean>(false);
+  const [progress, setProgress] = useState<RequestProgress>();
+
+  useEffect(() => {
+    invoke<NetworkInformation>("network_information")
+      .then(network => setNodes(network.node_names))
+      .catch(console.error);
+  }, []);
+
+  useEffect(() => {
+    const unlisten = listen<RequestProgress>("request-progress", event => setProgress(event.payload));
+    return () => {
+      unlisten.then(f => f()).catch(console.error);
+    };
+  }, []);
+
+  function run<T>(command: string, args: Record<string, unknown>, then: (result: T) => void) {
+    setBusy(true);
+    setError(undefined);
+    setMessage(undefined);
+    setProgress(undefined);
+    invoke<T>(command, args)
+      .then(then)
+      .catch(setError)
+      .finally(() => {
+        setBusy(false);
+        setProgress(undefined);
+      });
+  }
+
+  function snapshot() {
+    run<number>("snapshot_parameters", {
+      node: node == ALL_NODES ? undefined : node,
+      name: name.trim().length == 0 ? path : name,
+      path,
+    }, count => setMessage(`Stored ${count} parameters`));
+  }
+
+  function showDiff() {
+    run<ParameterDiff[]>("diff_parameters", { path }, diff => {
+      setDiff(diff);
+      setDiffPath(path);
+    });
+  }
+
+  function restore() {
+    run<ParameterDiff[]>("restore_parameters", { path, atomic }, result => {
+      setDiff(result);
+      const failed = result.filter(entry => entry.status != "equal").length;
+      setMessage(failed == 0 ? `Restored ${result.length} parameters` : `${failed} parameters were not restored`);
+    });
+  }
+
+  const color = (status: ParameterDiff["status"]) => status == "equal" ? theme.palette.text.secondary
+    : status == "different" ? theme.palette.warning.main : theme.palette.error.main;
+
+  return (
+    <Stack spacing={2} sx={{ padding: 2 }}>
+      <Stack direction="row" spacing={2} alignItems="center">
+        <TextField size="small" placeholder="Parameter file (.yaml or .json)" value={path}
+          onChange={event => setPath(event.target.value)} sx={{ flexGrow: 1 }} />
+        <Select size="small" value={node} onChange={event => setNode(event.target.value)}>
+          <MenuItem value={ALL_NODES}>All nodes</MenuItem>
+          {nodes.map(name => <MenuItem key={name} value={name}>{name}</MenuItem>)}
+        </Select>
+        <TextField size="small" placeholder="Name" value={name} onChange={event => setName(event.target.value)} />
+      </Stack>
+      <Stack direction="row" spacing={2} alignItems="center">
+        <Button variant="outlined" disabled={busy || path.length == 0} onClick={snapshot}>Snapshot</Button>
+        <Button variant="outlined" disabled={busy || path.length == 0} onClick={showDiff}>Diff</Button>
+        <Button variant="contained" disabled={busy || diffPath === undefined || diffPath != path} onClick={restore}>
+          Restore
+        </Button>
+        <FormControlLabel label="Atomic per node" control={
+          <Checkbox size="small" checked={atomic} onChange={event => setAtomic(event.target.checked)} />
+        } />
+        {message && <Typography>{message}</Typography>}
+        {error && <Typography color="error" sx={{ whiteSpace: "pre-line" }}>{error}</Typography>}
+      </Stack>
+      {busy && progress && progress.total > 0 && (
+        <Stack direction="row" spacing={2} alignItems="center">
+          <LinearProgress variant="determinate" value={100 * progress.completed / progress.total} sx={{ flexGrow: 1 }} />
+          <Typography variant="body2">
+            {progress.operation} {progress.completed}/{progress.total}{progress.failed > 0 && ` (${progress.failed} failed)`}
+          </Typography>
+        </Stack>
+      )}
+      {diff && (
+        <Paper sx={{ padding: 1, maxHeight: 600, overflowY: "auto" }}>
+          {diff.map(entry => (
+            <Box key={`${entry.node}::${entry.objectEntry}`} sx={{ fontFamily: "monospace", color: color(entry.status) }}>
+              {entry.node}::{entry.objectEntry} : {JSON.stringify(entry.file)} {entry.status == "equal" ? "==" : "!="} {JSON.stringify(entry.live)}
+              {entry.error && ` (${entry.error})`}
+            </Box>
+          ))}
+        </Paper>
+      )}
+    </Stack>
+  );
+}
+
+export default Parameters;
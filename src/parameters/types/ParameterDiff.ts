@@ -0,0 +1,9 @@
+
+export interface ParameterDiff {
+  node : string,
+  objectEntry : string,
+  file? : unknown,
+  live? : unknown,
+  status : "equal" | "different" | "unknown" | "invalid",
+  error? : string,
+}
@@ -0,0 +1,85 @@
+import { IconButton, MenuItem, Paper, Select, Slider, Stack, Typography, useTheme } from "@mui/material";
+import PlayArrowIcon from '@mui/icons-material/PlayArrow';
+import PauseIcon from '@mui/icons-material/Pause';
+import { invoke } from "@tauri-apps/api";
+import { listen } from "@tauri-apps/api/event";
+import { useEffect, useState } from "react";
+import { ReplayStatus } from "./ReplayStatus";
+
+const SPEEDS = [0.25, 0.5, 1, 2, 4, 8];
+
+function formatTime(ms: number) {
+  const seconds = Math.floor(ms / 1000);
+  return `${Math.floor(seconds / 60)}:${(seconds % 60).toString().padStart(2, "0")}`;
+}
+
+// Play, pause, seek and speed controls, only shown if a recording is replayed.
+function ReplayControls() {
+  const theme = useTheme();
+  const [status, setStatus] = useState<ReplayStatus>();
+  // position of the slider while it is dragged.
+  const [seeking, setSeeking] = useState<number>();
+
+  useEffect(() => {
+    let unlisten = listen<ReplayStatus>("replay-status", event => setStatus(event.payload));
+    // fails if the control panel is connected to a network.
+    invoke<ReplayStatus>("replay_status").then(setStatus).catch(() => setStatus(undefined));
+    return () => {
+      unlisten.then(f => f()).catch(console.error);
+    };
+  }, []);
+
+  if (status === undefined) {
+    return <></>;
+  }
+
+  function togglePlay() {
+    invoke(status!.playing ? "replay_pause" : "replay_play").catch(console.error);
+  }
+
+  function seek(positionMs: number) {
+    setSeeking(undefined);
+    invoke("replay_seek", { positionMs: Math.round(positionMs) }).catch(console.error);
+  }
+
+  const position = seeking ?? status.positionMs;
+
+  return (
+    <Paper sx={{
+      position: "fixed",
+      bottom: 16,
+      right: 16,
+      width: 480,
+      padding: 1,
+      zIndex: theme.zIndex.drawer + 2,
+      backgroundColor: theme.palette.background.paper2,
+    }}>
+      <Stack direction="row" spacing={2} alignItems="center">
+        <IconButton onClick={togglePlay} size="small">
+          {status.playing ? <PauseIcon /> : <PlayArrowIcon />}
+        </IconButton>
+        <Typography variant="body2" sx={{ minWidth: 90 }}>
+          {formatTime(position)} / {formatTime(status.durationMs)}
+        </Typography>
+        <Slider
+          size="small"
+          min={0}
+          max={status.durationMs}
+          value={position}
+          onChange={(_, value) => setSeeking(value as number)}
+          onChangeCommitted={(_, value) => seek(value as number)}
+        />
+        <Select
+          size="small"
+          variant="standard"
+          value={status.speed}
+          onChange={event => invoke("replay_set_speed", { speed: event.target.value }).catch(console.error)}
+        >
+          {SPEEDS.map(speed => <MenuItem key={speed} value={speed}>{speed}x</MenuItem>)}
+        </Select>
+      </Stack>
+    </Paper>
+  );
+}
+
+export default ReplayControls;
@@ -0,0 +1,9 @@
+
+export interface ReplayStatus {
+  playing: boolean,
+  speed: number,
+  positionMs: number,
+  durationMs: number,
+  frameIndex: number,
+  frameCount: number,
+}
@@ -0,0 +1,81 @@
+import { Box, Button, Paper, Stack, TextField, Typography, useTheme } from "@mui/material";
+import { invoke } from "@tauri-apps/api";
+import { listen } from "@tauri-apps/api/event";
+import { useEffect, useState } from "react";
+import { ScriptOutput, ScriptRunInfo } from "./types/ScriptOutput.ts";
+
+const EXAMPLE_SCRIPT = `// get / set object entries by node.entry
+// set("node.entry", 42);
+// assert(wait_until(|| read("node.entry") == 42, 1000), "entry was not set");
+// command("node.command", #{ argument: 1 });
+// send("can0", 0x123, [0xDE, 0xAD]);
+log("hello from the control panel");
+`;
+
+function Scripting() {
+  const theme = useTheme();
+  const [source, setSource] = useState<string>(EXAMPLE_SCRIPT);
+  const [run, setRun] = useState<ScriptRunInfo>();
+  const [output, setOutput] = useState<ScriptOutput[]>([]);
+  const [error, setError] = useState<string>();
+
+  useEffect(() => {
+    let unlistenOutput = listen<ScriptOutput>("script-output", event => {
+      setOutput(output => [...output, event.payload]);
+    });
+    let unlistenStatus = listen<ScriptRunInfo>("script-status", event => {
+      setRun(run => run?.runId == event.payload.runId ? event.payload : run);
+    });
+    return () => {
+      unlistenOutput.then(f => f()).catch(console.error);
+      unlistenStatus.then(f => f()).catch(console.error);
+    };
+  }, []);
+
+  function start() {
+    setOutput([]);
+    setError(undefined);
+    invoke<ScriptRunInfo>("run_script", { name: "console", source })
+      .then(setRun)
+      .catch(setError);
+  }
+
+  function stop() {
+    if (run) {
+      invoke("stop_script", { runId: run.runId }).catch(console.error);
+    }
+  }
+
+  const running = run?.status == "running";
+  return (
+    <Stack spacing={2} sx={{ padding: 2 }}>
+      <TextField
+        multiline
+        minRows={12}
+        value={source}
+        onChange={event => setSource(event.target.value)}
+        error={error !== undefined}
+        helperText={error}
+        inputProps={{ style: { fontFamily: "monospace" }, spellCheck: false }}
+      />
+      <Stack direction="row" spacing={2} alignItems="center">
+        <Button variant="contained" disabled={running} onClick={start}>Run</Button>
+        <Button variant="outlined" disabled={!running} onClick={stop}>Stop</Button>
+        {run && <Typography>{run.name} : {run.status}</Typography>}
+      </Stack>
+      <Paper sx={{ padding: 1, minHeight: 200, maxHeight: 400, overflowY: "auto" }}>
+        {output.filter(line => line.runId == run?.runId).map((line, i) => (
+          <Box key={i} sx={{
+            fontFamily: "monospace",
+            color: line.level == "error" ? theme.palette.error.main
+              : line.level == "debug" ? theme.palette.text.disabled : theme.palette.text.primary,
+          }}>
+            [{(line.time / 1000).toFixed(3)}s] {line.message}
+          </Box>
+        ))}
+      </Paper>
+    </Stack>
+  );
+}
+
+export default Scripting;
@@ -0,0 +1,13 @@
+
+export interface ScriptOutput {
+  runId : number,
+  level : "debug" | "info" | "error",
+  message : string,
+  time : number,
+}
+
+export interface ScriptRunInfo {
+  runId : number,
+  name : string,
+  status : "running" | "passed" | "failed" | "stopped",
+}
@@ -0,0 +1,79 @@
+import { IconButton, Stack, Tooltip, Typography } from "@mui/material";
+import PauseIcon from '@mui/icons-material/Pause';
+import PlayArrowIcon from '@mui/icons-material/PlayArrow';
+import KeyboardArrowUpIcon from '@mui/icons-material/KeyboardArrowUp';
+import KeyboardArrowDownIcon from '@mui/icons-material/KeyboardArrowDown';
+import VerticalAlignBottomIcon from '@mui/icons-material/VerticalAlignBottom';
+import { invoke } from "@tauri-apps/api";
+import { TracePage } from "./types/TracePage";
+
+export const CHRONOLOGICAL_PAGE_SIZE = 200;
+
+interface ChronologicalControlsProps {
+  page: TracePage,
+  // undefined follows the newest frames.
+  offset: number | undefined,
+  setOffset: (offset: number | undefined) => void,
+}
+
+function ChronologicalControls({ page, offset, setOffset }: ChronologicalControlsProps) {
+
+  function selectPage(offset: number | undefined) {
+    setOffset(offset);
+    invoke("set_chronological_trace_page", { offset, size: CHRONOLOGICAL_PAGE_SIZE })
+      .catch(console.error);
+  }
+
+  function togglePause() {
+    invoke(page.paused ? "resume_trace" : "pause_trace").catch(console.error);
+  }
+
+  const start = page.offset;
+  const end = Math.min(start + CHRONOLOGICAL_PAGE_SIZE, page.total);
+
+  return <Stack direction="row" alignItems="center" spacing={1}>
+    <Tooltip title={page.paused ? "Resume" : "Pause"}>
+      <IconButton size="small" onClick={togglePause}>
+        {page.paused ? <PlayArrowIcon fontSize="small" /> : <PauseIcon fontSize="small" />}
+      </IconButton>
+    </Tooltip>
+    <Tooltip title="Older frames">
+      <span>
+        <IconButton
+          size="small"
+          disabled={start <= page.first}
+          onClick={() => selectPage(Math.max(start - CHRONOLOGICAL_PAGE_SIZE, page.first))}
+        >
+          <KeyboardArrowUpIcon fontSize="small" />
+        </IconButton>
+      </span>
+    </Tooltip>
+    <Tooltip title="Newer frames">
+      <span>
+        <IconButton
+          size="small"
+          disabled={end >= page.total}
+          onClick={() => {
+            const next = start + CHRONOLOGICAL_PAGE_SIZE;
+            selectPage(next + CHRONOLOGICAL_PAGE_SIZE >= page.total ? undefined : next);
+          }}
+        >
+          <KeyboardArrowDownIcon fontSize="small" />
+        </IconButton>
+      </span>
+    </Tooltip>
+    <Tooltip title="Follow newest frames">
+      <span>
+        <IconButton size="small" disabled={offset === undefined} onClick={() => selectPage(undefined)}>
+          <VerticalAlignBottomIcon fontSize="small" />
+        </IconButton>
+      </span>
+    </Tooltip>
+    <Typography variant="body2">
+      {page.total == page.first ? "no frames" : `${start - page.first + 1}-${end - page.first} of ${page.total - page.first}`}
+      {page.dropped > 0 ? ` (${page.dropped} overwritten)` : ""}
+    </Typography>
+  </Stack>
+}
+
+export default ChronologicalControls;
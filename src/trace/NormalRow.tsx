@@ -69,7 +69,7 @@ function NormalRow({ useAbsoluteTime, open, setOpen, event }: Readonly<Undefined
       </TableCell>
       <TableCell>
         <Typography style={{ opacity: event.timeSinceLast > 5000 ? "20%" : "100%" }} variant="body2">
-          {event.bus}
+          {event.direction === "tx" ? `${event.bus} (TX)` : event.bus}
         </Typography>
       </TableCell>
       <TableCell>
@@ -1,7 +1,9 @@
-import { IconButton, InputAdornment, Paper, Stack, Table, TableBody, TableCell, TableContainer, TableHead, TableRow, TextField, Typography, styled } from '@mui/material';
+import { IconButton, InputAdornment, Paper, Stack, Table, TableBody, TableCell, TableContainer, TableHead, TableRow, TextField, Tooltip, Typography, styled } from '@mui/material';
 import SearchIcon from '@mui/icons-material/Search';
 import AccessTimeIcon from '@mui/icons-material/AccessTime';
 import ChangeHistoryIcon from '@mui/icons-material/ChangeHistory';
+import HistoryIcon from '@mui/icons-material/History';
+import ViewListIcon from '@mui/icons-material/ViewList';
 import { useEffect, useRef, useState } from 'react';
 import Sortable, { Sorting } from './Sortable';
 import useFocusOnCtrlShortcut from './FocusOnKey';
@@ -11,6 +13,8 @@ import { TraceEvent } from './types/TraceEvent';
 import ErrorRow from './ErrorRow';
 import UndefinedRow from './UndefinedRow';
 import NormalRow from './NormalRow';
+import { TracePage } from './types/TracePage';
+import ChronologicalControls, { CHRONOLOGICAL_PAGE_SIZE } from './ChronologicalControls';
 
 
 const StyledTableHeader = styled(TableRow)(({theme}) => ({
@@ -77,6 +81,11 @@ function Trace() {
 
   const [frames, setFrames] = useState<TraceEvent[]>([]);
 
+  // the chronological trace shows every frame instead of the latest frame per id.
+  const [chronological, setChronological] = useState<boolean>(false);
+  const [page, setPage] = useState<TracePage>();
+  const [pageOffset, setPageOffset] = useState<number>();
+
   useEffect(() => {
     async function asyncSetup() {
       const storedSearchString = await invoke<string | null>("get_stored_search_string", {page: "trace"});
@@ -84,6 +93,18 @@ function Trace() {
           setSearchString(storedSearchString)
           updateFilter(storedSearchString)
       }
+      if (chronological) {
+        await invoke("set_chronological_trace_page", { offset: undefined, size: CHRONOLOGICAL_PAGE_SIZE });
+        const eventName = await invoke<string>("listen_to_chronological_trace");
+        const unlisten = await listen<TracePage>(eventName, event => {
+          setPage(event.payload);
+          setFrames(event.payload.events);
+        });
+        return () => {
+          invoke("unlisten_from_chronological_trace").catch(console.error);
+          unlisten();
+        };
+      }
       const eventName = await invoke<string>("listen_to_trace");
       const unlisten = await listen<TraceEvent[]>(eventName, event => {
         setFrames(event.payload);
@@ -94,11 +115,14 @@ function Trace() {
       };
     }
     setSearchString("");
+    setFrames([]);
+    setPage(undefined);
+    setPageOffset(undefined);
     const asyncCleanup = asyncSetup();
     return () => {
       asyncCleanup.then(f => f()).catch(console.error);
     };
-  }, []);
+  }, [chronological]);
 
 
   return <TableContainer
@@ -126,6 +150,18 @@ function Trace() {
                 borderRightStyle: "solid",
               }}
           >
+            <Tooltip title={chronological ? "Latest frame per id" : "All frames in order of arrival"}>
+              <IconButton
+                  size="small"
+                  sx={{
+                    boxShadow: "none",
+                    color: "black",
+                  }}
+                  onClick={() => setChronological(prev => !prev)}
+              >
+                {chronological ? <ViewListIcon fontSize="small" /> : <HistoryIcon fontSize="small" />}
+              </IconButton>
+            </Tooltip>
           </TableCell>
           <TableCell
               align="left"
@@ -198,6 +234,8 @@ function Trace() {
                   Name
                 </Typography>
               </Sortable>
+              {chronological && page !== undefined ?
+                <ChronologicalControls page={page} offset={pageOffset} setOffset={setPageOffset} /> : <></>}
               <TextField
                   inputRef={searchFieldRef}
                   value={searchString}
@@ -267,13 +305,15 @@ function Trace() {
         </StyledTableHeader>
       </TableHead>
       <TableBody>
-        {frames.map(event => {
+        {frames.map((event, i) => {
+          // a frame occurs multiple times in the chronological trace, therefor the key is its position.
+          const key = chronological && page !== undefined ? page.offset + i : event.key;
           if (event.frame.ty == "normal") {
-            return <NormalRow key={event.key} open={open} setOpen={setOpen} event={event} useAbsoluteTime={useAbsoluteTime} />
+            return <NormalRow key={key} open={open} setOpen={setOpen} event={event} useAbsoluteTime={useAbsoluteTime} />
           }else if (event.frame.ty == "undefined") {
-            return <UndefinedRow key={event.key} event={event} useAbsoluteTime={useAbsoluteTime} />
+            return <UndefinedRow key={key} event={event} useAbsoluteTime={useAbsoluteTime} />
           } else if (event.frame.ty == "error") {
-            return <ErrorRow key={event.key} open={open} setOpen={setOpen} event={event} useAbsoluteTime={useAbsoluteTime} />
+            return <ErrorRow key={key} open={open} setOpen={setOpen} event={event} useAbsoluteTime={useAbsoluteTime} />
           }
         })}
 
@@ -33,7 +33,7 @@ function UndefinedRow({ useAbsoluteTime, event }: Readonly<UndefinedRowProps>) {
       </TableCell>
       <TableCell>
           <Typography style={{ opacity: event.timeSinceLast > 5000 ? "20%" : "100%" }} variant="body2">
-              {event.bus}
+              {event.direction === "tx" ? `${event.bus} (TX)` : event.bus}
           </Typography>
       </TableCell>
       <TableCell>
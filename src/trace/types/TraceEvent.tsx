@@ -19,5 +19,6 @@ export interface TraceEvent {
   timeSinceLast : number,
   key : number,
   bus : string,
+  direction : "rx" | "tx",
   frame : TraceFrame,
 }
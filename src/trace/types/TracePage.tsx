@@ -0,0 +1,10 @@
+import { TraceEvent } from "./TraceEvent";
+
+export interface TracePage {
+  events : TraceEvent[],
+  offset : number,
+  first : number,
+  total : number,
+  dropped : number,
+  paused : boolean,
+}
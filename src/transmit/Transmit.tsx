@@ -0,0 +1,149 @@
+import { Button, Checkbox, FormControlLabel, IconButton, Paper, Stack, Table, TableBody, TableCell, TableHead, TableRow, TextField, Typography } from "@mui/material";
+import StopIcon from '@mui/icons-material/Stop';
+import { invoke } from "@tauri-apps/api";
+import { useEffect, useState } from "react";
+import { TxJobInfo } from "./types/TxJobInfo.ts";
+
+// data bytes as hex, separated by spaces or dots, e.g. "DE AD BE EF".
+function parseData(data: string): number[] | undefined {
+  const bytes = data.split(/[\s.]+/).filter(byte => byte.length > 0);
+  if (bytes.some(byte => !/^[0-9a-fA-F]{1,2}$/.test(byte))) {
+    return undefined;
+  }
+  return bytes.map(byte => parseInt(byte, 16));
+}
+
+function formatData(data: number[]) {
+  return data.map(byte => byte.toString(16).toUpperCase().padStart(2, "0")).join(" ");
+}
+
+function Transmit() {
+  const [bus, setBus] = useState<string>("can0");
+  const [id, setId] = useState<string>("0x100");
+  const [ide, setIde] = useState<boolean>(false);
+  const [data, setData] = useState<string>("");
+  const [dlc, setDlc] = useState<string>("");
+  const [periodMs, setPeriodMs] = useState<string>("100");
+  const [jobs, setJobs] = useState<TxJobInfo[]>([]);
+  const [error, setError] = useState<string>();
+
+  const [messageName, setMessageName] = useState<string>("");
+  const [valuesJson, setValuesJson] = useState<string>("{}");
+  const [messageError, setMessageError] = useState<string>();
+
+  function refreshJobs() {
+    invoke<TxJobInfo[]>("list_cyclic_frames").then(setJobs).catch(console.error);
+  }
+
+  useEffect(() => {
+    refreshJobs();
+    // the number of sent frames changes continuously.
+    const interval = setInterval(refreshJobs, 1000);
+    return () => clearInterval(interval);
+  }, []);
+
+  function frameArgs() {
+    const bytes = parseData(data);
+    if (bytes === undefined) {
+      setError(`Invalid data ${data}`);
+      return undefined;
+    }
+    setError(undefined);
+    return { bus, id: Number(id), ide, dlc: dlc.length == 0 ? undefined : Number(dlc), data: bytes };
+  }
+
+  function send() {
+    const args = frameArgs();
+    if (args) {
+      invoke("send_frame", args).catch(setError);
+    }
+  }
+
+  function startCyclic() {
+    const args = frameArgs();
+    if (args) {
+      invoke<TxJobInfo>("start_cyclic_frame", { ...args, periodMs: Number(periodMs) })
+        .then(refreshJobs)
+        .catch(setError);
+    }
+  }
+
+  function stopCyclic(jobId: number) {
+    invoke("stop_cyclic_frame", { jobId }).then(refreshJobs).catch(console.error);
+  }
+
+  function sendMessage() {
+    setMessageError(undefined);
+    invoke("send_message", { messageName, valuesJson }).catch(setMessageError);
+  }
+
+  return (
+    <Stack spacing={2} sx={{ padding: 2 }}>
+      <Paper sx={{ padding: 2 }}>
+        <Stack spacing={2}>
+          <Typography variant="h6">Raw frame</Typography>
+          <Stack direction="row" spacing={2} alignItems="center">
+            <TextField label="Bus" size="small" value={bus} onChange={event => setBus(event.target.value)} />
+            <TextField label="Id" size="small" value={id} onChange={event => setId(event.target.value)} />
+            <FormControlLabel label="Extended" control={<Checkbox checked={ide} onChange={event => setIde(event.target.checked)} />} />
+            <TextField label="Data" size="small" placeholder="DE AD BE EF" sx={{ flexGrow: 1 }} value={data}
+              onChange={event => setData(event.target.value)} inputProps={{ style: { fontFamily: "monospace" } }} />
+            <TextField label="DLC" size="small" type="number" placeholder="auto" sx={{ width: 90 }} value={dlc}
+              onChange={event => setDlc(event.target.value)} />
+            <Button variant="contained" onClick={send}>Send</Button>
+          </Stack>
+          <Stack direction="row" spacing={2} alignItems="center">
+            <TextField label="Period (ms)" size="small" type="number" value={periodMs}
+              onChange={event => setPeriodMs(event.target.value)} />
+            <Button variant="outlined" onClick={startCyclic}>Send cyclic</Button>
+          </Stack>
+          {error && <Typography color="error">{error}</Typography>}
+        </Stack>
+      </Paper>
+      <Paper sx={{ padding: 2 }}>
+        <Stack spacing={2}>
+          <Typography variant="h6">Message</Typography>
+          <Stack direction="row" spacing={2} alignItems="flex-start">
+            <TextField label="Message" size="small" value={messageName} onChange={event => setMessageName(event.target.value)} />
+            <TextField label="Values" size="small" multiline sx={{ flexGrow: 1 }} value={valuesJson}
+              onChange={event => setValuesJson(event.target.value)} inputProps={{ style: { fontFamily: "monospace" } }} />
+            <Button variant="contained" onClick={sendMessage}>Send</Button>
+          </Stack>
+          {messageError && <Typography color="error">{messageError}</Typography>}
+        </Stack>
+      </Paper>
+      <Paper>
+        <Table size="small">
+          <TableHead>
+            <TableRow>
+              <TableCell>Bus</TableCell>
+              <TableCell>Id</TableCell>
+              <TableCell>Data</TableCell>
+              <TableCell>Period</TableCell>
+              <TableCell>Sent</TableCell>
+              <TableCell />
+            </TableRow>
+          </TableHead>
+          <TableBody>
+            {jobs.map(job => (
+              <TableRow key={job.jobId}>
+                <TableCell>{job.bus}</TableCell>
+                <TableCell>0x{job.id.toString(16).toUpperCase()}{job.ide ? "x" : ""}</TableCell>
+                <TableCell sx={{ fontFamily: "monospace" }}>{formatData(job.data)}</TableCell>
+                <TableCell>{job.periodMs}ms</TableCell>
+                <TableCell>{job.sent}</TableCell>
+                <TableCell align="right">
+                  <IconButton size="small" onClick={() => stopCyclic(job.jobId)}>
+                    <StopIcon fontSize="small" />
+                  </IconButton>
+                </TableCell>
+              </TableRow>
+            ))}
+          </TableBody>
+        </Table>
+      </Paper>
+    </Stack>
+  );
+}
+
+export default Transmit;
@@ -0,0 +1,11 @@
+
+export interface TxJobInfo {
+  jobId : number,
+  bus : string,
+  id : number,
+  ide : boolean,
+  dlc : number,
+  data : number[],
+  periodMs : number,
+  sent : number,
+}